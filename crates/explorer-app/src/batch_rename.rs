@@ -0,0 +1,910 @@
+//! 批量重命名
+//!
+//! 规则按以下顺序作用于每个条目：
+//! 1. 对主文件名（不含扩展名）执行查找替换（支持正则和 `$1` 捕获组）
+//! 2. 按名称模板生成新的主文件名（`{name}`、`{n}`、`{n:3}`、`{date}`、`{date:%Y%m%d}`、`{ext}`）
+//! 3. 拼接扩展名（可替换或移除）
+//! 4. 对完整名称进行大小写转换
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use chrono::{
+    DateTime, Local,
+    format::{Item, StrftimeItems},
+};
+use gpui::{prelude::*, *};
+use regex::Regex;
+
+use explorer_common::{FileItem, ItemType};
+use explorer_component::{Button, ButtonVariant, Dialog, TextInput, TextInputEvent, Theme};
+
+use crate::{file_ops::FileOperation, paths};
+
+/// 默认日期格式
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 编号的最大宽度（`u64` 最多 20 位）
+const MAX_COUNTER_WIDTH: usize = 20;
+
+// ===== 规则 =====
+
+/// 大小写转换
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaseTransform {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    Title,
+}
+
+impl CaseTransform {
+    pub const ALL: [CaseTransform; 4] = [
+        CaseTransform::Keep,
+        CaseTransform::Lower,
+        CaseTransform::Upper,
+        CaseTransform::Title,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CaseTransform::Keep => "保持",
+            CaseTransform::Lower => "小写",
+            CaseTransform::Upper => "大写",
+            CaseTransform::Title => "首字母大写",
+        }
+    }
+
+    fn apply(&self, name: &str) -> String {
+        match self {
+            CaseTransform::Keep => name.to_string(),
+            CaseTransform::Lower => name.to_lowercase(),
+            CaseTransform::Upper => name.to_uppercase(),
+            CaseTransform::Title => {
+                let mut result = String::with_capacity(name.len());
+                let mut word_start = true;
+                for ch in name.chars() {
+                    if word_start {
+                        result.extend(ch.to_uppercase());
+                    } else {
+                        result.extend(ch.to_lowercase());
+                    }
+                    word_start = !ch.is_alphanumeric();
+                }
+                result
+            }
+        }
+    }
+}
+
+/// 名称模板中的片段
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Name,
+    Ext,
+    Counter(usize),
+    Date(String),
+}
+
+/// 解析名称模板
+fn parse_template(template: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+
+        let body = &rest[start + 1..start + len];
+        let (key, arg) = match body.split_once(':') {
+            Some((key, arg)) => (key, Some(arg)),
+            None => (body, None),
+        };
+        let token = match (key, arg) {
+            ("name", None) => Token::Name,
+            ("ext", None) => Token::Ext,
+            ("n", None) => Token::Counter(1),
+            ("n", Some(width)) => match width.parse() {
+                Ok(width) if width <= MAX_COUNTER_WIDTH => Token::Counter(width),
+                Ok(_) => {
+                    return Err(format!(
+                        "编号宽度不能超过 {}: {{{}}}",
+                        MAX_COUNTER_WIDTH, body
+                    ));
+                }
+                Err(_) => return Err(format!("编号宽度无效: {{{}}}", body)),
+            },
+            ("date", format) => {
+                let format = format.unwrap_or(DEFAULT_DATE_FORMAT);
+                if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("日期格式无效: {{{}}}", body));
+                }
+                Token::Date(format.to_string())
+            }
+            _ => return Err(format!("未知的模板标记: {{{}}}", body)),
+        };
+        tokens.push(token);
+        rest = &rest[start + len + 1..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+/// 查找方式
+enum Finder {
+    None,
+    Literal(String),
+    Regex(Regex),
+}
+
+/// 批量重命名规则
+#[derive(Debug, Clone)]
+pub struct RenameRule {
+    /// 查找内容（为空时不替换）
+    pub find: String,
+    /// 替换内容（正则模式下支持 `$1`、`${name}`）
+    pub replace: String,
+    /// 是否把查找内容作为正则表达式
+    pub use_regex: bool,
+    /// 名称模板
+    pub template: String,
+    /// 编号起始值
+    pub counter_start: u64,
+    /// 新扩展名：`None` 保持不变，`Some("")` 移除扩展名
+    pub extension: Option<String>,
+    /// 大小写转换
+    pub case: CaseTransform,
+}
+
+impl Default for RenameRule {
+    fn default() -> Self {
+        Self {
+            find: String::new(),
+            replace: String::new(),
+            use_regex: false,
+            template: "{name}".to_string(),
+            counter_start: 1,
+            extension: None,
+            case: CaseTransform::Keep,
+        }
+    }
+}
+
+impl RenameRule {
+    /// 生成预览，规则本身无效（正则、模板错误）时返回错误
+    ///
+    /// `items` 为待重命名的条目（按显示顺序），`entries` 为所在目录的全部条目。
+    pub fn preview(
+        &self,
+        items: &[FileItem],
+        entries: &[FileItem],
+    ) -> Result<Vec<PreviewRow>, String> {
+        let finder = if self.find.is_empty() {
+            Finder::None
+        } else if self.use_regex {
+            Finder::Regex(Regex::new(&self.find).map_err(|e| format!("正则表达式无效: {}", e))?)
+        } else {
+            Finder::Literal(self.find.clone())
+        };
+        let tokens = parse_template(&self.template)?;
+
+        let mut rows = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let counter = self.counter_start.saturating_add(index as u64);
+                let new_name = self.apply(item, counter, &finder, &tokens);
+                let error = paths::validate_file_name(&new_name, &item.name, &[]).err();
+                PreviewRow {
+                    path: item.path.clone(),
+                    old_name: item.name.clone(),
+                    new_name,
+                    error,
+                }
+            })
+            .collect::<Vec<_>>();
+        detect_conflicts(&mut rows, entries);
+        Ok(rows)
+    }
+
+    fn apply(&self, item: &FileItem, counter: u64, finder: &Finder, tokens: &[Token]) -> String {
+        let (stem, ext) = split_name(&item.name, item.item_type);
+        let stem = match finder {
+            Finder::None => stem.to_string(),
+            Finder::Literal(find) => stem.replace(find.as_str(), &self.replace),
+            Finder::Regex(regex) => regex.replace_all(stem, self.replace.as_str()).into_owned(),
+        };
+
+        let mut name = String::new();
+        for token in tokens {
+            match token {
+                Token::Text(text) => name.push_str(text),
+                Token::Name => name.push_str(&stem),
+                Token::Ext => name.push_str(ext.unwrap_or_default()),
+                Token::Counter(width) => {
+                    let _ = write!(name, "{:0width$}", counter, width = *width);
+                }
+                Token::Date(format) => {
+                    let modified: DateTime<Local> = item.modified.into();
+                    let _ = write!(name, "{}", modified.format(format));
+                }
+            }
+        }
+
+        // 目录没有扩展名，不受扩展名设置影响
+        let ext = match &self.extension {
+            _ if item.item_type == ItemType::Directory => None,
+            Some(ext) if ext.is_empty() => None,
+            Some(ext) => Some(ext.as_str()),
+            None => ext,
+        };
+        if let Some(ext) = ext {
+            name.push('.');
+            name.push_str(ext);
+        }
+        self.case.apply(&name)
+    }
+}
+
+/// 拆分主文件名和扩展名（目录和以点开头且没有其他点的名称没有扩展名）
+fn split_name(name: &str, item_type: ItemType) -> (&str, Option<&str>) {
+    if item_type == ItemType::Directory {
+        return (name, None);
+    }
+    match name.rfind('.') {
+        Some(index) if index > 0 => (&name[..index], Some(&name[index + 1..])),
+        _ => (name, None),
+    }
+}
+
+// ===== 预览与执行计划 =====
+
+/// 预览中的一行
+#[derive(Debug, Clone)]
+pub struct PreviewRow {
+    pub path: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub error: Option<String>,
+}
+
+impl PreviewRow {
+    pub fn is_changed(&self) -> bool {
+        self.new_name != self.old_name
+    }
+}
+
+/// 检测新名称之间、以及与目录中其他条目之间的冲突
+fn detect_conflicts(rows: &mut [PreviewRow], entries: &[FileItem]) {
+    let renamed: HashSet<&str> = rows
+        .iter()
+        .filter(|row| row.is_changed())
+        .map(|row| row.path.as_str())
+        .collect();
+    // 不参与重命名的条目保留原名称
+    let occupied: HashSet<String> = entries
+        .iter()
+        .filter(|entry| !renamed.contains(entry.path.as_str()))
+        .map(|entry| paths::name_key(&entry.name))
+        .collect();
+
+    let mut targets: HashMap<String, usize> = HashMap::new();
+    for row in rows.iter() {
+        *targets.entry(paths::name_key(&row.new_name)).or_default() += 1;
+    }
+
+    for row in rows
+        .iter_mut()
+        .filter(|row| row.is_changed() && row.error.is_none())
+    {
+        let key = paths::name_key(&row.new_name);
+        if targets.get(&key).is_some_and(|count| *count > 1) {
+            row.error = Some("与其他条目的新名称重复".to_string());
+        } else if occupied.contains(&key) {
+            row.error = Some("目录中已存在同名条目".to_string());
+        }
+    }
+}
+
+/// 将预览转换为重命名操作
+///
+/// 当新名称与批量中其他条目的旧名称重叠时（例如互换名称），
+/// 先把所有条目改为临时名称再改为最终名称，避免中途冲突。
+pub fn plan(rows: &[PreviewRow]) -> Vec<FileOperation> {
+    let changed: Vec<(&PreviewRow, String)> = rows
+        .iter()
+        .filter(|row| row.is_changed())
+        .filter_map(|row| paths::parent_path(&row.path).map(|dir| (row, dir)))
+        .collect();
+
+    let old_names: HashSet<String> = changed
+        .iter()
+        .map(|(row, _)| paths::name_key(&row.old_name))
+        .collect();
+    let needs_staging = changed
+        .iter()
+        .any(|(row, _)| old_names.contains(&paths::name_key(&row.new_name)));
+
+    if !needs_staging {
+        return changed
+            .into_iter()
+            .map(|(row, dir)| FileOperation::Rename {
+                from: row.path.clone(),
+                to: paths::join_path(&dir, &row.new_name),
+            })
+            .collect();
+    }
+
+    let staged: Vec<String> = changed
+        .iter()
+        .enumerate()
+        .map(|(index, (_, dir))| {
+            paths::join_path(
+                dir,
+                &format!(".explorer-rename-{}-{}", std::process::id(), index),
+            )
+        })
+        .collect();
+
+    let first = changed
+        .iter()
+        .zip(&staged)
+        .map(|((row, _), temp)| FileOperation::Rename {
+            from: row.path.clone(),
+            to: temp.clone(),
+        });
+    let second = changed
+        .iter()
+        .zip(&staged)
+        .map(|((row, dir), temp)| FileOperation::Rename {
+            from: temp.clone(),
+            to: paths::join_path(dir, &row.new_name),
+        });
+    first.chain(second).collect()
+}
+
+// ===== 对话框 =====
+
+/// 批量重命名对话框事件
+pub enum BatchRenameEvent {
+    /// 确认执行，附带重命名操作
+    Apply(Vec<FileOperation>),
+    /// 关闭对话框
+    Dismiss,
+}
+
+/// 批量重命名对话框
+pub struct BatchRenameDialog {
+    items: Vec<FileItem>,
+    entries: Vec<FileItem>,
+    find: Entity<TextInput>,
+    replace: Entity<TextInput>,
+    template: Entity<TextInput>,
+    counter_start: Entity<TextInput>,
+    extension: Entity<TextInput>,
+    use_regex: bool,
+    case: CaseTransform,
+    preview: Result<Vec<PreviewRow>, String>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<BatchRenameEvent> for BatchRenameDialog {}
+
+impl BatchRenameDialog {
+    pub fn new(
+        items: Vec<FileItem>,
+        entries: Vec<FileItem>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let rule = RenameRule::default();
+        let mut new_input = |text: String, placeholder: &'static str| {
+            cx.new(|cx| {
+                let mut input = TextInput::new(window, cx).placeholder(placeholder);
+                input.set_text(text, cx);
+                input
+            })
+        };
+        let find = new_input(rule.find.clone(), "查找的内容");
+        let replace = new_input(rule.replace.clone(), "替换为（正则模式下可用 $1）");
+        let template = new_input(rule.template.clone(), "{name}");
+        let counter_start = new_input(rule.counter_start.to_string(), "1");
+        let extension = new_input(String::new(), "留空保持不变，输入 . 移除扩展名");
+
+        let _subscriptions = [&find, &replace, &template, &counter_start, &extension]
+            .into_iter()
+            .map(|input| {
+                cx.subscribe(input, |this, _, event: &TextInputEvent, cx| match event {
+                    TextInputEvent::Change(_) => this.update_preview(cx),
+                    TextInputEvent::Confirm => this.apply(cx),
+                    TextInputEvent::Cancel => cx.emit(BatchRenameEvent::Dismiss),
+                    TextInputEvent::Blur => {}
+                })
+            })
+            .collect();
+
+        let mut dialog = Self {
+            items,
+            entries,
+            find,
+            replace,
+            template,
+            counter_start,
+            extension,
+            use_regex: rule.use_regex,
+            case: rule.case,
+            preview: Ok(vec![]),
+            _subscriptions,
+        };
+        dialog.update_preview(cx);
+        dialog
+    }
+
+    /// 聚焦第一个输入框
+    pub fn focus(&self, window: &mut Window, cx: &App) {
+        self.find.read(cx).focus(window);
+    }
+
+    /// 根据输入框内容构建规则
+    fn rule(&self, cx: &App) -> Result<RenameRule, String> {
+        let counter_start = self.counter_start.read(cx).text();
+        let counter_start = if counter_start.trim().is_empty() {
+            1
+        } else {
+            counter_start
+                .trim()
+                .parse()
+                .map_err(|_| format!("起始编号无效: {}", counter_start))?
+        };
+        let extension = self.extension.read(cx).text();
+        let extension =
+            (!extension.is_empty()).then(|| extension.trim_start_matches('.').to_string());
+
+        Ok(RenameRule {
+            find: self.find.read(cx).text().to_string(),
+            replace: self.replace.read(cx).text().to_string(),
+            use_regex: self.use_regex,
+            template: self.template.read(cx).text().to_string(),
+            counter_start,
+            extension,
+            case: self.case,
+        })
+    }
+
+    fn update_preview(&mut self, cx: &mut Context<Self>) {
+        self.preview = self
+            .rule(cx)
+            .and_then(|rule| rule.preview(&self.items, &self.entries));
+        cx.notify();
+    }
+
+    /// 有改动且所有改动都合法时才能执行
+    fn can_apply(&self) -> bool {
+        self.preview.as_ref().is_ok_and(|rows| {
+            rows.iter().any(PreviewRow::is_changed)
+                && rows
+                    .iter()
+                    .all(|row| !row.is_changed() || row.error.is_none())
+        })
+    }
+
+    fn apply(&mut self, cx: &mut Context<Self>) {
+        if !self.can_apply() {
+            return;
+        }
+        if let Ok(rows) = &self.preview {
+            cx.emit(BatchRenameEvent::Apply(plan(rows)));
+        }
+    }
+
+    fn render_field(&self, label: &'static str, input: &Entity<TextInput>, theme: &Theme) -> Div {
+        div()
+            .flex()
+            .items_center()
+            .gap(theme.spacing.sm)
+            .child(
+                div()
+                    .w(px(80.))
+                    .flex_shrink_0()
+                    .text_sm()
+                    .text_color(theme.colors.muted_foreground)
+                    .child(label),
+            )
+            .child(div().flex_1().child(input.clone()))
+    }
+}
+
+impl Render for BatchRenameDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let this = cx.entity().downgrade();
+
+        let case_buttons = CaseTransform::ALL.into_iter().map(|case| {
+            let this = this.clone();
+            Button::new(SharedString::from(format!("case-{:?}", case)), case.label())
+                .selected(self.case == case)
+                .on_click(move |_, cx| {
+                    let _ = this.update(cx, |dialog, cx| {
+                        dialog.case = case;
+                        dialog.update_preview(cx);
+                    });
+                })
+        });
+
+        let summary = match &self.preview {
+            Ok(rows) => {
+                let changed = rows.iter().filter(|row| row.is_changed()).count();
+                let conflicts = rows
+                    .iter()
+                    .filter(|row| row.is_changed() && row.error.is_some())
+                    .count();
+                if conflicts > 0 {
+                    div()
+                        .text_color(theme.colors.danger)
+                        .child(format!("{} 项存在问题，请调整规则", conflicts))
+                } else {
+                    div()
+                        .text_color(theme.colors.muted_foreground)
+                        .child(format!("将重命名 {} / {} 项", changed, rows.len()))
+                }
+            }
+            Err(e) => div().text_color(theme.colors.danger).child(e.clone()),
+        };
+
+        let rows = self
+            .preview
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|row| {
+                let new_color = match (&row.error, row.is_changed()) {
+                    (Some(_), true) => theme.colors.danger,
+                    (None, true) => theme.colors.foreground,
+                    (_, false) => theme.colors.muted_foreground,
+                };
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(theme.spacing.sm)
+                    .py(theme.spacing.xxs)
+                    .text_sm()
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .text_color(theme.colors.muted_foreground)
+                            .child(row.old_name.clone()),
+                    )
+                    .child(div().text_color(theme.colors.muted_foreground).child("→"))
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .text_color(new_color)
+                            .child(row.new_name.clone()),
+                    )
+                    .when_some(
+                        row.error.clone().filter(|_| row.is_changed()),
+                        |this, error| {
+                            this.child(div().text_xs().text_color(theme.colors.danger).child(error))
+                        },
+                    )
+            });
+
+        let this_cancel = this.clone();
+        let this_apply = this.clone();
+        let this_regex = this.clone();
+
+        Dialog::new(format!("批量重命名（{} 项）", self.items.len()))
+            .width(px(720.))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(theme.spacing.sm)
+                    .child(
+                        self.render_field("查找", &self.find, theme).child(
+                            Button::new("batch-rename-regex", "正则")
+                                .selected(self.use_regex)
+                                .on_click(move |_, cx| {
+                                    let _ = this_regex.update(cx, |dialog, cx| {
+                                        dialog.use_regex = !dialog.use_regex;
+                                        dialog.update_preview(cx);
+                                    });
+                                }),
+                        ),
+                    )
+                    .child(self.render_field("替换为", &self.replace, theme))
+                    .child(self.render_field("名称模板", &self.template, theme))
+                    .child(
+                        div()
+                            .pl(px(88.))
+                            .text_xs()
+                            .text_color(theme.colors.muted_foreground)
+                            .child("{name} 原名称  {n} 编号  {n:3} 补零编号  {date} 修改日期  {date:%Y%m%d} 自定义日期  {ext} 原扩展名"),
+                    )
+                    .child(self.render_field("起始编号", &self.counter_start, theme))
+                    .child(self.render_field("扩展名", &self.extension, theme))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap(theme.spacing.sm)
+                            .child(
+                                div()
+                                    .w(px(80.))
+                                    .flex_shrink_0()
+                                    .text_sm()
+                                    .text_color(theme.colors.muted_foreground)
+                                    .child("大小写"),
+                            )
+                            .children(case_buttons),
+                    ),
+            )
+            .child(div().text_sm().child(summary))
+            .child(
+                div()
+                    .id("batch-rename-preview")
+                    .flex()
+                    .flex_col()
+                    .max_h(px(280.))
+                    .overflow_y_scroll()
+                    .p(theme.spacing.sm)
+                    .rounded(theme.radius.md)
+                    .bg(theme.colors.background)
+                    .border_1()
+                    .border_color(theme.colors.border)
+                    .children(rows),
+            )
+            .footer(Button::new("batch-rename-cancel", "取消").on_click(move |_, cx| {
+                let _ = this_cancel.update(cx, |_, cx| cx.emit(BatchRenameEvent::Dismiss));
+            }))
+            .footer(
+                Button::new("batch-rename-apply", "重命名")
+                    .variant(ButtonVariant::Primary)
+                    .disabled(!self.can_apply())
+                    .on_click(move |_, cx| {
+                        let _ = this_apply.update(cx, |dialog, cx| dialog.apply(cx));
+                    }),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        time::{Duration, SystemTime},
+    };
+
+    use chrono::{DateTime, Local};
+
+    use explorer_common::{FileItem, ItemType};
+
+    // 不使用 `super::*`，以免引入 gpui 的 `test` 属性
+    use super::{
+        CaseTransform, DEFAULT_DATE_FORMAT, FileOperation, MAX_COUNTER_WIDTH, PreviewRow,
+        RenameRule, Token, detect_conflicts, parse_template, plan,
+    };
+
+    fn item(name: &str, item_type: ItemType) -> FileItem {
+        FileItem {
+            name: name.to_string(),
+            path: format!("/dir/{}", name),
+            item_type,
+            is_hidden: name.starts_with('.'),
+            size: 0,
+            // 2024-01-02 00:00:00 UTC
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_153_600),
+            metadata: Default::default(),
+        }
+    }
+
+    fn file(name: &str) -> FileItem {
+        item(name, ItemType::File)
+    }
+
+    fn new_names(rule: &RenameRule, items: &[FileItem]) -> Vec<String> {
+        rule.preview(items, items)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.new_name)
+            .collect()
+    }
+
+    fn row(old_name: &str, new_name: &str) -> PreviewRow {
+        PreviewRow {
+            path: format!("/dir/{}", old_name),
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn parses_templates() {
+        assert_eq!(
+            parse_template("a{name}-{n}{n:3}.{ext}{date}{date:%Y}").unwrap(),
+            [
+                Token::Text("a".into()),
+                Token::Name,
+                Token::Text("-".into()),
+                Token::Counter(1),
+                Token::Counter(3),
+                Token::Text(".".into()),
+                Token::Ext,
+                Token::Date(DEFAULT_DATE_FORMAT.into()),
+                Token::Date("%Y".into()),
+            ]
+        );
+        // 没有闭合的花括号按原文保留
+        assert_eq!(
+            parse_template("x{name").unwrap(),
+            [Token::Text("x{name".into())]
+        );
+        assert_eq!(parse_template("").unwrap(), []);
+        assert_eq!(
+            parse_template("{n:20}").unwrap(),
+            [Token::Counter(MAX_COUNTER_WIDTH)]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        for template in [
+            "{n:x}",
+            "{n:-1}",
+            "{n:21}",
+            "{n:99999999}",
+            "{date:%Q}",
+            "{foo}",
+        ] {
+            assert!(parse_template(template).is_err(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn previews_tokens_and_counters() {
+        let items = [file("a.txt"), file("b.tar.gz"), file(".hidden"), file("c")];
+        let rule = RenameRule {
+            template: "{name}_{n:2}_{ext}".into(),
+            counter_start: 9,
+            ..Default::default()
+        };
+        assert_eq!(
+            new_names(&rule, &items),
+            ["a_09_txt.txt", "b.tar_10_gz.gz", ".hidden_11_", "c_12_"]
+        );
+
+        let rule = RenameRule {
+            template: "{date:%Y%m%d}-{n}".into(),
+            ..Default::default()
+        };
+        let expected: DateTime<Local> = items[0].modified.into();
+        assert_eq!(
+            new_names(&rule, &items[..1]),
+            [format!("{}-1.txt", expected.format("%Y%m%d"))]
+        );
+    }
+
+    #[test]
+    fn previews_replacement_extension_and_case() {
+        let items = [file("IMG_001.JPG"), item("IMG_dir", ItemType::Directory)];
+        let rule = RenameRule {
+            find: r"IMG_(\d+)".into(),
+            replace: "photo $1".into(),
+            use_regex: true,
+            extension: Some("jpeg".into()),
+            case: CaseTransform::Title,
+            ..Default::default()
+        };
+        // 目录不加扩展名
+        assert_eq!(new_names(&rule, &items), ["Photo 001.Jpeg", "Img_Dir"]);
+
+        let rule = RenameRule {
+            find: ".".into(),
+            replace: "-".into(),
+            extension: Some(String::new()),
+            case: CaseTransform::Lower,
+            ..Default::default()
+        };
+        assert_eq!(
+            new_names(&rule, &[file("A.B.txt"), item("d.e", ItemType::Directory)]),
+            ["a-b", "d-e"]
+        );
+
+        let rule = RenameRule {
+            find: "(".into(),
+            use_regex: true,
+            ..Default::default()
+        };
+        assert!(rule.preview(&items, &items).is_err());
+    }
+
+    #[test]
+    fn previews_invalid_names() {
+        let rule = RenameRule {
+            template: "{name}/x".into(),
+            ..Default::default()
+        };
+        let rows = rule.preview(&[file("a")], &[]).unwrap();
+        assert!(rows[0].error.is_some());
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let entries = [file("a"), file("b"), file("c"), file("taken")];
+        let mut rows = vec![
+            row("a", "same"),
+            row("b", "same"),
+            row("c", "taken"),
+            row("taken", "taken"),
+        ];
+        detect_conflicts(&mut rows, &entries);
+        let errors: Vec<bool> = rows.iter().map(|row| row.error.is_some()).collect();
+        // 未改名的条目不报告冲突
+        assert_eq!(errors, [true, true, true, false]);
+
+        // 改名腾出的名称可以被占用，包括互换
+        let mut rows = vec![row("a", "b"), row("b", "a"), row("c", "d")];
+        detect_conflicts(&mut rows, &entries);
+        assert!(rows.iter().all(|row| row.error.is_none()));
+    }
+
+    /// 依次执行重命名，返回最终的名称；中途目标已存在时失败
+    fn simulate(names: &[&str], operations: &[FileOperation]) -> Vec<String> {
+        let mut existing: HashSet<String> =
+            names.iter().map(|name| format!("/dir/{}", name)).collect();
+        for operation in operations {
+            let FileOperation::Rename { from, to } = operation else {
+                panic!("{:?}", operation);
+            };
+            assert!(existing.remove(from), "{} 不存在", from);
+            assert!(existing.insert(to.clone()), "{} 已存在", to);
+        }
+        let mut names: Vec<String> = existing.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn plans_direct_renames() {
+        let rows = [row("a", "x"), row("b", "b"), row("c", "y")];
+        let operations = plan(&rows);
+        assert_eq!(operations.len(), 2);
+        assert_eq!(
+            simulate(&["a", "b", "c"], &operations),
+            ["/dir/b", "/dir/x", "/dir/y"]
+        );
+    }
+
+    #[test]
+    fn plans_cycles_through_temporary_names() {
+        // a → b，b → a
+        let operations = plan(&[row("a", "b"), row("b", "a")]);
+        assert_eq!(operations.len(), 4);
+        assert_eq!(simulate(&["a", "b"], &operations), ["/dir/a", "/dir/b"]);
+        let FileOperation::Rename { from, to } = &operations[3] else {
+            unreachable!()
+        };
+        assert_eq!(
+            (from.contains(".explorer-rename-"), to.as_str()),
+            (true, "/dir/a")
+        );
+
+        // 链式：a → b，b → c
+        let operations = plan(&[row("a", "b"), row("b", "c")]);
+        assert_eq!(simulate(&["a", "b"], &operations), ["/dir/b", "/dir/c"]);
+    }
+}
@@ -0,0 +1,397 @@
+//! 文件剪贴板
+//!
+//! 与桌面环境互通的文件复制/剪切：
+//! - 写入：Linux 下有 X11 显示（包括 Wayland 会话中的 XWayland，混成器会同步到 Wayland 剪贴板）时，
+//!   由后台线程持有 CLIPBOARD 选区，同时提供 `x-special/gnome-copied-files`（GNOME、Cinnamon、
+//!   MATE、Xfce 等文件管理器使用）、`text/uri-list` 和 `application/x-kde-cutselection`
+//!   （Dolphin 使用）以及纯文本路径。只有 Wayland 时通过 `wl-copy` 写入，它每次只能提供一种格式，
+//!   KDE 桌面写入 `text/uri-list`，其他桌面写入 `x-special/gnome-copied-files`；
+//!   都不可用时由调用方退化为纯文本路径（每行一个）
+//! - 读取：依次尝试 `x-special/gnome-copied-files`、`text/uri-list`，最后回退到纯文本
+//!   （`file://` URI 或绝对路径，兼容终端中复制的路径）
+
+use std::{
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use url::Url;
+
+/// gnome-copied-files 的 MIME 类型
+const GNOME_COPIED_FILES: &str = "x-special/gnome-copied-files";
+/// uri-list 的 MIME 类型
+const URI_LIST: &str = "text/uri-list";
+/// KDE 标记剪切的 MIME 类型（内容为 `1`）
+#[cfg(target_os = "linux")]
+const KDE_CUT_SELECTION: &str = "application/x-kde-cutselection";
+
+/// 剪贴板中文件的处理方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardMode {
+    Copy,
+    Cut,
+}
+
+/// 剪贴板中的文件
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileClipboard {
+    pub mode: ClipboardMode,
+    pub paths: Vec<String>,
+}
+
+impl FileClipboard {
+    pub fn new(mode: ClipboardMode, paths: Vec<String>) -> Self {
+        Self { mode, paths }
+    }
+
+    /// 编码为 `text/uri-list`（RFC 2483，CRLF 分隔）
+    pub fn to_uri_list(&self) -> String {
+        self.uris().map(|uri| format!("{}\r\n", uri)).collect()
+    }
+
+    /// 编码为 `x-special/gnome-copied-files`（首行为 copy/cut）
+    pub fn to_gnome_copied_files(&self) -> String {
+        let mode = match self.mode {
+            ClipboardMode::Copy => "copy",
+            ClipboardMode::Cut => "cut",
+        };
+        std::iter::once(mode.to_string())
+            .chain(self.uris())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 编码为纯文本（每行一个路径）
+    pub fn to_plain_text(&self) -> String {
+        self.paths.join("\n")
+    }
+
+    /// 解析剪贴板内容（自动识别 gnome-copied-files、uri-list 和纯文本路径）
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .peekable();
+
+        let mode = match lines.peek().copied() {
+            Some("copy") => {
+                lines.next();
+                ClipboardMode::Copy
+            }
+            Some("cut") => {
+                lines.next();
+                ClipboardMode::Cut
+            }
+            _ => ClipboardMode::Copy,
+        };
+
+        let paths = lines
+            // uri-list 中以 # 开头的行是注释
+            .filter(|line| !line.starts_with('#'))
+            .map(parse_line)
+            .collect::<Option<Vec<_>>>()?;
+        (!paths.is_empty()).then_some(Self { mode, paths })
+    }
+
+    fn uris(&self) -> impl Iterator<Item = String> + '_ {
+        self.paths
+            .iter()
+            .filter_map(|path| Url::from_file_path(path).ok().map(|url| url.to_string()))
+    }
+}
+
+/// 解析单行：`file://` URI 或绝对路径
+fn parse_line(line: &str) -> Option<String> {
+    if line.starts_with("file://") {
+        let path = Url::parse(line).ok()?.to_file_path().ok()?;
+        return Some(path.display().to_string());
+    }
+    Path::new(line).is_absolute().then(|| line.to_string())
+}
+
+// ===== 系统剪贴板 =====
+
+/// 写入系统剪贴板，没有可用的剪贴板时返回 `false`
+pub fn write_system(clipboard: &FileClipboard) -> bool {
+    #[cfg(target_os = "linux")]
+    if std::env::var_os("DISPLAY").is_some() {
+        let mut targets = vec![
+            (GNOME_COPIED_FILES, clipboard.to_gnome_copied_files()),
+            (URI_LIST, clipboard.to_uri_list()),
+        ];
+        if clipboard.mode == ClipboardMode::Cut {
+            targets.push((KDE_CUT_SELECTION, "1".to_string()));
+        }
+        match x11::own_selection(targets, clipboard.to_plain_text()) {
+            Ok(()) => return true,
+            Err(e) => tracing::warn!("无法持有 X11 剪贴板，改用剪贴板工具: {}", e),
+        }
+    }
+
+    let is_kde = std::env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.contains("KDE"));
+    let (mime, content) = if is_kde {
+        (URI_LIST, clipboard.to_uri_list())
+    } else {
+        (GNOME_COPIED_FILES, clipboard.to_gnome_copied_files())
+    };
+    clipboard_tools(ToolAction::Write(mime))
+        .into_iter()
+        .any(|(program, args)| run_write(program, &args, &content))
+}
+
+/// 从系统剪贴板读取文件（依次尝试 gnome-copied-files 和 uri-list）
+pub fn read_system() -> Option<FileClipboard> {
+    [GNOME_COPIED_FILES, URI_LIST].into_iter().find_map(|mime| {
+        clipboard_tools(ToolAction::Read(mime))
+            .into_iter()
+            .find_map(|(program, args)| run_read(program, &args))
+            .and_then(|text| FileClipboard::parse(&text))
+    })
+}
+
+enum ToolAction {
+    Read(&'static str),
+    Write(&'static str),
+}
+
+/// 按当前会话类型列出可用的剪贴板工具及参数
+#[cfg(target_os = "linux")]
+fn clipboard_tools(action: ToolAction) -> Vec<(&'static str, Vec<&'static str>)> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let x11 = std::env::var_os("DISPLAY").is_some();
+
+    let mut tools = vec![];
+    match action {
+        ToolAction::Write(mime) => {
+            if wayland {
+                tools.push(("wl-copy", vec!["--type", mime]));
+            }
+            if x11 {
+                tools.push(("xclip", vec!["-selection", "clipboard", "-t", mime, "-i"]));
+            }
+        }
+        ToolAction::Read(mime) => {
+            if wayland {
+                tools.push(("wl-paste", vec!["--no-newline", "--type", mime]));
+            }
+            if x11 {
+                tools.push(("xclip", vec!["-selection", "clipboard", "-t", mime, "-o"]));
+            }
+        }
+    }
+    tools
+}
+
+/// 其他平台暂不支持文件格式，由调用方回退到纯文本
+#[cfg(not(target_os = "linux"))]
+fn clipboard_tools(_action: ToolAction) -> Vec<(&'static str, Vec<&'static str>)> {
+    vec![]
+}
+
+fn run_write(program: &str, args: &[&str], content: &str) -> bool {
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = child else {
+        return false;
+    };
+
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(content.as_bytes()).is_ok());
+    // wl-copy 和 xclip 读取完输入后会转入后台继续持有剪贴板
+    let exited = child.wait().is_ok_and(|status| status.success());
+    if !(written && exited) {
+        tracing::warn!("写入剪贴板失败: {}", program);
+    }
+    written && exited
+}
+
+fn run_read(program: &str, args: &[&str]) -> Option<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    let status = child.wait().ok()?;
+    (status.success() && !output.trim().is_empty()).then_some(output)
+}
+
+/// X11 剪贴板选区的持有者
+///
+/// 每次写入在新线程中建立连接并取得选区，其他程序（包括下一次写入）取得选区后线程退出。
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::{error::Error, sync::mpsc, thread};
+
+    use x11rb::{
+        COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME, NONE,
+        connection::Connection,
+        protocol::{
+            Event,
+            xproto::{
+                Atom, AtomEnum, ConnectionExt as _, CreateWindowAux, EventMask, PropMode,
+                SELECTION_NOTIFY_EVENT, SelectionNotifyEvent, SelectionRequestEvent, WindowClass,
+            },
+        },
+        rust_connection::RustConnection,
+        wrapper::ConnectionExt as _,
+    };
+
+    type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+    /// 纯文本的 MIME 类型
+    const TEXT_TARGETS: [&str; 3] = ["UTF8_STRING", "text/plain;charset=utf-8", "text/plain"];
+
+    /// 提供的格式及其内容
+    struct Offer {
+        targets_atom: Atom,
+        entries: Vec<(Atom, Vec<u8>)>,
+    }
+
+    /// 取得 CLIPBOARD 选区并在后台提供 `targets`（MIME 类型和内容）以及纯文本 `text`
+    pub fn own_selection(targets: Vec<(&'static str, String)>, text: String) -> Result<()> {
+        let (ready, owned) = mpsc::channel();
+        thread::Builder::new()
+            .name("clipboard-x11".to_string())
+            .spawn(move || {
+                if let Err(e) = serve(targets, text, &ready) {
+                    // 取得选区前失败时通知调用方，之后失败只记录
+                    if ready.send(Err(e.to_string())).is_err() {
+                        tracing::warn!("X11 剪贴板连接中断: {}", e);
+                    }
+                }
+            })?;
+        owned
+            .recv()
+            .map_err(|_| "剪贴板线程意外退出")?
+            .map_err(Into::into)
+    }
+
+    fn serve(
+        targets: Vec<(&'static str, String)>,
+        text: String,
+        ready: &mpsc::Sender<std::result::Result<(), String>>,
+    ) -> Result<()> {
+        let (connection, screen) = x11rb::connect(None)?;
+        let root = connection.setup().roots[screen].root;
+        let window = connection.generate_id()?;
+        connection.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new(),
+        )?;
+
+        let clipboard = intern(&connection, "CLIPBOARD")?;
+        let mut entries = vec![];
+        for (mime, content) in targets {
+            entries.push((intern(&connection, mime)?, content.into_bytes()));
+        }
+        for mime in TEXT_TARGETS {
+            entries.push((intern(&connection, mime)?, text.clone().into_bytes()));
+        }
+        let offer = Offer {
+            targets_atom: intern(&connection, "TARGETS")?,
+            entries,
+        };
+
+        connection.set_selection_owner(window, clipboard, CURRENT_TIME)?;
+        if connection.get_selection_owner(clipboard)?.reply()?.owner != window {
+            return Err("其他程序持有剪贴板".into());
+        }
+        let _ = ready.send(Ok(()));
+
+        loop {
+            match connection.wait_for_event()? {
+                Event::SelectionRequest(request) => respond(&connection, &offer, &request)?,
+                Event::SelectionClear(_) => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+
+    fn intern(connection: &RustConnection, name: &str) -> Result<Atom> {
+        Ok(connection
+            .intern_atom(false, name.as_bytes())?
+            .reply()?
+            .atom)
+    }
+
+    /// 把请求的格式写入请求方窗口的属性，并通知请求方（不支持的格式回复 `NONE`）
+    fn respond(
+        connection: &RustConnection,
+        offer: &Offer,
+        request: &SelectionRequestEvent,
+    ) -> Result<()> {
+        // 过时的客户端不指定属性，此时使用格式名作为属性
+        let property = if request.property == NONE {
+            request.target
+        } else {
+            request.property
+        };
+        let stored = if request.target == offer.targets_atom {
+            let atoms: Vec<Atom> = std::iter::once(offer.targets_atom)
+                .chain(offer.entries.iter().map(|(atom, _)| *atom))
+                .collect();
+            connection.change_property32(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                AtomEnum::ATOM,
+                &atoms,
+            )?;
+            true
+        } else if let Some((target, content)) = offer
+            .entries
+            .iter()
+            .find(|(atom, _)| *atom == request.target)
+        {
+            connection.change_property8(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                *target,
+                content,
+            )?;
+            true
+        } else {
+            false
+        };
+
+        connection.send_event(
+            false,
+            request.requestor,
+            EventMask::NO_EVENT,
+            SelectionNotifyEvent {
+                response_type: SELECTION_NOTIFY_EVENT,
+                sequence: 0,
+                time: request.time,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                property: if stored { property } else { NONE },
+            },
+        )?;
+        connection.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! 命令注册表
+//!
+//! 记录可以在命令面板中执行的动作及其显示名称。动作仍然通过 gpui 的键位绑定和
+//! `on_action` 处理，注册表只负责列举，新增动作时在 [`init`] 中登记即可出现在命令面板中。
+
+use gpui::{Action, App, Global, SharedString};
+
+use crate::{
+    AnalyzeDiskUsage, BatchRename, ClearRecentLocations, CompressSelected, ComputeFolderSize,
+    CopyFiles, CopyPath, CopyRelativePath, CutFiles, EditLocation, ExtractHere, ExtractToFolder,
+    GoToParent, NewFolder, OpenSelected, OpenTerminal, PasteFiles, PasteLinks, PinToQuickAccess,
+    Redo, Rename, SelectAll, ShowProperties, SplitHorizontal, SplitVertical, ToggleCommandPalette,
+    TrashSelected, Undo,
+};
+
+/// 已登记的命令
+pub struct Command {
+    /// 显示名称
+    pub label: SharedString,
+    pub action: Box<dyn Action>,
+}
+
+impl Command {
+    /// 命令的唯一标识（动作名称，例如 `explorer::Undo`）
+    pub fn id(&self) -> &'static str {
+        self.action.name()
+    }
+}
+
+/// 命令注册表（全局状态）
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl Global for CommandRegistry {}
+
+impl CommandRegistry {
+    /// 登记一个命令，同一个动作只保留第一次登记的名称
+    pub fn register(&mut self, label: impl Into<SharedString>, action: impl Action) {
+        if self.find(action.name()).is_some() {
+            return;
+        }
+        self.commands.push(Command {
+            label: label.into(),
+            action: Box::new(action),
+        });
+    }
+
+    /// 所有命令（按登记顺序）
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// 按标识查找命令
+    pub fn find(&self, id: &str) -> Option<&Command> {
+        self.commands.iter().find(|command| command.id() == id)
+    }
+}
+
+/// 登记 Explorer 的所有命令
+pub fn init(cx: &mut App) {
+    let registry = cx.default_global::<CommandRegistry>();
+    registry.register("打开", OpenSelected);
+    registry.register("新建文件夹", NewFolder);
+    registry.register("重命名", Rename);
+    registry.register("批量重命名", BatchRename);
+    registry.register("剪切", CutFiles);
+    registry.register("复制", CopyFiles);
+    registry.register("粘贴", PasteFiles);
+    registry.register("粘贴为链接", PasteLinks);
+    registry.register("移入回收站", TrashSelected);
+    registry.register("压缩", CompressSelected);
+    registry.register("解压到此处", ExtractHere);
+    registry.register("解压到文件夹", ExtractToFolder);
+    registry.register("全选", SelectAll);
+    registry.register("上一级目录", GoToParent);
+    registry.register("编辑地址", EditLocation);
+    registry.register("固定到快捷访问", PinToQuickAccess);
+    registry.register("清除最近位置", ClearRecentLocations);
+    registry.register("计算文件夹大小", ComputeFolderSize);
+    registry.register("分析磁盘占用", AnalyzeDiskUsage);
+    registry.register("撤销", Undo);
+    registry.register("重做", Redo);
+    registry.register("复制路径", CopyPath);
+    registry.register("复制相对路径", CopyRelativePath);
+    registry.register("在终端中打开", OpenTerminal);
+    registry.register("属性", ShowProperties);
+    registry.register("横向拆分面板", SplitHorizontal);
+    registry.register("纵向拆分面板", SplitVertical);
+    registry.register("命令面板", ToggleCommandPalette);
+}
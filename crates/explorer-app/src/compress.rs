@@ -0,0 +1,228 @@
+//! 压缩对话框
+//!
+//! 选择压缩包的名称、格式和压缩级别，确认后生成一个压缩操作交给文件操作队列。
+
+use gpui::{prelude::*, *};
+
+use explorer_common::{ArchiveFormat, CompressOptions, CompressionLevel, FileItem, ItemType};
+use explorer_component::{Button, ButtonVariant, Dialog, TextInput, TextInputEvent, Theme};
+
+use crate::{file_ops::FileOperation, paths};
+
+fn format_label(format: ArchiveFormat) -> &'static str {
+    match format {
+        ArchiveFormat::Zip => "zip",
+        ArchiveFormat::TarGz => "tar.gz",
+        ArchiveFormat::TarZst => "tar.zst",
+        ArchiveFormat::Tar => "tar",
+        ArchiveFormat::TarBz2 => "tar.bz2",
+        ArchiveFormat::TarXz => "tar.xz",
+    }
+}
+
+fn level_label(level: CompressionLevel) -> &'static str {
+    match level {
+        CompressionLevel::Fast => "最快",
+        CompressionLevel::Normal => "标准",
+        CompressionLevel::Best => "最小体积",
+    }
+}
+
+/// 压缩对话框事件
+pub enum CompressEvent {
+    /// 确认压缩，附带压缩操作
+    Apply(FileOperation),
+    /// 关闭对话框
+    Dismiss,
+}
+
+/// 压缩对话框
+pub struct CompressDialog {
+    sources: Vec<String>,
+    /// 压缩包所在的目录
+    dir: String,
+    /// 目录中已有的条目（用于检查重名）
+    entries: Vec<FileItem>,
+    name: Entity<TextInput>,
+    format: ArchiveFormat,
+    level: CompressionLevel,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<CompressEvent> for CompressDialog {}
+
+impl CompressDialog {
+    pub fn new(
+        sources: Vec<String>,
+        dir: String,
+        entries: Vec<FileItem>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        // 单个条目以其主文件名命名，多个条目以所在目录命名
+        let default_name = match sources.as_slice() {
+            [source] => {
+                let name = paths::file_name(source).unwrap_or_default();
+                let is_dir = entries
+                    .iter()
+                    .any(|entry| entry.path == *source && entry.item_type == ItemType::Directory);
+                match name.rfind('.') {
+                    Some(index) if index > 0 && !is_dir => name[..index].to_string(),
+                    _ => name,
+                }
+            }
+            _ => paths::file_name(&dir).unwrap_or_else(|| "压缩包".to_string()),
+        };
+        let name = cx.new(|cx| {
+            let mut input = TextInput::new(window, cx).placeholder("压缩包名称");
+            input.set_text(default_name, cx);
+            input
+        });
+        let _subscription =
+            cx.subscribe(&name, |this, _, event: &TextInputEvent, cx| match event {
+                TextInputEvent::Change(_) => cx.notify(),
+                TextInputEvent::Confirm => this.apply(cx),
+                TextInputEvent::Cancel => cx.emit(CompressEvent::Dismiss),
+                TextInputEvent::Blur => {}
+            });
+
+        Self {
+            sources,
+            dir,
+            entries,
+            name,
+            format: ArchiveFormat::Zip,
+            level: CompressionLevel::Normal,
+            _subscription,
+        }
+    }
+
+    pub fn focus(&self, window: &mut Window, cx: &App) {
+        self.name.read(cx).focus(window);
+    }
+
+    /// 压缩包的完整文件名
+    fn file_name(&self, cx: &App) -> String {
+        format!(
+            "{}{}",
+            self.name.read(cx).text().trim(),
+            self.format.extension()
+        )
+    }
+
+    fn validate(&self, cx: &App) -> Result<String, String> {
+        if self.name.read(cx).text().trim().is_empty() {
+            return Err("名称不能为空".to_string());
+        }
+        let file_name = self.file_name(cx);
+        paths::validate_file_name(&file_name, "", &self.entries)?;
+        Ok(file_name)
+    }
+
+    fn apply(&mut self, cx: &mut Context<Self>) {
+        let Ok(file_name) = self.validate(cx) else {
+            return;
+        };
+        cx.emit(CompressEvent::Apply(FileOperation::Compress {
+            sources: self.sources.clone(),
+            target: paths::join_path(&self.dir, &file_name),
+            options: CompressOptions {
+                format: self.format,
+                level: self.level,
+            },
+        }));
+    }
+
+    fn render_row(&self, label: &'static str, theme: &Theme) -> Div {
+        div().flex().items_center().gap(theme.spacing.sm).child(
+            div()
+                .w(px(80.))
+                .flex_shrink_0()
+                .text_sm()
+                .text_color(theme.colors.muted_foreground)
+                .child(label),
+        )
+    }
+}
+
+impl Render for CompressDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let this = cx.entity().downgrade();
+
+        let format_buttons = ArchiveFormat::WRITABLE.into_iter().map(|format| {
+            let this = this.clone();
+            Button::new(
+                SharedString::from(format!("compress-format-{:?}", format)),
+                format_label(format),
+            )
+            .selected(self.format == format)
+            .on_click(move |_, cx| {
+                let _ = this.update(cx, |dialog, cx| {
+                    dialog.format = format;
+                    cx.notify();
+                });
+            })
+        });
+        let level_buttons = [
+            CompressionLevel::Fast,
+            CompressionLevel::Normal,
+            CompressionLevel::Best,
+        ]
+        .into_iter()
+        .map(|level| {
+            let this = this.clone();
+            Button::new(
+                SharedString::from(format!("compress-level-{:?}", level)),
+                level_label(level),
+            )
+            .selected(self.level == level)
+            .on_click(move |_, cx| {
+                let _ = this.update(cx, |dialog, cx| {
+                    dialog.level = level;
+                    cx.notify();
+                });
+            })
+        });
+
+        let validation = self.validate(cx);
+        let summary = match &validation {
+            Ok(file_name) => div()
+                .text_color(theme.colors.muted_foreground)
+                .child(format!("将 {} 项压缩为 {}", self.sources.len(), file_name)),
+            Err(e) => div().text_color(theme.colors.danger).child(e.clone()),
+        };
+
+        let this_cancel = this.clone();
+        let this_apply = this.clone();
+
+        Dialog::new("压缩")
+            .width(px(480.))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(theme.spacing.sm)
+                    .child(
+                        self.render_row("名称", theme)
+                            .child(div().flex_1().child(self.name.clone())),
+                    )
+                    .child(self.render_row("格式", theme).children(format_buttons))
+                    .child(self.render_row("压缩级别", theme).children(level_buttons)),
+            )
+            .child(div().text_sm().child(summary))
+            .footer(
+                Button::new("compress-cancel", "取消").on_click(move |_, cx| {
+                    let _ = this_cancel.update(cx, |_, cx| cx.emit(CompressEvent::Dismiss));
+                }),
+            )
+            .footer(
+                Button::new("compress-apply", "压缩")
+                    .variant(ButtonVariant::Primary)
+                    .disabled(validation.is_err())
+                    .on_click(move |_, cx| {
+                        let _ = this_apply.update(cx, |dialog, cx| dialog.apply(cx));
+                    }),
+            )
+    }
+}
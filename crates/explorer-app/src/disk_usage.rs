@@ -0,0 +1,930 @@
+//! 磁盘占用分析
+//!
+//! 从一个文件夹开始，多个后台任务通过存储提供者并行列出目录，界面边接收边累计每个
+//! 文件夹的大小。左侧是可排序、可展开的大小树，右侧是当前层级的矩形树图（squarified
+//! 布局）。双击文件夹进入下一层；选中的条目可以移入回收站或永久删除，实际操作由
+//! [`DiskUsageEvent`] 交给主界面执行。
+//!
+//! 开启“同一文件系统”时，设备号与起点不同的文件夹（其他文件系统的挂载点）不会被扫描。
+//! 符号链接不跟随，也不计入大小。
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    f32::consts::FRAC_PI_2,
+    sync::{
+        Arc,
+        atomic::{self, AtomicUsize},
+    },
+    time::Duration,
+};
+
+use gpui::{prelude::*, *};
+use smol::channel::{self, Receiver};
+
+use explorer_component::{Button, ButtonVariant, Dialog, Icon, IconName, ListItem, Theme};
+use explorer_storage::{ItemType, StorageProvider};
+
+use crate::{paths, properties::format_size};
+
+/// 并行扫描的任务数
+const WORKERS: usize = 8;
+/// 界面合并扫描结果的间隔
+const REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+/// 大小树最多显示的行数
+const MAX_ROWS: usize = 500;
+/// 树图最多显示的矩形数（更小的条目不显示）
+const MAX_TILES: usize = 150;
+/// 树图区域的尺寸
+const TREEMAP_WIDTH: f32 = 460.;
+const TREEMAP_HEIGHT: f32 = 400.;
+
+// ===== 扫描 =====
+
+/// 扫描到的一个条目
+struct ScannedEntry {
+    name: String,
+    path: String,
+    size: u64,
+    is_dir: bool,
+}
+
+/// 后台扫描发给界面的消息
+enum ScanEvent {
+    /// 列出了一个文件夹
+    Listed {
+        dir: String,
+        entries: Vec<ScannedEntry>,
+    },
+    /// 文件夹无法读取
+    Failed { dir: String, error: String },
+}
+
+/// 开始并行扫描 `root`，返回消息接收端和后台任务（丢弃任务即停止扫描）
+///
+/// 每个文件夹的消息都先于其子文件夹的消息发出。`device` 不为空时只进入该设备上的文件夹
+fn start_scan(
+    provider: Arc<dyn StorageProvider>,
+    root: String,
+    device: Option<u64>,
+    executor: &BackgroundExecutor,
+) -> (Receiver<ScanEvent>, Vec<Task<()>>) {
+    let (work_tx, work_rx) = channel::unbounded::<String>();
+    let (event_tx, event_rx) = channel::unbounded();
+    // 已加入队列但还没有扫描完的文件夹数，降为 0 时关闭队列让任务退出
+    let pending = Arc::new(AtomicUsize::new(1));
+    let _ = work_tx.try_send(root);
+
+    let workers = (0..WORKERS)
+        .map(|_| {
+            let provider = provider.clone();
+            let work_tx = work_tx.clone();
+            let work_rx = work_rx.clone();
+            let event_tx = event_tx.clone();
+            let pending = pending.clone();
+            executor.spawn(async move {
+                while let Ok(dir) = work_rx.recv().await {
+                    match provider.list_entries(&dir).await {
+                        Ok(items) => {
+                            let mut subdirs = vec![];
+                            let entries = items
+                                .into_iter()
+                                .filter_map(|item| {
+                                    let is_dir = match item.item_type {
+                                        ItemType::Directory => true,
+                                        ItemType::File => false,
+                                        ItemType::Symlink => return None,
+                                    };
+                                    if is_dir {
+                                        if device.is_some()
+                                            && item.metadata.device.is_some()
+                                            && item.metadata.device != device
+                                        {
+                                            return None;
+                                        }
+                                        subdirs.push(item.path.clone());
+                                    }
+                                    Some(ScannedEntry {
+                                        name: item.name,
+                                        path: item.path,
+                                        size: if is_dir { 0 } else { item.size },
+                                        is_dir,
+                                    })
+                                })
+                                .collect();
+                            let _ = event_tx.send(ScanEvent::Listed { dir, entries }).await;
+                            pending.fetch_add(subdirs.len(), atomic::Ordering::SeqCst);
+                            for subdir in subdirs {
+                                let _ = work_tx.try_send(subdir);
+                            }
+                        }
+                        Err(e) => {
+                            let _ = event_tx
+                                .send(ScanEvent::Failed {
+                                    dir,
+                                    error: e.to_string(),
+                                })
+                                .await;
+                        }
+                    }
+                    if pending.fetch_sub(1, atomic::Ordering::SeqCst) == 1 {
+                        work_tx.close();
+                    }
+                }
+            })
+        })
+        .collect();
+    (event_rx, workers)
+}
+
+// ===== 大小树 =====
+
+/// 树中的一个条目
+struct Node {
+    name: String,
+    path: String,
+    is_dir: bool,
+    /// 大小（文件夹为其中所有文件之和）
+    size: u64,
+    /// 包含的文件数
+    files: u64,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// 文件夹是否已经列出
+    scanned: bool,
+}
+
+/// 扫描结果组成的树，第 0 项为起点
+struct UsageTree {
+    nodes: Vec<Node>,
+    index: HashMap<String, usize>,
+    /// 无法读取的文件夹数
+    failed: usize,
+}
+
+impl UsageTree {
+    fn new(root: &str) -> Self {
+        Self {
+            nodes: vec![Node {
+                name: paths::file_name(root).unwrap_or_else(|| root.to_string()),
+                path: root.to_string(),
+                is_dir: true,
+                size: 0,
+                files: 0,
+                parent: None,
+                children: vec![],
+                scanned: false,
+            }],
+            index: HashMap::from([(root.to_string(), 0)]),
+            failed: 0,
+        }
+    }
+
+    /// 合并一条扫描结果（已被删除的文件夹的结果会被忽略）
+    fn apply(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::Listed { dir, entries } => {
+                let Some(&parent) = self.index.get(&dir) else {
+                    return;
+                };
+                self.nodes[parent].scanned = true;
+                for entry in entries {
+                    let ix = self.nodes.len();
+                    self.index.insert(entry.path.clone(), ix);
+                    self.nodes[parent].children.push(ix);
+                    let (size, is_dir) = (entry.size, entry.is_dir);
+                    self.nodes.push(Node {
+                        name: entry.name,
+                        path: entry.path,
+                        is_dir,
+                        size: 0,
+                        files: 0,
+                        parent: Some(parent),
+                        children: vec![],
+                        scanned: false,
+                    });
+                    if !is_dir {
+                        self.add(ix, size as i64, 1);
+                    }
+                }
+            }
+            ScanEvent::Failed { dir, error } => {
+                tracing::debug!("无法读取 {}: {}", dir, error);
+                if let Some(&ix) = self.index.get(&dir) {
+                    self.nodes[ix].scanned = true;
+                }
+                self.failed += 1;
+            }
+        }
+    }
+
+    /// 把大小和文件数累加到条目及其所有上级
+    fn add(&mut self, ix: usize, size: i64, files: i64) {
+        let mut current = Some(ix);
+        while let Some(ix) = current {
+            let node = &mut self.nodes[ix];
+            node.size = node.size.saturating_add_signed(size);
+            node.files = node.files.saturating_add_signed(files);
+            current = node.parent;
+        }
+    }
+
+    /// 从树中移除条目（及其下的所有条目），起点不能移除
+    fn remove(&mut self, ix: usize) {
+        let Some(parent) = self.nodes[ix].parent else {
+            return;
+        };
+        let (size, files) = (self.nodes[ix].size as i64, self.nodes[ix].files as i64);
+        self.add(parent, -size, -files);
+        self.nodes[parent].children.retain(|&child| child != ix);
+
+        let mut stack = vec![ix];
+        while let Some(ix) = stack.pop() {
+            self.index.remove(&self.nodes[ix].path);
+            stack.extend(self.nodes[ix].children.iter().copied());
+        }
+    }
+
+    /// `ancestor` 是否为 `ix` 本身或其上级
+    fn contains(&self, ancestor: usize, ix: usize) -> bool {
+        let mut current = Some(ix);
+        while let Some(ix) = current {
+            if ix == ancestor {
+                return true;
+            }
+            current = self.nodes[ix].parent;
+        }
+        false
+    }
+
+    /// 按排序方式排列的子条目
+    fn sorted_children(&self, ix: usize, sort: SortBy) -> Vec<usize> {
+        let mut children = self.nodes[ix].children.clone();
+        children.sort_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            match sort {
+                SortBy::Size => b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)),
+                SortBy::Name => match (a.is_dir, b.is_dir) {
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    _ => paths::name_key(&a.name).cmp(&paths::name_key(&b.name)),
+                },
+            }
+        });
+        children
+    }
+}
+
+// ===== 树图布局 =====
+
+/// 树图中的矩形（像素）
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+/// squarified 布局：把按大小降序排列的 `sizes` 铺满 `rect`，使矩形尽量接近正方形
+fn squarify(sizes: &[u64], rect: Rect) -> Vec<Rect> {
+    let total: u64 = sizes.iter().sum();
+    if total == 0 {
+        return vec![];
+    }
+    let scale = (rect.w * rect.h) as f64 / total as f64;
+    let areas: Vec<f64> = sizes.iter().map(|&size| size as f64 * scale).collect();
+
+    // 一行中最差的长宽比
+    let worst = |row: &[f64], side: f64| {
+        let sum: f64 = row.iter().sum();
+        let max = row.iter().copied().fold(0., f64::max);
+        let min = row.iter().copied().fold(f64::INFINITY, f64::min);
+        let (sum2, side2) = (sum * sum, side * side);
+        (side2 * max / sum2).max(sum2 / (side2 * min))
+    };
+
+    let mut result = Vec::with_capacity(areas.len());
+    let mut rest = rect;
+    let mut start = 0;
+    while start < areas.len() {
+        let side = rest.w.min(rest.h) as f64;
+        let mut end = start + 1;
+        while end < areas.len()
+            && worst(&areas[start..=end], side) <= worst(&areas[start..end], side)
+        {
+            end += 1;
+        }
+
+        // 沿短边排列这一行
+        let row_area: f64 = areas[start..end].iter().sum();
+        if rest.w >= rest.h {
+            let w = (row_area / rest.h as f64) as f32;
+            let mut y = rest.y;
+            for &area in &areas[start..end] {
+                let h = (area / w as f64) as f32;
+                result.push(Rect { x: rest.x, y, w, h });
+                y += h;
+            }
+            rest.x += w;
+            rest.w -= w;
+        } else {
+            let h = (row_area / rest.w as f64) as f32;
+            let mut x = rest.x;
+            for &area in &areas[start..end] {
+                let w = (area / h as f64) as f32;
+                result.push(Rect { x, y: rest.y, w, h });
+                x += w;
+            }
+            rest.y += h;
+            rest.h -= h;
+        }
+        start = end;
+    }
+    result
+}
+
+// ===== 视图 =====
+
+/// 大小树的排序方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SortBy {
+    Size,
+    Name,
+}
+
+/// 磁盘占用视图发出的事件
+pub enum DiskUsageEvent {
+    /// 移入回收站
+    Trash(Vec<String>),
+    /// 永久删除
+    Delete(Vec<String>),
+    /// 关闭
+    Dismiss,
+}
+
+/// 磁盘占用分析视图
+pub struct DiskUsageView {
+    focus_handle: FocusHandle,
+    provider: Arc<dyn StorageProvider>,
+    root: String,
+    tree: UsageTree,
+    /// 当前层级（树图显示其子条目，大小树从这里展开）
+    current: usize,
+    selected: Option<usize>,
+    expanded: HashSet<usize>,
+    sort: SortBy,
+    /// 只扫描与起点相同的文件系统
+    one_filesystem: bool,
+    /// 永久删除需要再点一次确认
+    confirm_delete: bool,
+    /// 正在进行的扫描，丢弃即停止
+    scan: Option<Task<()>>,
+}
+
+impl EventEmitter<DiskUsageEvent> for DiskUsageView {}
+
+impl DiskUsageView {
+    pub fn new(
+        root: String,
+        provider: Arc<dyn StorageProvider>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut view = Self {
+            focus_handle: cx.focus_handle(),
+            provider,
+            tree: UsageTree::new(&root),
+            root,
+            current: 0,
+            selected: None,
+            expanded: HashSet::new(),
+            sort: SortBy::Size,
+            one_filesystem: true,
+            confirm_delete: false,
+            scan: None,
+        };
+        view.rescan(window, cx);
+        view
+    }
+
+    pub fn focus(&self, window: &mut Window) {
+        self.focus_handle.focus(window);
+    }
+
+    /// 从头开始扫描（替换正在进行的扫描）
+    fn rescan(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        tracing::info!("分析磁盘占用: {}", self.root);
+        self.tree = UsageTree::new(&self.root);
+        self.current = 0;
+        self.selected = None;
+        self.expanded.clear();
+        self.confirm_delete = false;
+
+        let provider = self.provider.clone();
+        let root = self.root.clone();
+        let one_filesystem = self.one_filesystem;
+        self.scan = Some(cx.spawn_in(window, async move |this, cx| {
+            let device = if one_filesystem {
+                let provider = provider.clone();
+                let root = root.clone();
+                cx.background_executor()
+                    .spawn(async move { provider.get_metadata(&root).await })
+                    .await
+                    .ok()
+                    .and_then(|item| item.metadata.device)
+            } else {
+                None
+            };
+            let (events, _workers) = start_scan(provider, root, device, cx.background_executor());
+
+            // 成批合并扫描结果，避免每个文件夹都重新渲染
+            while let Ok(first) = events.recv().await {
+                let mut batch = vec![first];
+                while let Ok(event) = events.try_recv() {
+                    batch.push(event);
+                }
+                let updated = this.update(cx, |view, cx| {
+                    for event in batch {
+                        view.tree.apply(event);
+                    }
+                    cx.notify();
+                });
+                if updated.is_err() {
+                    return;
+                }
+                cx.background_executor().timer(REFRESH_INTERVAL).await;
+            }
+
+            let _ = this.update(cx, |view, cx| {
+                tracing::info!(
+                    "磁盘占用分析完成: {}，{} 个文件",
+                    format_size(view.tree.nodes[0].size),
+                    view.tree.nodes[0].files
+                );
+                view.scan = None;
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    /// 停止扫描，保留已统计的结果
+    fn stop(&mut self, cx: &mut Context<Self>) {
+        if self.scan.take().is_some() {
+            tracing::info!("停止分析磁盘占用: {}", self.root);
+            cx.notify();
+        }
+    }
+
+    fn select(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected = Some(ix);
+        self.confirm_delete = false;
+        cx.notify();
+    }
+
+    fn toggle_expanded(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if !self.expanded.remove(&ix) {
+            self.expanded.insert(ix);
+        }
+        cx.notify();
+    }
+
+    /// 进入文件夹
+    fn drill_down(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if self.tree.nodes[ix].is_dir {
+            self.current = ix;
+            self.selected = None;
+            self.confirm_delete = false;
+            cx.notify();
+        }
+    }
+
+    /// 返回上一层
+    fn drill_up(&mut self, cx: &mut Context<Self>) {
+        if let Some(parent) = self.tree.nodes[self.current].parent {
+            self.selected = Some(self.current);
+            self.current = parent;
+            self.confirm_delete = false;
+            cx.notify();
+        }
+    }
+
+    /// 把选中的条目交给主界面移入回收站或删除，并从树中移除
+    fn remove_selected(&mut self, permanently: bool, cx: &mut Context<Self>) {
+        let Some(ix) = self.selected.filter(|&ix| ix != 0) else {
+            return;
+        };
+        if permanently && !self.confirm_delete {
+            self.confirm_delete = true;
+            cx.notify();
+            return;
+        }
+
+        let path = self.tree.nodes[ix].path.clone();
+        cx.emit(if permanently {
+            DiskUsageEvent::Delete(vec![path])
+        } else {
+            DiskUsageEvent::Trash(vec![path])
+        });
+        if self.tree.contains(ix, self.current) {
+            self.current = self.tree.nodes[ix].parent.unwrap_or(0);
+        }
+        self.tree.remove(ix);
+        self.expanded.remove(&ix);
+        self.selected = None;
+        self.confirm_delete = false;
+        cx.notify();
+    }
+
+    /// 大小树中可见的行：(条目, 缩进层级)
+    fn visible_rows(&self) -> Vec<(usize, usize)> {
+        let mut rows = vec![];
+        let mut stack: Vec<(usize, usize)> = self
+            .tree
+            .sorted_children(self.current, self.sort)
+            .into_iter()
+            .rev()
+            .map(|ix| (ix, 0))
+            .collect();
+        while let Some((ix, depth)) = stack.pop() {
+            if rows.len() >= MAX_ROWS {
+                break;
+            }
+            rows.push((ix, depth));
+            if self.expanded.contains(&ix) {
+                stack.extend(
+                    self.tree
+                        .sorted_children(ix, self.sort)
+                        .into_iter()
+                        .rev()
+                        .map(|child| (child, depth + 1)),
+                );
+            }
+        }
+        rows
+    }
+
+    fn render_tree(&self, theme: &Theme, cx: &mut Context<Self>) -> impl IntoElement {
+        let total = self.tree.nodes[self.current].size.max(1);
+        let rows = self.visible_rows().into_iter().map(|(ix, depth)| {
+            let node = &self.tree.nodes[ix];
+            let ratio = node.size as f32 / total as f32;
+            let icon = if node.is_dir {
+                IconName::FolderClosed
+            } else {
+                IconName::File
+            };
+            let chevron = div().w_4().flex_shrink_0().when(
+                node.is_dir && !node.children.is_empty(),
+                |this| {
+                    let icon =
+                        Icon::new(IconName::ChevronRight).text_color(theme.colors.muted_foreground);
+                    this.child(if self.expanded.contains(&ix) {
+                        icon.rotate(radians(FRAC_PI_2))
+                    } else {
+                        icon
+                    })
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |view, _, _, cx| {
+                            cx.stop_propagation();
+                            view.toggle_expanded(ix, cx);
+                        }),
+                    )
+                },
+            );
+            let pending = node.is_dir && !node.scanned;
+
+            ListItem::new(SharedString::from(format!("disk-usage-{}", ix)))
+                .selected(self.selected == Some(ix))
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .w_full()
+                        .gap(theme.spacing.sm)
+                        .pl(px(depth as f32 * 16.))
+                        .text_sm()
+                        .child(chevron)
+                        .child(Icon::new(icon).text_color(theme.colors.foreground))
+                        .child(
+                            div()
+                                .flex_1()
+                                .min_w_0()
+                                .overflow_hidden()
+                                .whitespace_nowrap()
+                                .child(node.name.clone()),
+                        )
+                        .child(
+                            div()
+                                .w(px(60.))
+                                .h_1()
+                                .flex_shrink_0()
+                                .rounded(theme.radius.sm)
+                                .bg(theme.colors.muted)
+                                .child(
+                                    div()
+                                        .h_full()
+                                        .w(relative(ratio.clamp(0., 1.)))
+                                        .rounded(theme.radius.sm)
+                                        .bg(theme.colors.brand),
+                                ),
+                        )
+                        .child(
+                            div()
+                                .w(px(80.))
+                                .flex_shrink_0()
+                                .text_right()
+                                .text_color(theme.colors.muted_foreground)
+                                .child(if pending {
+                                    format!("{}…", format_size(node.size))
+                                } else {
+                                    format_size(node.size)
+                                }),
+                        ),
+                )
+                .on_click({
+                    let this = cx.entity().downgrade();
+                    move |_, cx| {
+                        let _ = this.update(cx, |view, cx| view.select(ix, cx));
+                    }
+                })
+                .on_double_click({
+                    let this = cx.entity().downgrade();
+                    move |_, cx| {
+                        let _ = this.update(cx, |view, cx| view.drill_down(ix, cx));
+                    }
+                })
+        });
+
+        div()
+            .id("disk-usage-tree")
+            .flex()
+            .flex_col()
+            .flex_1()
+            .min_w_0()
+            .h(px(TREEMAP_HEIGHT))
+            .overflow_y_scroll()
+            .p(theme.spacing.xs)
+            .rounded(theme.radius.md)
+            .bg(theme.colors.background)
+            .border_1()
+            .border_color(theme.colors.border)
+            .children(rows)
+    }
+
+    fn render_treemap(&self, theme: &Theme, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut children = self.tree.sorted_children(self.current, SortBy::Size);
+        children.retain(|&ix| self.tree.nodes[ix].size > 0);
+        children.truncate(MAX_TILES);
+        let sizes: Vec<u64> = children
+            .iter()
+            .map(|&ix| self.tree.nodes[ix].size)
+            .collect();
+        let rects = squarify(
+            &sizes,
+            Rect {
+                x: 0.,
+                y: 0.,
+                w: TREEMAP_WIDTH,
+                h: TREEMAP_HEIGHT,
+            },
+        );
+
+        let tiles = children.into_iter().zip(rects).map(|(ix, rect)| {
+            let node = &self.tree.nodes[ix];
+            let is_selected = self.selected == Some(ix);
+            let show_label = rect.w >= 60. && rect.h >= 32.;
+            div()
+                .id(SharedString::from(format!("disk-usage-tile-{}", ix)))
+                .absolute()
+                .left(px(rect.x))
+                .top(px(rect.y))
+                .w(px(rect.w))
+                .h(px(rect.h))
+                .p(px(4.))
+                .overflow_hidden()
+                .border_1()
+                .border_color(if is_selected {
+                    theme.colors.brand
+                } else {
+                    theme.colors.background
+                })
+                .bg(if node.is_dir {
+                    theme.colors.brand_background_hover
+                } else {
+                    theme.colors.muted
+                })
+                .hover(|style| style.border_color(theme.colors.brand_hover))
+                .cursor_pointer()
+                .text_xs()
+                .when(show_label, |this| {
+                    this.child(
+                        div()
+                            .overflow_hidden()
+                            .whitespace_nowrap()
+                            .text_color(theme.colors.foreground)
+                            .child(node.name.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_color(theme.colors.muted_foreground)
+                            .child(format_size(node.size)),
+                    )
+                })
+                .on_click(cx.listener(move |view, event: &ClickEvent, _, cx| {
+                    if event.click_count() >= 2 {
+                        view.drill_down(ix, cx);
+                    } else {
+                        view.select(ix, cx);
+                    }
+                }))
+        });
+
+        div()
+            .relative()
+            .flex_shrink_0()
+            .w(px(TREEMAP_WIDTH))
+            .h(px(TREEMAP_HEIGHT))
+            .rounded(theme.radius.md)
+            .overflow_hidden()
+            .bg(theme.colors.background)
+            .children(tiles)
+    }
+}
+
+impl Render for DiskUsageView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>().clone();
+        let this = cx.entity().downgrade();
+        let root = &self.tree.nodes[0];
+        let current = &self.tree.nodes[self.current];
+
+        let status = if self.scan.is_some() {
+            format!(
+                "正在扫描… 已统计 {}，{} 个文件",
+                format_size(root.size),
+                root.files
+            )
+        } else {
+            format!("共 {}，{} 个文件", format_size(root.size), root.files)
+        };
+        let status = match self.tree.failed {
+            0 => status,
+            failed => format!("{}（{} 个文件夹无法读取）", status, failed),
+        };
+
+        let toolbar = div()
+            .flex()
+            .items_center()
+            .gap(theme.spacing.sm)
+            .child(
+                Button::new("disk-usage-up", "上一级")
+                    .disabled(current.parent.is_none())
+                    .on_click({
+                        let this = this.clone();
+                        move |_, cx| {
+                            let _ = this.update(cx, |view, cx| view.drill_up(cx));
+                        }
+                    }),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .text_sm()
+                    .child(format!("{}（{}）", current.path, format_size(current.size))),
+            )
+            .child(
+                Button::new("disk-usage-sort-size", "按大小")
+                    .selected(self.sort == SortBy::Size)
+                    .on_click({
+                        let this = this.clone();
+                        move |_, cx| {
+                            let _ = this.update(cx, |view, cx| {
+                                view.sort = SortBy::Size;
+                                cx.notify();
+                            });
+                        }
+                    }),
+            )
+            .child(
+                Button::new("disk-usage-sort-name", "按名称")
+                    .selected(self.sort == SortBy::Name)
+                    .on_click({
+                        let this = this.clone();
+                        move |_, cx| {
+                            let _ = this.update(cx, |view, cx| {
+                                view.sort = SortBy::Name;
+                                cx.notify();
+                            });
+                        }
+                    }),
+            )
+            .child(
+                Button::new("disk-usage-one-fs", "同一文件系统")
+                    .selected(self.one_filesystem)
+                    .on_click({
+                        let this = this.clone();
+                        move |window, cx| {
+                            let _ = this.update(cx, |view, cx| {
+                                view.one_filesystem = !view.one_filesystem;
+                                view.rescan(window, cx);
+                            });
+                        }
+                    }),
+            );
+
+        let has_selection = self.selected.is_some_and(|ix| ix != 0);
+        let this_trash = this.clone();
+        let this_delete = this.clone();
+        let this_close = this.clone();
+        let this_scan = this.clone();
+        let scanning = self.scan.is_some();
+
+        div()
+            .absolute()
+            .inset_0()
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(|view, event: &KeyDownEvent, _, cx| {
+                match event.keystroke.key.as_str() {
+                    "escape" => cx.emit(DiskUsageEvent::Dismiss),
+                    "backspace" => view.drill_up(cx),
+                    "enter" => {
+                        if let Some(ix) = view.selected {
+                            view.drill_down(ix, cx);
+                        }
+                    }
+                    _ => {}
+                }
+            }))
+            .child(
+                Dialog::new(format!(
+                    "磁盘占用：{}",
+                    paths::file_name(&self.root).unwrap_or_else(|| self.root.clone())
+                ))
+                .width(px(960.))
+                .child(toolbar)
+                .child(
+                    div()
+                        .flex()
+                        .gap(theme.spacing.md)
+                        .child(self.render_tree(&theme, cx))
+                        .child(self.render_treemap(&theme, cx)),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.colors.muted_foreground)
+                        .child(status),
+                )
+                .footer(
+                    Button::new(
+                        "disk-usage-scan",
+                        if scanning { "停止" } else { "重新扫描" },
+                    )
+                    .on_click(move |window, cx| {
+                        let _ = this_scan.update(cx, |view, cx| {
+                            if view.scan.is_some() {
+                                view.stop(cx);
+                            } else {
+                                view.rescan(window, cx);
+                            }
+                        });
+                    }),
+                )
+                .footer(
+                    Button::new("disk-usage-trash", "移入回收站")
+                        .disabled(!has_selection)
+                        .on_click(move |_, cx| {
+                            let _ =
+                                this_trash.update(cx, |view, cx| view.remove_selected(false, cx));
+                        }),
+                )
+                .footer(
+                    Button::new(
+                        "disk-usage-delete",
+                        if self.confirm_delete {
+                            "确认永久删除"
+                        } else {
+                            "永久删除"
+                        },
+                    )
+                    .disabled(!has_selection)
+                    .on_click(move |_, cx| {
+                        let _ = this_delete.update(cx, |view, cx| view.remove_selected(true, cx));
+                    }),
+                )
+                .footer(
+                    Button::new("disk-usage-close", "关闭")
+                        .variant(ButtonVariant::Primary)
+                        .on_click(move |_, cx| {
+                            let _ = this_close.update(cx, |_, cx| cx.emit(DiskUsageEvent::Dismiss));
+                        }),
+                ),
+            )
+    }
+}
@@ -0,0 +1,146 @@
+//! 拖放支持
+//!
+//! - 应用内拖动：[`DraggedFiles`]，可拖到其他面板、侧边栏位置和文件夹行上
+//! - 快捷访问排序：[`DraggedBookmark`]，拖到其他快捷访问项上调整顺序
+//! - 外部拖入：GPUI 的 [`ExternalPaths`]，默认复制
+
+use std::{path::Path, time::Duration};
+
+use gpui::{prelude::*, *};
+
+use explorer_common::RootItem;
+use explorer_component::{Icon, IconName, Theme};
+
+use crate::{clipboard::ClipboardMode, paths};
+
+/// 拖动时在文件夹上悬停多久后自动打开该文件夹
+pub const HOVER_OPEN_DELAY: Duration = Duration::from_millis(800);
+
+/// 应用内拖动的文件
+#[derive(Clone, Debug)]
+pub struct DraggedFiles {
+    pub paths: Vec<String>,
+}
+
+impl DraggedFiles {
+    /// 是否可以放到 `target_dir`（不能放进自身或自身的子目录）
+    pub fn can_drop_into(&self, target_dir: &str) -> bool {
+        self.paths
+            .iter()
+            .all(|path| !Path::new(target_dir).starts_with(path))
+    }
+}
+
+/// 拖动调整顺序的快捷访问项
+#[derive(Clone, Debug)]
+pub struct DraggedBookmark {
+    /// 在快捷访问列表中的位置
+    pub index: usize,
+    pub name: String,
+}
+
+/// 外部拖入的路径
+pub fn external_paths(paths: &ExternalPaths) -> Vec<String> {
+    paths
+        .paths()
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect()
+}
+
+/// 决定放下时的操作
+///
+/// 按住 Ctrl 或 Alt 时复制；否则同一根目录内移动，跨根目录复制。
+pub fn drop_mode(
+    paths: &[String],
+    target_dir: &str,
+    roots: &[RootItem],
+    modifiers: &Modifiers,
+) -> ClipboardMode {
+    if modifiers.control || modifiers.alt {
+        return ClipboardMode::Copy;
+    }
+    let target_root = root_of(target_dir, roots);
+    if paths.iter().all(|path| root_of(path, roots) == target_root) {
+        ClipboardMode::Cut
+    } else {
+        ClipboardMode::Copy
+    }
+}
+
+/// 路径所在的根目录（取最长匹配）
+fn root_of<'a>(path: &str, roots: &'a [RootItem]) -> Option<&'a str> {
+    roots
+        .iter()
+        .filter(|root| Path::new(path).starts_with(&root.path))
+        .max_by_key(|root| root.path.len())
+        .map(|root| root.path.as_str())
+}
+
+/// 拖动时跟随鼠标的预览
+pub struct DragPreview {
+    label: String,
+    count: usize,
+    icon: IconName,
+}
+
+impl DragPreview {
+    pub fn new(dragged: &DraggedFiles) -> Self {
+        let label = match dragged.paths.as_slice() {
+            [path] => paths::file_name(path).unwrap_or_else(|| path.clone()),
+            paths => format!("{} 项", paths.len()),
+        };
+        let count = dragged.paths.len();
+        Self {
+            label,
+            count,
+            icon: if count > 1 {
+                IconName::FolderClosed
+            } else {
+                IconName::File
+            },
+        }
+    }
+
+    /// 单个文件夹的预览（拖动快捷访问项）
+    pub fn folder(label: String) -> Self {
+        Self {
+            label,
+            count: 1,
+            icon: IconName::Folder,
+        }
+    }
+}
+
+impl Render for DragPreview {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        div()
+            .flex()
+            .items_center()
+            .gap(theme.spacing.sm)
+            .px(theme.spacing.sm)
+            .py(theme.spacing.xs)
+            .rounded(theme.radius.md)
+            .bg(theme.colors.card)
+            .border_1()
+            .border_color(theme.colors.brand)
+            .shadow_md()
+            .text_sm()
+            .text_color(theme.colors.card_foreground)
+            .child(Icon::new(self.icon).text_color(theme.colors.foreground))
+            .child(self.label.clone())
+            .when(self.count > 1, |this| {
+                this.child(
+                    div()
+                        .px(theme.spacing.xs)
+                        .rounded(theme.radius.sm)
+                        .bg(theme.colors.brand)
+                        .text_xs()
+                        .text_color(theme.colors.brand_foreground)
+                        .child(self.count.to_string()),
+                )
+            })
+    }
+}
@@ -0,0 +1,874 @@
+//! 文件操作
+//!
+//! 定义可执行的文件操作，以及执行完成后写入撤销日志的操作记录。
+//! 撤销/重做前会重新检查文件系统的当前状态，状态不符时拒绝执行。
+//! 新建、复制等操作的结果会记下大小和修改时间，撤销前文件被修改或替换时同样拒绝。
+
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use explorer_storage::*;
+
+/// 待执行的文件操作
+#[derive(Debug, Clone)]
+pub enum FileOperation {
+    /// 新建文件或目录
+    Create { path: String, item_type: ItemType },
+    /// 重命名（同一目录内）
+    Rename { from: String, to: String },
+    /// 移动到其他位置
+    Move { from: String, to: String },
+    /// 复制到其他位置
+    Copy { from: String, to: String },
+    /// 创建指向 `target` 的符号链接
+    Link { target: String, path: String },
+    /// 移入回收站
+    Trash { path: String },
+    /// 创建压缩包
+    Compress {
+        sources: Vec<String>,
+        target: String,
+        options: CompressOptions,
+    },
+    /// 解压到目录（目录不存在时创建）
+    Extract { archive: String, target_dir: String },
+}
+
+impl FileOperation {
+    /// 操作涉及的所有路径（用于刷新面板）
+    pub fn paths(&self) -> Vec<String> {
+        match self {
+            Self::Create { path, .. } | Self::Link { path, .. } | Self::Trash { path } => {
+                vec![path.clone()]
+            }
+            Self::Rename { from, to } | Self::Move { from, to } | Self::Copy { from, to } => {
+                vec![from.clone(), to.clone()]
+            }
+            Self::Compress { target, .. } => vec![target.clone()],
+            Self::Extract { target_dir, .. } => vec![target_dir.clone()],
+        }
+    }
+}
+
+/// 操作结果的大小和修改时间，撤销前用来确认文件没有被修改或替换
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+impl FileStamp {
+    pub fn of(item: &FileItem) -> Self {
+        Self {
+            size: item.size,
+            modified: item.modified,
+        }
+    }
+
+    /// 读取路径当前的状态，读取失败时返回 `None`（撤销时不检查）
+    async fn read(provider: &dyn StorageProvider, path: &str) -> Option<Self> {
+        provider
+            .get_metadata(path)
+            .await
+            .ok()
+            .map(|item| Self::of(&item))
+    }
+}
+
+/// 已完成的文件操作（撤销日志中的记录）
+///
+/// `stamp` 为操作结果的状态，旧版本的日志中没有这一项，此时撤销前不检查。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CompletedOperation {
+    Create {
+        path: String,
+        item_type: ItemType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stamp: Option<FileStamp>,
+    },
+    Rename {
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stamp: Option<FileStamp>,
+    },
+    Move {
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stamp: Option<FileStamp>,
+    },
+    Copy {
+        from: String,
+        to: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stamp: Option<FileStamp>,
+    },
+    Link {
+        target: String,
+        path: String,
+    },
+    Trash {
+        original: String,
+        trashed: String,
+    },
+    Compress {
+        sources: Vec<String>,
+        target: String,
+        options: CompressOptions,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stamp: Option<FileStamp>,
+    },
+    /// `created` 为解压时新建的条目
+    Extract {
+        archive: String,
+        target_dir: String,
+        created: Vec<String>,
+    },
+}
+
+impl CompletedOperation {
+    /// 操作涉及的所有路径（用于刷新面板）
+    pub fn paths(&self) -> Vec<String> {
+        match self {
+            Self::Create { path, .. } | Self::Link { path, .. } => vec![path.clone()],
+            Self::Rename { from, to, .. }
+            | Self::Move { from, to, .. }
+            | Self::Copy { from, to, .. } => vec![from.clone(), to.clone()],
+            Self::Trash { original, .. } => vec![original.clone()],
+            Self::Compress { target, .. } => vec![target.clone()],
+            Self::Extract { created, .. } => created.clone(),
+        }
+    }
+
+    /// 重做时需要再次执行的操作
+    fn redo_operation(&self) -> FileOperation {
+        match self {
+            Self::Create {
+                path, item_type, ..
+            } => FileOperation::Create {
+                path: path.clone(),
+                item_type: *item_type,
+            },
+            Self::Rename { from, to, .. } => FileOperation::Rename {
+                from: from.clone(),
+                to: to.clone(),
+            },
+            Self::Move { from, to, .. } => FileOperation::Move {
+                from: from.clone(),
+                to: to.clone(),
+            },
+            Self::Copy { from, to, .. } => FileOperation::Copy {
+                from: from.clone(),
+                to: to.clone(),
+            },
+            Self::Link { target, path } => FileOperation::Link {
+                target: target.clone(),
+                path: path.clone(),
+            },
+            Self::Trash { original, .. } => FileOperation::Trash {
+                path: original.clone(),
+            },
+            Self::Compress {
+                sources,
+                target,
+                options,
+                ..
+            } => FileOperation::Compress {
+                sources: sources.clone(),
+                target: target.clone(),
+                options: *options,
+            },
+            Self::Extract {
+                archive,
+                target_dir,
+                ..
+            } => FileOperation::Extract {
+                archive: archive.clone(),
+                target_dir: target_dir.clone(),
+            },
+        }
+    }
+}
+
+/// 执行单个文件操作，压缩和解压会更新 `progress`
+pub async fn execute(
+    provider: &dyn StorageProvider,
+    operation: &FileOperation,
+    progress: &Arc<Progress>,
+) -> StorageResult<CompletedOperation> {
+    match operation {
+        FileOperation::Create { path, item_type } => {
+            match item_type {
+                ItemType::Directory => provider.create_dir(path).await?,
+                _ => provider.create_file(path).await?,
+            }
+            Ok(CompletedOperation::Create {
+                path: path.clone(),
+                item_type: *item_type,
+                stamp: FileStamp::read(provider, path).await,
+            })
+        }
+        FileOperation::Rename { from, to } => {
+            provider.rename(from, to).await?;
+            Ok(CompletedOperation::Rename {
+                from: from.clone(),
+                to: to.clone(),
+                stamp: FileStamp::read(provider, to).await,
+            })
+        }
+        FileOperation::Move { from, to } => {
+            provider.move_entry(from, to).await?;
+            Ok(CompletedOperation::Move {
+                from: from.clone(),
+                to: to.clone(),
+                stamp: FileStamp::read(provider, to).await,
+            })
+        }
+        FileOperation::Copy { from, to } => {
+            provider.copy(from, to).await?;
+            Ok(CompletedOperation::Copy {
+                from: from.clone(),
+                to: to.clone(),
+                stamp: FileStamp::read(provider, to).await,
+            })
+        }
+        FileOperation::Link { target, path } => {
+            provider.create_symlink(target, path).await?;
+            Ok(CompletedOperation::Link {
+                target: target.clone(),
+                path: path.clone(),
+            })
+        }
+        FileOperation::Trash { path } => {
+            let trashed = provider.trash(path).await?;
+            Ok(CompletedOperation::Trash {
+                original: path.clone(),
+                trashed,
+            })
+        }
+        FileOperation::Compress {
+            sources,
+            target,
+            options,
+        } => {
+            provider
+                .compress(sources, target, *options, progress.clone())
+                .await?;
+            Ok(CompletedOperation::Compress {
+                sources: sources.clone(),
+                target: target.clone(),
+                options: *options,
+                stamp: FileStamp::read(provider, target).await,
+            })
+        }
+        FileOperation::Extract {
+            archive,
+            target_dir,
+        } => {
+            let created = provider
+                .extract(archive, target_dir, progress.clone())
+                .await?;
+            Ok(CompletedOperation::Extract {
+                archive: archive.clone(),
+                target_dir: target_dir.clone(),
+                created,
+            })
+        }
+    }
+}
+
+/// 前置检查时使用的路径状态推演
+///
+/// 一组操作之间可能相互依赖（例如批量重命名中的互换），
+/// 因此按执行顺序推演每一步之后的存在性，而不是只看当前文件系统。
+#[derive(Default)]
+struct StateOverlay {
+    known: HashMap<String, bool>,
+}
+
+impl StateOverlay {
+    async fn exists(&self, provider: &dyn StorageProvider, path: &str) -> StorageResult<bool> {
+        match self.known.get(path) {
+            Some(exists) => Ok(*exists),
+            None => provider.exists(path).await,
+        }
+    }
+
+    /// 检查路径存在性是否符合预期
+    async fn expect(
+        &self,
+        provider: &dyn StorageProvider,
+        path: &str,
+        expected: bool,
+    ) -> StorageResult<()> {
+        match (self.exists(provider, path).await?, expected) {
+            (true, false) => Err(StorageError::AlreadyExists(path.to_string())),
+            (false, true) => Err(StorageError::PathNotFound(path.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// 检查路径的大小和修改时间是否与记录一致
+    ///
+    /// 路径已被之前推演的步骤改变时跳过，那时它的状态由推演决定。
+    async fn expect_unchanged(
+        &self,
+        provider: &dyn StorageProvider,
+        path: &str,
+        stamp: &Option<FileStamp>,
+    ) -> StorageResult<()> {
+        let Some(stamp) = stamp else {
+            return Ok(());
+        };
+        if self.known.contains_key(path) {
+            return Ok(());
+        }
+        let item = provider.get_metadata(path).await?;
+        if FileStamp::of(&item) != *stamp {
+            return Err(StorageError::Other(format!("文件已被修改: {}", path)));
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, path: &str, exists: bool) {
+        self.known.insert(path.to_string(), exists);
+    }
+
+    /// 检查撤销前的状态：操作的结果必须仍然保持原样
+    async fn check_undo(
+        &mut self,
+        provider: &dyn StorageProvider,
+        operation: &CompletedOperation,
+    ) -> StorageResult<()> {
+        match operation {
+            CompletedOperation::Create {
+                path,
+                item_type,
+                stamp,
+            } => {
+                self.expect(provider, path, true).await?;
+                // 新建的目录中已有内容时不再撤销，避免连带移走用户的新文件
+                if *item_type == ItemType::Directory
+                    && !self.known.contains_key(path)
+                    && !provider.list_entries(path).await?.is_empty()
+                {
+                    return Err(StorageError::Other(format!("目录已不为空: {}", path)));
+                }
+                self.expect_unchanged(provider, path, stamp).await?;
+                self.set(path, false);
+            }
+            CompletedOperation::Rename { from, to, stamp }
+            | CompletedOperation::Move { from, to, stamp } => {
+                self.expect(provider, to, true).await?;
+                self.expect(provider, from, false).await?;
+                self.expect_unchanged(provider, to, stamp).await?;
+                self.set(to, false);
+                self.set(from, true);
+            }
+            CompletedOperation::Copy { to, stamp, .. }
+            | CompletedOperation::Compress {
+                target: to, stamp, ..
+            } => {
+                self.expect(provider, to, true).await?;
+                self.expect_unchanged(provider, to, stamp).await?;
+                self.set(to, false);
+            }
+            CompletedOperation::Link { path, .. } => {
+                self.expect(provider, path, true).await?;
+                self.set(path, false);
+            }
+            CompletedOperation::Extract { created, .. } => {
+                for path in created {
+                    self.expect(provider, path, true).await?;
+                    self.set(path, false);
+                }
+            }
+            CompletedOperation::Trash { original, trashed } => {
+                self.expect(provider, trashed, true).await?;
+                self.expect(provider, original, false).await?;
+                self.set(trashed, false);
+                self.set(original, true);
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查重做前的状态：撤销后的状态必须仍然保持原样
+    async fn check_redo(
+        &mut self,
+        provider: &dyn StorageProvider,
+        operation: &CompletedOperation,
+    ) -> StorageResult<()> {
+        match operation {
+            CompletedOperation::Create { path, .. } => {
+                self.expect(provider, path, false).await?;
+                self.set(path, true);
+            }
+            CompletedOperation::Rename { from, to, .. }
+            | CompletedOperation::Move { from, to, .. } => {
+                self.expect(provider, from, true).await?;
+                self.expect(provider, to, false).await?;
+                self.set(from, false);
+                self.set(to, true);
+            }
+            CompletedOperation::Copy { from, to, .. } => {
+                self.expect(provider, from, true).await?;
+                self.expect(provider, to, false).await?;
+                self.set(to, true);
+            }
+            // 链接的目标可以不存在
+            CompletedOperation::Link { path, .. } => {
+                self.expect(provider, path, false).await?;
+                self.set(path, true);
+            }
+            CompletedOperation::Trash { original, .. } => {
+                self.expect(provider, original, true).await?;
+                self.set(original, false);
+            }
+            CompletedOperation::Compress {
+                sources, target, ..
+            } => {
+                for source in sources {
+                    self.expect(provider, source, true).await?;
+                }
+                self.expect(provider, target, false).await?;
+                self.set(target, true);
+            }
+            CompletedOperation::Extract {
+                archive, created, ..
+            } => {
+                self.expect(provider, archive, true).await?;
+                for path in created {
+                    self.expect(provider, path, false).await?;
+                    self.set(path, true);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 撤销或重做一组操作的结果
+#[derive(Debug)]
+pub struct Replay {
+    /// 已执行的部分，按原来的顺序排列（撤销时为可重做的记录，重做时为新的操作记录）
+    pub done: Vec<CompletedOperation>,
+    /// 没有执行的部分，按原来的顺序排列，应放回原来的栈中
+    pub pending: Vec<CompletedOperation>,
+    /// 中途停止的原因
+    pub error: Option<StorageError>,
+}
+
+/// 撤销一组操作（按相反顺序执行逆操作）
+///
+/// 所有前置检查通过后才开始执行，中途失败时停止，已撤销的部分不会恢复。
+pub async fn undo(provider: &dyn StorageProvider, operations: &[CompletedOperation]) -> Replay {
+    let mut overlay = StateOverlay::default();
+    for operation in operations.iter().rev() {
+        if let Err(e) = overlay.check_undo(provider, operation).await {
+            return Replay {
+                done: vec![],
+                pending: operations.to_vec(),
+                error: Some(e),
+            };
+        }
+    }
+
+    let mut pending = operations.to_vec();
+    let mut done = Vec::with_capacity(operations.len());
+    while let Some(operation) = pending.pop() {
+        if let Err((e, rest)) = undo_operation(provider, &operation).await {
+            pending.push(rest);
+            done.reverse();
+            return Replay {
+                done,
+                pending,
+                error: Some(e),
+            };
+        }
+        done.push(operation);
+    }
+    done.reverse();
+    Replay {
+        done,
+        pending,
+        error: None,
+    }
+}
+
+/// 执行单个操作的逆操作，失败时返回还没有撤销的部分
+async fn undo_operation(
+    provider: &dyn StorageProvider,
+    operation: &CompletedOperation,
+) -> Result<(), (StorageError, CompletedOperation)> {
+    let ret = match operation {
+        // 撤销新建、复制、链接、压缩和解压时移入回收站，而不是直接删除
+        CompletedOperation::Create { path, .. }
+        | CompletedOperation::Copy { to: path, .. }
+        | CompletedOperation::Link { path, .. }
+        | CompletedOperation::Compress { target: path, .. } => provider.trash(path).await.map(drop),
+        // 解压的条目逐个移入回收站，中途失败时只保留还没有移走的条目
+        CompletedOperation::Extract {
+            archive,
+            target_dir,
+            created,
+        } => {
+            for (i, path) in created.iter().enumerate() {
+                if let Err(e) = provider.trash(path).await {
+                    let rest = CompletedOperation::Extract {
+                        archive: archive.clone(),
+                        target_dir: target_dir.clone(),
+                        created: created[i..].to_vec(),
+                    };
+                    return Err((e, rest));
+                }
+            }
+            Ok(())
+        }
+        CompletedOperation::Rename { from, to, .. } => provider.rename(to, from).await,
+        CompletedOperation::Move { from, to, .. } => provider.move_entry(to, from).await,
+        CompletedOperation::Trash { original, trashed } => {
+            provider.restore(trashed, original).await
+        }
+    };
+    ret.map_err(|e| (e, operation.clone()))
+}
+
+/// 重做一组已撤销的操作
+///
+/// 已执行的部分返回新的操作记录（例如再次移入回收站后的新位置）。
+pub async fn redo(provider: &dyn StorageProvider, operations: &[CompletedOperation]) -> Replay {
+    let mut overlay = StateOverlay::default();
+    for operation in operations {
+        if let Err(e) = overlay.check_redo(provider, operation).await {
+            return Replay {
+                done: vec![],
+                pending: operations.to_vec(),
+                error: Some(e),
+            };
+        }
+    }
+
+    let progress = Arc::default();
+    let mut done = Vec::with_capacity(operations.len());
+    for (i, operation) in operations.iter().enumerate() {
+        match execute(provider, &operation.redo_operation(), &progress).await {
+            Ok(completed) => done.push(completed),
+            Err(e) => {
+                return Replay {
+                    done,
+                    pending: operations[i..].to_vec(),
+                    error: Some(e),
+                };
+            }
+        }
+    }
+    Replay {
+        done,
+        pending: vec![],
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use explorer_memory_provider::MemoryProvider;
+
+    use super::*;
+
+    fn url(path: &str) -> String {
+        MemoryProvider::url(path)
+    }
+
+    fn provider() -> MemoryProvider {
+        let provider = MemoryProvider::new();
+        provider.insert_file(&url("/a.txt"), "a").unwrap();
+        provider.insert_dir(&url("/dir")).unwrap();
+        provider
+    }
+
+    fn run(provider: &MemoryProvider, operation: FileOperation) -> CompletedOperation {
+        smol::block_on(execute(provider, &operation, &Arc::default())).unwrap()
+    }
+
+    /// 前置检查的结果（不执行操作）
+    fn check_undo(
+        provider: &MemoryProvider,
+        operations: &[CompletedOperation],
+    ) -> StorageResult<()> {
+        smol::block_on(async {
+            let mut overlay = StateOverlay::default();
+            for operation in operations.iter().rev() {
+                overlay.check_undo(provider, operation).await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn check_redo(
+        provider: &MemoryProvider,
+        operations: &[CompletedOperation],
+    ) -> StorageResult<()> {
+        smol::block_on(async {
+            let mut overlay = StateOverlay::default();
+            for operation in operations {
+                overlay.check_redo(provider, operation).await?;
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn redo_repeats_the_original_operation() {
+        let options = CompressOptions {
+            format: ArchiveFormat::Zip,
+            level: CompressionLevel::default(),
+        };
+        let cases = [
+            FileOperation::Create {
+                path: "/new".into(),
+                item_type: ItemType::Directory,
+            },
+            FileOperation::Rename {
+                from: "/a".into(),
+                to: "/b".into(),
+            },
+            FileOperation::Move {
+                from: "/a".into(),
+                to: "/dir/a".into(),
+            },
+            FileOperation::Copy {
+                from: "/a".into(),
+                to: "/a copy".into(),
+            },
+            FileOperation::Link {
+                target: "a".into(),
+                path: "/link".into(),
+            },
+            FileOperation::Compress {
+                sources: vec!["/a".into(), "/b".into()],
+                target: "/a.zip".into(),
+                options,
+            },
+            FileOperation::Extract {
+                archive: "/a.zip".into(),
+                target_dir: "/a".into(),
+            },
+        ];
+        for operation in cases {
+            let completed = match operation.clone() {
+                FileOperation::Create { path, item_type } => CompletedOperation::Create {
+                    path,
+                    item_type,
+                    stamp: None,
+                },
+                FileOperation::Rename { from, to } => CompletedOperation::Rename {
+                    from,
+                    to,
+                    stamp: None,
+                },
+                FileOperation::Move { from, to } => CompletedOperation::Move {
+                    from,
+                    to,
+                    stamp: None,
+                },
+                FileOperation::Copy { from, to } => CompletedOperation::Copy {
+                    from,
+                    to,
+                    stamp: None,
+                },
+                FileOperation::Link { target, path } => CompletedOperation::Link { target, path },
+                FileOperation::Compress {
+                    sources,
+                    target,
+                    options,
+                } => CompletedOperation::Compress {
+                    sources,
+                    target,
+                    options,
+                    stamp: None,
+                },
+                FileOperation::Extract {
+                    archive,
+                    target_dir,
+                } => CompletedOperation::Extract {
+                    archive,
+                    target_dir: target_dir.clone(),
+                    created: vec![format!("{}/x", target_dir)],
+                },
+                FileOperation::Trash { .. } => unreachable!(),
+            };
+            assert_eq!(
+                format!("{:?}", completed.redo_operation()),
+                format!("{:?}", operation)
+            );
+        }
+
+        // 重做移入回收站时针对原来的位置
+        let trashed = CompletedOperation::Trash {
+            original: "/a".into(),
+            trashed: "/trash/a".into(),
+        };
+        assert!(matches!(
+            trashed.redo_operation(),
+            FileOperation::Trash { path } if path == "/a"
+        ));
+    }
+
+    #[test]
+    fn undo_refuses_replaced_files() {
+        let provider = provider();
+        let copied = run(
+            &provider,
+            FileOperation::Copy {
+                from: url("/a.txt"),
+                to: url("/b.txt"),
+            },
+        );
+        assert!(matches!(
+            copied,
+            CompletedOperation::Copy { stamp: Some(_), .. }
+        ));
+        check_undo(&provider, std::slice::from_ref(&copied)).unwrap();
+
+        provider.insert_file(&url("/b.txt"), "replaced").unwrap();
+        assert!(matches!(
+            check_undo(&provider, &[copied]),
+            Err(StorageError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn undo_refuses_changed_state() {
+        let provider = provider();
+        let created = run(
+            &provider,
+            FileOperation::Create {
+                path: url("/dir/new"),
+                item_type: ItemType::Directory,
+            },
+        );
+        let renamed = run(
+            &provider,
+            FileOperation::Rename {
+                from: url("/a.txt"),
+                to: url("/c.txt"),
+            },
+        );
+        check_undo(&provider, &[created.clone(), renamed.clone()]).unwrap();
+
+        // 新建的目录中有了内容
+        provider.insert_file(&url("/dir/new/x"), "").unwrap();
+        assert!(check_undo(&provider, std::slice::from_ref(&created)).is_err());
+
+        // 原来的位置被占用
+        provider.insert_file(&url("/a.txt"), "").unwrap();
+        assert!(matches!(
+            check_undo(&provider, &[renamed]),
+            Err(StorageError::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn undo_follows_earlier_steps() {
+        // 互换名称：a → tmp，b → a，tmp → b
+        let provider = provider();
+        provider.insert_file(&url("/b.txt"), "bb").unwrap();
+        let operations: Vec<_> = [("/a.txt", "/tmp"), ("/b.txt", "/a.txt"), ("/tmp", "/b.txt")]
+            .into_iter()
+            .map(|(from, to)| {
+                run(
+                    &provider,
+                    FileOperation::Rename {
+                        from: url(from),
+                        to: url(to),
+                    },
+                )
+            })
+            .collect();
+        check_undo(&provider, &operations).unwrap();
+
+        let replay = smol::block_on(undo(&provider, &operations));
+        assert!(replay.error.is_none(), "{:?}", replay.error);
+        assert_eq!(replay.done.len(), 3);
+        assert!(replay.pending.is_empty());
+        let size = |path| {
+            smol::block_on(provider.get_metadata(&url(path)))
+                .unwrap()
+                .size
+        };
+        assert_eq!((size("/a.txt"), size("/b.txt")), (1, 2));
+
+        check_redo(&provider, &replay.done).unwrap();
+        assert!(check_redo(&provider, &operations[1..]).is_err());
+    }
+
+    #[test]
+    fn undo_keeps_unfinished_operations() {
+        // 内存存储没有回收站，撤销复制时失败
+        let provider = provider();
+        let copied = run(
+            &provider,
+            FileOperation::Copy {
+                from: url("/a.txt"),
+                to: url("/b.txt"),
+            },
+        );
+        let renamed = run(
+            &provider,
+            FileOperation::Rename {
+                from: url("/a.txt"),
+                to: url("/c.txt"),
+            },
+        );
+
+        let replay = smol::block_on(undo(&provider, &[copied, renamed]));
+        assert!(matches!(replay.error, Some(StorageError::Unsupported(_))));
+        assert!(matches!(
+            &replay.done[..],
+            [CompletedOperation::Rename { .. }]
+        ));
+        assert!(matches!(
+            &replay.pending[..],
+            [CompletedOperation::Copy { .. }]
+        ));
+        assert!(smol::block_on(provider.exists(&url("/a.txt"))).unwrap());
+
+        // 重做已撤销的部分，得到新的记录
+        let redone = smol::block_on(redo(&provider, &replay.done));
+        assert!(redone.error.is_none());
+        assert!(matches!(
+            &redone.done[..],
+            [CompletedOperation::Rename { stamp: Some(_), .. }]
+        ));
+        assert!(smol::block_on(provider.exists(&url("/c.txt"))).unwrap());
+    }
+
+    #[test]
+    fn redo_keeps_unfinished_operations() {
+        let provider = provider();
+        let operations = [
+            CompletedOperation::Create {
+                path: url("/new.txt"),
+                item_type: ItemType::File,
+                stamp: None,
+            },
+            CompletedOperation::Trash {
+                original: url("/a.txt"),
+                trashed: url("/trash/a.txt"),
+            },
+        ];
+        let replay = smol::block_on(redo(&provider, &operations));
+        assert!(matches!(replay.error, Some(StorageError::Unsupported(_))));
+        assert!(matches!(
+            &replay.done[..],
+            [CompletedOperation::Create { .. }]
+        ));
+        assert!(matches!(
+            &replay.pending[..],
+            [CompletedOperation::Trash { .. }]
+        ));
+    }
+}
@@ -0,0 +1,62 @@
+//! 文件夹大小
+//!
+//! 通过存储提供者逐层列出目录，累计其中所有文件的大小。不跟随符号链接，
+//! 无法读取的子目录会被跳过。
+
+use explorer_storage::{ItemType, StorageProvider, StorageResult};
+
+/// 递归统计的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FolderSize {
+    /// 文件总大小（字节）
+    pub bytes: u64,
+    /// 文件数
+    pub files: u64,
+    /// 子文件夹数
+    pub dirs: u64,
+    /// 无法读取而跳过的文件夹数
+    pub skipped: u64,
+}
+
+impl FolderSize {
+    /// 合并另一个统计结果
+    pub fn add(&mut self, other: FolderSize) {
+        self.bytes += other.bytes;
+        self.files += other.files;
+        self.dirs += other.dirs;
+        self.skipped += other.skipped;
+    }
+}
+
+/// 统计文件夹的总大小，文件夹本身无法读取时返回错误
+///
+/// 每读取一个目录都会让出执行，丢弃返回的 future 即可取消统计
+pub async fn folder_size(provider: &dyn StorageProvider, path: &str) -> StorageResult<FolderSize> {
+    let mut size = FolderSize::default();
+    let mut pending = vec![path.to_string()];
+    while let Some(dir) = pending.pop() {
+        let entries = match provider.list_entries(&dir).await {
+            Ok(entries) => entries,
+            Err(e) if dir == path => return Err(e),
+            Err(e) => {
+                tracing::debug!("跳过无法读取的文件夹 {}: {}", dir, e);
+                size.skipped += 1;
+                continue;
+            }
+        };
+        for entry in entries {
+            match entry.item_type {
+                ItemType::Directory => {
+                    size.dirs += 1;
+                    pending.push(entry.path);
+                }
+                ItemType::File => {
+                    size.bytes += entry.size;
+                    size.files += 1;
+                }
+                ItemType::Symlink => {}
+            }
+        }
+    }
+    Ok(size)
+}
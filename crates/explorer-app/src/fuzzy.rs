@@ -0,0 +1,204 @@
+//! 模糊匹配
+//!
+//! 查询中的字符按顺序出现在候选文本中即视为匹配（不区分大小写，忽略查询中的空白）。
+//! 得分奖励连续匹配和单词开头的匹配，惩罚匹配之间的间隔，用动态规划取最高得分的匹配位置。
+
+/// 每个匹配字符的基础得分
+const MATCH_SCORE: i64 = 16;
+/// 与上一个匹配字符相邻
+const CONSECUTIVE_BONUS: i64 = 20;
+/// 匹配在单词开头（分隔符之后或驼峰边界）
+const WORD_START_BONUS: i64 = 24;
+/// 匹配在文本开头
+const FIRST_CHAR_BONUS: i64 = 8;
+/// 每跳过一个字符的惩罚（单次间隔最多计 `MAX_GAP_PENALTY`）
+const GAP_PENALTY: i64 = 1;
+const MAX_GAP_PENALTY: i64 = 12;
+
+/// 匹配结果
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// 匹配字符在候选文本中的字节位置
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyMatch {
+    /// 把匹配位置合并为连续的字节区间（用于高亮）
+    pub fn ranges(&self, text: &str) -> Vec<std::ops::Range<usize>> {
+        let mut ranges: Vec<std::ops::Range<usize>> = vec![];
+        for &start in &self.positions {
+            let end = start + text[start..].chars().next().map_or(0, char::len_utf8);
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(start..end),
+            }
+        }
+        ranges
+    }
+}
+
+/// 模糊匹配 `query` 与 `candidate`，不匹配时返回 `None`；空查询匹配任何文本
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: vec![],
+        });
+    }
+
+    let chars: Vec<(usize, char)> = candidate.char_indices().collect();
+    let lower: Vec<char> = chars.iter().map(|(_, c)| lowercase(*c)).collect();
+    let (m, n) = (query.len(), chars.len());
+    if m > n {
+        return None;
+    }
+
+    // best[i][j]：查询前 i+1 个字符匹配完成且第 i 个字符落在候选第 j 个字符上的最高得分
+    let mut best = vec![vec![None::<i64>; n]; m];
+    let mut from = vec![vec![0usize; n]; m];
+    for i in 0..m {
+        for j in i..n {
+            if lower[j] != query[i] {
+                continue;
+            }
+            let bonus = MATCH_SCORE
+                + if j == 0 {
+                    WORD_START_BONUS + FIRST_CHAR_BONUS
+                } else if is_word_start(chars[j - 1].1, chars[j].1) {
+                    WORD_START_BONUS
+                } else {
+                    0
+                };
+            if i == 0 {
+                best[i][j] = Some(bonus - (j as i64 * GAP_PENALTY).min(MAX_GAP_PENALTY));
+                continue;
+            }
+            for k in (i - 1)..j {
+                let Some(previous) = best[i - 1][k] else {
+                    continue;
+                };
+                let gap = (j - k - 1) as i64;
+                let score = previous
+                    + bonus
+                    + if gap == 0 {
+                        CONSECUTIVE_BONUS
+                    } else {
+                        -(gap * GAP_PENALTY).min(MAX_GAP_PENALTY)
+                    };
+                if best[i][j].is_none_or(|current| score > current) {
+                    best[i][j] = Some(score);
+                    from[i][j] = k;
+                }
+            }
+        }
+    }
+
+    let (mut j, score) = best[m - 1]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = chars[j].0;
+        j = from[i][j];
+    }
+    // 同分时较短的文本排在前面
+    Some(FuzzyMatch {
+        score: score * 4 - n as i64 / 4,
+        positions,
+    })
+}
+
+/// 逐字符比较用的小写形式（小写为多个字符时只取第一个，如 `İ`），查询和候选文本一致
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// `current` 是否是单词的开头
+fn is_word_start(previous: char, current: char) -> bool {
+    matches!(previous, ' ' | '/' | '\\' | '_' | '-' | '.' | ':')
+        || (previous.is_lowercase() && current.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> i64 {
+        fuzzy_match(query, candidate).unwrap().score
+    }
+
+    fn positions(query: &str, candidate: &str) -> Vec<usize> {
+        fuzzy_match(query, candidate).unwrap().positions
+    }
+
+    #[test]
+    fn matches_in_order() {
+        assert_eq!(positions("fb", "foo_bar"), [0, 4]);
+        assert_eq!(positions("fb", "fooBar"), [0, 3]);
+        assert_eq!(positions("abc", "xaxbxc"), [1, 3, 5]);
+        assert!(fuzzy_match("ba", "ab").is_none());
+        assert!(fuzzy_match("abcd", "abc").is_none());
+        assert!(fuzzy_match("x", "").is_none());
+
+        let empty = fuzzy_match(" ", "anything").unwrap();
+        assert_eq!((empty.score, empty.positions.len()), (0, 0));
+    }
+
+    #[test]
+    fn prefers_word_starts_and_runs() {
+        // 单词开头的匹配优先于单词中间的同一字符
+        assert_eq!(positions("b", "abc b"), [4]);
+        assert_eq!(positions("dl", "download_list"), [0, 9]);
+        // 连续匹配优先于分散的匹配
+        assert_eq!(positions("ab", "a_x_ab"), [4, 5]);
+
+        assert!(score("doc", "Documents") > score("doc", "my_docs"));
+        assert!(score("doc", "my_docs") > score("doc", "ado_cat"));
+        assert!(score("fb", "foo_bar") > score("fb", "fabric"));
+        // 同分时较短的文本排在前面
+        assert!(score("doc", "doc") > score("doc", "doc.txt"));
+    }
+
+    #[test]
+    fn ignores_case_and_whitespace() {
+        assert_eq!(positions("FB", "foo_bar"), [0, 4]);
+        assert_eq!(positions("fb", "FOO_BAR"), [0, 4]);
+        assert_eq!(positions("f b", "foo_bar"), [0, 4]);
+        assert_eq!(score("Doc", "docs"), score("doc", "DOCS"));
+    }
+
+    #[test]
+    fn handles_non_ascii() {
+        assert_eq!(positions("文件", "我的文件夹"), [6, 9]);
+        assert_eq!(positions("été", "ÉTÉ 2024"), [0, 2, 3]);
+        assert_eq!(positions("é", "café"), [3]);
+        // 小写为多个字符的字母
+        assert_eq!(positions("İst", "İstanbul"), [0, 2, 3]);
+        assert_eq!(positions("ist", "İSTANBUL"), [0, 2, 3]);
+    }
+
+    #[test]
+    fn merges_highlight_ranges() {
+        let text = "我的文件夹/docs";
+        let found = fuzzy_match("文件do", text).unwrap();
+        assert_eq!(found.ranges(text), [6..12, 16..18]);
+
+        let text = "a_b_c";
+        assert_eq!(
+            fuzzy_match("abc", text).unwrap().ranges(text),
+            [0..1, 2..3, 4..5]
+        );
+        assert_eq!(
+            fuzzy_match("", text).unwrap().ranges(text),
+            Vec::<std::ops::Range<usize>>::new()
+        );
+    }
+}
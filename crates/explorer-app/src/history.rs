@@ -0,0 +1,139 @@
+//! 撤销与重做
+//!
+//! 在后台重放撤销日志中的文件操作，并把日志保存到磁盘（日志本身见 [`crate::undo`]）。
+
+use gpui::{Context, Window};
+
+use crate::{
+    Explorer, Redo, Undo,
+    file_ops::{self, CompletedOperation},
+    undo::{UndoEntry, UndoJournal},
+};
+
+impl Explorer {
+    /// 撤销最近一次文件操作
+    pub(crate) fn undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_replay_undo_entry() {
+            cx.notify();
+            return;
+        }
+        let Some(entry) = self.undo_journal.pop_undo() else {
+            tracing::info!("没有可撤销的操作");
+            return;
+        };
+        self.replay_undo_entry(entry, true, window, cx);
+    }
+
+    /// 重做最近一次撤销的文件操作
+    pub(crate) fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_replay_undo_entry() {
+            cx.notify();
+            return;
+        }
+        let Some(entry) = self.undo_journal.pop_redo() else {
+            tracing::info!("没有可重做的操作");
+            return;
+        };
+        self.replay_undo_entry(entry, false, window, cx);
+    }
+
+    /// 合并启动时从磁盘读取的撤销日志，此后才开始保存
+    pub(crate) fn restore_undo_journal(&mut self, journal: UndoJournal) {
+        self.undo_journal.merge_loaded(journal);
+        self.undo_journal_loaded = true;
+        self.save_undo_journal();
+    }
+
+    /// 文件操作执行或排队期间不能撤销/重做，此时撤销栈顶还不是最终的结果
+    fn can_replay_undo_entry(&mut self) -> bool {
+        if self.undo_in_progress {
+            return false;
+        }
+        if self.running_operation.is_some() || !self.operation_queue.is_empty() {
+            self.operation_error = Some("文件操作完成后才能撤销或重做".to_string());
+            return false;
+        }
+        true
+    }
+
+    /// 在后台执行撤销（`is_undo`）或重做
+    ///
+    /// 完成的部分放入另一个栈，没有执行的部分放回原栈。
+    fn replay_undo_entry(
+        &mut self,
+        entry: UndoEntry,
+        is_undo: bool,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.undo_in_progress = true;
+        let provider = self.provider.clone();
+        let affected: Vec<String> = entry
+            .operations
+            .iter()
+            .flat_map(CompletedOperation::paths)
+            .collect();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let operations = entry.operations.clone();
+            let replay = cx
+                .background_executor()
+                .spawn(async move {
+                    if is_undo {
+                        file_ops::undo(provider.as_ref(), &operations).await
+                    } else {
+                        file_ops::redo(provider.as_ref(), &operations).await
+                    }
+                })
+                .await;
+
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.undo_in_progress = false;
+                    let action = if is_undo { "撤销" } else { "重做" };
+                    match &replay.error {
+                        Some(e) => {
+                            tracing::warn!("无法{} {}: {}", action, entry.label, e);
+                            explorer.operation_error =
+                                Some(format!("无法{}“{}”: {}", action, entry.label, e));
+                        }
+                        None => tracing::info!("已{}: {}", action, entry.label),
+                    }
+                    let part = |operations| UndoEntry {
+                        operations,
+                        ..entry.clone()
+                    };
+                    let (done, pending) = (part(replay.done), part(replay.pending));
+                    let journal = &mut explorer.undo_journal;
+                    if is_undo {
+                        if !pending.operations.is_empty() {
+                            journal.push_undo(pending);
+                        }
+                        if !done.operations.is_empty() {
+                            journal.push_redo(done);
+                        }
+                    } else {
+                        if !pending.operations.is_empty() {
+                            journal.push_redo(pending);
+                        }
+                        if !done.operations.is_empty() {
+                            journal.push_undo(done);
+                        }
+                    }
+                    explorer.save_undo_journal();
+                    explorer.refresh_panels(&affected, window, cx);
+                    explorer.process_operation_queue(window, cx);
+                    cx.notify();
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// 在后台保存撤销日志
+    pub(crate) fn save_undo_journal(&self) {
+        if self.undo_journal_loaded {
+            self.journal_writer.save(self.undo_journal.clone());
+        }
+    }
+}
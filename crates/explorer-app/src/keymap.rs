@@ -0,0 +1,494 @@
+//! 快捷键配置
+//!
+//! 默认快捷键定义在代码中（[`default_bindings`] 和各组件的 `key_bindings`），
+//! 用户可以在 `~/.explorer/keymap.json` 中按上下文覆盖或解除：
+//!
+//! ```json
+//! [
+//!   {
+//!     "context": "FileList",
+//!     "bindings": { "ctrl-o": "explorer::OpenSelected", "delete": null }
+//!   }
+//! ]
+//! ```
+//!
+//! - 上下文：`Explorer`（全局）、`FileList`（文件列表）、`Sidebar`（侧边栏）、
+//!   `LocationBar`（地址栏）、`TextInput`（重命名等输入框）、`CommandPalette`（命令面板）、`ContextMenu`（右键菜单）
+//! - 动作写作名称字符串，带参数的动作写作 `["名称", 参数]`，`null` 解除该按键的绑定
+//! - 同一上下文中同一按键绑定到不同动作、全局绑定被子上下文的默认绑定遮挡时会报告冲突
+//! - 同一段中重复的按键只有最后一项生效，同样会报告
+//!
+//! 配置文件修改后自动重新加载，配置中的问题记录在 [`KeymapStatus`] 中。
+
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+use gpui::{Action, App, Global, KeyBinding, KeyBindingContextPredicate, NoAction, SharedString};
+use serde::{Deserialize, Deserializer, de};
+use serde_json::Value;
+
+use crate::{
+    AcceptCompletion, BatchRename, CopyFiles, CopyPath, CopyRelativePath, CutFiles, EditLocation,
+    ExtendSelectionDown, ExtendSelectionUp, GoToParent, NewFolder, OpenSelected, PasteFiles,
+    PasteLinks, Redo, Rename, SelectAll, SelectFirst, SelectLast, SelectNext, SelectPrevious,
+    ShowProperties, ToggleCommandPalette, TrashSelected, Undo, palette, paths,
+};
+
+/// 全局上下文（Explorer 根元素）
+pub const GLOBAL_CONTEXT: &str = "Explorer";
+/// 文件列表上下文（激活面板）
+pub const FILE_LIST_CONTEXT: &str = "FileList";
+/// 侧边栏上下文
+pub const SIDEBAR_CONTEXT: &str = "Sidebar";
+/// 地址栏上下文（面包屑编辑状态，包含 `TextInput`）
+pub const LOCATION_BAR_CONTEXT: &str = "LocationBar";
+
+/// 可以在配置中使用的上下文；除全局上下文外都嵌套在全局上下文之内
+const KNOWN_CONTEXTS: [&str; 7] = [
+    GLOBAL_CONTEXT,
+    FILE_LIST_CONTEXT,
+    SIDEBAR_CONTEXT,
+    LOCATION_BAR_CONTEXT,
+    "TextInput",
+    "CommandPalette",
+    "ContextMenu",
+];
+
+/// 检查配置文件是否修改的间隔
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 快捷键配置的加载结果
+#[derive(Default)]
+pub struct KeymapStatus {
+    /// 配置中的问题（解析错误、未知动作、冲突）
+    pub problems: Vec<String>,
+}
+
+impl Global for KeymapStatus {}
+
+/// 配置文件中的一段
+#[derive(Deserialize)]
+struct KeymapSection {
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(default)]
+    bindings: SectionBindings,
+}
+
+/// 一段中的绑定，按配置文件中的顺序排列
+///
+/// JSON 对象中同一按键出现多次时只保留最后一项，重复的按键记录在 `duplicates` 中。
+#[derive(Default)]
+struct SectionBindings {
+    entries: Vec<(String, Value)>,
+    duplicates: Vec<String>,
+}
+
+impl<'de> Deserialize<'de> for SectionBindings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = SectionBindings;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("按键到动作的对象")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut bindings = SectionBindings::default();
+                while let Some((keystrokes, value)) = map.next_entry::<String, Value>()? {
+                    if let Some(index) = bindings
+                        .entries
+                        .iter()
+                        .position(|(existing, _)| *existing == keystrokes)
+                    {
+                        bindings.entries.remove(index);
+                        bindings.duplicates.push(keystrokes.clone());
+                    }
+                    bindings.entries.push((keystrokes, value));
+                }
+                Ok(bindings)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+/// 应用的默认快捷键
+pub fn default_bindings() -> Vec<KeyBinding> {
+    let global = Some(GLOBAL_CONTEXT);
+    let file_list = Some(FILE_LIST_CONTEXT);
+    let sidebar = Some(SIDEBAR_CONTEXT);
+    let location_bar = Some(LOCATION_BAR_CONTEXT);
+    vec![
+        // 全局
+        KeyBinding::new("secondary-z", Undo, global),
+        KeyBinding::new("secondary-shift-z", Redo, global),
+        KeyBinding::new("secondary-shift-p", ToggleCommandPalette, global),
+        KeyBinding::new("secondary-l", EditLocation, global),
+        // 文件列表
+        KeyBinding::new("up", SelectPrevious, file_list),
+        KeyBinding::new("down", SelectNext, file_list),
+        KeyBinding::new("home", SelectFirst, file_list),
+        KeyBinding::new("end", SelectLast, file_list),
+        KeyBinding::new("shift-up", ExtendSelectionUp, file_list),
+        KeyBinding::new("shift-down", ExtendSelectionDown, file_list),
+        KeyBinding::new("secondary-a", SelectAll, file_list),
+        KeyBinding::new("backspace", GoToParent, file_list),
+        KeyBinding::new("alt-up", GoToParent, file_list),
+        KeyBinding::new("enter", OpenSelected, file_list),
+        KeyBinding::new("alt-enter", ShowProperties, file_list),
+        KeyBinding::new("secondary-shift-n", NewFolder, file_list),
+        KeyBinding::new("delete", TrashSelected, file_list),
+        KeyBinding::new("f2", Rename, file_list),
+        KeyBinding::new("shift-f2", BatchRename, file_list),
+        KeyBinding::new("secondary-c", CopyFiles, file_list),
+        KeyBinding::new("secondary-x", CutFiles, file_list),
+        KeyBinding::new("secondary-v", PasteFiles, file_list),
+        KeyBinding::new("secondary-shift-v", PasteLinks, file_list),
+        KeyBinding::new("secondary-shift-c", CopyPath, file_list),
+        KeyBinding::new("secondary-alt-shift-c", CopyRelativePath, file_list),
+        // 侧边栏
+        KeyBinding::new("up", SelectPrevious, sidebar),
+        KeyBinding::new("down", SelectNext, sidebar),
+        KeyBinding::new("home", SelectFirst, sidebar),
+        KeyBinding::new("end", SelectLast, sidebar),
+        // 地址栏
+        KeyBinding::new("up", SelectPrevious, location_bar),
+        KeyBinding::new("down", SelectNext, location_bar),
+        KeyBinding::new("tab", AcceptCompletion, location_bar),
+    ]
+}
+
+/// 用户配置文件路径
+pub fn file_path() -> PathBuf {
+    paths::data_file("keymap.json")
+}
+
+/// 重新注册所有快捷键：组件默认值、应用默认值，最后是用户配置（优先级最高）
+pub fn reload(cx: &mut App) {
+    let mut defaults = explorer_component::key_bindings();
+    defaults.extend(palette::key_bindings());
+    defaults.extend(default_bindings());
+
+    let (user, problems) = match fs::read_to_string(file_path()) {
+        Ok(content) => load_user_bindings(&content, &defaults, cx),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (vec![], vec![]),
+        Err(e) => (
+            vec![],
+            vec![format!("无法读取 {}: {}", file_path().display(), e)],
+        ),
+    };
+    for problem in &problems {
+        tracing::warn!("快捷键配置: {}", problem);
+    }
+    tracing::info!("已加载快捷键配置（{} 条用户绑定）", user.len());
+
+    cx.clear_key_bindings();
+    cx.bind_keys(defaults);
+    cx.bind_keys(user);
+    cx.set_global(KeymapStatus { problems });
+}
+
+/// 配置文件修改后自动重新加载
+pub fn watch(cx: &mut App) {
+    cx.spawn(async move |cx| {
+        let mut last_modified = modified_time();
+        loop {
+            cx.background_executor().timer(RELOAD_INTERVAL).await;
+            let modified = modified_time();
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+            tracing::info!("快捷键配置已修改，重新加载");
+            if cx.update(reload).is_err() {
+                break;
+            }
+        }
+    })
+    .detach();
+}
+
+fn modified_time() -> Option<SystemTime> {
+    fs::metadata(file_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// 按键序列的显示形式（用于比较和提示）
+fn keystrokes_text(binding: &KeyBinding) -> String {
+    binding
+        .keystrokes()
+        .iter()
+        .map(|keystroke| keystroke.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 绑定所在的上下文（没有上下文时为空字符串）
+fn context_text(binding: &KeyBinding) -> String {
+    binding
+        .predicate()
+        .map(|predicate| predicate.to_string())
+        .unwrap_or_default()
+}
+
+/// 解析用户配置，返回可注册的绑定和发现的问题
+fn load_user_bindings(
+    content: &str,
+    defaults: &[KeyBinding],
+    cx: &App,
+) -> (Vec<KeyBinding>, Vec<String>) {
+    let sections: Vec<KeymapSection> = match serde_json::from_str(content) {
+        Ok(sections) => sections,
+        Err(e) => {
+            return (
+                vec![],
+                vec![format!("{} 格式错误: {}", file_path().display(), e)],
+            );
+        }
+    };
+
+    let mut bindings = vec![];
+    let mut problems = vec![];
+
+    for section in sections {
+        let context = section.context.unwrap_or_default();
+        let predicate: Option<Rc<KeyBindingContextPredicate>> = if context.is_empty() {
+            None
+        } else {
+            match KeyBindingContextPredicate::parse(&context) {
+                Ok(predicate) => Some(predicate.into()),
+                Err(e) => {
+                    problems.push(format!("无效的上下文 \"{}\": {}", context, e));
+                    continue;
+                }
+            }
+        };
+        if let Some(KeyBindingContextPredicate::Identifier(name)) = predicate.as_deref()
+            && !KNOWN_CONTEXTS.contains(&name.as_ref())
+        {
+            problems.push(format!("未知的上下文 \"{}\"", name));
+        }
+
+        for keystrokes in &section.bindings.duplicates {
+            problems.push(format!(
+                "重复的按键 \"{}\"（{}）: 只有最后一项生效",
+                keystrokes, context
+            ));
+        }
+
+        for (keystrokes, value) in section.bindings.entries {
+            let action = match build_action(&value, cx) {
+                Ok(action) => action,
+                Err(e) => {
+                    problems.push(format!("{}（{}）: {}", keystrokes, context, e));
+                    continue;
+                }
+            };
+            match KeyBinding::load(
+                &keystrokes,
+                action,
+                predicate.clone(),
+                false,
+                None,
+                cx.keyboard_mapper().as_ref(),
+            ) {
+                Ok(binding) => bindings.push(binding),
+                Err(e) => problems.push(format!("无效的按键 \"{}\": {}", keystrokes, e)),
+            }
+        }
+    }
+
+    problems.extend(conflicting_bindings(&bindings));
+    problems.extend(shadowed_global_bindings(&bindings, defaults));
+    (bindings, problems)
+}
+
+/// 用户在同一上下文中把同一按键绑定到不同动作
+fn conflicting_bindings(user: &[KeyBinding]) -> Vec<String> {
+    // (上下文, 按键) -> 动作名称
+    let mut seen: HashMap<(String, String), &'static str> = HashMap::new();
+    let mut problems = vec![];
+    for binding in user {
+        let keys = keystrokes_text(binding);
+        let context = context_text(binding);
+        let name = binding.action().name();
+        match seen.insert((context.clone(), keys.clone()), name) {
+            Some(previous) if previous != name => problems.push(format!(
+                "冲突：{} 在上下文 \"{}\" 中同时绑定到 {} 和 {}",
+                keys, context, previous, name
+            )),
+            _ => {}
+        }
+    }
+    problems
+}
+
+/// 用户在全局上下文中的绑定，如果子上下文的默认绑定使用相同按键（且没有被用户解除），
+/// 在该子上下文获得焦点时不会生效
+fn shadowed_global_bindings(user: &[KeyBinding], defaults: &[KeyBinding]) -> Vec<String> {
+    let overridden: Vec<(String, String)> = user
+        .iter()
+        .map(|binding| (context_text(binding), keystrokes_text(binding)))
+        .collect();
+
+    let mut problems = vec![];
+    for binding in user {
+        if context_text(binding) != GLOBAL_CONTEXT || gpui::is_no_action(binding.action()) {
+            continue;
+        }
+        let keys = keystrokes_text(binding);
+        for default in defaults {
+            let context = context_text(default);
+            if context.is_empty()
+                || context == GLOBAL_CONTEXT
+                || keystrokes_text(default) != keys
+                || overridden.contains(&(context.clone(), keys.clone()))
+            {
+                continue;
+            }
+            problems.push(format!(
+                "冲突：全局的 {} -> {} 在上下文 \"{}\" 中被 {} 遮挡",
+                keys,
+                binding.action().name(),
+                context,
+                default.action().name()
+            ));
+        }
+    }
+    problems
+}
+
+/// 按配置的值构造动作：名称字符串、`["名称", 参数]` 或 `null`（解除绑定）
+fn build_action(value: &Value, cx: &App) -> Result<Box<dyn Action>, SharedString> {
+    let (name, data) = match value {
+        Value::Null => return Ok(Box::new(NoAction)),
+        Value::String(name) => (name.as_str(), None),
+        Value::Array(items) => match items.as_slice() {
+            [Value::String(name)] => (name.as_str(), None),
+            [Value::String(name), data] => (name.as_str(), Some(data.clone())),
+            _ => return Err("动作数组应为 [\"名称\", 参数]".into()),
+        },
+        _ => return Err("动作应为名称字符串、数组或 null".into()),
+    };
+    cx.build_action(name, data)
+        .map_err(|e| format!("无法创建动作 {}: {}", name, e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{KeyBinding, NoAction};
+
+    // 不使用 `super::*`，以免引入 gpui 的 `test` 属性
+    use super::{
+        FILE_LIST_CONTEXT, GLOBAL_CONTEXT, KeymapSection, SIDEBAR_CONTEXT, conflicting_bindings,
+        context_text, keystrokes_text, shadowed_global_bindings,
+    };
+    use crate::{CopyFiles, OpenSelected, Redo, SelectNext, Undo};
+
+    fn parse(content: &str) -> Vec<KeymapSection> {
+        serde_json::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn parses_sections_in_order() {
+        let sections = parse(
+            r#"[
+                { "context": "FileList", "bindings": { "ctrl-o": "explorer::OpenSelected", "delete": null, "a": ["x", 1] } },
+                { "bindings": {} },
+                {}
+            ]"#,
+        );
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].context.as_deref(), Some("FileList"));
+        let keys: Vec<&str> = sections[0]
+            .bindings
+            .entries
+            .iter()
+            .map(|(keys, _)| keys.as_str())
+            .collect();
+        assert_eq!(keys, ["ctrl-o", "delete", "a"]);
+        assert!(sections[0].bindings.entries[1].1.is_null());
+        assert!(sections[0].bindings.duplicates.is_empty());
+        assert!(sections[1].context.is_none());
+        assert!(sections[2].bindings.entries.is_empty());
+
+        assert!(serde_json::from_str::<Vec<KeymapSection>>(r#"[{ "bindings": [] }]"#).is_err());
+    }
+
+    #[test]
+    fn reports_duplicate_keys() {
+        let sections = parse(
+            r#"[{ "bindings": { "ctrl-o": "a", "ctrl-p": "b", "ctrl-o": "c", "ctrl-o": null } }]"#,
+        );
+        let bindings = &sections[0].bindings;
+        assert_eq!(bindings.duplicates, ["ctrl-o", "ctrl-o"]);
+        assert_eq!(bindings.entries.len(), 2);
+        assert_eq!(bindings.entries[0].0, "ctrl-p");
+        assert_eq!(
+            bindings.entries[1],
+            ("ctrl-o".to_string(), serde_json::Value::Null)
+        );
+    }
+
+    #[test]
+    fn formats_bindings() {
+        let binding = KeyBinding::new("g g", Undo, Some(FILE_LIST_CONTEXT));
+        assert_eq!(keystrokes_text(&binding), "g g");
+        assert_eq!(context_text(&binding), FILE_LIST_CONTEXT);
+        assert_eq!(context_text(&KeyBinding::new("a", Undo, None)), "");
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let file_list = Some(FILE_LIST_CONTEXT);
+        let problems = conflicting_bindings(&[
+            KeyBinding::new("ctrl-o", OpenSelected, file_list),
+            KeyBinding::new("ctrl-o", CopyFiles, file_list),
+            // 同一动作重复绑定、不同上下文中的同一按键不算冲突
+            KeyBinding::new("ctrl-p", Undo, file_list),
+            KeyBinding::new("ctrl-p", Undo, file_list),
+            KeyBinding::new("ctrl-o", Redo, Some(SIDEBAR_CONTEXT)),
+        ]);
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].contains("OpenSelected"), "{}", problems[0]);
+    }
+
+    #[test]
+    fn detects_shadowed_global_bindings() {
+        let defaults = [
+            KeyBinding::new("down", SelectNext, Some(FILE_LIST_CONTEXT)),
+            KeyBinding::new("down", SelectNext, Some(SIDEBAR_CONTEXT)),
+            KeyBinding::new("ctrl-z", Undo, Some(GLOBAL_CONTEXT)),
+        ];
+
+        let user = [KeyBinding::new("down", Redo, Some(GLOBAL_CONTEXT))];
+        assert_eq!(shadowed_global_bindings(&user, &defaults).len(), 2);
+
+        // 用户在子上下文中解除了默认绑定
+        let user = [
+            KeyBinding::new("down", Redo, Some(GLOBAL_CONTEXT)),
+            KeyBinding::new("down", NoAction, Some(SIDEBAR_CONTEXT)),
+        ];
+        let problems = shadowed_global_bindings(&user, &defaults);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains(FILE_LIST_CONTEXT), "{}", problems[0]);
+
+        // 子上下文中的绑定、解除全局绑定和没有被遮挡的按键不报告
+        let user = [
+            KeyBinding::new("down", Redo, Some(FILE_LIST_CONTEXT)),
+            KeyBinding::new("down", NoAction, Some(GLOBAL_CONTEXT)),
+            KeyBinding::new("ctrl-z", Redo, Some(GLOBAL_CONTEXT)),
+        ];
+        assert!(shadowed_global_bindings(&user, &defaults).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+//! 启动外部程序
+//!
+//! - 用默认程序打开文件：Linux 使用 `xdg-open`，macOS 使用 `open`，Windows 使用 `start`
+//! - 打开方式：Linux 下读取 XDG 应用目录中的 `.desktop` 文件，按 MIME 类型筛选
+//! - 在终端中打开目录：依次尝试 `$TERMINAL` 和常见的终端模拟器
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+use dirs::{data_dir, home_dir};
+
+/// 可以打开文件的应用
+#[derive(Clone, Debug)]
+pub struct DesktopApp {
+    /// 显示名称
+    pub name: String,
+    /// 启动命令（`Exec` 字段）
+    exec: String,
+    /// 支持的 MIME 类型
+    mime_types: Vec<String>,
+}
+
+impl DesktopApp {
+    /// 是否支持指定的 MIME 类型（支持 `image/*` 形式的通配）
+    fn supports(&self, mime: &str) -> bool {
+        self.mime_types.iter().any(|supported| {
+            supported == mime
+                || supported
+                    .strip_suffix("/*")
+                    .is_some_and(|prefix| mime.split('/').next() == Some(prefix))
+        })
+    }
+
+    /// 用此应用打开文件
+    ///
+    /// `%F`、`%U` 一次传入所有文件；`%f`、`%u` 每个文件启动一次；没有字段代码时追加到命令末尾。
+    pub fn launch(&self, paths: &[String]) -> io::Result<()> {
+        let args = split_exec(&self.exec);
+        let multiple = args.iter().any(|arg| arg == "%F" || arg == "%U");
+        let single = args.iter().any(|arg| arg == "%f" || arg == "%u");
+
+        let batches: Vec<&[String]> = if single && !multiple {
+            paths.chunks(1).collect()
+        } else {
+            vec![paths]
+        };
+        for batch in batches {
+            let mut command_line = Vec::with_capacity(args.len() + batch.len());
+            for arg in &args {
+                match arg.as_str() {
+                    "%f" | "%u" | "%F" | "%U" => command_line.extend(batch.iter().cloned()),
+                    // 已废弃或与文件无关的字段代码
+                    "%i" | "%c" | "%k" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => {}
+                    _ => command_line.push(arg.replace("%%", "%")),
+                }
+            }
+            if !single && !multiple {
+                command_line.extend(batch.iter().cloned());
+            }
+            let Some((program, rest)) = command_line.split_first() else {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Exec 为空"));
+            };
+            spawn_detached(Command::new(program).args(rest))?;
+        }
+        Ok(())
+    }
+}
+
+/// 用系统默认程序打开文件或目录
+pub fn open_default(path: &str) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = Command::new("open");
+        command.arg(path);
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "", path]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = {
+        let mut command = Command::new("xdg-open");
+        command.arg(path);
+        command
+    };
+    spawn_detached(&mut command)
+}
+
+/// 可以打开指定 MIME 类型的应用（按名称排序）
+pub fn apps_for_mime(mime: &str) -> Vec<DesktopApp> {
+    let mut apps: Vec<DesktopApp> = desktop_apps()
+        .iter()
+        .filter(|app| app.supports(mime))
+        .cloned()
+        .collect();
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps
+}
+
+/// 在终端中打开目录
+pub fn open_terminal(dir: &str) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        spawn_detached(Command::new("open").args(["-a", "Terminal", dir]))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        spawn_detached(
+            Command::new("cmd")
+                .args(["/C", "start", "cmd"])
+                .current_dir(dir),
+        )
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let candidates = std::env::var("TERMINAL").ok().into_iter().chain(
+            [
+                "x-terminal-emulator",
+                "gnome-terminal",
+                "konsole",
+                "xfce4-terminal",
+                "alacritty",
+                "kitty",
+                "wezterm",
+                "xterm",
+            ]
+            .map(String::from),
+        );
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "没有找到终端程序");
+        for program in candidates {
+            match spawn_detached(Command::new(&program).current_dir(dir)) {
+                Ok(()) => return Ok(()),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// 启动进程并在后台线程中等待其退出（避免留下僵尸进程）
+fn spawn_detached(command: &mut Command) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+// ===== .desktop 文件 =====
+
+/// 已安装的应用（首次使用时扫描一次）
+fn desktop_apps() -> &'static [DesktopApp] {
+    static APPS: OnceLock<Vec<DesktopApp>> = OnceLock::new();
+    APPS.get_or_init(|| {
+        // 按优先级从低到高读取，同名（desktop id）的文件覆盖低优先级目录中的
+        let mut apps: HashMap<String, DesktopApp> = HashMap::new();
+        for dir in application_dirs().into_iter().rev() {
+            let Ok(read_dir) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in read_dir.flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "desktop") {
+                    continue;
+                }
+                let Some(id) = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                match fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| parse_desktop_entry(&content))
+                {
+                    Some(app) => {
+                        apps.insert(id, app);
+                    }
+                    None => {
+                        apps.remove(&id);
+                    }
+                }
+            }
+        }
+        apps.into_values().collect()
+    })
+}
+
+/// XDG 应用目录（优先级从高到低）
+fn application_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(data) = data_dir().or_else(|| home_dir().map(|home| home.join(".local/share"))) {
+        dirs.push(data.join("applications"));
+    }
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(
+        system
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| PathBuf::from(dir).join("applications")),
+    );
+    dirs
+}
+
+/// 解析 `.desktop` 文件的 `[Desktop Entry]` 段，隐藏或无法启动的应用返回 `None`
+fn parse_desktop_entry(content: &str) -> Option<DesktopApp> {
+    let mut in_entry = false;
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            fields.entry(key.trim()).or_insert(value.trim());
+        }
+    }
+
+    if fields
+        .get("Type")
+        .is_some_and(|kind| *kind != "Application")
+        || fields.get("NoDisplay") == Some(&"true")
+        || fields.get("Hidden") == Some(&"true")
+    {
+        return None;
+    }
+    Some(DesktopApp {
+        name: fields.get("Name")?.to_string(),
+        exec: fields.get("Exec")?.to_string(),
+        mime_types: fields
+            .get("MimeType")
+            .map(|types| {
+                types
+                    .split(';')
+                    .filter(|mime| !mime.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
+}
+
+/// 按 Desktop Entry 规范拆分 `Exec` 字段（支持双引号和反斜杠转义）
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            '\\' if in_quotes => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
@@ -0,0 +1,183 @@
+//! 地址栏
+//!
+//! 面包屑进入编辑状态后接受的输入：绝对路径、`~`、环境变量（`$VAR` 或 `${VAR}`）
+//! 以及 URI。`file://` URI 转换为本地路径，其他 URI（`sftp://`、`s3://` 等）原样交给
+//! 当前的 provider，能否打开由 provider 决定。补全按最后一个 `/` 拆分输入，用前面的目录的子目录匹配后面的前缀，
+//! 之后是路径中包含输入内容的最近访问位置。
+
+use std::path::Path;
+
+use url::Url;
+
+use crate::paths;
+
+/// 最多显示的补全候选数
+pub const MAX_SUGGESTIONS: usize = 8;
+/// 最多显示的最近访问位置数
+const MAX_RECENT: usize = 3;
+
+/// 把地址栏输入解析为绝对路径或 provider 的 URI
+pub fn resolve(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("请输入路径".to_string());
+    }
+
+    if let Some((scheme, _)) = text.split_once("://") {
+        if scheme != "file" {
+            return Ok(text.to_string());
+        }
+        let path = Url::parse(text)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .ok_or_else(|| format!("无效的 URI：{}", text))?;
+        return Ok(path.display().to_string());
+    }
+
+    let expanded = expand_env(text)?;
+    let path = paths::expand_home(&expanded).ok_or_else(|| format!("无法展开 {}", text))?;
+    if !Path::new(&path).is_absolute() {
+        return Err("请输入绝对路径".to_string());
+    }
+    Ok(path)
+}
+
+/// 展开 `$VAR` 和 `${VAR}`，未定义的变量视为错误
+fn expand_env(text: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remaining) = if let Some(braced) = after.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| "环境变量缺少 }".to_string())?;
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], &after[end..])
+        };
+        if name.is_empty() {
+            result.push('$');
+        } else {
+            let value = std::env::var(name).map_err(|_| format!("环境变量 {} 未定义", name))?;
+            result.push_str(&value);
+        }
+        rest = remaining;
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 补全位置：输入中最后一个 `/` 及之前的部分（原样保留）、对应的目录和待补全的前缀
+pub struct CompletionTarget {
+    pub typed_dir: String,
+    pub dir: String,
+    pub prefix: String,
+}
+
+/// 计算输入的补全位置，输入不是可解析的路径时返回 `None`
+pub fn completion_target(text: &str) -> Option<CompletionTarget> {
+    let split = text.rfind('/')? + 1;
+    let typed_dir = &text[..split];
+    let dir = resolve(typed_dir).ok()?;
+    Some(CompletionTarget {
+        typed_dir: typed_dir.to_string(),
+        dir,
+        prefix: text[split..].to_string(),
+    })
+}
+
+/// 补全候选
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    /// 显示文本
+    pub label: String,
+    /// 选中后填入输入框的文本
+    pub text: String,
+    /// 是否来自最近访问的位置
+    pub recent: bool,
+}
+
+/// 计算补全候选：先是输入所在目录中以前缀开头（不区分大小写）的子目录，
+/// 然后是包含输入内容的最近访问位置（`recent` 按 frecency 排列）
+///
+/// 隐藏目录只在前缀以 `.` 开头时出现
+pub fn suggestions(text: &str, children: &[String], recent: &[String]) -> Vec<Suggestion> {
+    let mut suggestions: Vec<Suggestion> = vec![];
+    if let Some(target) = completion_target(text) {
+        let lower = target.prefix.to_lowercase();
+        suggestions.extend(
+            children
+                .iter()
+                .filter(|name| target.prefix.starts_with('.') || !name.starts_with('.'))
+                .filter(|name| {
+                    name.to_lowercase().starts_with(&lower) && name.as_str() != target.prefix
+                })
+                .take(MAX_SUGGESTIONS)
+                .map(|name| Suggestion {
+                    label: name.clone(),
+                    text: format!("{}{}/", target.typed_dir, name),
+                    recent: false,
+                }),
+        );
+    }
+
+    let query = text.trim();
+    if query.is_empty() {
+        return suggestions;
+    }
+    let resolved = resolve(query).unwrap_or_else(|_| query.to_string());
+    let current = resolved.trim_end_matches('/');
+    let lower = resolved.to_lowercase();
+    let remaining = MAX_SUGGESTIONS
+        .saturating_sub(suggestions.len())
+        .min(MAX_RECENT);
+    let recent: Vec<Suggestion> = recent
+        .iter()
+        .filter(|path| path.as_str() != current && path.to_lowercase().contains(&lower))
+        .filter(|path| {
+            !suggestions
+                .iter()
+                .any(|s| s.text.trim_end_matches('/') == *path)
+        })
+        .take(remaining)
+        .map(|path| Suggestion {
+            label: path.clone(),
+            text: format!("{}/", path.trim_end_matches('/')),
+            recent: true,
+        })
+        .collect();
+    suggestions.extend(recent);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve;
+
+    #[test]
+    fn resolves_file_uris() {
+        assert_eq!(resolve(" file:///tmp/a%20b ").unwrap(), "/tmp/a b");
+        assert!(resolve("file://host/tmp").is_err());
+    }
+
+    #[test]
+    fn passes_other_uris_to_the_provider() {
+        assert_eq!(
+            resolve("sftp://user@host/srv/data").unwrap(),
+            "sftp://user@host/srv/data"
+        );
+        assert_eq!(resolve("s3://bucket/key/").unwrap(), "s3://bucket/key/");
+    }
+
+    #[test]
+    fn requires_absolute_paths() {
+        assert_eq!(resolve("/usr/lib").unwrap(), "/usr/lib");
+        assert!(resolve("relative/path").is_err());
+        assert!(resolve("  ").is_err());
+    }
+}
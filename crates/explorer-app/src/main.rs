@@ -1,6 +1,13 @@
 use std::{
-    cmp::Ordering, collections::HashSet, fs::create_dir_all, io::stdout, mem::forget,
-    panic::Location, rc::Rc, sync::Arc,
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+    fs::create_dir_all,
+    io::stdout,
+    mem::forget,
+    panic::Location,
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
 };
 
 use dirs::home_dir;
@@ -11,15 +18,94 @@ use tracing_appender::{
 };
 use tracing_subscriber::{EnvFilter, fmt::layer, prelude::*};
 
+use explorer_archive_provider::{ArchiveProvider, is_archive};
 use explorer_common::*;
 use explorer_component::{
-    Assets, Breadcrumb, BreadcrumbItem, BreadcrumbState, GroupedList, Icon, IconName, ListGroup,
-    ListItem, Resizable, ResizableState, Theme, TitleBar, VirtualList, VirtualListScrollHandle,
+    Assets, Breadcrumb, BreadcrumbItem, BreadcrumbState, ContextMenu, ContextMenuItem, GroupedList,
+    Icon, IconName, ListGroup, ListItem, Resizable, ResizableState, TextInput, TextInputEvent,
+    Theme, TitleBar, VirtualList, VirtualListScrollHandle,
 };
 use explorer_local_provider::LocalFileSystemProvider;
 use explorer_storage::*;
 
+use batch_rename::{BatchRenameDialog, BatchRenameEvent};
+use clipboard::{ClipboardMode, FileClipboard};
+use compress::{CompressDialog, CompressEvent};
+use disk_usage::{DiskUsageEvent, DiskUsageView};
+use dnd::{DragPreview, DraggedBookmark, DraggedFiles};
+use file_ops::FileOperation;
+use folder_size::FolderSize;
+use keymap::KeymapStatus;
+use palette::{CommandPalette, CommandPaletteEvent, PaletteHistory, PaletteTarget};
+use properties::PropertiesDialog;
+use quick_access::QuickAccess;
+use recent::RecentLocations;
+use undo::{JournalWriter, UndoEntry, UndoJournal};
+
+mod batch_rename;
+mod clipboard;
+mod commands;
+mod compress;
+mod disk_usage;
+mod dnd;
+mod file_ops;
+mod folder_size;
+mod fuzzy;
+mod history;
+mod keymap;
+mod launcher;
+mod location;
+mod palette;
+mod paths;
+mod properties;
 mod quick_access;
+mod recent;
+mod roots;
+mod transfer;
+mod undo;
+
+// ===== 动作定义 =====
+
+actions!(
+    explorer,
+    [
+        Undo,
+        Redo,
+        NewFolder,
+        TrashSelected,
+        Rename,
+        BatchRename,
+        CopyFiles,
+        CutFiles,
+        PasteFiles,
+        PasteLinks,
+        CopyPath,
+        CopyRelativePath,
+        OpenSelected,
+        OpenTerminal,
+        ShowProperties,
+        SplitHorizontal,
+        SplitVertical,
+        ToggleCommandPalette,
+        SelectPrevious,
+        SelectNext,
+        SelectFirst,
+        SelectLast,
+        ExtendSelectionUp,
+        ExtendSelectionDown,
+        SelectAll,
+        GoToParent,
+        EditLocation,
+        AcceptCompletion,
+        PinToQuickAccess,
+        ClearRecentLocations,
+        ComputeFolderSize,
+        AnalyzeDiskUsage,
+        CompressSelected,
+        ExtractHere,
+        ExtractToFolder,
+    ]
+);
 
 // ===== 辅助函数 =====
 
@@ -69,6 +155,85 @@ fn parse_path_to_breadcrumb_items(path: &str) -> Vec<BreadcrumbItem> {
     items
 }
 
+/// 生成不与已有名称冲突的名称（`name.txt`、`name (2).txt`、`name (3).txt` ...）
+fn unique_name(taken: &HashSet<String>, name: &str) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rfind('.') {
+        Some(index) if index > 0 => name.split_at(index),
+        _ => (name, ""),
+    };
+    (2..)
+        .map(|i| format!("{} ({}){}", stem, i, ext))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap()
+}
+
+/// 重命名时默认选中的范围：文件只选中主文件名（不含扩展名）
+fn rename_selection(name: &str, item_type: ItemType) -> usize {
+    match item_type {
+        ItemType::Directory => name.len(),
+        _ => name
+            .rfind('.')
+            .filter(|&index| index > 0)
+            .unwrap_or(name.len()),
+    }
+}
+
+/// 条目的图标：指向目录的符号链接显示为文件夹，链接在左下角加箭头，悬空的链接显示为危险色
+fn entry_icon(entry: &FileItem, theme: &Theme) -> Div {
+    let icon = if entry.is_dir_like() {
+        Icon::new(IconName::FolderClosed)
+    } else {
+        Icon::new(IconName::File)
+    };
+    let color = if entry.is_broken_link() {
+        theme.colors.danger
+    } else {
+        theme.colors.foreground
+    };
+    div()
+        .relative()
+        .flex_none()
+        .child(icon.text_color(color))
+        .when(entry.item_type == ItemType::Symlink, |this| {
+            this.child(
+                Icon::new(IconName::ArrowUpRight)
+                    .absolute()
+                    .left(px(-2.))
+                    .bottom(px(-2.))
+                    .size(px(8.))
+                    .text_color(color),
+            )
+        })
+}
+
+/// 卷的使用率条：接近写满时显示为警告色，几乎写满时显示为危险色
+fn usage_bar(space: &SpaceInfo, theme: &Theme) -> Div {
+    let usage = space.usage().clamp(0., 1.);
+    let color = if usage >= 0.95 {
+        theme.colors.danger
+    } else if usage >= 0.85 {
+        theme.colors.warning
+    } else {
+        theme.colors.brand
+    };
+    div()
+        .h_1()
+        .w_full()
+        .rounded(theme.radius.sm)
+        .bg(theme.colors.muted)
+        .overflow_hidden()
+        .child(
+            div()
+                .h_full()
+                .w(relative(usage))
+                .rounded(theme.radius.sm)
+                .bg(color),
+        )
+}
+
 // ===== 面板数据结构 =====
 
 /// 面板节点枚举，用于构建面板树
@@ -264,6 +429,26 @@ impl PanelNode {
         }
     }
 
+    /// 替换面板中指定路径的条目（保持条目位置不变）
+    pub fn replace_entry(&mut self, target_id: PanelId, old_path: &str, item: FileItem) -> bool {
+        match self {
+            PanelNode::Leaf { id, entries, .. } if *id == target_id => {
+                match entries.iter_mut().find(|entry| entry.path == old_path) {
+                    Some(entry) => {
+                        *entry = item;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            PanelNode::Split { first, second, .. } => {
+                first.replace_entry(target_id, old_path, item.clone())
+                    || second.replace_entry(target_id, old_path, item)
+            }
+            _ => false,
+        }
+    }
+
     /// 更新指定面板的 bounds（仅用于 Leaf 节点）
     pub fn update_panel_bounds(&mut self, target_id: PanelId, new_bounds: Bounds<Pixels>) -> bool {
         match self {
@@ -336,6 +521,18 @@ impl PanelNode {
         }
     }
 
+    /// 收集所有叶子面板的 ID 和路径
+    pub fn leaf_paths(&self) -> Vec<(PanelId, String)> {
+        match self {
+            PanelNode::Leaf { id, path, .. } => vec![(*id, path.clone())],
+            PanelNode::Split { first, second, .. } => {
+                let mut leaves = first.leaf_paths();
+                leaves.extend(second.leaf_paths());
+                leaves
+            }
+        }
+    }
+
     /// 统计叶子面板数量
     pub fn count_leaves(&self) -> usize {
         match self {
@@ -353,14 +550,44 @@ struct SidebarItem {
     name: String,
     path: String,
     icon_name: IconName,
+    kind: SidebarKind,
+    // 名称后的说明文字（如文件系统类型）
+    detail: Option<String>,
+    // 卷的容量（存储位置）
+    space: Option<SpaceInfo>,
 }
 
-impl From<&QuickAccessItem> for SidebarItem {
-    fn from(item: &QuickAccessItem) -> Self {
+/// 侧边栏项所在的分组
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SidebarKind {
+    /// 快捷访问（在列表中的位置）
+    QuickAccess(usize),
+    /// 最近访问
+    Recent,
+    /// 存储位置
+    Root,
+}
+
+impl SidebarItem {
+    fn quick_access(index: usize, item: &QuickAccessItem) -> Self {
         Self {
             name: item.name.clone(),
             path: item.path.clone(),
             icon_name: IconName::Folder,
+            kind: SidebarKind::QuickAccess(index),
+            detail: None,
+            space: None,
+        }
+    }
+
+    fn recent(path: &str) -> Self {
+        Self {
+            name: paths::file_name(path).unwrap_or_else(|| path.to_string()),
+            path: path.to_string(),
+            icon_name: IconName::FolderOpen,
+            kind: SidebarKind::Recent,
+            detail: None,
+            space: None,
         }
     }
 }
@@ -375,16 +602,85 @@ impl From<&RootItem> for SidebarItem {
                 ProviderType::NetworkDrive => IconName::FolderClosed,
                 ProviderType::CloudStorage { .. } => IconName::FolderClosed,
             },
+            kind: SidebarKind::Root,
+            detail: item.mount.as_ref().map(|mount| {
+                if mount.read_only {
+                    format!("{} · 只读", mount.fs_type)
+                } else {
+                    mount.fs_type.clone()
+                }
+            }),
+            space: None,
         }
     }
 }
 
+// ===== 重命名状态 =====
+
+/// 行内重命名的编辑状态
+struct RenameState {
+    panel_id: PanelId,
+    path: String,
+    original_name: String,
+    input: Entity<TextInput>,
+    error: Option<String>,
+    // 是否正在提交（等待 provider 返回）
+    committing: bool,
+    _subscription: Subscription,
+}
+
+/// 文件操作完成（或失败）后在 UI 线程上调用
+type OperationCallback =
+    Box<dyn FnOnce(&mut Explorer, Option<StorageError>, &mut Window, &mut Context<Explorer>)>;
+
+/// 等待执行的一组文件操作
+struct QueuedOperations {
+    label: String,
+    operations: Vec<FileOperation>,
+    // 完成后的回调，设置时由回调报告错误
+    on_complete: Option<OperationCallback>,
+}
+
+/// 地址栏编辑状态
+struct LocationEditState {
+    panel_id: PanelId,
+    input: Entity<TextInput>,
+    error: Option<String>,
+    // 补全候选所在的目录和它的子目录名称
+    completion_dir: Option<String>,
+    children: Vec<String>,
+    suggestions: Vec<location::Suggestion>,
+    selected_suggestion: Option<usize>,
+    // 是否正在检查路径（等待 provider 返回）
+    checking: bool,
+    _subscription: Subscription,
+}
+
+/// 侧边栏快捷访问项的重命名状态
+struct SidebarRenameState {
+    path: String,
+    input: Entity<TextInput>,
+    _subscription: Subscription,
+}
+
+/// 选中文件夹的递归大小
+struct FolderSizeState {
+    // 统计的文件夹（排序后），与当前选中的文件夹不同时结果不再显示
+    paths: Vec<String>,
+    // 统计结果，计算中为 None
+    result: Option<FolderSize>,
+    // 后台统计任务，丢弃即取消
+    _task: Task<()>,
+}
+
 // ===== Explorer 组件 =====
 
 /// Explorer 主组件
 pub struct Explorer {
     provider: Arc<dyn StorageProvider>,
     roots: Vec<RootItem>,
+    // 存储根节点的容量（按根路径）
+    root_space: HashMap<String, SpaceInfo>,
     selected_sidebar_path: Option<String>,
     // 面板树管理
     panel_tree: PanelNode,
@@ -393,12 +689,65 @@ pub struct Explorer {
     // 文件选中状态
     selected_items: HashSet<String>,
     last_selected_index: Option<usize>,
+    // 键盘焦点（根元素、激活面板的文件列表、侧边栏）
+    focus_handle: FocusHandle,
+    file_list_focus: FocusHandle,
+    sidebar_focus: FocusHandle,
+    // 撤销日志
+    undo_journal: UndoJournal,
+    // 撤销日志是否已从磁盘加载（加载前不保存，以免覆盖磁盘上的日志）
+    undo_journal_loaded: bool,
+    // 依次在后台保存撤销日志
+    journal_writer: JournalWriter,
+    // 是否有撤销/重做正在执行
+    undo_in_progress: bool,
+    // 最近一次失败的文件操作或撤销/重做，显示在窗口顶部直到关闭
+    operation_error: Option<String>,
+    // 行内重命名
+    renaming: Option<RenameState>,
+    // 地址栏编辑
+    location_edit: Option<LocationEditState>,
+    // 慢速双击触发的延迟重命名
+    pending_rename: Option<Task<()>>,
+    // 批量重命名对话框
+    batch_rename: Option<(Entity<BatchRenameDialog>, Subscription)>,
+    // 压缩对话框
+    compress: Option<(Entity<CompressDialog>, Subscription)>,
+    // 等待执行的文件操作（依次执行）
+    operation_queue: VecDeque<QueuedOperations>,
+    // 正在执行的文件操作及其进度
+    running_operation: Option<(String, Arc<Progress>)>,
+    // 应用内最近一次复制/剪切的文件
+    file_clipboard: Option<FileClipboard>,
+    // 拖动时悬停的文件夹（面板、路径、延迟打开任务）
+    drag_hover: Option<(PanelId, String, Task<()>)>,
+    // 右键菜单
+    context_menu: Option<(Entity<ContextMenu>, Subscription)>,
+    // 属性对话框
+    properties: Option<(Entity<PropertiesDialog>, Subscription)>,
+    // 磁盘占用分析
+    disk_usage: Option<(Entity<DiskUsageView>, Subscription)>,
+    // 命令面板
+    command_palette: Option<(Entity<CommandPalette>, Subscription)>,
+    // 命令面板最近使用的条目
+    palette_history: PaletteHistory,
+    // 快捷访问列表
+    quick_access: QuickAccess,
+    // 侧边栏快捷访问项的重命名
+    sidebar_rename: Option<SidebarRenameState>,
+    // 最近访问的位置
+    recent: RecentLocations,
+    // 选中文件夹的递归大小
+    folder_size: Option<FolderSizeState>,
 }
 
 impl Explorer {
     /// 创建 Explorer 实例
     pub fn new(cx: &mut Context<Self>) -> Self {
-        let provider: Arc<dyn StorageProvider> = Arc::new(LocalFileSystemProvider::new());
+        // 压缩包可以像文件夹一样浏览
+        let provider: Arc<dyn StorageProvider> = Arc::new(ArchiveProvider::new(Arc::new(
+            LocalFileSystemProvider::new(),
+        )));
 
         // 使用用户主目录作为默认路径，如果获取失败则使用根目录
         let default_path = home_dir()
@@ -409,15 +758,46 @@ impl Explorer {
         let initial_panel_id = 0;
         let panel_tree = PanelNode::new_leaf(initial_panel_id, default_path.clone(), cx);
 
+        // 快捷键配置重新加载后刷新问题提示
+        cx.observe_global::<KeymapStatus>(|_, cx| cx.notify())
+            .detach();
+
         Self {
             provider,
             roots: vec![],
+            root_space: HashMap::new(),
             selected_sidebar_path: Some(default_path),
             panel_tree,
             active_panel_id: Some(initial_panel_id),
             next_panel_id: 1,
             selected_items: HashSet::new(),
             last_selected_index: None,
+            focus_handle: cx.focus_handle(),
+            file_list_focus: cx.focus_handle(),
+            sidebar_focus: cx.focus_handle(),
+            undo_journal: UndoJournal::default(),
+            undo_journal_loaded: false,
+            journal_writer: JournalWriter::new(cx.background_executor()),
+            undo_in_progress: false,
+            operation_error: None,
+            renaming: None,
+            location_edit: None,
+            pending_rename: None,
+            batch_rename: None,
+            compress: None,
+            operation_queue: VecDeque::new(),
+            running_operation: None,
+            file_clipboard: None,
+            drag_hover: None,
+            context_menu: None,
+            properties: None,
+            disk_usage: None,
+            command_palette: None,
+            palette_history: PaletteHistory::default(),
+            quick_access: QuickAccess::default(),
+            sidebar_rename: None,
+            recent: RecentLocations::default(),
+            folder_size: None,
         }
     }
 
@@ -622,6 +1002,137 @@ impl Explorer {
         cx.notify();
     }
 
+    // ===== 键盘导航 =====
+
+    /// 选中激活面板中的第 `index` 项并滚动到可见位置，`extend` 时保留已有选中
+    fn select_index(&mut self, index: usize, extend: bool, cx: &mut Context<Self>) {
+        let Some((panel_id, _, entries)) = self.active_leaf() else {
+            return;
+        };
+        let Some(entry) = entries.get(index) else {
+            return;
+        };
+        if extend {
+            self.selected_items.insert(entry.path.clone());
+            self.last_selected_index = Some(index);
+            cx.notify();
+        } else {
+            self.set_single_selection(entry.path.clone(), index, cx);
+        }
+        if let Some(PanelNode::Leaf { scroll_handle, .. }) = self.panel_tree.find_panel(panel_id) {
+            scroll_handle.scroll_to_item(index, ScrollStrategy::Top);
+        }
+    }
+
+    /// 按偏移移动激活面板中的选中项，没有选中项时从第一项开始
+    fn move_selection(&mut self, offset: isize, extend: bool, cx: &mut Context<Self>) {
+        let Some((_, _, entries)) = self.active_leaf() else {
+            return;
+        };
+        if entries.is_empty() {
+            return;
+        }
+        let index = match self.last_selected_index {
+            Some(index) => index.saturating_add_signed(offset).min(entries.len() - 1),
+            None => 0,
+        };
+        self.select_index(index, extend, cx);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_selection(-1, false, cx);
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_selection(1, false, cx);
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_index(0, false, cx);
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some((_, _, entries)) = self.active_leaf() {
+            self.select_index(entries.len().saturating_sub(1), false, cx);
+        }
+    }
+
+    fn extend_selection_up(
+        &mut self,
+        _: &ExtendSelectionUp,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_selection(-1, true, cx);
+    }
+
+    fn extend_selection_down(
+        &mut self,
+        _: &ExtendSelectionDown,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.move_selection(1, true, cx);
+    }
+
+    fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some((_, _, entries)) = self.active_leaf() {
+            self.selected_items = entries.into_iter().map(|entry| entry.path).collect();
+            cx.notify();
+        }
+    }
+
+    /// 激活面板转到上一级目录
+    fn go_to_parent(&mut self, _: &GoToParent, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(parent) = self
+            .active_leaf()
+            .and_then(|(_, path, _)| paths::parent_path(&path))
+        {
+            self.load_directory(parent, window, cx);
+        }
+    }
+
+    /// 侧边栏中的位置（快捷访问 + 最近访问 + 存储位置，与显示顺序一致）
+    fn sidebar_paths(&self) -> Vec<String> {
+        self.quick_access
+            .items()
+            .iter()
+            .map(|item| item.path.clone())
+            .chain(self.sidebar_recent())
+            .chain(self.roots.iter().map(|root| root.path.clone()))
+            .collect()
+    }
+
+    /// 打开侧边栏中的第 `index` 个位置
+    fn select_sidebar_index(&mut self, index: usize, window: &Window, cx: &mut Context<Self>) {
+        if let Some(path) = self.sidebar_paths().into_iter().nth(index) {
+            self.selected_sidebar_path = Some(path.clone());
+            self.load_directory(path, window, cx);
+        }
+    }
+
+    /// 按偏移移动侧边栏中的选中位置
+    fn move_sidebar_selection(&mut self, offset: isize, window: &Window, cx: &mut Context<Self>) {
+        let paths = self.sidebar_paths();
+        if paths.is_empty() {
+            return;
+        }
+        let index = match self
+            .selected_sidebar_path
+            .as_ref()
+            .and_then(|selected| paths.iter().position(|path| path == selected))
+        {
+            Some(index) => index.saturating_add_signed(offset).min(paths.len() - 1),
+            None => 0,
+        };
+        self.select_sidebar_index(index, window, cx);
+    }
+
+    /// 让激活面板的文件列表获得键盘焦点
+    fn focus_file_list(&self, window: &mut Window) {
+        self.file_list_focus.focus(window);
+    }
+
     /// 更新面板的 bounds
     pub fn update_panel_bounds(&mut self, panel_id: PanelId, bounds: Bounds<Pixels>) {
         self.panel_tree.update_panel_bounds(panel_id, bounds);
@@ -666,10 +1177,10 @@ impl Explorer {
                                 (false, true) => Ordering::Less,
                                 (true, false) => Ordering::Greater,
                                 _ => {
-                                    // 然后按类型排序（目录在前）
-                                    match (&a.item_type, &b.item_type) {
-                                        (ItemType::Directory, ItemType::File) => Ordering::Less,
-                                        (ItemType::File, ItemType::Directory) => Ordering::Greater,
+                                    // 然后按类型排序（目录和指向目录的链接在前）
+                                    match (a.is_dir_like(), b.is_dir_like()) {
+                                        (true, false) => Ordering::Less,
+                                        (false, true) => Ordering::Greater,
                                         _ => {
                                             // 最后按名称排序（不区分大小写）
                                             a.name.to_lowercase().cmp(&b.name.to_lowercase())
@@ -680,6 +1191,9 @@ impl Explorer {
                         });
 
                         tracing::info!("面板 {} 成功加载 {} 个条目", panel_id, entries.len());
+                        if explorer.recent.record(&path_clone) {
+                            explorer.save_recent(cx);
+                        }
                         explorer.panel_tree.update_panel_data(
                             panel_id,
                             path_clone.clone(),
@@ -743,8 +1257,29 @@ impl Explorer {
                 })
                 .await;
 
+            // 加载撤销日志、命令面板记录、快捷访问和最近访问的位置
+            let (journal, palette_history, quick_access, recent) = cx
+                .background_executor()
+                .spawn(async move {
+                    (
+                        UndoJournal::load(),
+                        PaletteHistory::load(),
+                        QuickAccess::load(),
+                        RecentLocations::load(),
+                    )
+                })
+                .await;
+
             // 更新 UI
-            let _ = cx.update(|_, cx| {
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.restore_undo_journal(journal);
+                    explorer.palette_history = palette_history;
+                    explorer.quick_access = quick_access;
+                    explorer.recent = recent;
+                    explorer.watch_gtk_bookmarks(window, cx);
+                    explorer.watch_roots(window, cx);
+                });
                 let _ = this.update(cx, |explorer, cx| match ret {
                     Ok((roots, mut entries)) => {
                         // 排序：非隐藏文件在前，然后按目录/文件分类，最后按名称排序
@@ -754,10 +1289,10 @@ impl Explorer {
                                 (false, true) => Ordering::Less,
                                 (true, false) => Ordering::Greater,
                                 _ => {
-                                    // 然后按类型排序（目录在前）
-                                    match (&a.item_type, &b.item_type) {
-                                        (ItemType::Directory, ItemType::File) => Ordering::Less,
-                                        (ItemType::File, ItemType::Directory) => Ordering::Greater,
+                                    // 然后按类型排序（目录和指向目录的链接在前）
+                                    match (a.is_dir_like(), b.is_dir_like()) {
+                                        (true, false) => Ordering::Less,
+                                        (false, true) => Ordering::Greater,
                                         _ => {
                                             // 最后按名称排序（不区分大小写）
                                             a.name.to_lowercase().cmp(&b.name.to_lowercase())
@@ -846,130 +1381,1949 @@ impl Explorer {
             tracing::error!("关闭面板失败: {}", panel_id);
         }
     }
-}
 
-impl Render for Explorer {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let theme = cx.global::<Theme>();
+    // ===== 文件操作 =====
 
-        // 获取快捷访问项
-        let quick_access_items = quick_access::get_quick_access_items();
+    /// 将文件操作加入队列，队列中的操作按顺序在后台执行
+    pub fn run_file_operations(
+        &mut self,
+        label: impl Into<String>,
+        operations: Vec<FileOperation>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.queue_file_operations(label, operations, None, window, cx);
+    }
 
-        // 获取 Explorer 实体的弱引用用于事件回调
-        let this_entity = cx.entity().downgrade();
+    /// 将文件操作加入队列，完成后调用 `on_complete`（失败时由它报告错误）
+    fn queue_file_operations(
+        &mut self,
+        label: impl Into<String>,
+        operations: Vec<FileOperation>,
+        on_complete: Option<OperationCallback>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.operation_queue.push_back(QueuedOperations {
+            label: label.into(),
+            operations,
+            on_complete,
+        });
+        self.process_operation_queue(window, cx);
+    }
 
-        // 构建侧边栏
-        let sidebar = self.render_sidebar(quick_access_items, theme, &this_entity);
+    /// 执行队列中的下一组文件操作，并将完成的部分记录到撤销日志
+    ///
+    /// 任一操作失败时停止执行这一组，已完成的操作仍然会被记录，以便撤销。
+    fn process_operation_queue(&mut self, window: &Window, cx: &mut Context<Self>) {
+        // 撤销/重做完成后会继续执行队列
+        if self.running_operation.is_some() || self.undo_in_progress {
+            return;
+        }
+        let Some(QueuedOperations {
+            label,
+            operations,
+            on_complete,
+        }) = self.operation_queue.pop_front()
+        else {
+            return;
+        };
+        let progress = Arc::new(Progress::default());
+        self.running_operation = Some((label.clone(), progress.clone()));
+        let provider = self.provider.clone();
+        tracing::info!("执行文件操作: {}（{} 项）", label, operations.len());
+
+        // 压缩、解压等耗时操作执行期间定时刷新状态栏中的进度
+        cx.spawn_in(window, {
+            let progress = progress.clone();
+            async move |this, cx| {
+                loop {
+                    cx.background_executor()
+                        .timer(Duration::from_millis(200))
+                        .await;
+                    let running = this.update(cx, |explorer, cx| {
+                        let running = explorer
+                            .running_operation
+                            .as_ref()
+                            .is_some_and(|(_, running)| Arc::ptr_eq(running, &progress));
+                        if running && progress.fraction().is_some() {
+                            cx.notify();
+                        }
+                        running
+                    });
+                    if !running.unwrap_or(false) {
+                        break;
+                    }
+                }
+            }
+        })
+        .detach();
 
-        // 构建面板树（递归渲染）
-        let panel_content = self.render_panel_node(&self.panel_tree.clone(), theme, &this_entity);
+        cx.spawn_in(window, async move |this, cx| {
+            let affected: Vec<String> = operations.iter().flat_map(|op| op.paths()).collect();
+            let (completed, error) = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut completed = Vec::with_capacity(operations.len());
+                    for operation in &operations {
+                        match file_ops::execute(provider.as_ref(), operation, &progress).await {
+                            Ok(done) => completed.push(done),
+                            Err(e) => return (completed, Some(e)),
+                        }
+                    }
+                    (completed, None)
+                })
+                .await;
 
-        // 主内容区域（侧边栏 + 面板）
-        let main_content = Resizable::new("explorer-layout", sidebar, panel_content)
-            .size(px(240.))
-            .range(px(180.)..px(480.));
+            if let Some(e) = &error {
+                tracing::error!("文件操作失败: {}: {}", label, e);
+            }
 
-        // 构建标题栏
-        let this_clone_h = this_entity.clone();
-        let this_clone_v = this_entity.clone();
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.running_operation = None;
+                    if let Some(e) = &error
+                        && on_complete.is_none()
+                    {
+                        explorer.operation_error = Some(format!("{}失败: {}", label, e));
+                    }
+                    explorer
+                        .undo_journal
+                        .record(UndoEntry::new(label, completed));
+                    explorer.save_undo_journal();
+                    explorer.refresh_panels(&affected, window, cx);
+                    if let Some(on_complete) = on_complete {
+                        on_complete(explorer, error, window, cx);
+                    }
+                    explorer.process_operation_queue(window, cx);
+                });
+            });
+        })
+        .detach();
+    }
 
-        div()
-            .flex()
-            .flex_col()
-            .size_full()
-            .child(
-                // 标题栏
-                TitleBar::new().child(
-                    div()
-                        .flex()
-                        .items_center()
-                        .justify_end()
-                        .gap(theme.spacing.sm)
-                        .pr_4()
-                        .child(
-                            // 横向拆分按钮
-                            div()
-                                .flex()
-                                .items_center()
-                                .justify_center()
-                                .size_7()
-                                .rounded(px(4.))
-                                .cursor_pointer()
-                                .hover(|style| style.bg(theme.colors.muted))
-                                .child(
-                                    svg()
-                                        .path("icons/columns-split.svg")
-                                        .size_4()
-                                        .text_color(theme.colors.foreground),
-                                )
-                                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
-                                    if let Some(this) = this_clone_h.upgrade() {
-                                        let _ = this.update(cx, |explorer, cx| {
-                                            explorer.split_panel_horizontal(window, cx);
-                                        });
-                                    }
-                                }),
-                        )
-                        .child(
-                            // 纵向拆分按钮
-                            div()
-                                .flex()
-                                .items_center()
-                                .justify_center()
-                                .size_7()
-                                .rounded(px(4.))
-                                .cursor_pointer()
-                                .hover(|style| style.bg(theme.colors.muted))
-                                .child(
-                                    svg()
-                                        .path("icons/rows-split.svg")
-                                        .size_4()
-                                        .text_color(theme.colors.foreground),
-                                )
-                                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
-                                    if let Some(this) = this_clone_v.upgrade() {
-                                        let _ = this.update(cx, |explorer, cx| {
-                                            explorer.split_panel_vertical(window, cx);
-                                        });
-                                    }
-                                }),
-                        ),
-                ),
-            )
-            .child(
-                // 主内容区域
-                div().flex_1().child(main_content),
-            )
+    /// 获取激活面板的目录和条目
+    fn active_leaf(&self) -> Option<(PanelId, String, Vec<FileItem>)> {
+        let active_id = self.active_panel_id?;
+        match self.panel_tree.find_panel(active_id)? {
+            PanelNode::Leaf { path, entries, .. } => {
+                Some((active_id, path.clone(), entries.clone()))
+            }
+            PanelNode::Split { .. } => None,
+        }
     }
-}
 
-impl Explorer {
-    /// 渲染侧边栏
-    fn render_sidebar(
-        &self,
-        quick_access_items: Vec<QuickAccessItem>,
-        theme: &Theme,
-        this_entity: &WeakEntity<Self>,
-    ) -> impl IntoElement {
-        let selected_path = self.selected_sidebar_path.clone();
+    /// 在激活面板中新建文件夹
+    fn new_folder(&mut self, _: &NewFolder, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((_, dir, entries)) = self.active_leaf() else {
+            return;
+        };
+        let taken = entries.into_iter().map(|entry| entry.name).collect();
+        let name = unique_name(&taken, "新建文件夹");
+        self.run_file_operations(
+            format!("新建文件夹 {}", name),
+            vec![FileOperation::Create {
+                path: paths::join_path(&dir, &name),
+                item_type: ItemType::Directory,
+            }],
+            window,
+            cx,
+        );
+    }
+
+    /// 将选中的条目移入回收站
+    fn trash_selected(&mut self, _: &TrashSelected, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_items.is_empty() {
+            return;
+        }
+        let mut selected: Vec<String> = self.selected_items.iter().cloned().collect();
+        selected.sort();
+        let operations = selected
+            .into_iter()
+            .map(|path| FileOperation::Trash { path })
+            .collect::<Vec<_>>();
+        self.run_file_operations(
+            format!("移入回收站（{} 项）", operations.len()),
+            operations,
+            window,
+            cx,
+        );
+    }
+
+    // ===== 压缩包 =====
+
+    /// 为选中的条目打开压缩对话框，压缩包保存在当前目录
+    fn compress_selected(
+        &mut self,
+        _: &CompressSelected,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let sources = self.selected_paths();
+        if sources.is_empty() || !self.provider.capabilities().can_archive {
+            return;
+        }
+        let Some((_, dir, entries)) = self.active_leaf() else {
+            return;
+        };
+
+        let dialog = cx.new(|cx| CompressDialog::new(sources, dir, entries, window, cx));
+        let subscription = cx.subscribe_in(&dialog, window, |explorer, _, event, window, cx| {
+            let operation = match event {
+                CompressEvent::Apply(operation) => Some(operation.clone()),
+                CompressEvent::Dismiss => None,
+            };
+            explorer.compress = None;
+            explorer.focus_file_list(window);
+            if let Some(operation) = operation {
+                explorer.run_file_operations("压缩", vec![operation], window, cx);
+            }
+            cx.notify();
+        });
+        dialog.read(cx).focus(window, cx);
+        self.compress = Some((dialog, subscription));
+        cx.notify();
+    }
+
+    /// 选中的压缩包（选中项中有其他条目时为空）
+    fn selected_archives(&self) -> Vec<FileItem> {
+        let selected = self.selected_entries();
+        if selected
+            .iter()
+            .all(|entry| entry.item_type == ItemType::File && is_archive(&entry.path))
+        {
+            selected
+        } else {
+            vec![]
+        }
+    }
+
+    /// 把选中的压缩包解压到当前目录
+    fn extract_here(&mut self, _: &ExtractHere, window: &mut Window, cx: &mut Context<Self>) {
+        let archives = self.selected_archives();
+        let Some((_, dir, _)) = self.active_leaf() else {
+            return;
+        };
+        if archives.is_empty() || !self.provider.capabilities().can_archive {
+            return;
+        }
+        let operations = archives
+            .into_iter()
+            .map(|archive| FileOperation::Extract {
+                archive: archive.path,
+                target_dir: dir.clone(),
+            })
+            .collect::<Vec<_>>();
+        self.run_file_operations(
+            format!("解压（{} 项）", operations.len()),
+            operations,
+            window,
+            cx,
+        );
+    }
+
+    /// 把选中的压缩包分别解压到以其名称命名的新文件夹中
+    fn extract_to_folder(
+        &mut self,
+        _: &ExtractToFolder,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let archives = self.selected_archives();
+        let Some((_, dir, entries)) = self.active_leaf() else {
+            return;
+        };
+        if archives.is_empty() || !self.provider.capabilities().can_archive {
+            return;
+        }
+        let mut taken: HashSet<String> = entries.into_iter().map(|entry| entry.name).collect();
+        let operations = archives
+            .into_iter()
+            .map(|archive| {
+                let stem = ArchiveFormat::strip_extension(&archive.name).unwrap_or(&archive.name);
+                let name = unique_name(&taken, stem);
+                taken.insert(name.clone());
+                FileOperation::Extract {
+                    archive: archive.path,
+                    target_dir: paths::join_path(&dir, &name),
+                }
+            })
+            .collect::<Vec<_>>();
+        self.run_file_operations(
+            format!("解压（{} 项）", operations.len()),
+            operations,
+            window,
+            cx,
+        );
+    }
+
+    /// 当前选中的路径（按路径排序）
+    fn selected_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.selected_items.iter().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    // ===== 拖放 =====
+
+    /// 放下文件：应用内拖动按修饰键和根目录决定移动或复制，外部拖入总是复制
+    fn drop_files(
+        &mut self,
+        sources: Vec<String>,
+        target_dir: String,
+        external: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.drag_hover = None;
+        if sources.is_empty() {
+            return;
+        }
+        let mode = if external {
+            ClipboardMode::Copy
+        } else {
+            dnd::drop_mode(&sources, &target_dir, &self.roots, &window.modifiers())
+        };
+        self.transfer_files(mode, sources, target_dir, "拖放", window, cx);
+    }
+
+    /// 拖动经过文件夹时更新悬停状态，悬停足够久后在该面板中打开文件夹
+    fn update_drag_hover(
+        &mut self,
+        panel_id: PanelId,
+        path: &str,
+        hovered: bool,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        let is_current = self
+            .drag_hover
+            .as_ref()
+            .is_some_and(|(id, hover_path, _)| *id == panel_id && hover_path == path);
+
+        if hovered && !is_current {
+            let target = path.to_string();
+            let task = cx.spawn_in(window, async move |this, cx| {
+                cx.background_executor().timer(dnd::HOVER_OPEN_DELAY).await;
+                let _ = cx.update(|window, cx| {
+                    let dragging = cx.has_active_drag();
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.drag_hover = None;
+                        if dragging {
+                            explorer.load_directory_for_panel(panel_id, target, window, cx);
+                        }
+                    });
+                });
+            });
+            self.drag_hover = Some((panel_id, path.to_string(), task));
+        } else if !hovered && is_current {
+            self.drag_hover = None;
+        }
+    }
+
+    /// 刷新显示了受影响路径（或其父目录）的面板
+    pub fn refresh_panels(
+        &mut self,
+        changed_paths: &[String],
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        let dirs: HashSet<String> = changed_paths
+            .iter()
+            .flat_map(|path| [Some(path.clone()), paths::parent_path(path)])
+            .flatten()
+            .collect();
+
+        for (panel_id, panel_path) in self.panel_tree.leaf_paths() {
+            if dirs.contains(&panel_path) {
+                self.load_directory_for_panel(panel_id, panel_path, window, cx);
+            }
+        }
+        // 文件变化后卷的剩余空间也随之变化
+        self.reload_roots(window, cx);
+    }
+}
+
+impl Explorer {
+    // ===== 行内重命名 =====
+
+    /// F2：重命名激活面板中选中的条目（选中多项时打开批量重命名）
+    fn rename_selected(&mut self, _: &Rename, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((panel_id, _, entries)) = self.active_leaf() else {
+            return;
+        };
+        let selected_count = entries
+            .iter()
+            .filter(|entry| self.is_selected(&entry.path))
+            .count();
+        if selected_count > 1 {
+            self.batch_rename(&BatchRename, window, cx);
+            return;
+        }
+        let selected = self
+            .last_selected_index
+            .and_then(|index| entries.get(index))
+            .filter(|entry| self.is_selected(&entry.path))
+            .or_else(|| entries.iter().find(|entry| self.is_selected(&entry.path)));
+        if let Some(entry) = selected {
+            let path = entry.path.clone();
+            self.start_rename(panel_id, path, window, cx);
+        }
+    }
+
+    /// 为激活面板中选中的条目打开批量重命名对话框
+    fn batch_rename(&mut self, _: &BatchRename, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((_, _, entries)) = self.active_leaf() else {
+            return;
+        };
+        let items: Vec<FileItem> = entries
+            .iter()
+            .filter(|entry| self.is_selected(&entry.path))
+            .cloned()
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+
+        let dialog = cx.new(|cx| BatchRenameDialog::new(items, entries, window, cx));
+        let subscription = cx.subscribe_in(&dialog, window, |explorer, _, event, window, cx| {
+            let operations = match event {
+                BatchRenameEvent::Apply(operations) => operations.clone(),
+                BatchRenameEvent::Dismiss => vec![],
+            };
+            explorer.batch_rename = None;
+            explorer.focus_file_list(window);
+            if !operations.is_empty() {
+                explorer.run_file_operations("批量重命名", operations, window, cx);
+            }
+            cx.notify();
+        });
+        dialog.read(cx).focus(window, cx);
+
+        self.cancel_pending_rename();
+        self.renaming = None;
+        self.batch_rename = Some((dialog, subscription));
+        cx.notify();
+    }
+
+    /// 再次单击已选中的条目时，延迟一段时间后进入重命名（期间发生双击则取消）
+    fn schedule_rename(
+        &mut self,
+        panel_id: PanelId,
+        path: String,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.pending_rename = Some(cx.spawn_in(window, async move |this, cx| {
+            cx.background_executor()
+                .timer(Duration::from_millis(500))
+                .await;
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.pending_rename = None;
+                    if explorer.is_selected(&path) && explorer.selected_items.len() == 1 {
+                        explorer.start_rename(panel_id, path, window, cx);
+                    }
+                });
+            });
+        }));
+    }
+
+    /// 取消尚未触发的延迟重命名
+    fn cancel_pending_rename(&mut self) {
+        self.pending_rename = None;
+    }
+
+    /// 进入行内重命名
+    fn start_rename(
+        &mut self,
+        panel_id: PanelId,
+        path: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(PanelNode::Leaf { entries, .. }) = self.panel_tree.find_panel(panel_id) else {
+            return;
+        };
+        let Some(entry) = entries.iter().find(|entry| entry.path == path) else {
+            return;
+        };
+        let name = entry.name.clone();
+        let selection = rename_selection(&name, entry.item_type);
+
+        let input = cx.new(|cx| {
+            let mut input = TextInput::new(window, cx);
+            input.set_text(name.clone(), cx);
+            input.select_range(0..selection, cx);
+            input
+        });
+        let subscription = cx.subscribe_in(&input, window, |explorer, _, event, window, cx| {
+            match event {
+                TextInputEvent::Change(text) => explorer.validate_rename(text, cx),
+                TextInputEvent::Confirm => explorer.commit_rename(window, cx),
+                TextInputEvent::Cancel => explorer.cancel_rename(window, cx),
+                TextInputEvent::Blur => {
+                    // 失去焦点时：名称有效则提交，否则放弃
+                    if explorer
+                        .renaming
+                        .as_ref()
+                        .is_some_and(|state| state.error.is_none())
+                    {
+                        explorer.commit_rename(window, cx);
+                    } else {
+                        explorer.cancel_rename(window, cx);
+                    }
+                }
+            }
+        });
+        input.read(cx).focus(window);
+
+        self.pending_rename = None;
+        self.renaming = Some(RenameState {
+            panel_id,
+            path,
+            original_name: name,
+            input,
+            error: None,
+            committing: false,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    /// 输入变化时校验名称
+    fn validate_rename(&mut self, text: &str, cx: &mut Context<Self>) {
+        let Some(state) = self.renaming.as_ref() else {
+            return;
+        };
+        let entries = match self.panel_tree.find_panel(state.panel_id) {
+            Some(PanelNode::Leaf { entries, .. }) => entries.as_slice(),
+            _ => &[],
+        };
+        let error = paths::validate_file_name(text, &state.original_name, entries).err();
+        let input = state.input.clone();
+        input.update(cx, |input, cx| input.set_invalid(error.is_some(), cx));
+        if let Some(state) = self.renaming.as_mut() {
+            state.error = error;
+        }
+        cx.notify();
+    }
+
+    /// 退出重命名，焦点回到文件列表
+    fn cancel_rename(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.renaming.take().is_some() {
+            self.focus_file_list(window);
+            cx.notify();
+        }
+    }
+
+    /// 提交重命名：调用 provider 重命名，成功后原地更新条目并记录到撤销日志
+    fn commit_rename(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(state) = self.renaming.as_mut() else {
+            return;
+        };
+        if state.committing {
+            return;
+        }
+
+        let new_name = state.input.read(cx).text().to_string();
+        if new_name == state.original_name {
+            self.cancel_rename(window, cx);
+            return;
+        }
+        let text = new_name.clone();
+        self.validate_rename(&text, cx);
+        let Some(state) = self.renaming.as_mut() else {
+            return;
+        };
+        if state.error.is_some() {
+            return;
+        }
+        let from = state.path.clone();
+        let Some(dir) = paths::parent_path(&from) else {
+            state.error = Some("无法重命名根目录".to_string());
+            state
+                .input
+                .update(cx, |input, cx| input.set_invalid(true, cx));
+            cx.notify();
+            return;
+        };
+        state.committing = true;
+
+        let panel_id = state.panel_id;
+        let to = paths::join_path(&dir, &new_name);
+        let label = format!("重命名 {} 为 {}", state.original_name, new_name);
+        tracing::info!("{}", label);
+
+        // 与其他文件操作一样排队执行，撤销日志中的顺序与实际执行的顺序一致
+        let operation = FileOperation::Rename {
+            from: from.clone(),
+            to: to.clone(),
+        };
+        let on_complete: OperationCallback =
+            Box::new(move |explorer, error, window, cx| match error {
+                None => explorer.finish_rename(panel_id, &from, &to, window, cx),
+                Some(e) => {
+                    if let Some(state) = explorer.renaming.as_mut()
+                        && state.path == from
+                    {
+                        state.committing = false;
+                        state.error = Some(e.to_string());
+                        state
+                            .input
+                            .update(cx, |input, cx| input.set_invalid(true, cx));
+                    } else {
+                        explorer.operation_error = Some(format!("重命名失败: {}", e));
+                    }
+                    cx.notify();
+                }
+            });
+        self.queue_file_operations(label, vec![operation], Some(on_complete), window, cx);
+    }
+
+    /// 重命名成功后原地更新条目和选中状态（显示同一目录的面板随后重新加载）
+    fn finish_rename(
+        &mut self,
+        panel_id: PanelId,
+        old_path: &str,
+        new_path: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self
+            .renaming
+            .as_ref()
+            .is_some_and(|state| state.path == old_path)
+        {
+            self.renaming = None;
+            self.focus_file_list(window);
+        }
+        if self.selected_items.remove(old_path) {
+            self.selected_items.insert(new_path.to_string());
+        }
+        let renamed = match self.panel_tree.find_panel(panel_id) {
+            Some(PanelNode::Leaf { entries, .. }) => {
+                entries.iter().find(|entry| entry.path == old_path).cloned()
+            }
+            _ => None,
+        };
+        if let Some(mut item) = renamed {
+            item.name = paths::file_name(new_path).unwrap_or_else(|| new_path.to_string());
+            item.path = new_path.to_string();
+            self.panel_tree.replace_entry(panel_id, old_path, item);
+        }
+        cx.notify();
+    }
+}
+
+impl Explorer {
+    // ===== 地址栏 =====
+
+    /// 激活面板的面包屑进入编辑状态（Ctrl+L）
+    fn edit_location(&mut self, _: &EditLocation, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(panel_id) = self.active_panel_id {
+            self.start_location_edit(panel_id, window, cx);
+        }
+    }
+
+    /// 把面板的面包屑切换为地址输入框，内容为当前路径并全选
+    fn start_location_edit(
+        &mut self,
+        panel_id: PanelId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(PanelNode::Leaf { path, .. }) = self.panel_tree.find_panel(panel_id) else {
+            return;
+        };
+        if let Some(state) = self.location_edit.as_ref()
+            && state.panel_id == panel_id
+        {
+            state.input.read(cx).focus(window);
+            return;
+        }
+        let path = path.clone();
+
+        let input = cx.new(|cx| {
+            let mut input = TextInput::new(window, cx).placeholder("输入路径");
+            input.set_text(path.clone(), cx);
+            input.select_range(0..path.len(), cx);
+            input
+        });
+        let subscription = cx.subscribe_in(&input, window, |explorer, _, event, window, cx| {
+            match event {
+                TextInputEvent::Change(text) => {
+                    explorer.update_location_completions(text, window, cx)
+                }
+                TextInputEvent::Confirm => explorer.commit_location(window, cx),
+                TextInputEvent::Cancel => {
+                    explorer.location_edit = None;
+                    explorer.focus_file_list(window);
+                    cx.notify();
+                }
+                TextInputEvent::Blur => {
+                    // 点击其他位置时放弃编辑，焦点留在被点击的位置
+                    explorer.location_edit = None;
+                    cx.notify();
+                }
+            }
+        });
+        input.read(cx).focus(window);
+
+        self.location_edit = Some(LocationEditState {
+            panel_id,
+            input,
+            error: None,
+            completion_dir: None,
+            children: vec![],
+            suggestions: vec![],
+            selected_suggestion: None,
+            checking: false,
+            _subscription: subscription,
+        });
+        self.update_location_completions(&path, window, cx);
+    }
+
+    /// 输入变化时更新补全候选，目录变化时在后台读取新目录的子目录
+    fn update_location_completions(&mut self, text: &str, window: &Window, cx: &mut Context<Self>) {
+        let recent = self.recent_paths();
+        let Some(state) = self.location_edit.as_mut() else {
+            return;
+        };
+        if state.error.take().is_some() {
+            state
+                .input
+                .update(cx, |input, cx| input.set_invalid(false, cx));
+        }
+        state.selected_suggestion = None;
+        cx.notify();
+
+        let target = location::completion_target(text);
+        if target
+            .as_ref()
+            .is_none_or(|target| state.completion_dir.as_ref() == Some(&target.dir))
+        {
+            state.suggestions = location::suggestions(text, &state.children, &recent);
+            return;
+        }
+        let Some(target) = target else {
+            return;
+        };
+
+        state.completion_dir = Some(target.dir.clone());
+        state.children.clear();
+        state.suggestions = location::suggestions(text, &[], &recent);
+        let provider = self.provider.clone();
+        let dir = target.dir;
+        cx.spawn_in(window, async move |this, cx| {
+            let list_dir = dir.clone();
+            let children = cx
+                .background_executor()
+                .spawn(async move { provider.list_entries(&list_dir).await })
+                .await;
+            let mut children: Vec<String> = match children {
+                Ok(entries) => entries
+                    .into_iter()
+                    .filter(|entry| entry.is_dir_like())
+                    .map(|entry| entry.name)
+                    .collect(),
+                Err(e) => {
+                    tracing::debug!("无法读取补全目录 {}: {}", dir, e);
+                    vec![]
+                }
+            };
+            children.sort_by_key(|name| name.to_lowercase());
+
+            let _ = this.update(cx, |explorer, cx| {
+                let recent = explorer.recent_paths();
+                let Some(state) = explorer.location_edit.as_mut() else {
+                    return;
+                };
+                if state.completion_dir.as_ref() != Some(&dir) {
+                    return;
+                }
+                let text = state.input.read(cx).text();
+                state.suggestions = location::suggestions(&text, &children, &recent);
+                state.children = children;
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// 在补全候选之间移动（上下方向键）
+    fn select_location_suggestion(&mut self, offset: isize, cx: &mut Context<Self>) {
+        let Some(state) = self.location_edit.as_mut() else {
+            return;
+        };
+        if state.suggestions.is_empty() {
+            return;
+        }
+        let last = state.suggestions.len() - 1;
+        state.selected_suggestion = Some(match state.selected_suggestion {
+            Some(index) => index.saturating_add_signed(offset).min(last),
+            None if offset < 0 => last,
+            None => 0,
+        });
+        cx.notify();
+    }
+
+    /// 用选中的补全候选（没有选中时为第一个）补全输入（Tab）
+    fn accept_completion(&mut self, window: &Window, cx: &mut Context<Self>) {
+        let Some(state) = self.location_edit.as_ref() else {
+            return;
+        };
+        let index = state.selected_suggestion.unwrap_or(0);
+        self.apply_location_suggestion(index, window, cx);
+    }
+
+    /// 把第 `index` 个补全候选填入输入框，并继续补全它的子目录
+    fn apply_location_suggestion(&mut self, index: usize, window: &Window, cx: &mut Context<Self>) {
+        let Some(state) = self.location_edit.as_ref() else {
+            return;
+        };
+        let Some(suggestion) = state.suggestions.get(index) else {
+            return;
+        };
+        let completed = suggestion.text.clone();
+        state
+            .input
+            .update(cx, |input, cx| input.set_text(completed.clone(), cx));
+        self.update_location_completions(&completed, window, cx);
+    }
+
+    /// 确认地址：路径存在且是文件夹时在面板中打开，否则显示错误
+    fn commit_location(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(state) = self.location_edit.as_mut() else {
+            return;
+        };
+        if state.checking {
+            return;
+        }
+        if let Some(index) = state.selected_suggestion {
+            self.apply_location_suggestion(index, window, cx);
+        }
+        let Some(state) = self.location_edit.as_mut() else {
+            return;
+        };
+        let text = state.input.read(cx).text();
+        let path = match location::resolve(&text) {
+            Ok(path) => path,
+            Err(e) => {
+                state.error = Some(e);
+                state
+                    .input
+                    .update(cx, |input, cx| input.set_invalid(true, cx));
+                cx.notify();
+                return;
+            }
+        };
+        state.checking = true;
+
+        let panel_id = state.panel_id;
+        let provider = self.provider.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let check_path = path.clone();
+            let ret = cx
+                .background_executor()
+                .spawn(async move {
+                    if !provider.exists(&check_path).await? {
+                        return Ok(Err("路径不存在".to_string()));
+                    }
+                    let item = provider.get_metadata(&check_path).await?;
+                    if !item.is_dir_like() && !is_archive(&check_path) {
+                        return Ok(Err("不是文件夹".to_string()));
+                    }
+                    Ok::<_, StorageError>(Ok(()))
+                })
+                .await;
+
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    let Some(state) = explorer.location_edit.as_mut() else {
+                        return;
+                    };
+                    state.checking = false;
+                    match ret.map_err(|e| e.to_string()).and_then(|ret| ret) {
+                        Ok(()) => {
+                            tracing::info!("地址栏打开: {}", path);
+                            explorer.location_edit = None;
+                            explorer.set_active_panel(panel_id, cx);
+                            explorer.load_directory_for_panel(panel_id, path, window, cx);
+                            explorer.focus_file_list(window);
+                        }
+                        Err(e) => {
+                            state.error = Some(format!("{}: {}", e, path));
+                            state
+                                .input
+                                .update(cx, |input, cx| input.set_invalid(true, cx));
+                        }
+                    }
+                    cx.notify();
+                });
+            });
+        })
+        .detach();
+    }
+}
+
+impl Explorer {
+    // ===== 快捷访问 =====
+
+    /// 在后台保存快捷访问列表（同时写入 GTK 书签）
+    fn save_quick_access(&self, cx: &mut Context<Self>) {
+        let quick_access = self.quick_access.clone();
+        cx.background_executor()
+            .spawn(async move {
+                if let Err(e) = quick_access.save() {
+                    tracing::error!("保存快捷访问失败: {}", e);
+                }
+            })
+            .detach();
+    }
+
+    /// 定期检查 GTK 书签文件，其他程序修改后合并到快捷访问
+    fn watch_gtk_bookmarks(&self, window: &Window, cx: &mut Context<Self>) {
+        cx.spawn_in(window, async move |this, cx| {
+            let mut last_modified = QuickAccess::gtk_modified();
+            loop {
+                cx.background_executor().timer(Duration::from_secs(2)).await;
+                let modified = QuickAccess::gtk_modified();
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+                let updated = this.update(cx, |explorer, cx| {
+                    if explorer.quick_access.sync_from_gtk() {
+                        tracing::info!("GTK 书签已变化，更新快捷访问");
+                        explorer.save_quick_access(cx);
+                        cx.notify();
+                    }
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// 可以固定的位置：选中的文件夹，没有选中条目时为当前目录（仅本地路径）
+    fn pin_targets(&self) -> Vec<String> {
+        if !self.provider.capabilities().local_paths {
+            return vec![];
+        }
+        let selected = self.selected_entries();
+        let targets: Vec<String> = if selected.is_empty() {
+            self.active_leaf()
+                .map(|(_, path, _)| vec![path])
+                .unwrap_or_default()
+        } else if selected.iter().all(|entry| entry.is_dir_like()) {
+            selected.into_iter().map(|entry| entry.path).collect()
+        } else {
+            vec![]
+        };
+        targets
+            .into_iter()
+            .filter(|path| !self.quick_access.contains(path))
+            .collect()
+    }
+
+    /// 把选中的文件夹（或当前目录）固定到快捷访问
+    fn pin_to_quick_access(
+        &mut self,
+        _: &PinToQuickAccess,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let targets = self.pin_targets();
+        if targets.is_empty() {
+            return;
+        }
+        for path in &targets {
+            tracing::info!("固定到快捷访问: {}", path);
+            self.quick_access.pin(path);
+        }
+        self.save_quick_access(cx);
+        cx.notify();
+    }
+
+    /// 调整快捷访问项的顺序
+    fn move_quick_access(&mut self, from: usize, to: usize, cx: &mut Context<Self>) {
+        if self.quick_access.move_item(from, to) {
+            self.save_quick_access(cx);
+            cx.notify();
+        }
+    }
+
+    /// 快捷访问项的右键菜单
+    fn quick_access_context_menu(
+        &self,
+        index: usize,
+        path: String,
+        cx: &Context<Self>,
+    ) -> Vec<ContextMenuItem> {
+        let count = self.quick_access.items().len();
+        let this = cx.entity().downgrade();
+        let handler =
+            move |f: fn(&mut Explorer, &str, usize, &mut Window, &mut Context<Explorer>)| {
+                let this = this.clone();
+                let path = path.clone();
+                move |window: &mut Window, cx: &mut App| {
+                    if let Some(this) = this.upgrade() {
+                        this.update(cx, |explorer, cx| f(explorer, &path, index, window, cx));
+                    }
+                }
+            };
+
+        vec![
+            ContextMenuItem::entry(
+                "重命名",
+                handler(|explorer, path, _, window, cx| {
+                    explorer.start_sidebar_rename(path.to_string(), window, cx)
+                }),
+            ),
+            ContextMenuItem::entry(
+                "从快捷访问移除",
+                handler(|explorer, path, _, _, cx| {
+                    tracing::info!("从快捷访问移除: {}", path);
+                    explorer.quick_access.unpin(path);
+                    explorer.save_quick_access(cx);
+                    cx.notify();
+                }),
+            ),
+            ContextMenuItem::separator(),
+            ContextMenuItem::entry(
+                "上移",
+                handler(|explorer, _, index, _, cx| {
+                    explorer.move_quick_access(index, index.saturating_sub(1), cx)
+                }),
+            )
+            .disabled(index == 0),
+            ContextMenuItem::entry(
+                "下移",
+                handler(|explorer, _, index, _, cx| {
+                    explorer.move_quick_access(index, index + 1, cx)
+                }),
+            )
+            .disabled(index + 1 >= count),
+        ]
+    }
+
+    /// 开始编辑快捷访问项的显示名称
+    fn start_sidebar_rename(&mut self, path: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(item) = self
+            .quick_access
+            .items()
+            .iter()
+            .find(|item| item.path == path)
+        else {
+            return;
+        };
+        let name = item.name.clone();
+
+        let input = cx.new(|cx| {
+            let mut input = TextInput::new(window, cx);
+            input.set_text(name.clone(), cx);
+            input.select_range(0..name.len(), cx);
+            input
+        });
+        let subscription =
+            cx.subscribe_in(
+                &input,
+                window,
+                |explorer, _, event, window, cx| match event {
+                    TextInputEvent::Change(_) => {}
+                    TextInputEvent::Confirm | TextInputEvent::Blur => {
+                        if let Some(state) = explorer.sidebar_rename.take() {
+                            let name = state.input.read(cx).text();
+                            explorer.quick_access.rename(&state.path, &name);
+                            explorer.save_quick_access(cx);
+                        }
+                        if matches!(event, TextInputEvent::Confirm) {
+                            explorer.sidebar_focus.focus(window);
+                        }
+                        cx.notify();
+                    }
+                    TextInputEvent::Cancel => {
+                        explorer.sidebar_rename = None;
+                        explorer.sidebar_focus.focus(window);
+                        cx.notify();
+                    }
+                },
+            );
+        input.read(cx).focus(window);
+
+        self.sidebar_rename = Some(SidebarRenameState {
+            path,
+            input,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+}
+
+impl Explorer {
+    // ===== 最近访问 =====
+
+    /// 在后台保存最近访问的位置
+    fn save_recent(&self, cx: &mut Context<Self>) {
+        let recent = self.recent.clone();
+        cx.background_executor()
+            .spawn(async move {
+                if let Err(e) = recent.save() {
+                    tracing::error!("保存最近访问的位置失败: {}", e);
+                }
+            })
+            .detach();
+    }
+
+    /// 最近访问的位置（按 frecency 排列，用于命令面板和地址栏补全）
+    fn recent_paths(&self) -> Vec<String> {
+        self.recent
+            .top(50)
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect()
+    }
+
+    /// 侧边栏“最近”分组中的位置（不含已在快捷访问和存储位置中的）
+    fn sidebar_recent(&self) -> Vec<String> {
+        self.recent_paths()
+            .into_iter()
+            .filter(|path| {
+                !self.quick_access.contains(path)
+                    && !self.roots.iter().any(|root| &root.path == path)
+            })
+            .take(5)
+            .collect()
+    }
+
+    /// 清除最近访问的位置
+    fn clear_recent_locations(
+        &mut self,
+        _: &ClearRecentLocations,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        tracing::info!("清除最近访问的位置");
+        self.recent.clear();
+        self.save_recent(cx);
+        cx.notify();
+    }
+
+    /// 侧边栏最近访问项的右键菜单
+    fn recent_context_menu(&self, path: String, cx: &Context<Self>) -> Vec<ContextMenuItem> {
+        let this = cx.entity().downgrade();
+        let handler = move |f: fn(&mut RecentLocations, &str)| {
+            let this = this.clone();
+            let path = path.clone();
+            move |_: &mut Window, cx: &mut App| {
+                if let Some(this) = this.upgrade() {
+                    this.update(cx, |explorer, cx| {
+                        f(&mut explorer.recent, &path);
+                        explorer.save_recent(cx);
+                        cx.notify();
+                    });
+                }
+            }
+        };
+
+        vec![
+            ContextMenuItem::entry("从最近位置中移除", handler(RecentLocations::remove)),
+            ContextMenuItem::entry("不再记录此位置", handler(RecentLocations::exclude)),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("清除最近位置", ClearRecentLocations),
+        ]
+    }
+}
+
+impl Explorer {
+    // ===== 文件夹大小 =====
+
+    /// 选中的文件夹（排序后）
+    fn selected_folders(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self
+            .selected_entries()
+            .into_iter()
+            .filter(|entry| entry.item_type == ItemType::Directory)
+            .map(|entry| entry.path)
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// 在后台递归统计选中文件夹的大小
+    fn compute_folder_size(
+        &mut self,
+        _: &ComputeFolderSize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let paths = self.selected_folders();
+        if paths.is_empty() {
+            return;
+        }
+        tracing::info!("统计文件夹大小: {} 个文件夹", paths.len());
+
+        let provider = self.provider.clone();
+        let task_paths = paths.clone();
+        let task = cx.spawn_in(window, async move |this, cx| {
+            let counted = task_paths.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut total = FolderSize::default();
+                    for path in &counted {
+                        total.add(folder_size::folder_size(provider.as_ref(), path).await?);
+                    }
+                    Ok::<_, StorageError>(total)
+                })
+                .await;
+
+            let _ = this.update(cx, |explorer, cx| {
+                let Some(state) = explorer
+                    .folder_size
+                    .as_mut()
+                    .filter(|state| state.paths == task_paths)
+                else {
+                    return;
+                };
+                match result {
+                    Ok(size) => state.result = Some(size),
+                    Err(e) => {
+                        tracing::error!("统计文件夹大小失败: {}", e);
+                        explorer.folder_size = None;
+                    }
+                }
+                cx.notify();
+            });
+        });
+
+        // 替换之前的统计（丢弃任务即取消）
+        self.folder_size = Some(FolderSizeState {
+            paths,
+            result: None,
+            _task: task,
+        });
+        cx.notify();
+    }
+
+    /// 取消正在进行的统计
+    fn cancel_folder_size(&mut self, cx: &mut Context<Self>) {
+        if self.folder_size.take().is_some() {
+            tracing::info!("取消统计文件夹大小");
+            cx.notify();
+        }
+    }
+}
+
+impl Explorer {
+    // ===== 打开与右键菜单 =====
+
+    /// 激活面板中选中的条目
+    fn selected_entries(&self) -> Vec<FileItem> {
+        self.active_leaf()
+            .map(|(_, _, entries)| {
+                entries
+                    .into_iter()
+                    .filter(|entry| self.is_selected(&entry.path))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 打开选中的条目：单个文件夹或压缩包在当前面板中进入，其他条目用默认程序打开
+    fn open_selected(&mut self, _: &OpenSelected, window: &mut Window, cx: &mut Context<Self>) {
+        let selected = self.selected_entries();
+        if let [entry] = selected.as_slice()
+            && (entry.is_dir_like() || is_archive(&entry.path))
+        {
+            self.load_directory(entry.path.clone(), window, cx);
+            return;
+        }
+        if !self.provider.capabilities().local_paths {
+            return;
+        }
+        for entry in selected {
+            if let Err(e) = launcher::open_default(&entry.path) {
+                tracing::error!("无法打开 {}: {}", entry.path, e);
+            }
+        }
+    }
+
+    /// 在终端中打开选中的文件夹（没有选中单个文件夹时为当前目录）
+    fn open_terminal(&mut self, _: &OpenTerminal, _: &mut Window, _: &mut Context<Self>) {
+        if !self.provider.capabilities().local_paths {
+            return;
+        }
+        let dir = match self.selected_entries().as_slice() {
+            [entry] if entry.is_dir_like() => entry.path.clone(),
+            _ => match self.active_leaf() {
+                Some((_, dir, _)) => dir,
+                None => return,
+            },
+        };
+        if let Err(e) = launcher::open_terminal(&dir) {
+            tracing::error!("无法在终端中打开 {}: {}", dir, e);
+        }
+    }
+
+    /// 显示选中条目（没有选中项时为当前目录）的属性
+    fn show_properties(&mut self, _: &ShowProperties, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = self.selected_or_current_paths();
+        if paths.is_empty() {
+            return;
+        }
+        let provider = self.provider.clone();
+        let dialog = cx.new(|cx| PropertiesDialog::new(paths, provider, window, cx));
+        let subscription = cx.subscribe_in(
+            &dialog,
+            window,
+            |explorer, _, _: &DismissEvent, window, cx| {
+                explorer.properties = None;
+                explorer.focus_file_list(window);
+                cx.notify();
+            },
+        );
+        dialog.read(cx).focus(window);
+        self.properties = Some((dialog, subscription));
+        cx.notify();
+    }
+
+    /// 分析选中文件夹（没有选中单个文件夹时为当前目录）的磁盘占用
+    fn analyze_disk_usage(
+        &mut self,
+        _: &AnalyzeDiskUsage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let root = match self.selected_entries().as_slice() {
+            [entry] if entry.item_type == ItemType::Directory => entry.path.clone(),
+            _ => match self.active_leaf() {
+                Some((_, dir, _)) => dir,
+                None => return,
+            },
+        };
+        let provider = self.provider.clone();
+        let view = cx.new(|cx| DiskUsageView::new(root, provider, window, cx));
+        let subscription = cx.subscribe_in(
+            &view,
+            window,
+            |explorer, _, event: &DiskUsageEvent, window, cx| match event {
+                DiskUsageEvent::Trash(paths) => {
+                    let operations = paths
+                        .iter()
+                        .map(|path| FileOperation::Trash { path: path.clone() })
+                        .collect::<Vec<_>>();
+                    explorer.run_file_operations(
+                        format!("移入回收站（{} 项）", operations.len()),
+                        operations,
+                        window,
+                        cx,
+                    );
+                }
+                DiskUsageEvent::Delete(paths) => {
+                    explorer.delete_permanently(paths.clone(), window, cx);
+                }
+                DiskUsageEvent::Dismiss => {
+                    explorer.disk_usage = None;
+                    explorer.focus_file_list(window);
+                    cx.notify();
+                }
+            },
+        );
+        view.read(cx).focus(window);
+        self.disk_usage = Some((view, subscription));
+        cx.notify();
+    }
+
+    /// 永久删除（不可撤销），完成后刷新受影响的面板
+    fn delete_permanently(&mut self, paths: Vec<String>, window: &Window, cx: &mut Context<Self>) {
+        let provider = self.provider.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let task_paths = paths.clone();
+            let deleted: Vec<String> = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut deleted = vec![];
+                    for path in task_paths {
+                        match provider.delete(&path).await {
+                            Ok(()) => deleted.push(path),
+                            Err(e) => tracing::error!("无法删除 {}: {}", path, e),
+                        }
+                    }
+                    deleted
+                })
+                .await;
+            tracing::info!("已永久删除 {}/{} 项", deleted.len(), paths.len());
+            let _ = this.update_in(cx, |explorer, window, cx| {
+                explorer.refresh_panels(&deleted, window, cx);
+            });
+        })
+        .detach();
+    }
+
+    /// 在窗口坐标 `position` 处显示右键菜单
+    fn deploy_context_menu(
+        &mut self,
+        position: Point<Pixels>,
+        items: Vec<ContextMenuItem>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.cancel_pending_rename();
+        let menu = cx.new(|cx| ContextMenu::new(position, items, cx));
+        let subscription = cx.subscribe_in(
+            &menu,
+            window,
+            |explorer, _, _: &DismissEvent, window, cx| {
+                explorer.context_menu = None;
+                explorer.focus_file_list(window);
+                cx.notify();
+            },
+        );
+        menu.read(cx).focus(window);
+        self.context_menu = Some((menu, subscription));
+        cx.notify();
+    }
+
+    /// 选中条目的右键菜单
+    fn entries_context_menu(&self, cx: &App) -> Vec<ContextMenuItem> {
+        let capabilities = self.provider.capabilities();
+        let selected = self.selected_entries();
+        let single = selected.len() == 1;
+
+        // 所有选中条目的 MIME 类型相同时才提供“打开方式”
+        let mime = selected
+            .iter()
+            .map(|entry| match entry.item_type {
+                ItemType::Directory => Some("inode/directory".to_string()),
+                _ => entry.metadata.mime_type.clone(),
+            })
+            .reduce(|a, b| if a == b { a } else { None })
+            .flatten()
+            .filter(|_| capabilities.local_paths);
+        let paths = self.selected_paths();
+        let open_with = mime
+            .map(|mime| launcher::apps_for_mime(&mime))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|app| {
+                let paths = paths.clone();
+                ContextMenuItem::entry(app.name.clone(), move |_, _| {
+                    if let Err(e) = app.launch(&paths) {
+                        tracing::error!("无法用 {} 打开: {}", app.name, e);
+                    }
+                })
+            })
+            .collect();
+        let is_directory = single && selected[0].is_dir_like();
+
+        vec![
+            ContextMenuItem::action("打开", OpenSelected)
+                .disabled(!is_directory && !capabilities.local_paths),
+            ContextMenuItem::submenu("打开方式", open_with),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("剪切", CutFiles).disabled(!capabilities.can_move),
+            ContextMenuItem::action("复制", CopyFiles).disabled(!capabilities.can_copy),
+            ContextMenuItem::action("粘贴", PasteFiles)
+                .disabled(!capabilities.can_copy || !self.can_paste(cx)),
+            ContextMenuItem::action("粘贴为链接", PasteLinks)
+                .disabled(!capabilities.can_symlink || !self.can_paste(cx)),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("重命名", Rename).disabled(!capabilities.can_rename),
+            ContextMenuItem::action("移入回收站", TrashSelected).disabled(!capabilities.can_trash),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("压缩…", CompressSelected).disabled(!capabilities.can_archive),
+            ContextMenuItem::action("解压到此处", ExtractHere)
+                .disabled(!capabilities.can_archive || self.selected_archives().is_empty()),
+            ContextMenuItem::action("解压到文件夹", ExtractToFolder)
+                .disabled(!capabilities.can_archive || self.selected_archives().is_empty()),
+            ContextMenuItem::separator(),
+            ContextMenuItem::submenu(
+                "复制路径",
+                vec![
+                    ContextMenuItem::action("完整路径", CopyPath),
+                    ContextMenuItem::action("相对路径", CopyRelativePath),
+                ],
+            ),
+            ContextMenuItem::action("在终端中打开", OpenTerminal)
+                .disabled(!capabilities.local_paths),
+            ContextMenuItem::action("固定到快捷访问", PinToQuickAccess)
+                .disabled(self.pin_targets().is_empty()),
+            ContextMenuItem::action("分析磁盘占用", AnalyzeDiskUsage).disabled(!is_directory),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("属性", ShowProperties),
+        ]
+    }
+
+    /// 面板空白处的右键菜单
+    fn background_context_menu(&self, cx: &App) -> Vec<ContextMenuItem> {
+        let capabilities = self.provider.capabilities();
+        vec![
+            ContextMenuItem::action("新建文件夹", NewFolder).disabled(!capabilities.can_create),
+            ContextMenuItem::action("粘贴", PasteFiles)
+                .disabled(!capabilities.can_copy || !self.can_paste(cx)),
+            ContextMenuItem::action("粘贴为链接", PasteLinks)
+                .disabled(!capabilities.can_symlink || !self.can_paste(cx)),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("复制路径", CopyPath),
+            ContextMenuItem::action("在终端中打开", OpenTerminal)
+                .disabled(!capabilities.local_paths),
+            ContextMenuItem::action("固定到快捷访问", PinToQuickAccess)
+                .disabled(self.pin_targets().is_empty()),
+            ContextMenuItem::action("分析磁盘占用", AnalyzeDiskUsage),
+            ContextMenuItem::separator(),
+            ContextMenuItem::action("属性", ShowProperties),
+        ]
+    }
+
+    /// 在面板中打开指定路径的菜单项
+    fn navigate_menu_item(
+        &self,
+        panel_id: PanelId,
+        label: String,
+        path: String,
+        cx: &Context<Self>,
+    ) -> ContextMenuItem {
+        let this = cx.entity().downgrade();
+        ContextMenuItem::entry(label, move |window, cx| {
+            if let Some(this) = this.upgrade() {
+                this.update(cx, |explorer, cx| {
+                    explorer.set_active_panel(panel_id, cx);
+                    explorer.load_directory_for_panel(panel_id, path.clone(), window, cx);
+                });
+            }
+        })
+    }
+
+    /// 面包屑分隔符的下拉菜单：在后台读取 `dir` 的子文件夹后显示
+    fn show_breadcrumb_children(
+        &mut self,
+        panel_id: PanelId,
+        dir: String,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let provider = self.provider.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let list_dir = dir.clone();
+            let entries = cx
+                .background_executor()
+                .spawn(async move { provider.list_entries(&list_dir).await })
+                .await;
+
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    let items = match entries {
+                        Ok(entries) => {
+                            let mut dirs: Vec<FileItem> = entries
+                                .into_iter()
+                                .filter(|entry| entry.is_dir_like() && !entry.is_hidden)
+                                .collect();
+                            dirs.sort_by_key(|entry| entry.name.to_lowercase());
+                            dirs.into_iter()
+                                .map(|entry| {
+                                    explorer
+                                        .navigate_menu_item(panel_id, entry.name, entry.path, cx)
+                                })
+                                .collect()
+                        }
+                        Err(e) => {
+                            tracing::error!("无法读取 {}: {}", dir, e);
+                            vec![
+                                ContextMenuItem::entry(format!("无法读取：{}", e), |_, _| {})
+                                    .disabled(true),
+                            ]
+                        }
+                    };
+                    let items = if items.is_empty() {
+                        vec![ContextMenuItem::entry("没有子文件夹", |_, _| {}).disabled(true)]
+                    } else {
+                        items
+                    };
+                    explorer.deploy_context_menu(position, items, window, cx);
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// 面包屑省略号菜单：列出被折叠的中间层级
+    fn show_breadcrumb_overflow(
+        &mut self,
+        panel_id: PanelId,
+        hidden: Vec<BreadcrumbItem>,
+        position: Point<Pixels>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let items = hidden
+            .into_iter()
+            .map(|item| self.navigate_menu_item(panel_id, item.label, item.value, cx))
+            .collect();
+        self.deploy_context_menu(position, items, window, cx);
+    }
+
+    // ===== 命令面板 =====
+
+    /// 打开或关闭命令面板
+    fn toggle_command_palette(
+        &mut self,
+        _: &ToggleCommandPalette,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.command_palette.take().is_some() {
+            self.focus_file_list(window);
+            cx.notify();
+            return;
+        }
+
+        // 书签：快捷访问位置和存储根节点
+        let bookmarks = self
+            .quick_access
+            .items()
+            .iter()
+            .map(|item| (item.name.clone(), item.path.clone()))
+            .chain(
+                self.roots
+                    .iter()
+                    .map(|root| (root.name.clone(), root.path.clone())),
+            )
+            .collect();
+        let history = self.palette_history.clone();
+        let recent = self
+            .recent_paths()
+            .into_iter()
+            .map(|path| {
+                (
+                    paths::file_name(&path).unwrap_or_else(|| path.clone()),
+                    path,
+                )
+            })
+            .collect();
+        let palette = cx.new(|cx| CommandPalette::new(bookmarks, recent, history, window, cx));
+        let subscription = cx.subscribe_in(&palette, window, |explorer, _, event, window, cx| {
+            explorer.command_palette = None;
+            explorer.focus_file_list(window);
+            cx.notify();
+
+            let CommandPaletteEvent::Confirm { key, target } = event else {
+                return;
+            };
+            explorer.palette_history.record(key);
+            let history = explorer.palette_history.clone();
+            cx.background_executor()
+                .spawn(async move {
+                    if let Err(e) = history.save() {
+                        tracing::error!("保存命令面板记录失败: {}", e);
+                    }
+                })
+                .detach();
+
+            match target {
+                PaletteTarget::Action(action) => window.dispatch_action(action.boxed_clone(), cx),
+                PaletteTarget::Navigate(path) => explorer.load_directory(path.clone(), window, cx),
+            }
+        });
+        palette.read(cx).focus(window, cx);
+        self.context_menu = None;
+        self.command_palette = Some((palette, subscription));
+        cx.notify();
+    }
+}
+
+impl Render for Explorer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        // 获取快捷访问项
+        let quick_access_items = self.quick_access.items().to_vec();
+
+        // 获取 Explorer 实体的弱引用用于事件回调
+        let this_entity = cx.entity().downgrade();
+
+        // 构建侧边栏（获得焦点时上下方向键在位置之间移动）
+        let sidebar = div()
+            .size_full()
+            .track_focus(&self.sidebar_focus)
+            .key_context(keymap::SIDEBAR_CONTEXT)
+            .on_action(cx.listener(|explorer, _: &SelectPrevious, window, cx| {
+                explorer.move_sidebar_selection(-1, window, cx)
+            }))
+            .on_action(cx.listener(|explorer, _: &SelectNext, window, cx| {
+                explorer.move_sidebar_selection(1, window, cx)
+            }))
+            .on_action(cx.listener(|explorer, _: &SelectFirst, window, cx| {
+                explorer.select_sidebar_index(0, window, cx)
+            }))
+            .on_action(cx.listener(|explorer, _: &SelectLast, window, cx| {
+                let last = explorer.sidebar_paths().len().saturating_sub(1);
+                explorer.select_sidebar_index(last, window, cx)
+            }))
+            .child(self.render_sidebar(quick_access_items, theme, &this_entity));
+
+        // 快捷键配置中的问题
+        let keymap_problems = cx
+            .try_global::<KeymapStatus>()
+            .map(|status| status.problems.clone())
+            .unwrap_or_default();
+
+        // 构建面板树（递归渲染）
+        let panel_content = self.render_panel_node(&self.panel_tree.clone(), theme, &this_entity);
+
+        // 主内容区域（侧边栏 + 面板）
+        let main_content = Resizable::new("explorer-layout", sidebar, panel_content)
+            .size(px(240.))
+            .range(px(180.)..px(480.));
+
+        // 构建标题栏
+        let this_clone_h = this_entity.clone();
+        let this_clone_v = this_entity.clone();
+
+        div()
+            .track_focus(&self.focus_handle)
+            .key_context(keymap::GLOBAL_CONTEXT)
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .on_action(cx.listener(Self::new_folder))
+            .on_action(cx.listener(Self::trash_selected))
+            .on_action(cx.listener(Self::rename_selected))
+            .on_action(cx.listener(Self::batch_rename))
+            .on_action(cx.listener(Self::compress_selected))
+            .on_action(cx.listener(Self::extract_here))
+            .on_action(cx.listener(Self::extract_to_folder))
+            .on_action(cx.listener(Self::copy_files))
+            .on_action(cx.listener(Self::cut_files))
+            .on_action(cx.listener(Self::paste_files))
+            .on_action(cx.listener(Self::paste_links))
+            .on_action(cx.listener(Self::copy_path))
+            .on_action(cx.listener(Self::copy_relative_path))
+            .on_action(cx.listener(Self::open_selected))
+            .on_action(cx.listener(Self::open_terminal))
+            .on_action(cx.listener(Self::show_properties))
+            .on_action(cx.listener(|explorer, _: &SplitHorizontal, window, cx| {
+                explorer.split_panel_horizontal(window, cx)
+            }))
+            .on_action(cx.listener(|explorer, _: &SplitVertical, window, cx| {
+                explorer.split_panel_vertical(window, cx)
+            }))
+            .on_action(cx.listener(Self::toggle_command_palette))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::extend_selection_up))
+            .on_action(cx.listener(Self::extend_selection_down))
+            .on_action(cx.listener(Self::select_all))
+            .on_action(cx.listener(Self::go_to_parent))
+            .on_action(cx.listener(Self::edit_location))
+            .on_action(cx.listener(Self::pin_to_quick_access))
+            .on_action(cx.listener(Self::clear_recent_locations))
+            .on_action(cx.listener(Self::compute_folder_size))
+            .on_action(cx.listener(Self::analyze_disk_usage))
+            .relative()
+            .flex()
+            .flex_col()
+            .size_full()
+            .child(
+                // 标题栏
+                TitleBar::new().child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .justify_end()
+                        .gap(theme.spacing.sm)
+                        .pr_4()
+                        .child(
+                            // 横向拆分按钮
+                            div()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .size_7()
+                                .rounded(px(4.))
+                                .cursor_pointer()
+                                .hover(|style| style.bg(theme.colors.muted))
+                                .child(
+                                    svg()
+                                        .path("icons/columns-split.svg")
+                                        .size_4()
+                                        .text_color(theme.colors.foreground),
+                                )
+                                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                                    if let Some(this) = this_clone_h.upgrade() {
+                                        let _ = this.update(cx, |explorer, cx| {
+                                            explorer.split_panel_horizontal(window, cx);
+                                        });
+                                    }
+                                }),
+                        )
+                        .child(
+                            // 纵向拆分按钮
+                            div()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .size_7()
+                                .rounded(px(4.))
+                                .cursor_pointer()
+                                .hover(|style| style.bg(theme.colors.muted))
+                                .child(
+                                    svg()
+                                        .path("icons/rows-split.svg")
+                                        .size_4()
+                                        .text_color(theme.colors.foreground),
+                                )
+                                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                                    if let Some(this) = this_clone_v.upgrade() {
+                                        let _ = this.update(cx, |explorer, cx| {
+                                            explorer.split_panel_vertical(window, cx);
+                                        });
+                                    }
+                                }),
+                        ),
+                ),
+            )
+            .when(!keymap_problems.is_empty(), |this| {
+                this.child(
+                    // 快捷键配置警告
+                    div()
+                        .flex()
+                        .flex_col()
+                        .px_4()
+                        .py_1()
+                        .text_xs()
+                        .bg(theme.colors.danger)
+                        .text_color(theme.colors.danger_foreground)
+                        .child(format!(
+                            "快捷键配置 {} 有 {} 个问题：",
+                            keymap::file_path().display(),
+                            keymap_problems.len()
+                        ))
+                        .children(keymap_problems),
+                )
+            })
+            .when_some(self.operation_error.clone(), |this, error| {
+                let this_dismiss = this_entity.clone();
+                this.child(
+                    // 文件操作失败的提示
+                    div()
+                        .flex()
+                        .items_center()
+                        .justify_between()
+                        .gap(theme.spacing.md)
+                        .px_4()
+                        .py_1()
+                        .text_xs()
+                        .bg(theme.colors.danger)
+                        .text_color(theme.colors.danger_foreground)
+                        .child(error)
+                        .child(
+                            div()
+                                .flex_shrink_0()
+                                .cursor_pointer()
+                                .hover(|style| style.underline())
+                                .child("关闭")
+                                .on_mouse_down(MouseButton::Left, move |_, _, cx| {
+                                    cx.stop_propagation();
+                                    if let Some(this) = this_dismiss.upgrade() {
+                                        let _ = this.update(cx, |explorer, cx| {
+                                            explorer.operation_error = None;
+                                            cx.notify();
+                                        });
+                                    }
+                                }),
+                        ),
+                )
+            })
+            .child(
+                // 主内容区域
+                div().flex_1().child(main_content),
+            )
+            .when_some(
+                self.batch_rename.as_ref().map(|(dialog, _)| dialog.clone()),
+                |this, dialog| this.child(dialog),
+            )
+            .when_some(
+                self.compress.as_ref().map(|(dialog, _)| dialog.clone()),
+                |this, dialog| this.child(dialog),
+            )
+            .when_some(
+                self.properties.as_ref().map(|(dialog, _)| dialog.clone()),
+                |this, dialog| this.child(dialog),
+            )
+            .when_some(
+                self.disk_usage.as_ref().map(|(view, _)| view.clone()),
+                |this, view| this.child(view),
+            )
+            .when_some(
+                self.command_palette
+                    .as_ref()
+                    .map(|(palette, _)| palette.clone()),
+                |this, palette| this.child(palette),
+            )
+            .when_some(
+                self.context_menu.as_ref().map(|(menu, _)| menu.clone()),
+                |this, menu| this.child(menu),
+            )
+    }
+}
+
+impl Explorer {
+    /// 渲染侧边栏
+    fn render_sidebar(
+        &self,
+        quick_access_items: Vec<QuickAccessItem>,
+        theme: &Theme,
+        this_entity: &WeakEntity<Self>,
+    ) -> impl IntoElement {
+        let selected_path = self.selected_sidebar_path.clone();
 
         // 构建分组数据
         let mut groups = vec![];
 
         // 快捷访问分组
         if !quick_access_items.is_empty() {
-            let items: Vec<SidebarItem> =
-                quick_access_items.iter().map(SidebarItem::from).collect();
+            let items: Vec<SidebarItem> = quick_access_items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| SidebarItem::quick_access(index, item))
+                .collect();
             groups.push(ListGroup::new("快捷访问", items));
         }
 
+        // 最近访问分组
+        let recent = self.sidebar_recent();
+        if !recent.is_empty() {
+            let items: Vec<SidebarItem> = recent
+                .iter()
+                .map(|path| SidebarItem::recent(path))
+                .collect();
+            groups.push(ListGroup::new("最近", items));
+        }
+
         // 存储位置分组
         if !self.roots.is_empty() {
-            let items: Vec<SidebarItem> = self.roots.iter().map(SidebarItem::from).collect();
+            let items: Vec<SidebarItem> = self
+                .roots
+                .iter()
+                .map(|root| SidebarItem {
+                    space: self.root_space.get(&root.path).copied(),
+                    ..SidebarItem::from(root)
+                })
+                .collect();
             groups.push(ListGroup::new("存储位置", items));
         }
 
         let this_entity_clone = this_entity.clone();
+        let sidebar_rename = self
+            .sidebar_rename
+            .as_ref()
+            .map(|state| (state.path.clone(), state.input.clone()));
 
         div()
             .flex()
@@ -998,28 +3352,74 @@ impl Explorer {
                                 let icon = Icon::new(item.icon_name);
                                 let item_path = item.path.clone();
                                 let this_clone = this_entity_clone.clone();
+                                let this_drop = this_entity_clone.clone();
+                                let this_drop_external = this_entity_clone.clone();
+                                let drop_path = item.path.clone();
+                                let drop_path_external = item.path.clone();
+                                let can_drop_path = item.path.clone();
+                                let drop_bg = theme.colors.brand_background_hover;
+                                let quick_access_index = match item.kind {
+                                    SidebarKind::QuickAccess(index) => Some(index),
+                                    _ => None,
+                                };
+                                let is_recent = item.kind == SidebarKind::Recent;
+                                let renaming_input = sidebar_rename
+                                    .as_ref()
+                                    .filter(|(path, _)| {
+                                        quick_access_index.is_some() && path == &item.path
+                                    })
+                                    .map(|(_, input)| input.clone());
 
-                                ListItem::new(item.path.clone())
+                                let row = div()
+                                    .flex()
+                                    .items_center()
+                                    .w_full()
+                                    .gap(theme.spacing.sm)
+                                    .child(icon.text_color(if is_selected {
+                                        theme.colors.brand_foreground
+                                    } else {
+                                        theme.colors.foreground
+                                    }))
+                                    .child(match renaming_input {
+                                        Some(input) => {
+                                            div().flex_1().child(input).into_any_element()
+                                        }
+                                        None => div()
+                                            .flex_1()
+                                            .min_w_0()
+                                            .overflow_hidden()
+                                            .whitespace_nowrap()
+                                            .text_sm()
+                                            .text_color(if is_selected {
+                                                theme.colors.brand_foreground
+                                            } else {
+                                                theme.colors.foreground
+                                            })
+                                            .child(item.name.clone())
+                                            .into_any_element(),
+                                    })
+                                    .children(item.detail.clone().map(|detail| {
+                                        div()
+                                            .flex_shrink_0()
+                                            .text_xs()
+                                            .text_color(theme.colors.muted_foreground)
+                                            .child(detail)
+                                    }));
+
+                                // 存储位置下方显示使用率条
+                                let list_item = ListItem::new(item.path.clone())
                                     .selected(is_selected)
                                     .child(
                                         div()
                                             .flex()
-                                            .items_center()
-                                            .gap(theme.spacing.sm)
-                                            .child(icon.text_color(if is_selected {
-                                                theme.colors.brand_foreground
-                                            } else {
-                                                theme.colors.foreground
-                                            }))
-                                            .child(
-                                                div()
-                                                    .text_sm()
-                                                    .text_color(if is_selected {
-                                                        theme.colors.brand_foreground
-                                                    } else {
-                                                        theme.colors.foreground
-                                                    })
-                                                    .child(item.name.clone()),
+                                            .flex_col()
+                                            .w_full()
+                                            .gap(theme.spacing.xs)
+                                            .child(row)
+                                            .children(
+                                                item.space
+                                                    .as_ref()
+                                                    .map(|space| usage_bar(space, theme)),
                                             ),
                                     )
                                     .on_click(move |window, cx| {
@@ -1031,13 +3431,251 @@ impl Explorer {
                                                 explorer.load_directory(path, window, cx);
                                             });
                                         }
+                                    });
+
+                                // 侧边栏位置可作为拖放目标
+                                div()
+                                    .id(SharedString::from(format!("sidebar-drop-{}", item.path)))
+                                    .rounded(theme.radius.md)
+                                    .drag_over::<DraggedFiles>(move |style, _, _, _| {
+                                        style.bg(drop_bg)
+                                    })
+                                    .drag_over::<ExternalPaths>(move |style, _, _, _| {
+                                        style.bg(drop_bg)
+                                    })
+                                    .can_drop(move |value, _, _| {
+                                        value.downcast_ref::<DraggedFiles>().is_none_or(|dragged| {
+                                            dragged.can_drop_into(&can_drop_path)
+                                        })
+                                    })
+                                    .on_drop(move |dragged: &DraggedFiles, window, cx| {
+                                        if let Some(this) = this_drop.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer.drop_files(
+                                                    dragged.paths.clone(),
+                                                    drop_path.clone(),
+                                                    false,
+                                                    window,
+                                                    cx,
+                                                );
+                                            });
+                                        }
+                                    })
+                                    .on_drop(move |paths: &ExternalPaths, window, cx| {
+                                        if let Some(this) = this_drop_external.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer.drop_files(
+                                                    dnd::external_paths(paths),
+                                                    drop_path_external.clone(),
+                                                    true,
+                                                    window,
+                                                    cx,
+                                                );
+                                            });
+                                        }
+                                    })
+                                    // 快捷访问项：拖动调整顺序，右键编辑
+                                    .when_some(quick_access_index, |this, index| {
+                                        let this_reorder = this_entity_clone.clone();
+                                        let this_menu = this_entity_clone.clone();
+                                        let menu_path = item.path.clone();
+                                        this.on_drag(
+                                            DraggedBookmark {
+                                                index,
+                                                name: item.name.clone(),
+                                            },
+                                            |dragged, _, _, cx| {
+                                                cx.new(|_| {
+                                                    DragPreview::folder(dragged.name.clone())
+                                                })
+                                            },
+                                        )
+                                        .drag_over::<DraggedBookmark>(move |style, _, _, _| {
+                                            style.bg(drop_bg)
+                                        })
+                                        .on_drop(move |dragged: &DraggedBookmark, _, cx| {
+                                            if let Some(this) = this_reorder.upgrade() {
+                                                let _ = this.update(cx, |explorer, cx| {
+                                                    explorer.move_quick_access(
+                                                        dragged.index,
+                                                        index,
+                                                        cx,
+                                                    );
+                                                });
+                                            }
+                                        })
+                                        .on_mouse_down(
+                                            MouseButton::Right,
+                                            move |event, window, cx| {
+                                                if let Some(this) = this_menu.upgrade() {
+                                                    let _ = this.update(cx, |explorer, cx| {
+                                                        let items = explorer
+                                                            .quick_access_context_menu(
+                                                                index,
+                                                                menu_path.clone(),
+                                                                cx,
+                                                            );
+                                                        explorer.deploy_context_menu(
+                                                            event.position,
+                                                            items,
+                                                            window,
+                                                            cx,
+                                                        );
+                                                    });
+                                                }
+                                            },
+                                        )
+                                    })
+                                    // 最近访问项：右键移除或不再记录
+                                    .when(is_recent, |this| {
+                                        let this_menu = this_entity_clone.clone();
+                                        let menu_path = item.path.clone();
+                                        this.on_mouse_down(
+                                            MouseButton::Right,
+                                            move |event, window, cx| {
+                                                if let Some(this) = this_menu.upgrade() {
+                                                    let _ = this.update(cx, |explorer, cx| {
+                                                        let items = explorer.recent_context_menu(
+                                                            menu_path.clone(),
+                                                            cx,
+                                                        );
+                                                        explorer.deploy_context_menu(
+                                                            event.position,
+                                                            items,
+                                                            window,
+                                                            cx,
+                                                        );
+                                                    });
+                                                }
+                                            },
+                                        )
                                     })
+                                    .child(list_item)
                                     .into_any_element()
                             }),
                     ),
             )
     }
 
+    /// 渲染地址栏输入框和补全候选（或错误提示）
+    fn render_location_editor(
+        state: &LocationEditState,
+        theme: &Theme,
+        this_entity: &WeakEntity<Self>,
+    ) -> AnyElement {
+        let this_previous = this_entity.clone();
+        let this_next = this_entity.clone();
+        let this_accept = this_entity.clone();
+
+        let popup =
+            if let Some(error) = &state.error {
+                Some(
+                    div()
+                        .px_2()
+                        .py_1()
+                        .text_sm()
+                        .text_color(theme.colors.danger)
+                        .child(error.clone())
+                        .into_any_element(),
+                )
+            } else if !state.suggestions.is_empty() {
+                Some(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .children(state.suggestions.iter().enumerate().map(
+                            |(index, suggestion)| {
+                                let this_click = this_entity.clone();
+                                div()
+                                    .id(SharedString::from(format!(
+                                        "location-suggestion-{}",
+                                        index
+                                    )))
+                                    .px_2()
+                                    .py_1()
+                                    .flex()
+                                    .items_center()
+                                    .gap(theme.spacing.sm)
+                                    .rounded(theme.radius.sm)
+                                    .text_sm()
+                                    .text_color(theme.colors.foreground)
+                                    .cursor_pointer()
+                                    .hover(|style| {
+                                        style.bg(theme.colors.list_item_background_hover)
+                                    })
+                                    .when(state.selected_suggestion == Some(index), |this| {
+                                        this.bg(theme.colors.list_item_background_hover)
+                                    })
+                                    .child(Icon::new(if suggestion.recent {
+                                        IconName::FolderOpen
+                                    } else {
+                                        IconName::FolderClosed
+                                    }))
+                                    .child(suggestion.label.clone())
+                                    // 点击候选时输入框保持焦点
+                                    .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                                        cx.stop_propagation();
+                                        if let Some(this) = this_click.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer
+                                                    .apply_location_suggestion(index, window, cx);
+                                            });
+                                        }
+                                    })
+                            },
+                        ))
+                        .into_any_element(),
+                )
+            } else {
+                None
+            };
+
+        div()
+            .relative()
+            .w_full()
+            .key_context(keymap::LOCATION_BAR_CONTEXT)
+            .on_action(move |_: &SelectPrevious, _, cx| {
+                if let Some(this) = this_previous.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.select_location_suggestion(-1, cx)
+                    });
+                }
+            })
+            .on_action(move |_: &SelectNext, _, cx| {
+                if let Some(this) = this_next.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.select_location_suggestion(1, cx)
+                    });
+                }
+            })
+            .on_action(move |_: &AcceptCompletion, window, cx| {
+                if let Some(this) = this_accept.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| explorer.accept_completion(window, cx));
+                }
+            })
+            .child(state.input.clone())
+            .when_some(popup, |this, popup| {
+                this.child(
+                    // 延迟绘制，避免被面包屑的 overflow_hidden 裁剪
+                    div().absolute().top_full().left_0().child(deferred(
+                        anchored().child(
+                            div()
+                                .mt_1()
+                                .min_w(px(320.))
+                                .p_1()
+                                .bg(theme.colors.card)
+                                .border_1()
+                                .border_color(theme.colors.border)
+                                .rounded(theme.radius.md)
+                                .shadow_lg()
+                                .child(popup),
+                        ),
+                    )),
+                )
+            })
+            .into_any_element()
+    }
+
     /// 递归渲染面板节点
     fn render_panel_node(
         &self,
@@ -1061,6 +3699,13 @@ impl Explorer {
                 let is_active = self.active_panel_id == Some(panel_id);
                 let this_clone_list = this_entity.clone();
                 let this_clone_title = this_entity.clone();
+                let this_clone_drop = this_entity.clone();
+                let this_clone_drop_external = this_entity.clone();
+                let this_clone_menu = this_entity.clone();
+                let panel_path = path.clone();
+                let panel_path_external = path.clone();
+                let panel_path_can_drop = path.clone();
+                let drop_border = theme.colors.brand;
 
                 // 解析路径为面包屑项
                 let breadcrumb_items = parse_path_to_breadcrumb_items(path);
@@ -1077,6 +3722,51 @@ impl Explorer {
                                 .items(breadcrumb_items)
                                 .active(is_active)
                                 .state(breadcrumb_state.clone())
+                                .editor(
+                                    self.location_edit
+                                        .as_ref()
+                                        .filter(|state| state.panel_id == panel_id)
+                                        .map(|state| {
+                                            Self::render_location_editor(state, theme, this_entity)
+                                        }),
+                                )
+                                .on_expand({
+                                    let this_clone_expand = this_entity.clone();
+                                    move |dir, position, window, cx| {
+                                        if let Some(this) = this_clone_expand.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer.set_active_panel(panel_id, cx);
+                                                explorer.show_breadcrumb_children(
+                                                    panel_id, dir, position, window, cx,
+                                                );
+                                            });
+                                        }
+                                    }
+                                })
+                                .on_overflow({
+                                    let this_clone_overflow = this_entity.clone();
+                                    move |hidden, position, window, cx| {
+                                        if let Some(this) = this_clone_overflow.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer.set_active_panel(panel_id, cx);
+                                                explorer.show_breadcrumb_overflow(
+                                                    panel_id, hidden, position, window, cx,
+                                                );
+                                            });
+                                        }
+                                    }
+                                })
+                                .on_edit({
+                                    let this_clone_edit = this_entity.clone();
+                                    move |window, cx| {
+                                        if let Some(this) = this_clone_edit.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer.set_active_panel(panel_id, cx);
+                                                explorer.start_location_edit(panel_id, window, cx);
+                                            });
+                                        }
+                                    }
+                                })
                                 .on_navigate(move |clicked_path, window, cx| {
                                     if let Some(this) = this_clone_title.upgrade() {
                                         let _ = this.update(cx, |explorer, cx| {
@@ -1125,12 +3815,71 @@ impl Explorer {
                         // 文件列表（使用虚拟列表）
                         div()
                             .id(SharedString::from(format!("panel-{}", panel_id)))
+                            .key_context(keymap::FILE_LIST_CONTEXT)
+                            .when(is_active, |this| this.track_focus(&self.file_list_focus))
                             .flex_1()
                             .p_4()
-                            .on_mouse_down(MouseButton::Left, move |_, _, cx| {
+                            .border_1()
+                            .border_color(transparent_black())
+                            // 拖到面板空白处：放入当前目录
+                            .drag_over::<DraggedFiles>(move |style, _, _, _| {
+                                style.border_color(drop_border)
+                            })
+                            .drag_over::<ExternalPaths>(move |style, _, _, _| {
+                                style.border_color(drop_border)
+                            })
+                            .can_drop(move |value, _, _| {
+                                value.downcast_ref::<DraggedFiles>().is_none_or(|dragged| {
+                                    dragged.can_drop_into(&panel_path_can_drop)
+                                })
+                            })
+                            .on_drop(move |dragged: &DraggedFiles, window, cx| {
+                                if let Some(this) = this_clone_drop.upgrade() {
+                                    let _ = this.update(cx, |explorer, cx| {
+                                        explorer.drop_files(
+                                            dragged.paths.clone(),
+                                            panel_path.clone(),
+                                            false,
+                                            window,
+                                            cx,
+                                        );
+                                    });
+                                }
+                            })
+                            .on_drop(move |paths: &ExternalPaths, window, cx| {
+                                if let Some(this) = this_clone_drop_external.upgrade() {
+                                    let _ = this.update(cx, |explorer, cx| {
+                                        explorer.drop_files(
+                                            dnd::external_paths(paths),
+                                            panel_path_external.clone(),
+                                            true,
+                                            window,
+                                            cx,
+                                        );
+                                    });
+                                }
+                            })
+                            .on_mouse_down(MouseButton::Left, move |_, window, cx| {
                                 if let Some(this) = this_clone_list.upgrade() {
                                     let _ = this.update(cx, |explorer, cx| {
                                         explorer.set_active_panel(panel_id, cx);
+                                        explorer.focus_file_list(window);
+                                    });
+                                }
+                            })
+                            // 右键点击空白处：清除选中并显示目录菜单
+                            .on_mouse_down(MouseButton::Right, move |event, window, cx| {
+                                if let Some(this) = this_clone_menu.upgrade() {
+                                    let _ = this.update(cx, |explorer, cx| {
+                                        explorer.set_active_panel(panel_id, cx);
+                                        explorer.clear_selection(cx);
+                                        let items = explorer.background_context_menu(cx);
+                                        explorer.deploy_context_menu(
+                                            event.position,
+                                            items,
+                                            window,
+                                            cx,
+                                        );
                                     });
                                 }
                             })
@@ -1171,23 +3920,40 @@ impl Explorer {
                                         let this_entity_clone = this_entity.clone();
                                         let entries_clone = entries.clone();
                                         let selected_items = self.selected_items.clone();
+                                        let selected_paths = self.selected_paths();
+                                        let cut_items: HashSet<String> = self
+                                            .file_clipboard
+                                            .as_ref()
+                                            .filter(|c| c.mode == ClipboardMode::Cut)
+                                            .map(|c| c.paths.iter().cloned().collect())
+                                            .unwrap_or_default();
+                                        let renaming = self
+                                            .renaming
+                                            .as_ref()
+                                            .filter(|state| state.panel_id == panel_id)
+                                            .map(|state| {
+                                                (
+                                                    state.path.clone(),
+                                                    state.input.clone(),
+                                                    state.error.clone(),
+                                                )
+                                            });
                                         move |entry, index, theme| {
-                                            let icon = match entry.item_type {
-                                                ItemType::Directory => {
-                                                    Icon::new(IconName::FolderClosed)
-                                                }
-                                                ItemType::File => Icon::new(IconName::File),
-                                                ItemType::Symlink => Icon::new(IconName::File),
-                                            };
-
-                                            let name_color = if entry.is_hidden {
+                                            let icon = entry_icon(entry, theme);
+
+                                            // 悬空的链接显示为危险色，隐藏文件和已剪切的条目显示为弱化颜色
+                                            let name_color = if entry.is_broken_link() {
+                                                theme.colors.danger
+                                            } else if entry.is_hidden
+                                                || cut_items.contains(&entry.path)
+                                            {
                                                 theme.colors.muted_foreground
                                             } else {
                                                 theme.colors.foreground
                                             };
 
                                             let entry_path = entry.path.clone();
-                                            let entry_type = entry.item_type;
+                                            let entry_is_dir = entry.is_dir_like();
                                             let this_clone_double = this_entity_clone.clone();
                                             let this_clone_click = this_entity_clone.clone();
 
@@ -1197,24 +3963,89 @@ impl Explorer {
                                             // 检查是否被选中
                                             let is_selected = selected_items.contains(&entry.path);
 
-                                            let mut item =
-                                                ListItem::new(entry.path.clone())
-                                                    .selected(is_selected)
+                                            // 正在重命名的条目显示输入框和校验提示
+                                            let name = match renaming
+                                                .as_ref()
+                                                .filter(|(path, _, _)| *path == entry.path)
+                                            {
+                                                Some((_, input, error)) => div()
+                                                    .flex()
+                                                    .items_center()
+                                                    .gap(theme.spacing.sm)
+                                                    .child(div().w(px(320.)).child(input.clone()))
+                                                    .when_some(error.clone(), |this, error| {
+                                                        this.child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.colors.danger)
+                                                                .child(error),
+                                                        )
+                                                    })
+                                                    .into_any_element(),
+                                                None => div()
+                                                    .flex()
+                                                    .items_center()
+                                                    .gap(theme.spacing.sm)
                                                     .child(
                                                         div()
-                                                            .flex()
-                                                            .items_center()
-                                                            .gap(theme.spacing.sm)
-                                                            .child(icon.text_color(
-                                                                theme.colors.foreground,
-                                                            ))
-                                                            .child(
+                                                            .text_sm()
+                                                            .text_color(name_color)
+                                                            .when(entry.is_broken_link(), |this| {
+                                                                this.line_through()
+                                                            })
+                                                            .child(entry.name.clone()),
+                                                    )
+                                                    // 符号链接在名称后显示目标
+                                                    .when_some(
+                                                        entry.metadata.link_target.clone(),
+                                                        |this, target| {
+                                                            this.child(
                                                                 div()
-                                                                    .text_sm()
-                                                                    .text_color(name_color)
-                                                                    .child(entry.name.clone()),
-                                                            ),
-                                                    );
+                                                                    .text_xs()
+                                                                    .text_color(
+                                                                        theme
+                                                                            .colors
+                                                                            .muted_foreground,
+                                                                    )
+                                                                    .child(format!("→ {}", target)),
+                                                            )
+                                                        },
+                                                    )
+                                                    .into_any_element(),
+                                            };
+
+                                            // 详细信息：权限和所有者，提供者不报告时不显示
+                                            let owner = match (
+                                                entry.metadata.user.clone(),
+                                                entry.metadata.group.clone(),
+                                            ) {
+                                                (Some(user), Some(group)) => {
+                                                    Some(format!("{} {}", user, group))
+                                                }
+                                                (user, group) => user.or(group),
+                                            };
+                                            let details = div()
+                                                .ml_auto()
+                                                .flex()
+                                                .flex_none()
+                                                .gap(theme.spacing.md)
+                                                .text_xs()
+                                                .text_color(theme.colors.muted_foreground)
+                                                .children(properties::format_mode(entry))
+                                                .children(owner);
+
+                                            let mut item = ListItem::new(entry.path.clone())
+                                                .selected(is_selected)
+                                                .child(
+                                                    div()
+                                                        .w_full()
+                                                        .flex()
+                                                        .items_center()
+                                                        .gap(theme.spacing.sm)
+                                                        .child(icon)
+                                                        .child(name)
+                                                        .child(details),
+                                                );
 
                                             // 所有类型都添加单击事件（选中）
                                             let entry_path_click = entry.path.clone();
@@ -1249,38 +4080,117 @@ impl Explorer {
                                                                 cx,
                                                             );
                                                         } else {
-                                                            // 普通点击：单选
+                                                            // 普通点击：单选，再次单击已选中的条目则进入重命名
+                                                            let was_selected =
+                                                                explorer.selected_items.len() == 1
+                                                                    && explorer.is_selected(
+                                                                        &entry_path_click,
+                                                                    );
                                                             explorer.set_single_selection(
                                                                 entry_path_click.clone(),
                                                                 entry_index,
                                                                 cx,
                                                             );
+                                                            if was_selected {
+                                                                explorer.schedule_rename(
+                                                                    panel_id,
+                                                                    entry_path_click.clone(),
+                                                                    event,
+                                                                    cx,
+                                                                );
+                                                            }
                                                         }
                                                     });
                                                 }
                                             });
 
-                                            // 为目录额外添加双击事件（进入目录）
-                                            if entry_type == ItemType::Directory {
-                                                item = item.on_double_click(move |window, cx| {
-                                                    tracing::info!("双击文件夹: {}", entry_path);
-                                                    if let Some(this) = this_clone_double.upgrade()
-                                                    {
-                                                        let path = entry_path.clone();
-                                                        let _ = this.update(cx, |explorer, cx| {
+                                            // 双击：取消延迟重命名，目录或压缩包则进入
+                                            item = item.on_double_click(move |window, cx| {
+                                                if let Some(this) = this_clone_double.upgrade() {
+                                                    let path = entry_path.clone();
+                                                    let _ = this.update(cx, |explorer, cx| {
+                                                        explorer.cancel_pending_rename();
+                                                        if entry_is_dir || is_archive(&path) {
+                                                            tracing::info!("双击进入: {}", path);
                                                             explorer.set_active_panel(panel_id, cx);
                                                             explorer
                                                                 .load_directory(path, window, cx);
+                                                        }
+                                                    });
+                                                }
+                                            });
+
+                                            // 拖动选中的条目（拖动未选中的条目时只拖动它自己）
+                                            let dragged = DraggedFiles {
+                                                paths: if is_selected {
+                                                    selected_paths.clone()
+                                                } else {
+                                                    vec![entry.path.clone()]
+                                                },
+                                            };
+                                            let mut row = div()
+                                                .id(SharedString::from(format!(
+                                                    "drag-{}",
+                                                    entry.path
+                                                )))
+                                                .rounded(theme.radius.md)
+                                                .on_drag(dragged, |dragged, _, _, cx| {
+                                                    cx.new(|_| DragPreview::new(dragged))
+                                                })
+                                                // 右键点击：未选中的条目先单选，再显示条目菜单
+                                                .on_mouse_down(MouseButton::Right, {
+                                                    let this = this_entity_clone.clone();
+                                                    let path = entry.path.clone();
+                                                    move |event, window, cx| {
+                                                        cx.stop_propagation();
+                                                        let Some(this) = this.upgrade() else {
+                                                            return;
+                                                        };
+                                                        let _ = this.update(cx, |explorer, cx| {
+                                                            explorer.set_active_panel(panel_id, cx);
+                                                            if !explorer.is_selected(&path) {
+                                                                explorer.set_single_selection(
+                                                                    path.clone(),
+                                                                    entry_index,
+                                                                    cx,
+                                                                );
+                                                            }
+                                                            let items =
+                                                                explorer.entries_context_menu(cx);
+                                                            explorer.deploy_context_menu(
+                                                                event.position,
+                                                                items,
+                                                                window,
+                                                                cx,
+                                                            );
                                                         });
                                                     }
                                                 });
+
+                                            // 文件夹行可作为拖放目标，悬停一段时间后自动打开
+                                            if entry_is_dir {
+                                                row = Self::folder_drop_target(
+                                                    row,
+                                                    panel_id,
+                                                    entry.path.clone(),
+                                                    theme,
+                                                    &this_entity_clone,
+                                                );
                                             }
 
-                                            item.into_any_element()
+                                            row.child(item).into_any_element()
                                         }
                                     })
                             }),
                     )
+                    .child(self.render_status_bar(
+                        panel_id,
+                        path,
+                        entries,
+                        *loading,
+                        theme,
+                        this_entity,
+                    ))
                     .into_any_element();
 
                 // 使用 PanelContainer 包装以捕获 bounds
@@ -1312,6 +4222,241 @@ impl Explorer {
         }
     }
 
+    /// 渲染面板底部的状态栏：条目数、选中项及其大小、后台任务和所在卷的剩余空间
+    fn render_status_bar(
+        &self,
+        panel_id: PanelId,
+        path: &str,
+        entries: &[FileItem],
+        loading: bool,
+        theme: &Theme,
+        this_entity: &WeakEntity<Self>,
+    ) -> Div {
+        let link = |label: &'static str| {
+            div()
+                .cursor_pointer()
+                .text_color(theme.colors.brand)
+                .hover(|style| style.underline())
+                .child(label)
+        };
+
+        let mut items: Vec<AnyElement> =
+            vec![format!("{} 个项目", entries.len()).into_any_element()];
+
+        // 选中项只属于激活面板
+        let selected: Vec<&FileItem> = if self.active_panel_id == Some(panel_id) {
+            entries
+                .iter()
+                .filter(|entry| self.is_selected(&entry.path))
+                .collect()
+        } else {
+            vec![]
+        };
+        if !selected.is_empty() {
+            let file_bytes: u64 = selected
+                .iter()
+                .filter(|entry| entry.item_type == ItemType::File)
+                .map(|entry| entry.size)
+                .sum();
+            let mut folders: Vec<&str> = selected
+                .iter()
+                .filter(|entry| entry.item_type == ItemType::Directory)
+                .map(|entry| entry.path.as_str())
+                .collect();
+            folders.sort();
+
+            let folder_size = self
+                .folder_size
+                .as_ref()
+                .filter(|state| state.paths == folders);
+            let summary = match folder_size.and_then(|state| state.result) {
+                Some(size) => format!(
+                    "已选择 {} 项，{}（含文件夹）",
+                    selected.len(),
+                    properties::format_size(file_bytes + size.bytes)
+                ),
+                None if file_bytes > 0 || folders.is_empty() => format!(
+                    "已选择 {} 项，{}",
+                    selected.len(),
+                    properties::format_size(file_bytes)
+                ),
+                None => format!("已选择 {} 项", selected.len()),
+            };
+            items.push(summary.into_any_element());
+
+            // 文件夹大小需要手动统计，统计过程中可以取消
+            if !folders.is_empty() {
+                match folder_size {
+                    Some(state) if state.result.is_none() => {
+                        let this_cancel = this_entity.clone();
+                        items.push(
+                            div()
+                                .flex()
+                                .gap(theme.spacing.sm)
+                                .child("正在计算文件夹大小…")
+                                .child(link("取消").on_mouse_down(
+                                    MouseButton::Left,
+                                    move |_, _, cx| {
+                                        cx.stop_propagation();
+                                        if let Some(this) = this_cancel.upgrade() {
+                                            let _ = this.update(cx, |explorer, cx| {
+                                                explorer.cancel_folder_size(cx);
+                                            });
+                                        }
+                                    },
+                                ))
+                                .into_any_element(),
+                        );
+                    }
+                    Some(_) => {}
+                    None => {
+                        let this_compute = this_entity.clone();
+                        items.push(
+                            link("计算文件夹大小")
+                                .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                                    cx.stop_propagation();
+                                    if let Some(this) = this_compute.upgrade() {
+                                        let _ = this.update(cx, |explorer, cx| {
+                                            explorer.compute_folder_size(
+                                                &ComputeFolderSize,
+                                                window,
+                                                cx,
+                                            );
+                                        });
+                                    }
+                                })
+                                .into_any_element(),
+                        );
+                    }
+                }
+            }
+        }
+
+        // 后台任务
+        if loading {
+            items.push("正在加载…".into_any_element());
+        }
+        if let Some((label, progress)) = &self.running_operation {
+            let status = match progress.fraction() {
+                Some(fraction) => format!("正在{}… {:.0}%", label, fraction * 100.),
+                None => format!("正在{}…", label),
+            };
+            let status = match self.operation_queue.len() {
+                0 => status,
+                queued => format!("{}（另有 {} 项等待）", status, queued),
+            };
+            items.push(status.into_any_element());
+        }
+
+        div()
+            .flex()
+            .items_center()
+            .justify_between()
+            .gap(theme.spacing.md)
+            .h_6()
+            .px_4()
+            .border_t_1()
+            .border_color(theme.colors.border)
+            .text_xs()
+            .text_color(theme.colors.muted_foreground)
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(theme.spacing.md)
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .children(items),
+            )
+            .children(self.space_for(path).map(|space| {
+                div()
+                    .flex()
+                    .flex_shrink_0()
+                    .items_center()
+                    .gap(theme.spacing.sm)
+                    .child(div().w(px(80.)).child(usage_bar(&space, theme)))
+                    .child(format!(
+                        "可用 {}，共 {}",
+                        properties::format_size(space.available),
+                        properties::format_size(space.total)
+                    ))
+            }))
+    }
+
+    /// 为文件夹行添加拖放目标行为
+    fn folder_drop_target(
+        row: Stateful<Div>,
+        panel_id: PanelId,
+        path: String,
+        theme: &Theme,
+        this_entity: &WeakEntity<Self>,
+    ) -> Stateful<Div> {
+        let drop_bg = theme.colors.brand_background_hover;
+        let this_drop = this_entity.clone();
+        let this_drop_external = this_entity.clone();
+        let this_hover = this_entity.clone();
+        let this_hover_external = this_entity.clone();
+        let (path_can_drop, path_drop, path_drop_external, path_hover, path_hover_external) =
+            (path.clone(), path.clone(), path.clone(), path.clone(), path);
+
+        row.drag_over::<DraggedFiles>(move |style, _, _, _| style.bg(drop_bg))
+            .drag_over::<ExternalPaths>(move |style, _, _, _| style.bg(drop_bg))
+            .can_drop(move |value, _, _| {
+                value
+                    .downcast_ref::<DraggedFiles>()
+                    .is_none_or(|dragged| dragged.can_drop_into(&path_can_drop))
+            })
+            .on_drop(move |dragged: &DraggedFiles, window, cx| {
+                if let Some(this) = this_drop.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.drop_files(
+                            dragged.paths.clone(),
+                            path_drop.clone(),
+                            false,
+                            window,
+                            cx,
+                        );
+                    });
+                }
+            })
+            .on_drop(move |paths: &ExternalPaths, window, cx| {
+                if let Some(this) = this_drop_external.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.drop_files(
+                            dnd::external_paths(paths),
+                            path_drop_external.clone(),
+                            true,
+                            window,
+                            cx,
+                        );
+                    });
+                }
+            })
+            .on_drag_move(move |event: &DragMoveEvent<DraggedFiles>, window, cx| {
+                let hovered = event.bounds.contains(&event.event.position)
+                    && event.drag(cx).can_drop_into(&path_hover);
+                if let Some(this) = this_hover.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.update_drag_hover(panel_id, &path_hover, hovered, window, cx);
+                    });
+                }
+            })
+            .on_drag_move(move |event: &DragMoveEvent<ExternalPaths>, window, cx| {
+                let hovered = event.bounds.contains(&event.event.position);
+                if let Some(this) = this_hover_external.upgrade() {
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.update_drag_hover(
+                            panel_id,
+                            &path_hover_external,
+                            hovered,
+                            window,
+                            cx,
+                        );
+                    });
+                }
+            })
+    }
+
     /// 渲染文件列表（已废弃，保留是为了兼容旧代码）
     #[allow(dead_code)]
     fn render_file_list_deprecated(
@@ -1402,9 +4547,7 @@ impl Element for PanelContainer {
 
 fn init() {
     // 设置日志目录
-    let log_dir = home_dir()
-        .map(|home| home.join(".explorer").join("logs"))
-        .expect("Failed to find log dir");
+    let log_dir = paths::data_dir().join("logs");
     if !log_dir.exists() {
         create_dir_all(&log_dir).expect("Failed to create log dir");
     }
@@ -1446,6 +4589,11 @@ fn main() {
         // 初始化全局主题（使用暗色主题）
         cx.set_global(Theme::dark());
 
+        // 注册快捷键（默认值 + ~/.explorer/keymap.json），配置修改后自动重新加载
+        keymap::reload(cx);
+        keymap::watch(cx);
+        commands::init(cx);
+
         cx.activate(true);
         cx.on_window_closed(|cx| {
             if cx.windows().is_empty() {
@@ -1468,6 +4616,7 @@ fn main() {
         .expect("failed to open window")
         .update(cx, |explorer, window, cx| {
             explorer.init(window, cx);
+            explorer.focus_file_list(window);
             window.activate_window();
         })
         .expect("failed to active window");
@@ -0,0 +1,499 @@
+//! 命令面板
+//!
+//! 列出命令注册表中的所有命令（附带快捷键）、书签位置、最近访问的位置，以及输入路径时的“前往路径”。
+//! 按模糊匹配得分排序，最近使用过的条目排在前面；查询为空时先列出最近使用的条目。
+
+use std::{fs, ops::Range, path::PathBuf};
+
+use gpui::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+
+use explorer_component::{Icon, IconName, TextInput, TextInputEvent, Theme};
+
+use crate::{
+    commands::CommandRegistry,
+    fuzzy::{FuzzyMatch, fuzzy_match},
+    paths,
+};
+
+actions!(command_palette, [SelectPrevious, SelectNext]);
+
+const CONTEXT: &str = "CommandPalette";
+/// 最多记录的最近使用条目数
+const MAX_HISTORY: usize = 20;
+/// 最近使用的条目在排序时的加分（越近越高）
+const RECENT_BONUS: i64 = 16;
+
+/// 命令面板的默认快捷键
+pub fn key_bindings() -> Vec<KeyBinding> {
+    let context = Some(CONTEXT);
+    vec![
+        KeyBinding::new("up", SelectPrevious, context),
+        KeyBinding::new("down", SelectNext, context),
+    ]
+}
+
+// ===== 最近使用 =====
+
+/// 最近使用的命令面板条目
+///
+/// 保存在 `~/.explorer/palette_history.json`，最近使用的在前
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaletteHistory {
+    recent: Vec<String>,
+}
+
+impl PaletteHistory {
+    fn file_path() -> PathBuf {
+        paths::data_file("palette_history.json")
+    }
+
+    /// 从磁盘加载（文件不存在或损坏时返回空记录）
+    pub fn load() -> Self {
+        fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存到磁盘
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 记录一次使用
+    pub fn record(&mut self, key: &str) {
+        self.recent.retain(|recent| recent != key);
+        self.recent.insert(0, key.to_string());
+        self.recent.truncate(MAX_HISTORY);
+    }
+
+    /// 条目在最近使用中的排名（0 为最近）
+    fn rank(&self, key: &str) -> Option<usize> {
+        self.recent.iter().position(|recent| recent == key)
+    }
+}
+
+// ===== 条目 =====
+
+/// 选中条目后要执行的操作
+pub enum PaletteTarget {
+    /// 派发动作
+    Action(Box<dyn Action>),
+    /// 在激活面板中打开目录
+    Navigate(String),
+}
+
+impl Clone for PaletteTarget {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Navigate(path) => Self::Navigate(path.clone()),
+        }
+    }
+}
+
+/// 条目类型
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ItemKind {
+    Command,
+    Bookmark,
+    Recent,
+    Path,
+}
+
+/// 面板中的一个条目
+struct PaletteItem {
+    /// 用于记录最近使用的唯一标识
+    key: String,
+    kind: ItemKind,
+    label: SharedString,
+    /// 参与匹配但不高亮的附加文本（命令的动作名称、书签的路径）
+    alias: String,
+    target: PaletteTarget,
+}
+
+/// 匹配结果
+struct PaletteMatch {
+    item: PaletteItem,
+    highlights: Vec<Range<usize>>,
+}
+
+/// 命令面板事件
+pub enum CommandPaletteEvent {
+    /// 选中了条目（`key` 用于记录最近使用）
+    Confirm {
+        key: String,
+        target: PaletteTarget,
+    },
+    Dismiss,
+}
+
+/// 书签：名称和路径
+pub type Bookmark = (String, String);
+
+/// 查询是否是路径
+fn is_path_like(query: &str) -> bool {
+    query.starts_with('/') || query.starts_with('~') || {
+        let bytes = query.as_bytes();
+        cfg!(windows) && bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+    }
+}
+
+// ===== 命令面板 =====
+
+pub struct CommandPalette {
+    input: Entity<TextInput>,
+    bookmarks: Vec<Bookmark>,
+    // 最近访问的位置（按 frecency 排列）
+    recent: Vec<Bookmark>,
+    history: PaletteHistory,
+    matches: Vec<PaletteMatch>,
+    selected: usize,
+    scroll_handle: ScrollHandle,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<CommandPaletteEvent> for CommandPalette {}
+
+impl CommandPalette {
+    pub fn new(
+        bookmarks: Vec<Bookmark>,
+        recent: Vec<Bookmark>,
+        history: PaletteHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let input = cx.new(|cx| {
+            TextInput::new(window, cx).placeholder("输入命令、书签名称或路径（/、~ 开头）")
+        });
+        let subscription =
+            cx.subscribe_in(&input, window, |palette, _, event, _, cx| match event {
+                TextInputEvent::Change(query) => palette.update_matches(query, cx),
+                TextInputEvent::Confirm => palette.confirm(palette.selected, cx),
+                TextInputEvent::Cancel => cx.emit(CommandPaletteEvent::Dismiss),
+                TextInputEvent::Blur => {}
+            });
+
+        let mut palette = Self {
+            input,
+            bookmarks,
+            recent,
+            history,
+            matches: vec![],
+            selected: 0,
+            scroll_handle: ScrollHandle::new(),
+            _subscription: subscription,
+        };
+        palette.update_matches("", cx);
+        palette
+    }
+
+    pub fn focus(&self, window: &mut Window, cx: &App) {
+        self.input.read(cx).focus(window);
+    }
+
+    /// 所有候选条目（命令在前，然后是书签和最近访问的位置）
+    fn candidates(&self, cx: &App) -> Vec<PaletteItem> {
+        let commands = cx
+            .global::<CommandRegistry>()
+            .commands()
+            .iter()
+            .map(|command| PaletteItem {
+                key: command.id().to_string(),
+                kind: ItemKind::Command,
+                label: command.label.clone(),
+                alias: command.id().to_string(),
+                target: PaletteTarget::Action(command.action.boxed_clone()),
+            });
+        let bookmarks = self.bookmarks.iter().map(|(name, path)| PaletteItem {
+            key: format!("bookmark:{}", path),
+            kind: ItemKind::Bookmark,
+            label: name.clone().into(),
+            alias: path.clone(),
+            target: PaletteTarget::Navigate(path.clone()),
+        });
+        let recent = self
+            .recent
+            .iter()
+            .filter(|(_, path)| !self.bookmarks.iter().any(|(_, bookmark)| bookmark == path))
+            .map(|(name, path)| PaletteItem {
+                key: format!("recent:{}", path),
+                kind: ItemKind::Recent,
+                label: name.clone().into(),
+                alias: path.clone(),
+                target: PaletteTarget::Navigate(path.clone()),
+            });
+        commands.chain(bookmarks).chain(recent).collect()
+    }
+
+    fn path_item(path: String) -> PaletteItem {
+        PaletteItem {
+            key: format!("path:{}", path),
+            kind: ItemKind::Path,
+            label: format!("前往 {}", path).into(),
+            alias: String::new(),
+            target: PaletteTarget::Navigate(path),
+        }
+    }
+
+    fn update_matches(&mut self, query: &str, cx: &mut Context<Self>) {
+        let query = query.trim();
+        let candidates = self.candidates(cx);
+        let mut matches = vec![];
+
+        if query.is_empty() {
+            // 最近使用的条目在前（包括之前前往过的路径）
+            let mut rest = vec![];
+            let mut recent: Vec<(usize, PaletteItem)> = vec![];
+            for item in candidates {
+                match self.history.rank(&item.key) {
+                    Some(rank) => recent.push((rank, item)),
+                    None => rest.push(item),
+                }
+            }
+            for (rank, key) in self.history.recent.iter().enumerate() {
+                if let Some(path) = key.strip_prefix("path:") {
+                    recent.push((rank, Self::path_item(path.to_string())));
+                }
+            }
+            recent.sort_by_key(|(rank, _)| *rank);
+            matches.extend(
+                recent
+                    .into_iter()
+                    .chain(rest.into_iter().map(|item| (0, item)))
+                    .map(|(_, item)| PaletteMatch {
+                        item,
+                        highlights: vec![],
+                    }),
+            );
+        } else {
+            if is_path_like(query)
+                && let Some(path) = paths::expand_home(query)
+            {
+                matches.push(PaletteMatch {
+                    item: Self::path_item(path),
+                    highlights: vec![],
+                });
+            }
+
+            let mut scored: Vec<(i64, PaletteMatch)> = candidates
+                .into_iter()
+                .filter_map(|item| {
+                    let label_match = fuzzy_match(query, &item.label);
+                    let alias_score = fuzzy_match(query, &item.alias).map(|m| m.score);
+                    let score = match (&label_match, alias_score) {
+                        (Some(label), Some(alias)) => label.score.max(alias),
+                        (Some(label), None) => label.score,
+                        (None, Some(alias)) => alias,
+                        (None, None) => return None,
+                    };
+                    let bonus = self
+                        .history
+                        .rank(&item.key)
+                        .map(|rank| (MAX_HISTORY - rank) as i64 * RECENT_BONUS)
+                        .unwrap_or_default();
+                    let highlights = label_match
+                        .as_ref()
+                        .map(|m: &FuzzyMatch| m.ranges(&item.label))
+                        .unwrap_or_default();
+                    Some((score + bonus, PaletteMatch { item, highlights }))
+                })
+                .collect();
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            matches.extend(scored.into_iter().map(|(_, m)| m));
+        }
+
+        self.matches = matches;
+        self.selected = 0;
+        self.scroll_handle.scroll_to_item(0);
+        cx.notify();
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.selected = self
+            .selected
+            .checked_sub(1)
+            .unwrap_or(self.matches.len() - 1);
+        self.scroll_handle.scroll_to_item(self.selected);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.selected = (self.selected + 1) % self.matches.len();
+        self.scroll_handle.scroll_to_item(self.selected);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(selected) = self.matches.get(index) else {
+            return;
+        };
+        cx.emit(CommandPaletteEvent::Confirm {
+            key: selected.item.key.clone(),
+            target: selected.item.target.clone(),
+        });
+    }
+
+    fn render_match(
+        &self,
+        index: usize,
+        palette_match: &PaletteMatch,
+        theme: &Theme,
+        window: &Window,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let item = &palette_match.item;
+        let is_selected = index == self.selected;
+
+        let icon = match item.kind {
+            ItemKind::Command => IconName::ChevronRight,
+            ItemKind::Bookmark => IconName::FolderClosed,
+            ItemKind::Recent | ItemKind::Path => IconName::FolderOpen,
+        };
+        // 命令显示快捷键，书签和最近访问的位置显示路径
+        let detail = match &item.target {
+            PaletteTarget::Action(action) => window
+                .highest_precedence_binding_for_action(action.as_ref())
+                .map(|binding| {
+                    binding
+                        .keystrokes()
+                        .iter()
+                        .map(|keystroke| keystroke.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }),
+            PaletteTarget::Navigate(path) if item.kind != ItemKind::Path => Some(path.clone()),
+            PaletteTarget::Navigate(_) => None,
+        };
+        let is_recent = self.history.rank(&item.key).is_some();
+        let highlight = HighlightStyle {
+            color: Some(theme.colors.brand.into()),
+            font_weight: Some(FontWeight::BOLD),
+            ..Default::default()
+        };
+
+        div()
+            .id(("palette-item", index))
+            .h(px(32.))
+            .px(theme.spacing.sm)
+            .flex()
+            .items_center()
+            .gap(theme.spacing.sm)
+            .rounded(theme.radius.sm)
+            .text_sm()
+            .text_color(theme.colors.foreground)
+            .when(is_selected, |this| {
+                this.bg(theme.colors.list_item_background_selected)
+            })
+            .hover(|style| style.bg(theme.colors.list_item_background_hover))
+            .child(Icon::new(icon).text_color(theme.colors.muted_foreground))
+            .child(
+                div().flex_1().overflow_hidden().child(
+                    StyledText::new(item.label.clone()).with_highlights(
+                        palette_match
+                            .highlights
+                            .iter()
+                            .map(|range| (range.clone(), highlight)),
+                    ),
+                ),
+            )
+            .when(is_recent, |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.colors.muted_foreground)
+                        .child("最近"),
+                )
+            })
+            .when_some(detail, |this, detail| {
+                this.child(
+                    div()
+                        .max_w(px(240.))
+                        .overflow_hidden()
+                        .text_xs()
+                        .text_color(theme.colors.muted_foreground)
+                        .child(detail),
+                )
+            })
+            .on_click(cx.listener(move |palette, _, _, cx| palette.confirm(index, cx)))
+    }
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        let rows = self
+            .matches
+            .iter()
+            .enumerate()
+            .map(|(index, palette_match)| {
+                self.render_match(index, palette_match, theme, window, cx)
+                    .into_any_element()
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("command-palette")
+            .absolute()
+            .inset_0()
+            .occlude()
+            .flex()
+            .flex_col()
+            .items_center()
+            .pt(px(80.))
+            .key_context(CONTEXT)
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_next))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| cx.emit(CommandPaletteEvent::Dismiss)),
+            )
+            .child(
+                div()
+                    .w(px(560.))
+                    .flex()
+                    .flex_col()
+                    .gap(theme.spacing.sm)
+                    .p(theme.spacing.sm)
+                    .bg(theme.colors.card)
+                    .text_color(theme.colors.card_foreground)
+                    .border_1()
+                    .border_color(theme.colors.border)
+                    .rounded(theme.radius.lg)
+                    .shadow_lg()
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .child(self.input.clone())
+                    .child(if rows.is_empty() {
+                        div()
+                            .p(theme.spacing.sm)
+                            .text_sm()
+                            .text_color(theme.colors.muted_foreground)
+                            .child("没有匹配的命令")
+                            .into_any_element()
+                    } else {
+                        div()
+                            .id("command-palette-list")
+                            .max_h(px(360.))
+                            .overflow_y_scroll()
+                            .track_scroll(&self.scroll_handle)
+                            .flex()
+                            .flex_col()
+                            .children(rows)
+                            .into_any_element()
+                    }),
+            )
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+
+use dirs::home_dir;
+
+use explorer_common::FileItem;
+
+/// 应用数据目录：`~/.explorer`
+pub fn data_dir() -> PathBuf {
+    home_dir()
+        .map(|home| home.join(".explorer"))
+        .unwrap_or_else(|| PathBuf::from(".explorer"))
+}
+
+/// 应用数据目录下的文件路径
+pub fn data_file(name: &str) -> PathBuf {
+    data_dir().join(name)
+}
+
+/// 把 `~` 开头的路径展开为主目录，`~user` 形式不支持
+pub fn expand_home(path: &str) -> Option<String> {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            let home = home_dir()?.display().to_string();
+            Some(format!("{}{}", home, rest))
+        }
+        Some(_) => None,
+        None => Some(path.to_string()),
+    }
+}
+
+/// 获取路径的父目录（字符串形式）
+pub fn parent_path(path: &str) -> Option<String> {
+    Path::new(path)
+        .parent()
+        .map(|parent| parent.display().to_string())
+}
+
+/// 获取路径的最后一级名称
+pub fn file_name(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// 计算相对于 `base` 的路径，`path` 不在 `base` 之下时返回 `None`
+pub fn relative_path(path: &str, base: &str) -> Option<String> {
+    let relative = Path::new(path).strip_prefix(base).ok()?;
+    if relative.as_os_str().is_empty() {
+        Some(".".to_string())
+    } else {
+        Some(relative.display().to_string())
+    }
+}
+
+/// 拼接目录与名称
+pub fn join_path(dir: &str, name: &str) -> String {
+    Path::new(dir).join(name).display().to_string()
+}
+
+/// 文件名的比较键
+///
+/// macOS 和 Windows 的文件系统默认不区分大小写
+pub fn name_key(name: &str) -> String {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+/// 判断两个文件名在当前平台上是否指向同一条目
+pub fn same_name(a: &str, b: &str) -> bool {
+    name_key(a) == name_key(b)
+}
+
+/// 校验新文件名，返回错误提示
+pub fn validate_file_name(name: &str, original: &str, entries: &[FileItem]) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("名称不能为空".to_string());
+    }
+    if name.contains(['/', '\\', '\0']) {
+        return Err("名称不能包含 / 或 \\".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err("名称无效".to_string());
+    }
+
+    // 仅大小写不同的改名不算重名
+    if !same_name(name, original) && entries.iter().any(|e| same_name(&e.name, name)) {
+        return Err(format!("已存在名为 {} 的条目", name));
+    }
+    Ok(())
+}
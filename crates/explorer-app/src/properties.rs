@@ -0,0 +1,311 @@
+//! 属性对话框
+//!
+//! 显示一个或多个条目的名称、位置、类型、链接目标、大小、时间、权限，
+//! 以及提供者报告的所有者、inode、硬链接数等 Unix 元数据。
+//! 元数据和目录中的条目数在后台读取，读取完成前显示“读取中”。
+
+use std::{sync::Arc, time::SystemTime};
+
+use chrono::{DateTime, Local};
+use gpui::{prelude::*, *};
+
+use explorer_common::{FileItem, ItemType};
+use explorer_component::{Button, Dialog, Theme};
+use explorer_storage::{StorageError, StorageProvider};
+
+use crate::paths;
+
+/// 格式化文件大小（1024 进制）
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KB", "MB", "GB", "TB", "PB"];
+    if bytes < 1024 {
+        return format!("{} 字节", bytes);
+    }
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// 把权限格式化为 `ls -l` 的形式，如 `drwxr-xr-x`
+///
+/// 类型字符优先取条目类型，普通文件再按 `st_mode` 中的类型位区分设备、管道和套接字。
+pub fn format_mode(item: &FileItem) -> Option<String> {
+    let mode = item.metadata.permissions?;
+    let kind = match item.item_type {
+        ItemType::Directory => 'd',
+        ItemType::Symlink => 'l',
+        ItemType::File => match mode & 0o170000 {
+            0o140000 => 's',
+            0o060000 => 'b',
+            0o020000 => 'c',
+            0o010000 => 'p',
+            _ => '-',
+        },
+    };
+    let mut text = String::with_capacity(10);
+    text.push(kind);
+    // 依次为所有者、组和其他用户，特殊位显示在执行位上
+    for (shift, special, set, unset) in [
+        (6, 0o4000, 's', 'S'),
+        (3, 0o2000, 's', 'S'),
+        (0, 0o1000, 't', 'T'),
+    ] {
+        let bits = mode >> shift;
+        text.push(if bits & 4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 2 != 0 { 'w' } else { '-' });
+        text.push(match (bits & 1 != 0, mode & special != 0) {
+            (true, true) => set,
+            (false, true) => unset,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    Some(text)
+}
+
+/// 格式化所有者或组：有名称时显示名称和 ID
+fn format_owner(name: Option<&str>, id: Option<u32>) -> Option<String> {
+    match (name, id) {
+        (Some(name), Some(id)) => Some(format!("{}（{}）", name, id)),
+        (Some(name), None) => Some(name.to_string()),
+        (None, Some(id)) => Some(id.to_string()),
+        (None, None) => None,
+    }
+}
+
+/// 格式化时间（本地时区）
+fn format_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// 后台读取的结果
+struct Loaded {
+    items: Vec<FileItem>,
+    // 目录中的直接子条目数（仅选中单个目录时读取）
+    children: Option<usize>,
+}
+
+/// 属性对话框，关闭时发出 `DismissEvent`
+pub struct PropertiesDialog {
+    focus_handle: FocusHandle,
+    paths: Vec<String>,
+    loaded: Option<Result<Loaded, String>>,
+}
+
+impl EventEmitter<DismissEvent> for PropertiesDialog {}
+
+impl PropertiesDialog {
+    pub fn new(
+        paths: Vec<String>,
+        provider: Arc<dyn StorageProvider>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let load_paths = paths.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let loaded = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut items = Vec::with_capacity(load_paths.len());
+                    for path in &load_paths {
+                        items.push(provider.get_metadata(path).await?);
+                    }
+                    let children = match items.as_slice() {
+                        [item] if item.item_type == ItemType::Directory => {
+                            Some(provider.list_entries(&item.path).await?.len())
+                        }
+                        _ => None,
+                    };
+                    Ok(Loaded { items, children })
+                })
+                .await
+                .map_err(|e: StorageError| e.to_string());
+            let _ = this.update(cx, |dialog, cx| {
+                dialog.loaded = Some(loaded);
+                cx.notify();
+            });
+        })
+        .detach();
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            paths,
+            loaded: None,
+        }
+    }
+
+    pub fn focus(&self, window: &mut Window) {
+        self.focus_handle.focus(window);
+    }
+
+    /// 属性行：(名称, 值)
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        let location = match self.paths.as_slice() {
+            [path] => paths::parent_path(path),
+            paths => {
+                let parents: Vec<Option<String>> =
+                    paths.iter().map(|path| paths::parent_path(path)).collect();
+                parents
+                    .windows(2)
+                    .all(|pair| pair[0] == pair[1])
+                    .then(|| parents.first().cloned().flatten())
+                    .flatten()
+            }
+        };
+
+        let loaded = match &self.loaded {
+            None => {
+                let mut rows = vec![];
+                rows.extend(location.map(|location| ("位置", location)));
+                rows.push(("状态", "读取中…".to_string()));
+                return rows;
+            }
+            Some(Err(e)) => return vec![("错误", e.clone())],
+            Some(Ok(loaded)) => loaded,
+        };
+
+        let mut rows = vec![];
+        match loaded.items.as_slice() {
+            [item] => {
+                rows.push(("名称", item.name.clone()));
+                rows.extend(location.map(|location| ("位置", location)));
+                let kind = match item.item_type {
+                    ItemType::File => "文件",
+                    ItemType::Directory => "文件夹",
+                    ItemType::Symlink => "符号链接",
+                };
+                rows.push(("类型", kind.to_string()));
+                if let Some(target) = &item.metadata.link_target {
+                    let target = if item.is_broken_link() {
+                        format!("{}（不存在）", target)
+                    } else {
+                        target.clone()
+                    };
+                    rows.push(("链接到", target));
+                }
+                if let Some(mime) = &item.metadata.mime_type {
+                    rows.push(("MIME 类型", mime.clone()));
+                }
+                match (item.item_type, loaded.children) {
+                    (ItemType::Directory, Some(children)) => {
+                        rows.push(("包含", format!("{} 项", children)))
+                    }
+                    _ => rows.push((
+                        "大小",
+                        format!("{}（{} 字节）", format_size(item.size), item.size),
+                    )),
+                }
+                rows.push(("修改时间", format_time(item.modified)));
+                if let Some(created) = item.metadata.created {
+                    rows.push(("创建时间", format_time(created)));
+                }
+                if let Some(accessed) = item.metadata.accessed {
+                    rows.push(("访问时间", format_time(accessed)));
+                }
+                if let (Some(permissions), Some(mode)) =
+                    (item.metadata.permissions, format_mode(item))
+                {
+                    rows.push(("权限", format!("{}（{:04o}）", mode, permissions & 0o7777)));
+                }
+                let metadata = &item.metadata;
+                if let Some(owner) = format_owner(metadata.user.as_deref(), metadata.uid) {
+                    rows.push(("所有者", owner));
+                }
+                if let Some(group) = format_owner(metadata.group.as_deref(), metadata.gid) {
+                    rows.push(("组", group));
+                }
+                if let Some(blocks) = metadata.blocks {
+                    rows.push(("占用空间", format_size(blocks * 512)));
+                }
+                if let Some(inode) = metadata.inode {
+                    rows.push(("inode", inode.to_string()));
+                }
+                if let Some(hard_links) = metadata.hard_links {
+                    rows.push(("硬链接数", hard_links.to_string()));
+                }
+                if let Some(device) = metadata.device {
+                    rows.push(("设备", device.to_string()));
+                }
+            }
+            items => {
+                let dirs = items
+                    .iter()
+                    .filter(|item| item.item_type == ItemType::Directory)
+                    .count();
+                rows.push((
+                    "选中",
+                    format!(
+                        "{} 项（{} 个文件夹，{} 个文件）",
+                        items.len(),
+                        dirs,
+                        items.len() - dirs
+                    ),
+                ));
+                rows.extend(location.map(|location| ("位置", location)));
+                let size: u64 = items
+                    .iter()
+                    .filter(|item| item.item_type != ItemType::Directory)
+                    .map(|item| item.size)
+                    .sum();
+                rows.push(("文件大小", format_size(size)));
+            }
+        }
+        rows
+    }
+}
+
+impl Render for PropertiesDialog {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let this = cx.entity().downgrade();
+
+        let title = match self.paths.as_slice() {
+            [path] => format!(
+                "{} 的属性",
+                paths::file_name(path).unwrap_or_else(|| path.clone())
+            ),
+            paths => format!("{} 项的属性", paths.len()),
+        };
+
+        let rows = self.rows().into_iter().map(|(label, value)| {
+            div()
+                .flex()
+                .gap(theme.spacing.md)
+                .text_sm()
+                .child(
+                    div()
+                        .w(px(88.))
+                        .flex_shrink_0()
+                        .text_color(theme.colors.muted_foreground)
+                        .child(label),
+                )
+                .child(div().flex_1().overflow_hidden().child(value))
+        });
+
+        div()
+            .absolute()
+            .inset_0()
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(|_, event: &KeyDownEvent, _, cx| {
+                if event.keystroke.key == "escape" {
+                    cx.emit(DismissEvent);
+                }
+            }))
+            .child(
+                Dialog::new(title)
+                    .width(px(480.))
+                    .child(div().flex().flex_col().gap(theme.spacing.xs).children(rows))
+                    .footer(
+                        Button::new("properties-close", "关闭").on_click(move |_, cx| {
+                            let _ = this.update(cx, |_, cx| cx.emit(DismissEvent));
+                        }),
+                    ),
+            )
+    }
+}
@@ -1,73 +1,311 @@
-use dirs;
+//! 快捷访问
+//!
+//! 侧边栏中用户可编辑的位置列表，保存在 `~/.explorer/quick_access.json`。
+//! 首次启动时使用系统的常用文件夹（主文件夹、桌面、文档等）作为默认内容，
+//! 名称取自 XDG 用户目录的文件夹名（已按系统语言本地化）。
+//!
+//! 与 GTK 书签（`~/.config/gtk-3.0/bookmarks`，Nautilus 等文件管理器使用）双向同步：
+//! 在应用中固定的位置写入书签文件，其他程序添加或删除的书签在文件变化后合并进来。
+//! 默认的常用文件夹不写入书签文件；书签文件中的非 `file://` 条目原样保留在原来的位置。
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
 
 use explorer_common::QuickAccessItem;
 
-/// 获取快捷访问项列表（跨平台）
-pub fn get_quick_access_items() -> Vec<QuickAccessItem> {
-    let mut items = Vec::new();
+use crate::paths;
 
-    // 主文件夹
-    if let Some(home) = dirs::home_dir() {
-        items.push(QuickAccessItem {
-            name: "主文件夹".to_string(),
-            path: home.to_string_lossy().to_string(),
-            icon: "home".to_string(),
-        });
+/// 快捷访问列表
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuickAccess {
+    items: Vec<QuickAccessItem>,
+    /// 与 GTK 书签同步的路径（即上次同步时书签文件中的位置）
+    #[serde(default)]
+    gtk_bookmarks: Vec<String>,
+}
+
+impl QuickAccess {
+    fn file_path() -> PathBuf {
+        paths::data_file("quick_access.json")
     }
 
-    // 桌面
-    if let Some(desktop) = dirs::desktop_dir() {
-        items.push(QuickAccessItem {
-            name: "桌面".to_string(),
-            path: desktop.to_string_lossy().to_string(),
-            icon: "desktop".to_string(),
-        });
+    /// 从磁盘加载（文件不存在时使用系统常用文件夹），并合并 GTK 书签
+    pub fn load() -> Self {
+        let mut quick_access = fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| Self {
+                items: default_items(),
+                gtk_bookmarks: vec![],
+            });
+        quick_access.sync_from_gtk();
+        quick_access
     }
 
-    // 文档
-    if let Some(documents) = dirs::document_dir() {
-        items.push(QuickAccessItem {
-            name: "文档".to_string(),
-            path: documents.to_string_lossy().to_string(),
-            icon: "documents".to_string(),
-        });
+    /// 保存到磁盘，并把需要同步的位置写入 GTK 书签
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        self.write_gtk_bookmarks()?;
+        Ok(())
     }
 
-    // 下载
-    if let Some(downloads) = dirs::download_dir() {
-        items.push(QuickAccessItem {
-            name: "下载".to_string(),
-            path: downloads.to_string_lossy().to_string(),
-            icon: "downloads".to_string(),
-        });
+    /// 所有位置（按显示顺序）
+    pub fn items(&self) -> &[QuickAccessItem] {
+        &self.items
     }
 
-    // 图片
-    if let Some(pictures) = dirs::picture_dir() {
-        items.push(QuickAccessItem {
-            name: "图片".to_string(),
-            path: pictures.to_string_lossy().to_string(),
-            icon: "pictures".to_string(),
-        });
+    /// 是否已固定
+    pub fn contains(&self, path: &str) -> bool {
+        self.items.iter().any(|item| item.path == path)
     }
 
-    // 音乐
-    if let Some(audio) = dirs::audio_dir() {
-        items.push(QuickAccessItem {
-            name: "音乐".to_string(),
-            path: audio.to_string_lossy().to_string(),
-            icon: "music".to_string(),
+    /// 固定一个位置（追加到末尾），已固定时返回 `false`
+    pub fn pin(&mut self, path: &str) -> bool {
+        if self.contains(path) {
+            return false;
+        }
+        self.items.push(QuickAccessItem {
+            name: paths::file_name(path).unwrap_or_else(|| path.to_string()),
+            path: path.to_string(),
+            icon: "folder".to_string(),
         });
+        self.gtk_bookmarks.push(path.to_string());
+        true
+    }
+
+    /// 取消固定
+    pub fn unpin(&mut self, path: &str) {
+        self.items.retain(|item| item.path != path);
+        self.gtk_bookmarks.retain(|bookmark| bookmark != path);
+    }
+
+    /// 修改显示名称，名称为空时恢复为文件夹名
+    pub fn rename(&mut self, path: &str, name: &str) {
+        let name = name.trim();
+        if let Some(item) = self.items.iter_mut().find(|item| item.path == path) {
+            item.name = if name.is_empty() {
+                paths::file_name(path).unwrap_or_else(|| path.to_string())
+            } else {
+                name.to_string()
+            };
+        }
+    }
+
+    /// 把第 `from` 项移动到第 `to` 项的位置
+    pub fn move_item(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.items.len() || to >= self.items.len() || from == to {
+            return false;
+        }
+        let item = self.items.remove(from);
+        self.items.insert(to, item);
+        true
+    }
+
+    /// GTK 书签文件的修改时间（用于检测其他程序的修改）
+    pub fn gtk_modified() -> Option<SystemTime> {
+        fs::metadata(gtk_bookmarks_path()?)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// 合并 GTK 书签的变化：书签文件中新增的位置追加到末尾，
+    /// 从书签文件中删除的位置从列表中移除。有变化时返回 `true`
+    pub fn sync_from_gtk(&mut self) -> bool {
+        let Some(bookmarks) = read_gtk_bookmarks() else {
+            return false;
+        };
+        let current: HashSet<&str> = bookmarks.iter().map(|b| b.path.as_str()).collect();
+        let previous: HashSet<String> = self.gtk_bookmarks.iter().cloned().collect();
+
+        let before = self.items.len();
+        self.items
+            .retain(|item| !previous.contains(&item.path) || current.contains(item.path.as_str()));
+        let mut changed = self.items.len() != before;
+
+        for bookmark in &bookmarks {
+            if previous.contains(&bookmark.path) || self.contains(&bookmark.path) {
+                continue;
+            }
+            self.items.push(QuickAccessItem {
+                name: bookmark.label.clone().unwrap_or_else(|| {
+                    paths::file_name(&bookmark.path).unwrap_or_else(|| bookmark.path.clone())
+                }),
+                path: bookmark.path.clone(),
+                icon: "folder".to_string(),
+            });
+            changed = true;
+        }
+
+        let synced: Vec<String> = bookmarks.into_iter().map(|b| b.path).collect();
+        changed |= synced != self.gtk_bookmarks;
+        self.gtk_bookmarks = synced;
+        changed
+    }
+
+    /// 把同步的位置写入 GTK 书签文件（保留其中的非本地书签）
+    fn write_gtk_bookmarks(&self) -> anyhow::Result<()> {
+        let Some(path) = gtk_bookmarks_path() else {
+            return Ok(());
+        };
+        // 没有 GTK 配置目录的系统上不创建书签文件
+        if !path.parent().is_some_and(Path::exists) {
+            return Ok(());
+        }
+
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let local: Vec<String> = self
+            .items
+            .iter()
+            .filter(|item| self.gtk_bookmarks.contains(&item.path))
+            .filter_map(|item| {
+                let url = Url::from_file_path(&item.path).ok()?;
+                let default_name = paths::file_name(&item.path);
+                Some(if default_name.as_deref() == Some(item.name.as_str()) {
+                    url.to_string()
+                } else {
+                    format!("{} {}", url, item.name)
+                })
+            })
+            .collect();
+
+        let content = merge_bookmark_lines(&existing, local);
+        if content != existing {
+            fs::write(path, content)?;
+        }
+        Ok(())
     }
+}
+
+/// 用 `local` 依次替换书签文件中的 `file://` 行，其他书签保持原来的位置，多出的本地书签追加到末尾
+fn merge_bookmark_lines(existing: &str, local: Vec<String>) -> String {
+    let mut local = local.into_iter();
+    let mut lines: Vec<String> = vec![];
+    for line in existing.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with("file://") {
+            lines.extend(local.next());
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    lines.extend(local);
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// GTK 书签文件中的本地位置
+struct GtkBookmark {
+    path: String,
+    label: Option<String>,
+}
 
-    // 视频
-    if let Some(videos) = dirs::video_dir() {
+/// GTK 书签文件路径
+fn gtk_bookmarks_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("gtk-3.0").join("bookmarks"))
+}
+
+/// 读取 GTK 书签中的本地位置，文件不存在时返回 `None`
+fn read_gtk_bookmarks() -> Option<Vec<GtkBookmark>> {
+    let content = fs::read_to_string(gtk_bookmarks_path()?).ok()?;
+    Some(
+        content
+            .lines()
+            .filter_map(|line| {
+                // 每行为 `URI [标签]`
+                let (uri, label) = match line.split_once(' ') {
+                    Some((uri, label)) => (uri, Some(label.trim().to_string())),
+                    None => (line.trim(), None),
+                };
+                if !uri.starts_with("file://") {
+                    return None;
+                }
+                let path = Url::parse(uri).ok()?.to_file_path().ok()?;
+                Some(GtkBookmark {
+                    path: path.display().to_string(),
+                    label: label.filter(|label| !label.is_empty()),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// 默认的快捷访问项：系统常用文件夹，以文件夹名作为名称
+fn default_items() -> Vec<QuickAccessItem> {
+    let folders = [
+        (dirs::home_dir(), "home"),
+        (dirs::desktop_dir(), "desktop"),
+        (dirs::document_dir(), "documents"),
+        (dirs::download_dir(), "downloads"),
+        (dirs::picture_dir(), "pictures"),
+        (dirs::audio_dir(), "music"),
+        (dirs::video_dir(), "videos"),
+    ];
+
+    let mut items: Vec<QuickAccessItem> = vec![];
+    for (dir, icon) in folders {
+        let Some(dir) = dir else {
+            continue;
+        };
+        let path = dir.to_string_lossy().to_string();
+        // 未配置 XDG 目录时多个目录可能都指向主文件夹
+        if items.iter().any(|item| item.path == path) {
+            continue;
+        }
         items.push(QuickAccessItem {
-            name: "视频".to_string(),
-            path: videos.to_string_lossy().to_string(),
-            icon: "videos".to_string(),
+            name: paths::file_name(&path).unwrap_or_else(|| path.clone()),
+            path,
+            icon: icon.to_string(),
         });
     }
-
     items
 }
+
+#[cfg(test)]
+mod tests {
+    use super::merge_bookmark_lines;
+
+    #[test]
+    fn keeps_remote_bookmarks_in_place() {
+        let existing =
+            "file:///home/a\nsftp://host/srv 服务器\nfile:///home/b\n\nsmb://nas/share\n";
+        let local = vec![
+            "file:///home/b".to_string(),
+            "file:///home/c 工作".to_string(),
+        ];
+        assert_eq!(
+            merge_bookmark_lines(existing, local),
+            "file:///home/b\nsftp://host/srv 服务器\nfile:///home/c 工作\nsmb://nas/share\n"
+        );
+    }
+
+    #[test]
+    fn appends_or_drops_local_bookmarks() {
+        let existing = "sftp://host/srv\nfile:///home/a\nfile:///home/b\n";
+        assert_eq!(
+            merge_bookmark_lines(existing, vec!["file:///home/b".to_string()]),
+            "sftp://host/srv\nfile:///home/b\n"
+        );
+        let local = vec![
+            "file:///home/a".to_string(),
+            "file:///home/b".to_string(),
+            "file:///home/c".to_string(),
+        ];
+        assert_eq!(
+            merge_bookmark_lines(existing, local),
+            "sftp://host/srv\nfile:///home/a\nfile:///home/b\nfile:///home/c\n"
+        );
+        assert_eq!(merge_bookmark_lines("", vec![]), "");
+    }
+}
@@ -0,0 +1,294 @@
+//! 最近访问的位置
+//!
+//! 记录每个目录的访问次数和最近访问时间，按 frecency（频率 × 时间衰减）排序，
+//! 保存在 `~/.explorer/recent.json`。`exclude` 中的模式（`*` 匹配任意字符，
+//! 支持 `~`）匹配的目录不会被记录，可以直接编辑该文件添加。
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// 最多保留的记录数
+const MAX_ENTRIES: usize = 200;
+
+/// 一个访问过的目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentEntry {
+    pub path: String,
+    /// 访问次数
+    pub visits: u32,
+    /// 最近访问时间（Unix 秒）
+    pub last_visit: u64,
+}
+
+impl RecentEntry {
+    /// frecency 得分：访问次数乘以按最近访问时间衰减的权重
+    pub fn frecency(&self, now: u64) -> f64 {
+        const HOUR: u64 = 60 * 60;
+        const DAY: u64 = 24 * HOUR;
+        let age = now.saturating_sub(self.last_visit);
+        let weight = if age < 4 * HOUR {
+            4.
+        } else if age < DAY {
+            2.
+        } else if age < 7 * DAY {
+            1.
+        } else if age < 30 * DAY {
+            0.5
+        } else {
+            0.25
+        };
+        self.visits as f64 * weight
+    }
+}
+
+/// 最近访问的位置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentLocations {
+    entries: Vec<RecentEntry>,
+    /// 不记录的路径模式
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+impl RecentLocations {
+    fn file_path() -> PathBuf {
+        paths::data_file("recent.json")
+    }
+
+    /// 从磁盘加载（文件不存在或损坏时返回空记录）
+    pub fn load() -> Self {
+        fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存到磁盘
+    pub fn save(&self) -> anyhow::Result<()> {
+        let path = Self::file_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 记录一次访问，被排除的路径返回 `false`
+    pub fn record(&mut self, path: &str) -> bool {
+        self.record_at(path, now())
+    }
+
+    fn record_at(&mut self, path: &str, now: u64) -> bool {
+        if self.is_excluded(path) {
+            return false;
+        }
+        match self.entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) => {
+                entry.visits = entry.visits.saturating_add(1);
+                entry.last_visit = now;
+            }
+            None => self.entries.push(RecentEntry {
+                path: path.to_string(),
+                visits: 1,
+                last_visit: now,
+            }),
+        }
+        // 超出上限时移除得分最低的记录，刚访问的记录得分可能同样最低，但总是保留
+        if self.entries.len() > MAX_ENTRIES {
+            self.sort(now);
+            let visited = self.entries.iter().position(|entry| entry.path == path);
+            if let Some(index) = visited.filter(|index| *index >= MAX_ENTRIES) {
+                let entry = self.entries.remove(index);
+                self.entries.insert(MAX_ENTRIES - 1, entry);
+            }
+            self.entries.truncate(MAX_ENTRIES);
+        }
+        true
+    }
+
+    /// 按 frecency 从高到低排列的记录
+    pub fn top(&self, limit: usize) -> Vec<RecentEntry> {
+        self.top_at(limit, now())
+    }
+
+    fn top_at(&self, limit: usize, now: u64) -> Vec<RecentEntry> {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        entries.truncate(limit);
+        entries
+    }
+
+    /// 移除一条记录
+    pub fn remove(&mut self, path: &str) {
+        self.entries.retain(|entry| entry.path != path);
+    }
+
+    /// 清除所有记录（保留排除模式）
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 添加排除模式，并移除已有的匹配记录
+    pub fn exclude(&mut self, pattern: &str) {
+        if !self.exclude.iter().any(|existing| existing == pattern) {
+            self.exclude.push(pattern.to_string());
+        }
+        self.entries
+            .retain(|entry| !pattern_matches(pattern, &entry.path));
+    }
+
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| pattern_matches(pattern, path))
+    }
+
+    fn sort(&mut self, now: u64) {
+        self.entries
+            .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// 路径是否匹配模式（`*` 匹配任意字符序列，模式开头的 `~` 展开为主目录）
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let Some(pattern) = paths::expand_home(pattern) else {
+        return false;
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // 没有通配符：完全相等
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    const NOW: u64 = 1_700_000_000;
+
+    fn entry(visits: u32, age: u64) -> RecentEntry {
+        RecentEntry {
+            path: String::new(),
+            visits,
+            last_visit: NOW - age,
+        }
+    }
+
+    fn top_paths(recent: &RecentLocations, limit: usize, now: u64) -> Vec<String> {
+        recent
+            .top_at(limit, now)
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect()
+    }
+
+    #[test]
+    fn decays_with_age() {
+        let scores: Vec<f64> = [0, 4 * HOUR, DAY, 7 * DAY, 30 * DAY, 365 * DAY]
+            .into_iter()
+            .map(|age| entry(4, age).frecency(NOW))
+            .collect();
+        assert_eq!(scores, [16., 8., 4., 2., 1., 1.]);
+        // 记录时间晚于当前时间（时钟回拨）时按刚访问计算
+        assert_eq!(entry(1, 0).frecency(NOW - DAY), 4.);
+    }
+
+    #[test]
+    fn ranks_by_frecency() {
+        let mut recent = RecentLocations::default();
+        // 一周前访问过多次的目录
+        for _ in 0..10 {
+            recent.record_at("/often", NOW - 8 * DAY);
+        }
+        // 昨天访问过两次
+        recent.record_at("/yesterday", NOW - DAY - HOUR);
+        recent.record_at("/yesterday", NOW - DAY);
+        // 刚访问过一次
+        recent.record_at("/now", NOW);
+
+        let often = recent.entries.iter().find(|e| e.path == "/often").unwrap();
+        assert_eq!(often.visits, 10);
+        assert_eq!(often.last_visit, NOW - 8 * DAY);
+
+        assert_eq!(
+            top_paths(&recent, 10, NOW),
+            ["/often", "/now", "/yesterday"]
+        );
+        assert_eq!(top_paths(&recent, 1, NOW), ["/often"]);
+        // 一个月后只看访问次数
+        assert_eq!(
+            top_paths(&recent, 10, NOW + 60 * DAY),
+            ["/often", "/yesterday", "/now"]
+        );
+    }
+
+    #[test]
+    fn prunes_lowest_scores() {
+        let mut recent = RecentLocations::default();
+        for i in 0..MAX_ENTRIES {
+            let path = format!("/dir{}", i);
+            recent.record_at(&path, NOW - 60 * DAY);
+            if i > 0 {
+                recent.record_at(&path, NOW - 60 * DAY);
+            }
+        }
+        assert_eq!(recent.entries.len(), MAX_ENTRIES);
+
+        // 超出上限时移除得分最低的 /dir0，刚访问的记录即使得分更低也保留
+        recent.record_at("/new", NOW - 60 * DAY);
+        assert_eq!(recent.entries.len(), MAX_ENTRIES);
+        assert!(recent.entries.iter().any(|entry| entry.path == "/new"));
+        assert!(!recent.entries.iter().any(|entry| entry.path == "/dir0"));
+
+        recent.record_at("/newer", NOW);
+        assert_eq!(recent.entries.len(), MAX_ENTRIES);
+        assert!(recent.entries.iter().any(|entry| entry.path == "/newer"));
+        assert!(!recent.entries.iter().any(|entry| entry.path == "/new"));
+    }
+
+    #[test]
+    fn skips_excluded_paths() {
+        let mut recent = RecentLocations::default();
+        recent.record_at("/tmp/a", NOW);
+        recent.record_at("/data/cache/x", NOW);
+        recent.record_at("/data/keep", NOW);
+        recent.exclude("/tmp*");
+        recent.exclude("/data/*/x");
+        assert_eq!(top_paths(&recent, 10, NOW), ["/data/keep"]);
+        assert!(!recent.record_at("/tmp/b", NOW));
+
+        assert!(pattern_matches("/a", "/a"));
+        assert!(!pattern_matches("/a", "/a/b"));
+        assert!(pattern_matches("/a/*/c", "/a/b/x/c"));
+        assert!(!pattern_matches("/a/*b*c", "/a/cb"));
+    }
+}
@@ -0,0 +1,104 @@
+//! 存储根节点的监视
+//!
+//! 挂载表变化时重新获取侧边栏中的存储根节点和它们的容量（状态栏显示的剩余空间）。
+
+use std::{collections::HashMap, time::Duration};
+
+#[cfg(target_os = "linux")]
+use std::sync::Arc;
+
+use gpui::{Context, Window};
+
+use explorer_common::SpaceInfo;
+#[cfg(target_os = "linux")]
+use explorer_local_provider::MountWatcher;
+use explorer_storage::StorageError;
+
+use crate::{Explorer, paths};
+
+/// 无法监视挂载表时检查存储根节点的间隔
+const ROOTS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+impl Explorer {
+    /// 挂载表变化（插入 U 盘、挂载网络共享等）时重新获取存储根节点和它们的容量
+    ///
+    /// Linux 上等待 `mountinfo` 的变化通知，无法监视时以及其他平台上定时检查。
+    pub(crate) fn watch_roots(&self, window: &Window, cx: &mut Context<Self>) {
+        self.reload_roots(window, cx);
+        cx.spawn_in(window, async move |this, cx| {
+            #[cfg(target_os = "linux")]
+            let mut mounts = match MountWatcher::new() {
+                Ok(watcher) => Some(Arc::new(watcher)),
+                Err(e) => {
+                    tracing::warn!("无法监视挂载表，改为定时检查: {}", e);
+                    None
+                }
+            };
+            loop {
+                #[cfg(target_os = "linux")]
+                if let Some(watcher) = mounts.clone() {
+                    if let Err(e) = smol::unblock(move || watcher.wait()).await {
+                        tracing::warn!("监视挂载表失败，改为定时检查: {}", e);
+                        mounts = None;
+                    }
+                } else {
+                    cx.background_executor().timer(ROOTS_POLL_INTERVAL).await;
+                }
+                #[cfg(not(target_os = "linux"))]
+                cx.background_executor().timer(ROOTS_POLL_INTERVAL).await;
+
+                let updated = this.update_in(cx, |explorer, window, cx| {
+                    explorer.reload_roots(window, cx);
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// 在后台重新获取存储根节点和它们的容量，有变化时更新侧边栏和状态栏
+    pub(crate) fn reload_roots(&self, window: &Window, cx: &mut Context<Self>) {
+        let provider = self.provider.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    let roots = provider.get_roots().await?;
+                    let mut space = HashMap::new();
+                    for root in &roots {
+                        if let Ok(info) = provider.get_space(&root.path).await {
+                            space.insert(root.path.clone(), info);
+                        }
+                    }
+                    Ok::<_, StorageError>((roots, space))
+                })
+                .await;
+            let _ = this.update(cx, |explorer, cx| {
+                let Ok((roots, space)) = result else {
+                    return;
+                };
+                if explorer.roots != roots {
+                    tracing::info!("存储根节点已变化，共 {} 个", roots.len());
+                    explorer.roots = roots;
+                    cx.notify();
+                }
+                if explorer.root_space != space {
+                    explorer.root_space = space;
+                    cx.notify();
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// 路径所在卷的容量（按最长匹配的存储根节点）
+    pub(crate) fn space_for(&self, path: &str) -> Option<SpaceInfo> {
+        self.roots
+            .iter()
+            .filter(|root| paths::relative_path(path, &root.path).is_some())
+            .max_by_key(|root| root.path.len())
+            .and_then(|root| self.root_space.get(&root.path).copied())
+    }
+}
@@ -0,0 +1,319 @@
+//! 文件的复制、剪切与粘贴
+//!
+//! 把选中的文件写入剪贴板，从剪贴板粘贴到激活面板的目录（剪贴板格式见 [`crate::clipboard`]），
+//! 以及复制条目的路径。
+
+use std::collections::HashSet;
+
+use dirs::home_dir;
+use gpui::{App, ClipboardItem, Context, Window};
+
+use explorer_common::FileItem;
+
+use crate::{
+    CopyFiles, CopyPath, CopyRelativePath, CutFiles, Explorer, PasteFiles, PasteLinks,
+    clipboard::{self, ClipboardMode, FileClipboard},
+    file_ops::FileOperation,
+    paths, unique_name,
+};
+
+/// 生成把文件复制或移动到 `dir` 的操作
+///
+/// 移动到原目录的条目会被跳过，重名时生成 `name (2)` 形式的新名称。
+fn plan_transfer(
+    mode: ClipboardMode,
+    sources: Vec<String>,
+    dir: &str,
+    entries: &[FileItem],
+) -> Vec<FileOperation> {
+    let mut taken: HashSet<String> = entries.iter().map(|entry| entry.name.clone()).collect();
+    let mut operations = vec![];
+    for from in sources {
+        let Some(name) = paths::file_name(&from) else {
+            continue;
+        };
+        if mode == ClipboardMode::Cut && paths::parent_path(&from).as_deref() == Some(dir) {
+            continue;
+        }
+
+        let name = unique_name(&taken, &name);
+        let to = paths::join_path(dir, &name);
+        taken.insert(name);
+        operations.push(match mode {
+            ClipboardMode::Copy => FileOperation::Copy { from, to },
+            ClipboardMode::Cut => FileOperation::Move { from, to },
+        });
+    }
+    operations
+}
+
+/// 生成在 `dir` 中创建指向 `sources` 的符号链接的操作，重名时生成新名称
+fn plan_links(sources: Vec<String>, dir: &str, entries: &[FileItem]) -> Vec<FileOperation> {
+    let mut taken: HashSet<String> = entries.iter().map(|entry| entry.name.clone()).collect();
+    let mut operations = vec![];
+    for target in sources {
+        let Some(name) = paths::file_name(&target) else {
+            continue;
+        };
+        let name = unique_name(&taken, &name);
+        let path = paths::join_path(dir, &name);
+        taken.insert(name);
+        operations.push(FileOperation::Link { target, path });
+    }
+    operations
+}
+
+impl Explorer {
+    pub(crate) fn copy_files(&mut self, _: &CopyFiles, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_file_clipboard(ClipboardMode::Copy, cx);
+    }
+
+    pub(crate) fn cut_files(&mut self, _: &CutFiles, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_file_clipboard(ClipboardMode::Cut, cx);
+    }
+
+    /// 把选中的文件写入剪贴板（系统剪贴板不可用时写入纯文本路径）
+    fn set_file_clipboard(&mut self, mode: ClipboardMode, cx: &mut Context<Self>) {
+        let paths = self.selected_paths();
+        if paths.is_empty() {
+            return;
+        }
+        let file_clipboard = FileClipboard::new(mode, paths);
+        self.file_clipboard = Some(file_clipboard.clone());
+        cx.notify();
+
+        cx.spawn(async move |_, cx| {
+            let text = file_clipboard.to_plain_text();
+            let written = cx
+                .background_executor()
+                .spawn(async move { clipboard::write_system(&file_clipboard) })
+                .await;
+            if !written {
+                let _ = cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string(text)));
+            }
+        })
+        .detach();
+    }
+
+    /// 把剪贴板中的文件复制或移动到激活面板的目录
+    pub(crate) fn paste_files(
+        &mut self,
+        _: &PasteFiles,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.paste(false, window, cx);
+    }
+
+    /// 在激活面板的目录中创建指向剪贴板中文件的符号链接
+    pub(crate) fn paste_links(
+        &mut self,
+        _: &PasteLinks,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.paste(true, window, cx);
+    }
+
+    fn paste(&mut self, as_links: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((_, dir, _)) = self.active_leaf() else {
+            return;
+        };
+        let internal = self.file_clipboard.clone();
+        let text = cx.read_from_clipboard().and_then(|item| item.text());
+
+        cx.spawn_in(window, async move |this, cx| {
+            let system = cx
+                .background_executor()
+                .spawn(async move { clipboard::read_system() })
+                .await;
+            let Some(mut file_clipboard) =
+                system.or_else(|| text.as_deref().and_then(FileClipboard::parse))
+            else {
+                tracing::info!("剪贴板中没有文件");
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.operation_error = Some("剪贴板中没有文件".to_string());
+                    cx.notify();
+                });
+                return;
+            };
+            // 纯文本无法携带剪切语义：内容与应用内最近一次复制/剪切相同时沿用其方式
+            if let Some(internal) = internal
+                && internal.paths == file_clipboard.paths
+            {
+                file_clipboard.mode = internal.mode;
+            }
+
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    if as_links {
+                        explorer.link_files(file_clipboard.paths, dir, window, cx);
+                        return;
+                    }
+                    // 剪切的文件只能粘贴一次
+                    if file_clipboard.mode == ClipboardMode::Cut {
+                        explorer.file_clipboard = None;
+                        cx.notify();
+                    }
+                    explorer.transfer_files(
+                        file_clipboard.mode,
+                        file_clipboard.paths,
+                        dir,
+                        "粘贴",
+                        window,
+                        cx,
+                    );
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// 把文件复制或移动到目录中，重名时自动生成新名称
+    ///
+    /// 先在后台读取目标目录的条目，再把操作加入文件操作队列。
+    pub(crate) fn transfer_files(
+        &mut self,
+        mode: ClipboardMode,
+        sources: Vec<String>,
+        dir: String,
+        action: &'static str,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        let provider = self.provider.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let target = dir.clone();
+            let entries = cx
+                .background_executor()
+                .spawn(async move { provider.list_entries(&target).await })
+                .await;
+            let entries = match entries {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::error!("无法读取目标目录: {}: {}", dir, e);
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.operation_error = Some(format!("无法读取目标目录 {}: {}", dir, e));
+                        cx.notify();
+                    });
+                    return;
+                }
+            };
+
+            let operations = plan_transfer(mode, sources, &dir, &entries);
+            if operations.is_empty() {
+                return;
+            }
+            let verb = match mode {
+                ClipboardMode::Copy => "复制",
+                ClipboardMode::Cut => "移动",
+            };
+            let label = format!("{}（{} {} 项）", action, verb, operations.len());
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.run_file_operations(label, operations, window, cx);
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// 在目录中创建指向 `sources` 的符号链接，重名时自动生成新名称
+    fn link_files(
+        &mut self,
+        sources: Vec<String>,
+        dir: String,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        let provider = self.provider.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let target = dir.clone();
+            let entries = cx
+                .background_executor()
+                .spawn(async move { provider.list_entries(&target).await })
+                .await;
+            let entries = match entries {
+                Ok(entries) => entries,
+                Err(e) => {
+                    tracing::error!("无法读取目标目录: {}: {}", dir, e);
+                    let _ = this.update(cx, |explorer, cx| {
+                        explorer.operation_error = Some(format!("无法读取目标目录 {}: {}", dir, e));
+                        cx.notify();
+                    });
+                    return;
+                }
+            };
+
+            let operations = plan_links(sources, &dir, &entries);
+            if operations.is_empty() {
+                return;
+            }
+            let label = format!("粘贴为链接（{} 项）", operations.len());
+            let _ = cx.update(|window, cx| {
+                let _ = this.update(cx, |explorer, cx| {
+                    explorer.run_file_operations(label, operations, window, cx);
+                });
+            });
+        })
+        .detach();
+    }
+
+    /// 剪贴板中是否有可粘贴的文件
+    pub(crate) fn can_paste(&self, cx: &App) -> bool {
+        self.file_clipboard.is_some()
+            || cx
+                .read_from_clipboard()
+                .and_then(|item| item.text())
+                .is_some_and(|text| FileClipboard::parse(&text).is_some())
+    }
+
+    /// 选中的路径，没有选中项时为激活面板的目录
+    pub(crate) fn selected_or_current_paths(&self) -> Vec<String> {
+        let paths = self.selected_paths();
+        if !paths.is_empty() {
+            return paths;
+        }
+        self.active_leaf()
+            .map(|(_, dir, _)| vec![dir])
+            .unwrap_or_default()
+    }
+
+    /// 复制选中条目（没有选中项时为当前目录）的完整路径
+    pub(crate) fn copy_path(&mut self, _: &CopyPath, _: &mut Window, cx: &mut Context<Self>) {
+        let paths = self.selected_or_current_paths();
+        if !paths.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(paths.join("\n")));
+        }
+    }
+
+    /// 复制选中条目（没有选中项时为当前目录）的相对路径
+    ///
+    /// 相对于侧边栏中选中的位置；不在其下时相对于用户主目录，否则使用完整路径。
+    pub(crate) fn copy_relative_path(
+        &mut self,
+        _: &CopyRelativePath,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let paths = self.selected_or_current_paths();
+        if paths.is_empty() {
+            return;
+        }
+        let home = home_dir().map(|home| home.display().to_string());
+        let bases: Vec<&str> = [self.selected_sidebar_path.as_deref(), home.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        let relative: Vec<String> = paths
+            .into_iter()
+            .map(|path| {
+                bases
+                    .iter()
+                    .find_map(|base| paths::relative_path(&path, base))
+                    .unwrap_or(path)
+            })
+            .collect();
+        cx.write_to_clipboard(ClipboardItem::new_string(relative.join("\n")));
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use gpui::BackgroundExecutor;
+use serde::{Deserialize, Serialize};
+use smol::channel::{self, Sender};
+
+use crate::{file_ops::CompletedOperation, paths};
+
+/// 撤销栈最多保留的记录数
+const MAX_ENTRIES: usize = 100;
+
+/// 撤销日志记录：一次用户操作（可能包含多个文件操作）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    /// 操作描述（用于日志和提示）
+    pub label: String,
+    /// 按执行顺序排列的已完成操作
+    pub operations: Vec<CompletedOperation>,
+    /// 记录时间（Unix 秒）
+    pub timestamp: u64,
+}
+
+impl UndoEntry {
+    pub fn new(label: impl Into<String>, operations: Vec<CompletedOperation>) -> Self {
+        Self {
+            label: label.into(),
+            operations,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// 撤销日志
+///
+/// 保存在 `~/.explorer/undo.json`，重启后仍可撤销之前的操作
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoJournal {
+    undo_stack: Vec<UndoEntry>,
+    redo_stack: Vec<UndoEntry>,
+}
+
+impl UndoJournal {
+    fn file_path() -> PathBuf {
+        paths::data_file("undo.json")
+    }
+
+    /// 从磁盘加载撤销日志（文件不存在或损坏时返回空日志）
+    pub fn load() -> Self {
+        fs::read_to_string(Self::file_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 保存撤销日志到磁盘
+    pub fn save(&self) -> anyhow::Result<()> {
+        self.save_to(&Self::file_path())
+    }
+
+    /// 先写入临时文件再替换，写入中途退出时不会留下不完整的日志
+    fn save_to(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// 合并从磁盘加载的日志
+    ///
+    /// 加载完成前记录的操作排在加载的记录之后。这期间有过新的记录时，
+    /// 加载的重做栈已经失效，只保留当前的重做栈。
+    pub fn merge_loaded(&mut self, loaded: UndoJournal) {
+        if self.undo_stack.is_empty() && self.redo_stack.is_empty() {
+            *self = loaded;
+            return;
+        }
+        let mut undo_stack = loaded.undo_stack;
+        undo_stack.append(&mut self.undo_stack);
+        let excess = undo_stack.len().saturating_sub(MAX_ENTRIES);
+        undo_stack.drain(..excess);
+        self.undo_stack = undo_stack;
+    }
+
+    /// 记录新完成的操作（会清空重做栈）
+    pub fn record(&mut self, entry: UndoEntry) {
+        if entry.operations.is_empty() {
+            return;
+        }
+        self.undo_stack.push(entry);
+        if self.undo_stack.len() > MAX_ENTRIES {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// 取出最近一次可撤销的记录
+    pub fn pop_undo(&mut self) -> Option<UndoEntry> {
+        self.undo_stack.pop()
+    }
+
+    /// 取出最近一次可重做的记录
+    pub fn pop_redo(&mut self) -> Option<UndoEntry> {
+        self.redo_stack.pop()
+    }
+
+    /// 放回撤销栈（撤销失败的部分或重做完成的部分）
+    pub fn push_undo(&mut self, entry: UndoEntry) {
+        self.undo_stack.push(entry);
+    }
+
+    /// 放入重做栈（撤销完成的部分或重做失败的部分）
+    pub fn push_redo(&mut self, entry: UndoEntry) {
+        self.redo_stack.push(entry);
+    }
+}
+
+/// 在后台依次保存撤销日志
+///
+/// 所有保存都经过同一个任务，较早的快照不会覆盖较新的快照；
+/// 写入期间积压的快照只保存最新的一份。
+pub struct JournalWriter {
+    sender: Sender<UndoJournal>,
+}
+
+impl JournalWriter {
+    pub fn new(executor: &BackgroundExecutor) -> Self {
+        let (sender, receiver) = channel::unbounded::<UndoJournal>();
+        executor
+            .spawn(async move {
+                while let Ok(mut journal) = receiver.recv().await {
+                    while let Ok(newer) = receiver.try_recv() {
+                        journal = newer;
+                    }
+                    if let Err(e) = journal.save() {
+                        tracing::error!("保存撤销日志失败: {}", e);
+                    }
+                }
+            })
+            .detach();
+        Self { sender }
+    }
+
+    pub fn save(&self, journal: UndoJournal) {
+        let _ = self.sender.try_send(journal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use explorer_storage::conformance::TempDir;
+
+    use super::*;
+
+    fn entry(label: &str) -> UndoEntry {
+        UndoEntry::new(
+            label,
+            vec![CompletedOperation::Rename {
+                from: format!("/{}", label),
+                to: format!("/{}.new", label),
+                stamp: None,
+            }],
+        )
+    }
+
+    fn labels(entries: &[UndoEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.label.as_str()).collect()
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let dir = TempDir::new("undo-test");
+        let path = dir.join("undo.json");
+        let mut journal = UndoJournal::default();
+        journal.record(entry("a"));
+        journal.record(entry("b"));
+        let undone = journal.pop_undo().unwrap();
+        journal.push_redo(undone);
+
+        journal.save_to(&path).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        let loaded: UndoJournal =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(labels(&loaded.undo_stack), ["a"]);
+        assert_eq!(labels(&loaded.redo_stack), ["b"]);
+        assert!(matches!(
+            &loaded.undo_stack[0].operations[..],
+            [CompletedOperation::Rename { from, to, stamp: None }] if from == "/a" && to == "/a.new"
+        ));
+    }
+
+    #[test]
+    fn reads_entries_without_stamps() {
+        let json = r#"{"undo_stack":[{"label":"a","timestamp":1,"operations":[{"Copy":{"from":"/a","to":"/b"}}]}],"redo_stack":[]}"#;
+        let journal: UndoJournal = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            &journal.undo_stack[0].operations[..],
+            [CompletedOperation::Copy { stamp: None, .. }]
+        ));
+    }
+
+    #[test]
+    fn record_clears_redo_and_caps_entries() {
+        let mut journal = UndoJournal::default();
+        journal.record(entry("a"));
+        let undone = journal.pop_undo().unwrap();
+        journal.push_redo(undone);
+        journal.record(UndoEntry::new("empty", vec![]));
+        assert_eq!(labels(&journal.redo_stack), ["a"]);
+        journal.record(entry("b"));
+        assert!(journal.redo_stack.is_empty());
+
+        for i in 0..MAX_ENTRIES {
+            journal.record(entry(&i.to_string()));
+        }
+        assert_eq!(journal.undo_stack.len(), MAX_ENTRIES);
+        assert_eq!(journal.undo_stack[0].label, "0");
+    }
+
+    #[test]
+    fn merges_loaded_entries_before_new_ones() {
+        let mut loaded = UndoJournal::default();
+        loaded.record(entry("old"));
+        loaded.push_redo(entry("undone"));
+
+        let mut journal = UndoJournal::default();
+        journal.merge_loaded(loaded.clone());
+        assert_eq!(labels(&journal.undo_stack), ["old"]);
+        assert_eq!(labels(&journal.redo_stack), ["undone"]);
+
+        let mut journal = UndoJournal::default();
+        journal.record(entry("new"));
+        journal.merge_loaded(loaded);
+        assert_eq!(labels(&journal.undo_stack), ["old", "new"]);
+        assert!(journal.redo_stack.is_empty());
+    }
+}
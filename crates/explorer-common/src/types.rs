@@ -1,4 +1,4 @@
-use std::{collections::HashMap, time::SystemTime};
+use std::{collections::HashMap, path::Path, time::SystemTime};
 
 use serde::{Deserialize, Serialize};
 
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 pub type PanelId = u64;
 
 /// 存储根节点信息（用于侧边栏显示）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RootItem {
     /// 唯一标识符
     pub id: String,
@@ -18,6 +18,49 @@ pub struct RootItem {
     pub provider_type: ProviderType,
     /// 图标（可选）
     pub icon: Option<String>,
+    /// 挂载信息（本地文件系统的挂载点）
+    #[serde(default)]
+    pub mount: Option<MountInfo>,
+}
+
+/// 卷的容量（字节）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpaceInfo {
+    /// 总容量
+    pub total: u64,
+    /// 剩余空间（包括只有 root 可用的保留空间）
+    pub free: u64,
+    /// 当前用户可用的空间
+    pub available: u64,
+}
+
+impl SpaceInfo {
+    /// 已用空间
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// 使用率（0.0 ~ 1.0），按用户可见的容量（已用 + 可用）计算
+    pub fn usage(&self) -> f32 {
+        let visible = self.used() + self.available;
+        if visible == 0 {
+            return 0.;
+        }
+        self.used() as f32 / visible as f32
+    }
+}
+
+/// 文件系统挂载信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MountInfo {
+    /// 设备（如 `/dev/sda1`，虚拟文件系统为其名称）
+    pub device: String,
+    /// 文件系统类型（如 `ext4`、`tmpfs`）
+    pub fs_type: String,
+    /// 卷标
+    pub label: Option<String>,
+    /// 是否只读挂载
+    pub read_only: bool,
 }
 
 /// 存储提供者类型
@@ -48,6 +91,22 @@ pub struct FileItem {
     pub metadata: EntryMetadata,
 }
 
+impl FileItem {
+    /// 是否可以像目录一样打开（目录或指向目录的符号链接）
+    pub fn is_dir_like(&self) -> bool {
+        match self.item_type {
+            ItemType::Directory => true,
+            ItemType::Symlink => self.metadata.link_target_type == Some(ItemType::Directory),
+            ItemType::File => false,
+        }
+    }
+
+    /// 是否为目标不存在的符号链接
+    pub fn is_broken_link(&self) -> bool {
+        self.item_type == ItemType::Symlink && self.metadata.broken_link
+    }
+}
+
 /// 条目类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ItemType {
@@ -57,7 +116,7 @@ pub enum ItemType {
 }
 
 /// 条目元数据
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EntryMetadata {
     /// Unix 权限（可选）
     pub permissions: Option<u32>,
@@ -77,22 +136,126 @@ pub struct EntryMetadata {
         with = "opt_systemtime_serde"
     )]
     pub accessed: Option<SystemTime>,
+    /// 所在设备号（Unix 的 `st_dev`），用于判断条目是否在同一文件系统上
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<u64>,
+    /// 所有者的用户 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    /// 所有者的用户名，无法解析时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// 所属组的组 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gid: Option<u32>,
+    /// 所属组的组名，无法解析时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// inode 编号
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inode: Option<u64>,
+    /// 硬链接数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hard_links: Option<u64>,
+    /// 实际占用的 512 字节块数（Unix 的 `st_blocks`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<u64>,
+    /// 符号链接中保存的目标路径（可能是相对路径），不是链接时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<String>,
+    /// 符号链接最终指向的条目类型，目标不存在或提供者无法获取时为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_target_type: Option<ItemType>,
+    /// 符号链接的目标不存在（悬空链接）
+    #[serde(default)]
+    pub broken_link: bool,
     /// 自定义字段，用于扩展不同存储提供者的特定信息
     pub custom_fields: HashMap<String, String>,
 }
 
-impl Default for EntryMetadata {
-    fn default() -> Self {
-        Self {
-            permissions: None,
-            mime_type: None,
-            created: None,
-            accessed: None,
-            custom_fields: HashMap::new(),
+/// 压缩包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarBz2,
+    TarXz,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// 可以创建的格式
+    pub const WRITABLE: [ArchiveFormat; 3] = [
+        ArchiveFormat::Zip,
+        ArchiveFormat::TarGz,
+        ArchiveFormat::TarZst,
+    ];
+
+    /// 所有扩展名及其格式（较长的扩展名在前）
+    const EXTENSIONS: &[(&str, ArchiveFormat)] = &[
+        (".tar.gz", ArchiveFormat::TarGz),
+        (".tar.bz2", ArchiveFormat::TarBz2),
+        (".tar.xz", ArchiveFormat::TarXz),
+        (".tar.zst", ArchiveFormat::TarZst),
+        (".tgz", ArchiveFormat::TarGz),
+        (".tbz2", ArchiveFormat::TarBz2),
+        (".tbz", ArchiveFormat::TarBz2),
+        (".txz", ArchiveFormat::TarXz),
+        (".tzst", ArchiveFormat::TarZst),
+        (".tar", ArchiveFormat::Tar),
+        (".zip", ArchiveFormat::Zip),
+    ];
+
+    /// 根据文件名（不区分大小写）识别格式
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let name = path.as_ref().file_name()?.to_string_lossy().to_lowercase();
+        Self::EXTENSIONS
+            .iter()
+            .find(|(extension, _)| name.len() > extension.len() && name.ends_with(extension))
+            .map(|(_, format)| *format)
+    }
+
+    /// 去掉压缩包扩展名后的文件名，不是压缩包时返回 `None`
+    pub fn strip_extension(name: &str) -> Option<&str> {
+        let lower = name.to_lowercase();
+        Self::EXTENSIONS
+            .iter()
+            .find(|(extension, _)| lower.len() > extension.len() && lower.ends_with(extension))
+            .map(|(extension, _)| &name[..name.len() - extension.len()])
+    }
+
+    /// 格式的标准扩展名（含开头的 `.`）
+    pub fn extension(self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => ".zip",
+            ArchiveFormat::Tar => ".tar",
+            ArchiveFormat::TarGz => ".tar.gz",
+            ArchiveFormat::TarBz2 => ".tar.bz2",
+            ArchiveFormat::TarXz => ".tar.xz",
+            ArchiveFormat::TarZst => ".tar.zst",
         }
     }
 }
 
+/// 压缩级别
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompressionLevel {
+    /// 速度优先
+    Fast,
+    #[default]
+    Normal,
+    /// 体积优先
+    Best,
+}
+
+/// 创建压缩包的选项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressOptions {
+    pub format: ArchiveFormat,
+    pub level: CompressionLevel,
+}
+
 /// 快捷访问项（用于侧边栏显示）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuickAccessItem {
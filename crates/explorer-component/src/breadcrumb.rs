@@ -1,15 +1,32 @@
-use std::rc::Rc;
+use std::{ops::Range, rc::Rc};
 
 use gpui::{prelude::*, *};
 
 use crate::{IconName, Theme};
 
+/// 单个面包屑项的最大宽度
+const ITEM_MAX_WIDTH: Pixels = px(200.);
+/// 面包屑项的水平内边距（两侧 px_2）
+const ITEM_PADDING: Pixels = px(16.);
+/// 分隔符（下拉按钮）占用的宽度
+const SEPARATOR_WIDTH: Pixels = px(24.);
+/// 省略号项的宽度
+const ELLIPSIS_WIDTH: Pixels = px(32.);
+
+type MenuCallback = Rc<dyn Fn(Vec<BreadcrumbItem>, Point<Pixels>, &mut Window, &mut App)>;
+
 /// 面包屑状态
-pub struct BreadcrumbState {}
+///
+/// 记录上一帧面包屑链可用的宽度，用于决定折叠哪些中间项
+pub struct BreadcrumbState {
+    available_width: Option<Pixels>,
+}
 
 impl BreadcrumbState {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            available_width: None,
+        }
     }
 }
 
@@ -47,6 +64,10 @@ pub struct Breadcrumb {
     prefix: Option<AnyElement>,
     suffix: Option<AnyElement>,
     state: Option<Entity<BreadcrumbState>>,
+    on_edit: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    editor: Option<AnyElement>,
+    on_expand: Option<Rc<dyn Fn(String, Point<Pixels>, &mut Window, &mut App)>>,
+    on_overflow: Option<MenuCallback>,
 }
 
 impl Breadcrumb {
@@ -60,6 +81,10 @@ impl Breadcrumb {
             prefix: None,
             suffix: None,
             state: None,
+            on_edit: None,
+            editor: None,
+            on_expand: None,
+            on_overflow: None,
         }
     }
 
@@ -96,6 +121,41 @@ impl Breadcrumb {
         self
     }
 
+    /// 设置点击空白处时的回调（进入编辑状态）
+    pub fn on_edit<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&mut Window, &mut App) + 'static,
+    {
+        self.on_edit = Some(Rc::new(f));
+        self
+    }
+
+    /// 设置编辑器元素，设置后代替面包屑链显示（地址栏编辑状态）
+    pub fn editor(mut self, editor: Option<impl IntoElement>) -> Self {
+        self.editor = editor.map(IntoElement::into_any_element);
+        self
+    }
+
+    /// 设置分隔符下拉按钮的回调，参数为该项的值和菜单位置
+    ///
+    /// 设置后每一项（包括最后一项）之后的分隔符都可以点击，用于列出该层级的子目录
+    pub fn on_expand<F>(mut self, f: F) -> Self
+    where
+        F: Fn(String, Point<Pixels>, &mut Window, &mut App) + 'static,
+    {
+        self.on_expand = Some(Rc::new(f));
+        self
+    }
+
+    /// 设置省略号菜单的回调，参数为被折叠的项和菜单位置
+    pub fn on_overflow<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Vec<BreadcrumbItem>, Point<Pixels>, &mut Window, &mut App) + 'static,
+    {
+        self.on_overflow = Some(Rc::new(f));
+        self
+    }
+
     /// 设置状态实体
     pub fn state(mut self, state: Entity<BreadcrumbState>) -> Self {
         self.state = Some(state);
@@ -109,8 +169,51 @@ impl Default for Breadcrumb {
     }
 }
 
+impl Breadcrumb {
+    /// 估算每一项的显示宽度（文本宽度加内边距，不超过最大宽度）
+    fn item_widths(&self, window: &mut Window) -> Vec<Pixels> {
+        let font_size = rems(0.875).to_pixels(window.rem_size());
+        let text_style = window.text_style();
+        self.items
+            .iter()
+            .map(|item| {
+                let label = SharedString::from(item.label.clone());
+                let run = text_style.to_run(label.len());
+                let line = window
+                    .text_system()
+                    .shape_line(label, font_size, &[run], None);
+                (line.width + ITEM_PADDING).min(ITEM_MAX_WIDTH)
+            })
+            .collect()
+    }
+
+    /// 需要折叠到省略号中的项（保留第一项和尽量多的末尾项），放得下时返回 `None`
+    fn collapsed_range(&self, window: &mut Window, cx: &App) -> Option<Range<usize>> {
+        let available = self.state.as_ref()?.read(cx).available_width?;
+        let widths = self.item_widths(window);
+        let total = widths.iter().copied().sum::<Pixels>() + SEPARATOR_WIDTH * widths.len() as f32;
+        if total <= available || widths.len() <= 2 {
+            return None;
+        }
+
+        // 第一项 + 省略号 + 末尾项
+        let mut width = widths[0] + ELLIPSIS_WIDTH + SEPARATOR_WIDTH * 2.;
+        let mut start = widths.len();
+        while start > 2 {
+            let next = widths[start - 1] + SEPARATOR_WIDTH;
+            if width + next > available && start < widths.len() {
+                break;
+            }
+            width += next;
+            start -= 1;
+        }
+        Some(1..start)
+    }
+}
+
 impl RenderOnce for Breadcrumb {
-    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let collapsed = self.collapsed_range(window, cx);
         let theme = cx.global::<Theme>();
 
         // 根据激活状态选择颜色
@@ -124,11 +227,49 @@ impl RenderOnce for Breadcrumb {
         let mut breadcrumb_elements = vec![];
 
         for (i, item) in self.items.iter().enumerate() {
+            if let Some(range) = &collapsed
+                && range.contains(&i)
+            {
+                if i != range.start {
+                    continue;
+                }
+                // 省略号：点击后以菜单列出被折叠的项
+                let hidden = self.items[range.clone()].to_vec();
+                breadcrumb_elements.push(
+                    div()
+                        .px_2()
+                        .py_1()
+                        .rounded(theme.radius.sm)
+                        .cursor_pointer()
+                        .text_sm()
+                        .text_color(theme.colors.breadcrumb_foreground)
+                        .hover(|style| style.bg(theme.colors.breadcrumb_item_background_hover))
+                        .child("…")
+                        .when_some(self.on_overflow.clone(), move |this, callback| {
+                            this.on_mouse_down(MouseButton::Left, move |event, window, cx| {
+                                cx.stop_propagation();
+                                callback(hidden.clone(), event.position, window, cx);
+                            })
+                        })
+                        .into_any_element(),
+                );
+                breadcrumb_elements.push(
+                    div()
+                        .mx(theme.spacing.xs)
+                        .text_sm()
+                        .child(IconName::ChevronRight)
+                        .text_color(theme.colors.muted_foreground)
+                        .into_any_element(),
+                );
+                continue;
+            }
+
             let is_last = i == self.items.len() - 1;
             let item_clone = item.clone();
 
             // 渲染面包屑项
             let item_element = div()
+                .flex_shrink_0()
                 .px_2()
                 .py_1()
                 .rounded(theme.radius.sm)
@@ -142,7 +283,7 @@ impl RenderOnce for Breadcrumb {
                     this.text_color(theme.colors.breadcrumb_foreground)
                 })
                 // 单个元素文本溢出省略
-                .max_w(px(200.))
+                .max_w(ITEM_MAX_WIDTH)
                 .overflow_hidden()
                 .child(
                     div()
@@ -153,25 +294,66 @@ impl RenderOnce for Breadcrumb {
                 .when_some(self.on_navigate.clone(), move |this, callback| {
                     let value = item_clone.value.clone();
                     this.on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                        cx.stop_propagation();
                         callback(value.clone(), window, cx);
                     })
                 });
 
             breadcrumb_elements.push(item_element.into_any_element());
 
-            // 添加分隔符（除了最后一个）
-            if !is_last {
-                breadcrumb_elements.push(
-                    div()
-                        .mx(theme.spacing.xs)
-                        .text_sm() // 使用小字号
-                        .child(IconName::ChevronRight)
-                        .text_color(theme.colors.muted_foreground)
-                        .into_any_element(),
-                );
+            // 分隔符：设置了下拉回调时每一项之后都有，可点击列出该层级的子目录；
+            // 否则只在项之间显示
+            match self.on_expand.clone() {
+                Some(callback) => {
+                    let value = item.value.clone();
+                    breadcrumb_elements.push(
+                        div()
+                            .flex_shrink_0()
+                            .mx(theme.spacing.xs)
+                            .rounded(theme.radius.sm)
+                            .cursor_pointer()
+                            .text_sm()
+                            .text_color(theme.colors.muted_foreground)
+                            .hover(|style| style.bg(theme.colors.breadcrumb_item_background_hover))
+                            .child(IconName::ChevronRight)
+                            .on_mouse_down(MouseButton::Left, move |event, window, cx| {
+                                cx.stop_propagation();
+                                callback(value.clone(), event.position, window, cx);
+                            })
+                            .into_any_element(),
+                    );
+                }
+                None if !is_last => {
+                    breadcrumb_elements.push(
+                        div()
+                            .mx(theme.spacing.xs)
+                            .text_sm() // 使用小字号
+                            .child(IconName::ChevronRight)
+                            .text_color(theme.colors.muted_foreground)
+                            .into_any_element(),
+                    );
+                }
+                None => {}
             }
         }
 
+        // 记录面包屑链可用的宽度，变化时重新渲染以更新折叠
+        let measure = self.state.map(|state| {
+            canvas(
+                move |bounds, window, cx| {
+                    state.update(cx, |state, _| {
+                        if state.available_width != Some(bounds.size.width) {
+                            state.available_width = Some(bounds.size.width);
+                            window.refresh();
+                        }
+                    })
+                },
+                |_, _, _, _| {},
+            )
+            .absolute()
+            .size_full()
+        });
+
         // 主容器
         div()
             .flex()
@@ -188,13 +370,26 @@ impl RenderOnce for Breadcrumb {
             .when_some(self.prefix, |this: gpui::Div, prefix| {
                 this.child(div().flex_shrink_0().mr(theme.spacing.sm).child(prefix))
             })
-            // 中间：面包屑链
-            .child(
-                div()
+            // 中间：编辑器或面包屑链（点击链后的空白处进入编辑状态）
+            .child(match self.editor {
+                Some(editor) => div().flex_1().child(editor),
+                None => div()
+                    .relative()
                     .flex_1()
+                    .min_w_0()
+                    .h_full()
+                    .flex()
+                    .items_center()
                     .overflow_hidden()
+                    .when_some(self.on_edit, |this, on_edit| {
+                        this.cursor_text()
+                            .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                                on_edit(window, cx);
+                            })
+                    })
+                    .children(measure)
                     .child(div().flex().items_center().children(breadcrumb_elements)),
-            )
+            })
             // 后缀（可选）
             .when_some(self.suffix, |this: gpui::Div, suffix| {
                 this.child(div().flex_shrink_0().ml(theme.spacing.sm).child(suffix))
@@ -0,0 +1,116 @@
+use std::rc::Rc;
+
+use gpui::{prelude::*, *};
+
+use crate::Theme;
+
+type ClickHandler = Rc<dyn Fn(&mut Window, &mut App) + 'static>;
+
+/// 按钮样式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ButtonVariant {
+    /// 主要操作（品牌色）
+    Primary,
+    /// 普通按钮
+    #[default]
+    Secondary,
+    /// 无背景按钮
+    Ghost,
+}
+
+/// 按钮组件
+#[derive(IntoElement)]
+pub struct Button {
+    id: ElementId,
+    label: SharedString,
+    variant: ButtonVariant,
+    selected: bool,
+    disabled: bool,
+    on_click: Option<ClickHandler>,
+}
+
+impl Button {
+    pub fn new(id: impl Into<ElementId>, label: impl Into<SharedString>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            variant: ButtonVariant::default(),
+            selected: false,
+            disabled: false,
+            on_click: None,
+        }
+    }
+
+    pub fn variant(mut self, variant: ButtonVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// 选中状态（用于开关、分段选择）
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn on_click<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&mut Window, &mut App) + 'static,
+    {
+        self.on_click = Some(Rc::new(handler));
+        self
+    }
+}
+
+impl RenderOnce for Button {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        let (bg, bg_hover, fg): (Hsla, Hsla, Hsla) = match (self.variant, self.selected) {
+            (ButtonVariant::Primary, _) | (_, true) => (
+                theme.colors.brand.into(),
+                theme.colors.brand_hover.into(),
+                theme.colors.brand_foreground.into(),
+            ),
+            (ButtonVariant::Secondary, false) => (
+                theme.colors.neutral_background.into(),
+                theme.colors.muted.into(),
+                theme.colors.foreground.into(),
+            ),
+            (ButtonVariant::Ghost, false) => (
+                transparent_black(),
+                theme.colors.muted.into(),
+                theme.colors.foreground.into(),
+            ),
+        };
+
+        div()
+            .id(self.id)
+            .flex()
+            .items_center()
+            .justify_center()
+            .h_7()
+            .px_3()
+            .rounded(theme.radius.sm)
+            .text_sm()
+            .bg(bg)
+            .text_color(fg)
+            .when(
+                self.variant == ButtonVariant::Secondary && !self.selected,
+                |this| this.border_1().border_color(theme.colors.border),
+            )
+            .child(self.label)
+            .when(self.disabled, |this| this.opacity(0.5))
+            .when(!self.disabled, |this| {
+                this.cursor_pointer()
+                    .hover(move |style| style.bg(bg_hover))
+                    .when_some(self.on_click, |this, handler| {
+                        this.on_click(move |_, window, cx| handler(window, cx))
+                    })
+            })
+    }
+}
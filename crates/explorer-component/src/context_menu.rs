@@ -0,0 +1,486 @@
+//! 右键菜单组件
+//!
+//! 支持嵌套子菜单、分隔线、禁用项、快捷键提示和键盘导航。
+//! 菜单覆盖整个父容器（父容器需要是 `relative` 定位），点击菜单外部或按 Esc 关闭，
+//! 关闭时发出 `DismissEvent`。
+
+use std::rc::Rc;
+
+use gpui::{prelude::*, *};
+
+use crate::{Icon, IconName, Theme};
+
+actions!(
+    context_menu,
+    [
+        SelectPrevious,
+        SelectNext,
+        OpenSubmenu,
+        CloseSubmenu,
+        Confirm,
+        Cancel
+    ]
+);
+
+const CONTEXT: &str = "ContextMenu";
+const MENU_WIDTH: Pixels = px(240.);
+const MENU_PADDING: Pixels = px(4.);
+const ITEM_HEIGHT: Pixels = px(28.);
+const SEPARATOR_HEIGHT: Pixels = px(9.);
+
+/// 菜单的默认键盘导航快捷键
+pub fn key_bindings() -> Vec<KeyBinding> {
+    let context = Some(CONTEXT);
+    vec![
+        KeyBinding::new("up", SelectPrevious, context),
+        KeyBinding::new("down", SelectNext, context),
+        KeyBinding::new("right", OpenSubmenu, context),
+        KeyBinding::new("left", CloseSubmenu, context),
+        KeyBinding::new("enter", Confirm, context),
+        KeyBinding::new("escape", Cancel, context),
+    ]
+}
+
+type MenuHandler = Rc<dyn Fn(&mut Window, &mut App)>;
+
+/// 菜单项执行的命令
+pub enum MenuCommand {
+    /// 派发动作（会显示该动作绑定的快捷键）
+    Action(Box<dyn Action>),
+    /// 执行回调
+    Handler(MenuHandler),
+}
+
+/// 菜单项
+pub enum ContextMenuItem {
+    Entry {
+        label: SharedString,
+        command: MenuCommand,
+        disabled: bool,
+    },
+    Submenu {
+        label: SharedString,
+        items: Rc<Vec<ContextMenuItem>>,
+        disabled: bool,
+    },
+    Separator,
+}
+
+impl ContextMenuItem {
+    /// 派发动作的菜单项，快捷键提示取自该动作的按键绑定
+    pub fn action(label: impl Into<SharedString>, action: impl Action) -> Self {
+        Self::Entry {
+            label: label.into(),
+            command: MenuCommand::Action(Box::new(action)),
+            disabled: false,
+        }
+    }
+
+    /// 执行回调的菜单项
+    pub fn entry(
+        label: impl Into<SharedString>,
+        handler: impl Fn(&mut Window, &mut App) + 'static,
+    ) -> Self {
+        Self::Entry {
+            label: label.into(),
+            command: MenuCommand::Handler(Rc::new(handler)),
+            disabled: false,
+        }
+    }
+
+    /// 子菜单（没有子项时自动禁用）
+    pub fn submenu(label: impl Into<SharedString>, items: Vec<ContextMenuItem>) -> Self {
+        Self::Submenu {
+            label: label.into(),
+            disabled: items.is_empty(),
+            items: Rc::new(items),
+        }
+    }
+
+    pub fn separator() -> Self {
+        Self::Separator
+    }
+
+    /// 设置是否禁用
+    pub fn disabled(mut self, value: bool) -> Self {
+        match &mut self {
+            Self::Entry { disabled, .. } | Self::Submenu { disabled, .. } => *disabled |= value,
+            Self::Separator => {}
+        }
+        self
+    }
+
+    fn is_selectable(&self) -> bool {
+        match self {
+            Self::Entry { disabled, .. } | Self::Submenu { disabled, .. } => !disabled,
+            Self::Separator => false,
+        }
+    }
+
+    fn height(&self) -> Pixels {
+        match self {
+            Self::Separator => SEPARATOR_HEIGHT,
+            _ => ITEM_HEIGHT,
+        }
+    }
+}
+
+/// 一级菜单（顶层菜单或展开的子菜单）
+struct MenuLevel {
+    items: Rc<Vec<ContextMenuItem>>,
+    selected: Option<usize>,
+}
+
+impl MenuLevel {
+    fn new(items: Rc<Vec<ContextMenuItem>>) -> Self {
+        Self {
+            items,
+            selected: None,
+        }
+    }
+
+    fn height(&self) -> Pixels {
+        self.items
+            .iter()
+            .map(ContextMenuItem::height)
+            .sum::<Pixels>()
+            + MENU_PADDING * 2.
+    }
+
+    /// 第 `index` 项相对菜单顶部的偏移
+    fn item_offset(&self, index: usize) -> Pixels {
+        self.items[..index]
+            .iter()
+            .map(ContextMenuItem::height)
+            .sum::<Pixels>()
+            + MENU_PADDING
+    }
+
+    /// 从 `start` 开始按方向查找下一个可选中的项（循环）
+    fn next_selectable(&self, start: Option<usize>, forward: bool) -> Option<usize> {
+        let len = self.items.len();
+        if len == 0 {
+            return None;
+        }
+        let start = match (start, forward) {
+            (Some(index), _) => index,
+            (None, true) => len - 1,
+            (None, false) => 0,
+        };
+        (1..=len)
+            .map(|step| {
+                if forward {
+                    (start + step) % len
+                } else {
+                    (start + len - step % len) % len
+                }
+            })
+            .find(|&index| self.items[index].is_selectable())
+    }
+}
+
+/// 右键菜单
+pub struct ContextMenu {
+    focus_handle: FocusHandle,
+    position: Point<Pixels>,
+    levels: Vec<MenuLevel>,
+}
+
+impl EventEmitter<DismissEvent> for ContextMenu {}
+
+impl ContextMenu {
+    /// 在窗口坐标 `position` 处创建菜单
+    pub fn new(
+        position: Point<Pixels>,
+        items: Vec<ContextMenuItem>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            position,
+            levels: vec![MenuLevel::new(Rc::new(items))],
+        }
+    }
+
+    /// 聚焦菜单（以便使用键盘导航）
+    pub fn focus(&self, window: &mut Window) {
+        self.focus_handle.focus(window);
+    }
+
+    fn dismiss(&mut self, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(level) = self.levels.last_mut() {
+            level.selected = level.next_selectable(level.selected, false);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(level) = self.levels.last_mut() {
+            level.selected = level.next_selectable(level.selected, true);
+            cx.notify();
+        }
+    }
+
+    fn open_submenu(&mut self, _: &OpenSubmenu, _: &mut Window, cx: &mut Context<Self>) {
+        let depth = self.levels.len() - 1;
+        if let Some(index) = self.levels[depth].selected {
+            self.expand(depth, index, true, cx);
+        }
+    }
+
+    fn close_submenu(&mut self, _: &CloseSubmenu, _: &mut Window, cx: &mut Context<Self>) {
+        if self.levels.len() > 1 {
+            self.levels.pop();
+            cx.notify();
+        }
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let depth = self.levels.len() - 1;
+        if let Some(index) = self.levels[depth].selected {
+            self.activate(depth, index, window, cx);
+        }
+    }
+
+    fn cancel(&mut self, _: &Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        if self.levels.len() > 1 {
+            self.levels.pop();
+            cx.notify();
+        } else {
+            self.dismiss(cx);
+        }
+    }
+
+    /// 选中第 `depth` 级菜单的第 `index` 项，如果是子菜单则展开
+    fn expand(&mut self, depth: usize, index: usize, select_first: bool, cx: &mut Context<Self>) {
+        self.levels.truncate(depth + 1);
+        self.levels[depth].selected = Some(index);
+        if let ContextMenuItem::Submenu {
+            items,
+            disabled: false,
+            ..
+        } = &self.levels[depth].items[index]
+        {
+            let mut level = MenuLevel::new(items.clone());
+            if select_first {
+                level.selected = level.next_selectable(None, true);
+            }
+            self.levels.push(level);
+        }
+        cx.notify();
+    }
+
+    /// 执行菜单项
+    fn activate(
+        &mut self,
+        depth: usize,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let items = self.levels[depth].items.clone();
+        match &items[index] {
+            ContextMenuItem::Entry {
+                command,
+                disabled: false,
+                ..
+            } => {
+                self.dismiss(cx);
+                match command {
+                    MenuCommand::Action(action) => window.dispatch_action(action.boxed_clone(), cx),
+                    MenuCommand::Handler(handler) => handler(window, cx),
+                }
+            }
+            ContextMenuItem::Submenu {
+                disabled: false, ..
+            } => self.expand(depth, index, true, cx),
+            _ => {}
+        }
+    }
+
+    fn render_item(
+        &self,
+        depth: usize,
+        index: usize,
+        item: &ContextMenuItem,
+        theme: &Theme,
+        window: &Window,
+        cx: &Context<Self>,
+    ) -> AnyElement {
+        let (label, disabled, trailing) = match item {
+            ContextMenuItem::Separator => {
+                return div()
+                    .h(SEPARATOR_HEIGHT)
+                    .flex()
+                    .items_center()
+                    .child(div().w_full().h(px(1.)).bg(theme.colors.border))
+                    .into_any_element();
+            }
+            ContextMenuItem::Entry {
+                label,
+                command,
+                disabled,
+            } => {
+                let shortcut = match command {
+                    MenuCommand::Action(action) => window
+                        .highest_precedence_binding_for_action(action.as_ref())
+                        .map(|binding| {
+                            binding
+                                .keystrokes()
+                                .iter()
+                                .map(|keystroke| keystroke.to_string())
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        }),
+                    MenuCommand::Handler(_) => None,
+                };
+                let trailing = shortcut.map(|shortcut| {
+                    div()
+                        .text_xs()
+                        .text_color(theme.colors.muted_foreground)
+                        .child(shortcut)
+                        .into_any_element()
+                });
+                (label.clone(), *disabled, trailing)
+            }
+            ContextMenuItem::Submenu {
+                label, disabled, ..
+            } => (
+                label.clone(),
+                *disabled,
+                Some(
+                    Icon::new(IconName::ChevronRight)
+                        .size_3()
+                        .text_color(theme.colors.muted_foreground)
+                        .into_any_element(),
+                ),
+            ),
+        };
+
+        let is_selected = self.levels[depth].selected == Some(index);
+        let text_color = if disabled {
+            theme.colors.muted_foreground
+        } else {
+            theme.colors.foreground
+        };
+
+        div()
+            .id(SharedString::from(format!(
+                "context-menu-{}-{}",
+                depth, index
+            )))
+            .h(ITEM_HEIGHT)
+            .px_2()
+            .flex()
+            .items_center()
+            .gap_2()
+            .rounded(theme.radius.sm)
+            .text_sm()
+            .text_color(text_color)
+            .when(is_selected && !disabled, |this| {
+                this.bg(theme.colors.list_item_background_hover)
+            })
+            .child(div().flex_1().overflow_hidden().child(label))
+            .children(trailing)
+            .when(!disabled, |this| {
+                this.on_hover(cx.listener(move |menu, hovered: &bool, _, cx| {
+                    if *hovered {
+                        menu.expand(depth, index, false, cx);
+                    }
+                }))
+                .on_click(cx.listener(move |menu, _, window, cx| {
+                    menu.activate(depth, index, window, cx);
+                }))
+            })
+            .into_any_element()
+    }
+}
+
+impl Render for ContextMenu {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+        let viewport = window.viewport_size();
+
+        // 计算每一级菜单的位置：子菜单显示在父菜单项右侧，超出窗口时翻转到左侧
+        let mut origins: Vec<Point<Pixels>> = Vec::with_capacity(self.levels.len());
+        for (depth, level) in self.levels.iter().enumerate() {
+            let (mut x, mut y) = match origins.last() {
+                None => (self.position.x, self.position.y),
+                Some(parent) => {
+                    let parent_level = &self.levels[depth - 1];
+                    let offset = parent_level.item_offset(parent_level.selected.unwrap_or(0));
+                    let x = if parent.x + MENU_WIDTH * 2. > viewport.width {
+                        parent.x - MENU_WIDTH
+                    } else {
+                        parent.x + MENU_WIDTH
+                    };
+                    (x, parent.y + offset - MENU_PADDING)
+                }
+            };
+            x = x.min(viewport.width - MENU_WIDTH).max(px(0.));
+            y = y.min(viewport.height - level.height()).max(px(0.));
+            origins.push(point(x, y));
+        }
+
+        let menus = self
+            .levels
+            .iter()
+            .zip(origins)
+            .enumerate()
+            .map(|(depth, (level, origin))| {
+                div()
+                    .absolute()
+                    .left(origin.x)
+                    .top(origin.y)
+                    .w(MENU_WIDTH)
+                    .p(MENU_PADDING)
+                    .flex()
+                    .flex_col()
+                    .bg(theme.colors.card)
+                    .border_1()
+                    .border_color(theme.colors.border)
+                    .rounded(theme.radius.md)
+                    .shadow_lg()
+                    // 点击菜单内部不关闭菜单
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_mouse_down(MouseButton::Right, |_, _, cx| cx.stop_propagation())
+                    .children(level.items.iter().enumerate().map(|(index, item)| {
+                        self.render_item(depth, index, item, theme, window, cx)
+                    }))
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("context-menu")
+            .absolute()
+            .inset_0()
+            .occlude()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::open_submenu))
+            .on_action(cx.listener(Self::close_submenu))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|menu, _, _, cx| menu.dismiss(cx)),
+            )
+            .on_mouse_down(
+                MouseButton::Right,
+                cx.listener(|menu, _, _, cx| menu.dismiss(cx)),
+            )
+            .children(menus)
+    }
+}
+
+impl Focusable for ContextMenu {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
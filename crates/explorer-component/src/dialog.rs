@@ -0,0 +1,91 @@
+use gpui::{prelude::*, *};
+
+use crate::Theme;
+
+/// 模态对话框组件
+///
+/// 覆盖整个父容器（父容器需要是 `relative` 定位），拦截背后的鼠标事件。
+/// 关闭逻辑由调用方负责（例如点击取消按钮或在输入框中按 Esc）。
+#[derive(IntoElement)]
+pub struct Dialog {
+    title: SharedString,
+    width: Pixels,
+    children: Vec<AnyElement>,
+    footer: Vec<AnyElement>,
+}
+
+impl Dialog {
+    pub fn new(title: impl Into<SharedString>) -> Self {
+        Self {
+            title: title.into(),
+            width: px(560.),
+            children: Vec::new(),
+            footer: Vec::new(),
+        }
+    }
+
+    /// 设置对话框宽度
+    pub fn width(mut self, width: Pixels) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// 添加底部按钮区域的元素（靠右排列）
+    pub fn footer(mut self, element: impl IntoElement) -> Self {
+        self.footer.push(element.into_any_element());
+        self
+    }
+}
+
+impl ParentElement for Dialog {
+    fn extend(&mut self, elements: impl IntoIterator<Item = AnyElement>) {
+        self.children.extend(elements);
+    }
+}
+
+impl RenderOnce for Dialog {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = cx.global::<Theme>();
+
+        div()
+            .id("dialog-overlay")
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(hsla(0., 0., 0., 0.4))
+            .occlude()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap(theme.spacing.md)
+                    .w(self.width)
+                    .max_h(relative(0.85))
+                    .p(theme.spacing.lg)
+                    .bg(theme.colors.card)
+                    .text_color(theme.colors.card_foreground)
+                    .border_1()
+                    .border_color(theme.colors.border)
+                    .rounded(theme.radius.lg)
+                    .shadow_lg()
+                    .child(
+                        div()
+                            .text_base()
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .child(self.title),
+                    )
+                    .children(self.children)
+                    .when(!self.footer.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .flex()
+                                .justify_end()
+                                .gap(theme.spacing.sm)
+                                .children(self.footer),
+                        )
+                    }),
+            )
+    }
+}
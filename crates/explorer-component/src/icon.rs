@@ -25,6 +25,7 @@ pub enum IconName {
     RowsSplit,
     ChevronRight,
     Close,
+    ArrowUpRight,
 }
 
 impl IconName {
@@ -47,6 +48,7 @@ impl IconNamed for IconName {
             Self::RowsSplit => "icons/rows-split.svg",
             Self::ChevronRight => "icons/chevron-right.svg",
             Self::Close => "icons/close.svg",
+            Self::ArrowUpRight => "icons/arrow-up-right.svg",
         }
         .into()
     }
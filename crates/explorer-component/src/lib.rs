@@ -1,23 +1,45 @@
 use std::borrow::Cow;
 
 use anyhow::anyhow;
-use gpui::{AssetSource, SharedString};
+use gpui::{App, AssetSource, KeyBinding, SharedString};
 use rust_embed::RustEmbed;
 
 mod breadcrumb;
+mod button;
+mod context_menu;
+mod dialog;
 mod icon;
 mod list;
 mod resizable;
+mod text_input;
 mod theme;
 mod title_bar;
 
 pub use breadcrumb::*;
+pub use button::*;
+pub use context_menu::{ContextMenu, ContextMenuItem, MenuCommand};
+pub use dialog::*;
 pub use icon::*;
 pub use list::*;
 pub use resizable::*;
+pub use text_input::{TextInput, TextInputEvent};
 pub use theme::*;
 pub use title_bar::*;
 
+/// 初始化组件库（注册组件自身的快捷键）
+pub fn init(cx: &mut App) {
+    cx.bind_keys(key_bindings());
+}
+
+/// 组件的默认快捷键
+///
+/// 应用重新加载快捷键配置时需要先清空所有绑定，再用这里的默认值和用户配置重新注册
+pub fn key_bindings() -> Vec<KeyBinding> {
+    let mut bindings = context_menu::key_bindings();
+    bindings.extend(text_input::key_bindings());
+    bindings
+}
+
 #[derive(RustEmbed)]
 #[folder = "assets"]
 #[include = "icons/**/*.svg"]
@@ -0,0 +1,558 @@
+//! 存储提供者的一致性测试
+//!
+//! 界面对所有提供者一视同仁，依赖一些 trait 签名表达不出来的约定：不存在的路径返回
+//! `PathNotFound`，列表中目录在前、同类按名称排序，元数据各字段的含义一致，符号链接
+//! 不被跟随等。各提供者在集成测试中实现 [`Fixture`]，准备好 [`TREE`]（以及
+//! [`LINKS`]）描述的目录树，然后调用 [`run`]。
+//!
+//! 检查不通过时 panic，信息中带有检查的项目和路径。需要启用 `conformance` 特性，
+//! 一般只在 `dev-dependencies` 中启用。
+
+use std::{
+    fs, io,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::SystemTime,
+};
+
+use explorer_common::{FileItem, ItemType};
+
+use crate::{StorageError, StorageProvider, StorageResult};
+
+/// 测试目录树：相对路径和文件内容，内容为 `None` 的是目录
+///
+/// 名称的大小写和开头的 `.` 用来检查排序是否按字节比较
+pub const TREE: &[(&str, Option<&[u8]>)] = &[
+    ("docs", None),
+    ("docs/readme.txt", Some(b"read me")),
+    ("Zeta", None),
+    ("Zeta/data.bin", Some(&[0, 1, 2, 3, 255])),
+    ("A.txt", Some(b"upper case")),
+    ("b.txt", Some(b"hello world")),
+    (".hidden", Some(b"secret")),
+];
+
+/// 测试目录中的符号链接：链接的名称和（相对的）目标，第二个是悬空链接
+pub const LINKS: &[(&str, &str)] = &[("link", "docs"), ("broken", "missing")];
+
+/// 一个准备好测试目录的提供者
+pub trait Fixture {
+    fn provider(&self) -> &dyn StorageProvider;
+
+    /// 测试目录的路径，其中已按 [`TREE`] 创建了条目
+    fn root(&self) -> String;
+
+    /// 测试目录中是否还创建了 [`LINKS`] 中的符号链接
+    fn symlinks(&self) -> bool {
+        false
+    }
+
+    /// 是否检查创建、写入、复制、重命名和删除（测试结束时目录恢复原样）
+    fn writable(&self) -> bool {
+        true
+    }
+
+    /// 目录中条目的路径
+    fn join(&self, dir: &str, name: &str) -> String {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+
+    /// 测试目录中的相对路径对应的路径
+    fn path(&self, relative: &str) -> String {
+        relative
+            .split('/')
+            .fold(self.root(), |dir, name| self.join(&dir, name))
+    }
+}
+
+/// 集成测试用的本机临时目录，离开作用域时连同其中的内容一起删除
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// 在系统临时目录中新建 `explorer-<name>-<进程号>-<序号>` 目录
+    pub fn new(name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "explorer-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).expect("无法创建临时目录");
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 在本机目录中创建 [`TREE`]，`symlinks` 为真时同时创建 [`LINKS`]
+pub fn create_tree(dir: &Path, symlinks: bool) -> io::Result<()> {
+    for (relative, content) in TREE {
+        let path = dir.join(relative);
+        match content {
+            Some(content) => fs::write(path, content)?,
+            None => fs::create_dir(path)?,
+        }
+    }
+    if symlinks {
+        for (name, target) in LINKS {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(target, dir.join(name))?;
+            #[cfg(windows)]
+            std::os::windows::fs::symlink_dir(target, dir.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+/// 用提供者自身的写操作在 `fixture.root()` 中创建 [`TREE`]（以及 [`LINKS`]）
+pub async fn write_tree(fixture: &dyn Fixture) -> StorageResult<()> {
+    let provider = fixture.provider();
+    for (relative, content) in TREE {
+        let path = fixture.path(relative);
+        match content {
+            Some(content) => {
+                provider.write_file(&path, &mut &content[..]).await?;
+            }
+            None => provider.create_dir(&path).await?,
+        }
+    }
+    if fixture.symlinks() {
+        for (name, target) in LINKS {
+            provider.create_symlink(target, &fixture.path(name)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// 执行全部检查
+pub async fn run(fixture: &dyn Fixture) {
+    check_missing_paths(fixture).await;
+    check_listing_order(fixture).await;
+    check_metadata(fixture).await;
+    check_contents(fixture).await;
+    if fixture.symlinks() {
+        check_symlinks(fixture).await;
+    }
+    if fixture.writable() {
+        check_mutations(fixture).await;
+        if fixture.provider().capabilities().can_symlink {
+            check_create_symlink(fixture).await;
+        }
+        // 修改操作之后目录应恢复原样
+        check_listing_order(fixture).await;
+    }
+}
+
+fn ok<T>(result: StorageResult<T>, operation: &str, path: &str) -> T {
+    result.unwrap_or_else(|e| panic!("{}失败: {}: {}", operation, path, e))
+}
+
+fn assert_not_found<T>(result: StorageResult<T>, operation: &str, path: &str) {
+    match result {
+        Err(StorageError::PathNotFound(_)) => {}
+        Err(e) => panic!("{}应返回 PathNotFound: {}: {:?}", operation, path, e),
+        Ok(_) => panic!("{}不存在的路径应失败: {}", operation, path),
+    }
+}
+
+fn assert_already_exists<T>(result: StorageResult<T>, operation: &str, path: &str) {
+    match result {
+        Err(StorageError::AlreadyExists(_)) => {}
+        Err(e) => panic!("{}应返回 AlreadyExists: {}: {:?}", operation, path, e),
+        Ok(_) => panic!("{}已存在的目标应失败: {}", operation, path),
+    }
+}
+
+async fn read(fixture: &dyn Fixture, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    ok(
+        fixture.provider().read_file(path, &mut content).await,
+        "读取文件",
+        path,
+    );
+    content
+}
+
+/// 测试目录中应有的条目名称（按界面的顺序）
+fn expected_names(fixture: &dyn Fixture) -> Vec<&'static str> {
+    let top_level = |relative: &&str| !relative.contains('/');
+    let mut directories: Vec<_> = TREE
+        .iter()
+        .filter(|(relative, content)| top_level(relative) && content.is_none())
+        .map(|(relative, _)| *relative)
+        .collect();
+    let mut others: Vec<_> = TREE
+        .iter()
+        .filter(|(relative, content)| top_level(relative) && content.is_some())
+        .map(|(relative, _)| *relative)
+        .collect();
+    if fixture.symlinks() {
+        others.extend(LINKS.iter().map(|(name, _)| *name));
+    }
+    directories.sort_unstable();
+    others.sort_unstable();
+    directories.into_iter().chain(others).collect()
+}
+
+/// 不存在的路径返回 `PathNotFound`
+pub async fn check_missing_paths(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    for relative in ["missing", "missing/deeper", "docs/missing.txt"] {
+        let path = fixture.path(relative);
+        assert_not_found(provider.get_metadata(&path).await, "获取元数据", &path);
+        assert_not_found(provider.list_entries(&path).await, "列出目录", &path);
+        assert_not_found(
+            provider.read_file(&path, &mut io::sink()).await,
+            "读取文件",
+            &path,
+        );
+        assert!(
+            !ok(provider.exists(&path).await, "检查是否存在", &path),
+            "不存在的路径 exists 应为 false: {}",
+            path
+        );
+
+        if fixture.writable() {
+            let target = fixture.path("target");
+            assert_not_found(provider.delete(&path).await, "删除", &path);
+            assert_not_found(provider.rename(&path, &target).await, "重命名", &path);
+            assert_not_found(provider.copy(&path, &target).await, "复制", &path);
+            assert!(
+                !ok(provider.exists(&target).await, "检查是否存在", &target),
+                "失败的操作不应创建目标: {}",
+                target
+            );
+        }
+    }
+}
+
+/// 列表中目录在前，同类按名称（逐字节）排序，不包括 `.` 和 `..`
+pub async fn check_listing_order(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    let root = fixture.root();
+    let entries = ok(provider.list_entries(&root).await, "列出目录", &root);
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, expected_names(fixture), "列表的顺序: {}", root);
+
+    let docs = fixture.path("docs");
+    let entries = ok(provider.list_entries(&docs).await, "列出目录", &docs);
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["readme.txt"], "子目录的列表: {}", docs);
+
+    let file = fixture.path("b.txt");
+    match provider.list_entries(&file).await {
+        Err(StorageError::PathNotFound(_)) => {
+            panic!("列出文件应返回 PathNotFound 以外的错误: {}", file)
+        }
+        Err(_) => {}
+        Ok(_) => panic!("列出文件应失败: {}", file),
+    }
+}
+
+/// 列表中的条目和 `get_metadata` 返回的条目一致，各字段符合约定
+pub async fn check_metadata(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    let root = fixture.root();
+    let entries = ok(provider.list_entries(&root).await, "列出目录", &root);
+
+    for (relative, content) in TREE.iter().filter(|(relative, _)| !relative.contains('/')) {
+        let path = fixture.path(relative);
+        let listed = entries
+            .iter()
+            .find(|entry| entry.name == *relative)
+            .unwrap_or_else(|| panic!("列表中没有条目: {}", path));
+        assert_eq!(listed.path, path, "条目的路径: {}", relative);
+
+        let item = ok(provider.get_metadata(&path).await, "获取元数据", &path);
+        check_item(&item, relative, *content);
+        check_item(listed, relative, *content);
+        assert_eq!(item.path, path, "元数据中的路径: {}", path);
+    }
+
+    // 路径末尾的 `/` 不影响结果
+    let docs = fixture.path("docs");
+    let item = ok(
+        provider.get_metadata(&format!("{}/", docs)).await,
+        "获取元数据",
+        &docs,
+    );
+    assert_eq!(item.name, "docs", "末尾有 / 的路径: {}", docs);
+    assert_eq!(
+        item.item_type,
+        ItemType::Directory,
+        "末尾有 / 的路径: {}",
+        docs
+    );
+}
+
+fn check_item(item: &FileItem, name: &str, content: Option<&[u8]>) {
+    assert_eq!(item.name, name, "条目的名称");
+    assert_eq!(item.is_hidden, name.starts_with('.'), "是否隐藏: {}", name);
+    match content {
+        Some(content) => {
+            assert_eq!(item.item_type, ItemType::File, "条目的类型: {}", name);
+            assert_eq!(item.size, content.len() as u64, "文件的大小: {}", name);
+            assert!(
+                item.modified > SystemTime::UNIX_EPOCH,
+                "文件应有修改时间: {}",
+                name
+            );
+            if name.ends_with(".txt") {
+                assert_eq!(
+                    item.metadata.mime_type.as_deref(),
+                    Some("text/plain"),
+                    "文件的 MIME 类型: {}",
+                    name
+                );
+            }
+        }
+        None => {
+            assert_eq!(item.item_type, ItemType::Directory, "条目的类型: {}", name);
+            assert_eq!(
+                item.metadata.mime_type, None,
+                "目录没有 MIME 类型: {}",
+                name
+            );
+        }
+    }
+}
+
+/// 读取的内容与写入的一致，`exists` 对文件和目录都为真
+pub async fn check_contents(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    for (relative, content) in TREE {
+        let path = fixture.path(relative);
+        assert!(
+            ok(provider.exists(&path).await, "检查是否存在", &path),
+            "exists 应为 true: {}",
+            path
+        );
+        if let Some(content) = content {
+            assert_eq!(read(fixture, &path).await, *content, "文件的内容: {}", path);
+        }
+    }
+}
+
+/// 符号链接按链接本身列出，悬空链接不影响列表，指向目录的链接可以打开
+///
+/// 提供者报告了链接的目标时，目标和悬空状态必须正确
+pub async fn check_symlinks(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    let root = fixture.root();
+    let entries = ok(provider.list_entries(&root).await, "列出目录", &root);
+    for (name, target) in LINKS {
+        let path = fixture.path(name);
+        let listed = entries
+            .iter()
+            .find(|entry| entry.name == *name)
+            .unwrap_or_else(|| panic!("列表中没有符号链接: {}", path));
+        assert_eq!(
+            listed.item_type,
+            ItemType::Symlink,
+            "列表中的链接类型: {}",
+            path
+        );
+
+        let item = ok(provider.get_metadata(&path).await, "获取元数据", &path);
+        assert_eq!(item.item_type, ItemType::Symlink, "链接的类型: {}", path);
+        assert!(
+            ok(provider.exists(&path).await, "检查是否存在", &path),
+            "符号链接（包括悬空的）exists 应为 true: {}",
+            path
+        );
+
+        let broken = !TREE.iter().any(|(relative, _)| relative == target);
+        for item in [listed, &item] {
+            let metadata = &item.metadata;
+            if let Some(link_target) = &metadata.link_target {
+                assert_eq!(link_target, target, "链接的目标: {}", path);
+                assert_eq!(metadata.broken_link, broken, "是否为悬空链接: {}", path);
+            }
+            assert!(
+                !metadata.broken_link || broken,
+                "链接不应是悬空的: {}",
+                path
+            );
+            let expected_type = if broken {
+                None
+            } else {
+                Some(ItemType::Directory)
+            };
+            assert!(
+                metadata.link_target_type.is_none() || metadata.link_target_type == expected_type,
+                "链接指向的类型: {}: {:?}",
+                path,
+                metadata.link_target_type
+            );
+        }
+    }
+
+    let link = fixture.path("link");
+    let entries = ok(
+        provider.list_entries(&link).await,
+        "打开指向目录的链接",
+        &link,
+    );
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["readme.txt"], "链接指向的目录: {}", link);
+    assert_eq!(
+        entries[0].path,
+        fixture.join(&link, "readme.txt"),
+        "链接中的条目的路径: {}",
+        link
+    );
+}
+
+/// 创建、写入、复制、重命名和删除，结束时删除创建的条目
+pub async fn check_mutations(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    let dir = fixture.path("new");
+    ok(provider.create_dir(&dir).await, "创建目录", &dir);
+    assert_already_exists(provider.create_dir(&dir).await, "创建目录", &dir);
+    let item = ok(provider.get_metadata(&dir).await, "获取元数据", &dir);
+    assert_eq!(item.item_type, ItemType::Directory, "新建的目录: {}", dir);
+
+    let empty = fixture.join(&dir, "empty.txt");
+    ok(provider.create_file(&empty).await, "创建文件", &empty);
+    assert_already_exists(provider.create_file(&empty).await, "创建文件", &empty);
+    let item = ok(provider.get_metadata(&empty).await, "获取元数据", &empty);
+    assert_eq!(
+        (item.item_type, item.size),
+        (ItemType::File, 0),
+        "新建的文件: {}",
+        empty
+    );
+
+    let data = fixture.join(&dir, "data.txt");
+    let written = ok(
+        provider.write_file(&data, &mut &b"some content"[..]).await,
+        "写入文件",
+        &data,
+    );
+    assert_eq!(written, 12, "写入的字节数: {}", data);
+    ok(
+        provider.write_file(&data, &mut &b"short"[..]).await,
+        "覆盖文件",
+        &data,
+    );
+    assert_eq!(
+        read(fixture, &data).await,
+        b"short",
+        "覆盖后的内容: {}",
+        data
+    );
+
+    let copy = fixture.path("copy");
+    ok(provider.copy(&dir, &copy).await, "复制目录", &copy);
+    assert_already_exists(provider.copy(&dir, &copy).await, "复制目录", &copy);
+    let copied = fixture.join(&copy, "data.txt");
+    assert_eq!(
+        read(fixture, &copied).await,
+        b"short",
+        "复制的文件: {}",
+        copied
+    );
+    let inside = fixture.join(&dir, "inside");
+    assert!(
+        provider.copy(&dir, &inside).await.is_err(),
+        "不能将目录复制到其自身内部: {}",
+        inside
+    );
+
+    let renamed = fixture.join(&copy, "renamed.txt");
+    ok(provider.rename(&copied, &renamed).await, "重命名", &copied);
+    assert!(
+        !ok(provider.exists(&copied).await, "检查是否存在", &copied),
+        "重命名后原路径不应存在: {}",
+        copied
+    );
+    assert_eq!(
+        read(fixture, &renamed).await,
+        b"short",
+        "重命名的文件: {}",
+        renamed
+    );
+    let other = fixture.join(&copy, "empty.txt");
+    assert_already_exists(provider.rename(&renamed, &other).await, "重命名", &other);
+
+    for path in [&copy, &dir] {
+        ok(provider.delete(path).await, "删除", path);
+        assert!(
+            !ok(provider.exists(path).await, "检查是否存在", path),
+            "删除后不应存在: {}",
+            path
+        );
+    }
+}
+
+/// 创建指向目录的链接和悬空链接，删除链接不影响目标
+pub async fn check_create_symlink(fixture: &dyn Fixture) {
+    let provider = fixture.provider();
+    let made = fixture.path("made");
+    ok(
+        provider.create_symlink("docs", &made).await,
+        "创建符号链接",
+        &made,
+    );
+    assert_already_exists(
+        provider.create_symlink("docs", &made).await,
+        "创建符号链接",
+        &made,
+    );
+    let item = ok(provider.get_metadata(&made).await, "获取元数据", &made);
+    assert_eq!(item.item_type, ItemType::Symlink, "新建的链接: {}", made);
+    if let Some(link_target) = &item.metadata.link_target {
+        assert_eq!(link_target, "docs", "新建的链接的目标: {}", made);
+    }
+    let entries = ok(provider.list_entries(&made).await, "打开新建的链接", &made);
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["readme.txt"], "新建的链接指向的目录: {}", made);
+
+    let dangling = fixture.path("dangling");
+    ok(
+        provider.create_symlink("missing", &dangling).await,
+        "创建悬空链接",
+        &dangling,
+    );
+    assert!(
+        ok(provider.exists(&dangling).await, "检查是否存在", &dangling),
+        "新建的悬空链接 exists 应为 true: {}",
+        dangling
+    );
+
+    for path in [&made, &dangling] {
+        ok(provider.delete(path).await, "删除符号链接", path);
+        assert!(
+            !ok(provider.exists(path).await, "检查是否存在", path),
+            "删除后不应存在: {}",
+            path
+        );
+    }
+    let readme = fixture.path("docs/readme.txt");
+    assert_eq!(
+        read(fixture, &readme).await,
+        b"read me",
+        "删除链接后目标应保持原样: {}",
+        readme
+    );
+}
@@ -8,9 +8,15 @@ pub enum StorageError {
     #[error("权限不足: {0}")]
     PermissionDenied(String),
 
+    #[error("目标已存在: {0}")]
+    AlreadyExists(String),
+
     #[error("IO 错误: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("压缩包中有不安全的路径（绝对路径或包含 ..）: {0}")]
+    UnsafeArchivePath(String),
+
     #[error("不支持的操作: {0}")]
     Unsupported(String),
 
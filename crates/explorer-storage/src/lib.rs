@@ -1,6 +1,10 @@
+#[cfg(feature = "conformance")]
+pub mod conformance;
 mod error;
+mod progress;
 mod provider;
 
 pub use error::*;
 pub use explorer_common::*;
+pub use progress::*;
 pub use provider::*;
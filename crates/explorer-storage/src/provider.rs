@@ -1,8 +1,48 @@
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
+
 use async_trait::async_trait;
 
-use explorer_common::{FileItem, ProviderType, RootItem};
+use explorer_common::{CompressOptions, FileItem, ItemType, ProviderType, RootItem, SpaceInfo};
+
+use crate::{Progress, StorageError, StorageResult};
 
-use crate::StorageResult;
+/// 按 `list_entries` 约定的顺序排列条目：目录在前，文件在后，各自按名称排序
+pub fn sort_entries(entries: &mut [FileItem]) {
+    entries.sort_by(|a, b| match (a.item_type, b.item_type) {
+        (ItemType::Directory, ItemType::Directory) => a.name.cmp(&b.name),
+        (ItemType::Directory, _) => std::cmp::Ordering::Less,
+        (_, ItemType::Directory) => std::cmp::Ordering::Greater,
+        _ => a.name.cmp(&b.name),
+    });
+}
+
+/// 存储提供者支持的操作
+///
+/// 界面根据这些能力启用或禁用对应的菜单项和快捷键。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProviderCapabilities {
+    /// 可以创建文件和目录
+    pub can_create: bool,
+    /// 可以重命名
+    pub can_rename: bool,
+    /// 可以复制
+    pub can_copy: bool,
+    /// 可以移动
+    pub can_move: bool,
+    /// 可以移入回收站
+    pub can_trash: bool,
+    /// 可以永久删除
+    pub can_delete: bool,
+    /// 可以创建符号链接
+    pub can_symlink: bool,
+    /// 可以创建和解压压缩包
+    pub can_archive: bool,
+    /// 路径是本机文件系统路径（可以用外部程序打开、在终端中打开）
+    pub local_paths: bool,
+}
 
 /// 存储提供者接口
 ///
@@ -29,7 +69,7 @@ pub trait StorageProvider: Send + Sync {
     /// * `path` - 要列出的目录路径
     ///
     /// # 返回
-    /// 返回该目录下所有文件和子目录的列表
+    /// 返回该目录下所有文件和子目录的列表，按 [`sort_entries`] 的顺序排列
     async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>>;
 
     /// 检查路径是否存在
@@ -38,6 +78,161 @@ pub trait StorageProvider: Send + Sync {
     /// * `path` - 要检查的路径
     async fn exists(&self, path: &str) -> StorageResult<bool>;
 
+    /// 读取文件内容并写入 `writer`
+    ///
+    /// 内容边读边写，不会整个读入内存
+    ///
+    /// # 返回
+    /// 返回写入的字节数
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        let _ = writer;
+        Err(StorageError::Unsupported(format!("读取文件: {}", path)))
+    }
+
+    /// 从 `reader` 读取内容写入文件
+    ///
+    /// 文件不存在时创建，已存在时覆盖；内容边读边写，不会整个读入内存
+    ///
+    /// # 返回
+    /// 返回写入的字节数
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        let _ = reader;
+        Err(StorageError::Unsupported(format!("写入文件: {}", path)))
+    }
+
+    /// 创建目录
+    ///
+    /// # 参数
+    /// * `path` - 新目录的路径，父目录必须存在
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported(format!("创建目录: {}", path)))
+    }
+
+    /// 创建空文件
+    ///
+    /// # 参数
+    /// * `path` - 新文件的路径，目标已存在时返回 `AlreadyExists`
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported(format!("创建文件: {}", path)))
+    }
+
+    /// 创建符号链接
+    ///
+    /// # 参数
+    /// * `target` - 链接指向的路径，原样保存在链接中，可以是相对路径，也可以不存在
+    /// * `path` - 新链接的路径，目标已存在时返回 `AlreadyExists`
+    async fn create_symlink(&self, target: &str, path: &str) -> StorageResult<()> {
+        let _ = target;
+        Err(StorageError::Unsupported(format!("创建符号链接: {}", path)))
+    }
+
+    /// 重命名文件或目录
+    ///
+    /// # 参数
+    /// * `from` - 原路径
+    /// * `to` - 新路径，目标已存在时返回 `AlreadyExists`
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported(format!(
+            "重命名: {} -> {}",
+            from, to
+        )))
+    }
+
+    /// 移动文件或目录
+    ///
+    /// 默认实现等同于 `rename`，跨设备移动需要由具体提供者处理
+    async fn move_entry(&self, from: &str, to: &str) -> StorageResult<()> {
+        self.rename(from, to).await
+    }
+
+    /// 复制文件或目录（目录递归复制）
+    ///
+    /// # 参数
+    /// * `from` - 源路径
+    /// * `to` - 目标路径，目标已存在时返回 `AlreadyExists`
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported(format!(
+            "复制: {} -> {}",
+            from, to
+        )))
+    }
+
+    /// 将文件或目录移入回收站
+    ///
+    /// # 返回
+    /// 返回条目在回收站中的位置，可用于 `restore` 还原
+    async fn trash(&self, path: &str) -> StorageResult<String> {
+        Err(StorageError::Unsupported(format!("移入回收站: {}", path)))
+    }
+
+    /// 将回收站中的条目还原到原位置
+    ///
+    /// # 参数
+    /// * `trashed` - `trash` 返回的回收站位置
+    /// * `original` - 原始路径
+    async fn restore(&self, trashed: &str, original: &str) -> StorageResult<()> {
+        self.rename(trashed, original).await
+    }
+
+    /// 永久删除文件或目录（目录递归删除）
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        Err(StorageError::Unsupported(format!("删除: {}", path)))
+    }
+
+    /// 把文件和目录压缩为一个压缩包
+    ///
+    /// # 参数
+    /// * `sources` - 要压缩的条目，在压缩包中位于顶层
+    /// * `target` - 压缩包路径，目标已存在时返回 `AlreadyExists`
+    /// * `progress` - 按未压缩的字节数更新
+    async fn compress(
+        &self,
+        sources: &[String],
+        target: &str,
+        options: CompressOptions,
+        progress: Arc<Progress>,
+    ) -> StorageResult<()> {
+        let _ = (sources, options, progress);
+        Err(StorageError::Unsupported(format!("压缩: {}", target)))
+    }
+
+    /// 把压缩包解压到目录中
+    ///
+    /// 成员中有绝对路径或 `..` 时返回 `UnsafeArchivePath`，不解压任何内容；
+    /// 顶层条目与目录中已有的条目同名时返回 `AlreadyExists`
+    ///
+    /// # 参数
+    /// * `archive` - 压缩包路径
+    /// * `target_dir` - 解压到的目录，不存在时会被创建
+    /// * `progress` - 按解压出的字节数更新
+    ///
+    /// # 返回
+    /// 返回新建的条目：目标目录原本不存在时为该目录，否则为解压出的顶层条目
+    async fn extract(
+        &self,
+        archive: &str,
+        target_dir: &str,
+        progress: Arc<Progress>,
+    ) -> StorageResult<Vec<String>> {
+        let _ = (target_dir, progress);
+        Err(StorageError::Unsupported(format!("解压: {}", archive)))
+    }
+
+    /// 获取路径所在卷的容量和剩余空间
+    ///
+    /// # 参数
+    /// * `path` - 卷中的任意路径
+    async fn get_space(&self, path: &str) -> StorageResult<SpaceInfo> {
+        Err(StorageError::Unsupported(format!("获取可用空间: {}", path)))
+    }
+
+    /// 获取提供者支持的操作
+    ///
+    /// 默认不支持任何修改操作，实现了对应方法的提供者需要覆盖此方法
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+
     /// 获取提供者类型标识
     fn provider_type(&self) -> ProviderType;
 }
@@ -0,0 +1,203 @@
+//! 创建压缩包
+//!
+//! 每个源条目位于压缩包的顶层，目录递归加入，符号链接按链接本身保存。
+//! 失败时移除未写完的压缩包。
+
+use std::{
+    fs::{self, File, Metadata},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Datelike, Local, Timelike};
+use explorer_storage::{
+    ArchiveFormat, CompressOptions, CompressionLevel, Progress, StorageError, StorageResult,
+};
+
+use crate::ProgressReader;
+
+/// 要写入压缩包的条目
+struct Source {
+    path: PathBuf,
+    /// 在压缩包中的路径（以 `/` 分隔）
+    name: String,
+    metadata: Metadata,
+}
+
+/// 把 `sources` 压缩为 `target`
+pub(crate) fn compress(
+    sources: &[PathBuf],
+    target: &Path,
+    options: CompressOptions,
+    progress: &Progress,
+) -> StorageResult<()> {
+    if fs::symlink_metadata(target).is_ok() {
+        return Err(StorageError::AlreadyExists(target.display().to_string()));
+    }
+    if let Some(source) = sources.iter().find(|source| target.starts_with(source)) {
+        return Err(StorageError::Other(format!(
+            "压缩包不能保存在要压缩的文件夹中: {}",
+            source.display()
+        )));
+    }
+
+    let entries = collect(sources)?;
+    progress.add_total(
+        entries
+            .iter()
+            .filter(|entry| entry.metadata.is_file())
+            .map(|entry| entry.metadata.len())
+            .sum(),
+    );
+
+    let file = File::options().write(true).create_new(true).open(target)?;
+    let writer = BufWriter::new(file);
+    let result = match options.format {
+        ArchiveFormat::Zip => write_zip(writer, &entries, options.level, progress),
+        ArchiveFormat::TarGz => {
+            let level = match options.level {
+                CompressionLevel::Fast => 1,
+                CompressionLevel::Normal => 6,
+                CompressionLevel::Best => 9,
+            };
+            let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::new(level));
+            write_tar(encoder, &entries, progress)
+                .and_then(|encoder| Ok(encoder.finish()?.flush()?))
+        }
+        ArchiveFormat::TarZst => {
+            let level = match options.level {
+                CompressionLevel::Fast => 1,
+                CompressionLevel::Normal => 3,
+                CompressionLevel::Best => 19,
+            };
+            zstd::Encoder::new(writer, level)
+                .map_err(StorageError::from)
+                .and_then(|encoder| write_tar(encoder, &entries, progress))
+                .and_then(|encoder| Ok(encoder.finish()?.flush()?))
+        }
+        format => Err(StorageError::Unsupported(format!(
+            "创建 {} 格式的压缩包",
+            format.extension()
+        ))),
+    };
+    if result.is_err() {
+        let _ = fs::remove_file(target);
+    }
+    result
+}
+
+/// 递归列出要写入的条目（目录在其内容之前）
+fn collect(sources: &[PathBuf]) -> StorageResult<Vec<Source>> {
+    let mut entries = vec![];
+    for source in sources {
+        let name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| StorageError::Other(format!("无效的路径: {}", source.display())))?;
+        let mut pending = vec![(source.clone(), name)];
+        while let Some((path, name)) = pending.pop() {
+            let metadata = fs::symlink_metadata(&path)?;
+            if metadata.is_dir() {
+                let mut children: Vec<_> = fs::read_dir(&path)?
+                    .map(|entry| entry.map(|entry| entry.file_name()))
+                    .collect::<io::Result<_>>()?;
+                // 倒序入栈，使同一目录中的条目按名称顺序写入
+                children.sort_by(|a, b| b.cmp(a));
+                pending.extend(children.into_iter().map(|child| {
+                    let child_name = format!("{}/{}", name, child.to_string_lossy());
+                    (path.join(child), child_name)
+                }));
+            } else if !metadata.is_file() && !metadata.is_symlink() {
+                tracing::warn!("跳过特殊文件: {}", path.display());
+                continue;
+            }
+            entries.push(Source {
+                path,
+                name,
+                metadata,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn write_zip(
+    writer: impl Write + io::Seek,
+    entries: &[Source],
+    level: CompressionLevel,
+    progress: &Progress,
+) -> StorageResult<()> {
+    let level = match level {
+        CompressionLevel::Fast => 1,
+        CompressionLevel::Normal => 6,
+        CompressionLevel::Best => 9,
+    };
+    let zip_error = |e: zip::result::ZipError| StorageError::Other(format!("写入 zip 失败: {}", e));
+
+    let mut zip = zip::ZipWriter::new(writer);
+    for entry in entries {
+        let mut options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .compression_level(Some(level))
+            .large_file(entry.metadata.len() >= u32::MAX as u64);
+        if let Some(modified) = entry.metadata.modified().ok().and_then(zip_datetime) {
+            options = options.last_modified_time(modified);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(entry.metadata.permissions().mode() & 0o777);
+        }
+
+        if entry.metadata.is_dir() {
+            zip.add_directory(format!("{}/", entry.name), options)
+                .map_err(zip_error)?;
+        } else if entry.metadata.is_symlink() {
+            let link = fs::read_link(&entry.path)?;
+            zip.add_symlink(entry.name.as_str(), link.to_string_lossy(), options)
+                .map_err(zip_error)?;
+        } else {
+            zip.start_file(entry.name.as_str(), options)
+                .map_err(zip_error)?;
+            let mut file = File::open(&entry.path)?;
+            io::copy(&mut ProgressReader::new(&mut file, progress), &mut zip)?;
+        }
+    }
+    zip.finish().map_err(zip_error)?.flush()?;
+    Ok(())
+}
+
+/// zip 中的时间没有时区，按本地时间保存
+fn zip_datetime(time: SystemTime) -> Option<zip::DateTime> {
+    let time = DateTime::<Local>::from(time);
+    zip::DateTime::from_date_and_time(
+        time.year().try_into().ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .ok()
+}
+
+fn write_tar<W: Write>(writer: W, entries: &[Source], progress: &Progress) -> StorageResult<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    for entry in entries {
+        if entry.metadata.is_file() {
+            let mut header = tar::Header::new_gnu();
+            header.set_metadata(&entry.metadata);
+            let mut file = File::open(&entry.path)?;
+            builder.append_data(
+                &mut header,
+                &entry.name,
+                ProgressReader::new(&mut file, progress),
+            )?;
+        } else {
+            builder.append_path_with_name(&entry.path, &entry.name)?;
+        }
+    }
+    Ok(builder.into_inner()?)
+}
@@ -0,0 +1,251 @@
+//! 解压
+//!
+//! 解压前先检查整个压缩包：有不安全的路径（zip-slip、绝对路径）时拒绝解压，顶层条目
+//! 与目标目录中已有的条目同名时也不解压。符号链接在其他成员之后创建，指向目标目录
+//! 之外的链接会使解压失败。解压中途失败会移除已经解压出的内容。
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use explorer_storage::{ArchiveFormat, Progress, StorageError, StorageResult};
+
+use crate::{
+    ProgressReader,
+    format::open_tar,
+    index::{ArchiveIndex, invalid_data, normalize, zip_time},
+};
+
+/// 把压缩包解压到 `target_dir`，返回新建的条目
+pub(crate) fn extract(
+    archive: &Path,
+    index: &ArchiveIndex,
+    target_dir: &Path,
+    progress: &Progress,
+) -> StorageResult<Vec<PathBuf>> {
+    if let Some(name) = index.unsafe_paths.first() {
+        return Err(unsafe_path(archive, name));
+    }
+
+    let top_level: Vec<PathBuf> = index
+        .children("")
+        .into_iter()
+        .flatten()
+        .map(|member| target_dir.join(&member.path))
+        .collect();
+    let create_target = fs::symlink_metadata(target_dir).is_err();
+    if create_target {
+        fs::create_dir(target_dir)?;
+    } else if let Some(existing) = top_level
+        .iter()
+        .find(|path| fs::symlink_metadata(path).is_ok())
+    {
+        return Err(StorageError::AlreadyExists(existing.display().to_string()));
+    }
+    let created = if create_target {
+        vec![target_dir.to_path_buf()]
+    } else {
+        top_level
+    };
+
+    progress.add_total(index.total_size());
+    let result = match index.format {
+        ArchiveFormat::Zip => extract_zip(archive, target_dir, progress),
+        format => extract_tar(archive, format, target_dir, progress),
+    };
+    if let Err(e) = result {
+        tracing::error!("解压失败，移除已解压的内容: {}", e);
+        for path in &created {
+            let _ = remove(path);
+        }
+        return Err(e);
+    }
+    Ok(created)
+}
+
+fn unsafe_path(archive: &Path, name: &str) -> StorageError {
+    StorageError::UnsafeArchivePath(format!("{}: {}", archive.display(), name))
+}
+
+fn remove(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// 推迟到其他成员解压完之后再创建的符号链接
+struct PendingLink {
+    /// 压缩包中的成员名，用于报错
+    name: String,
+    path: PathBuf,
+    target: String,
+}
+
+/// 创建 `path` 的上级目录，并确认上级目录在 `root`（已规范化的目标目录）之内
+fn create_parent(archive: &Path, root: &Path, name: &str, path: &Path) -> StorageResult<()> {
+    let Some(parent) = path.parent() else {
+        return Ok(());
+    };
+    fs::create_dir_all(parent)?;
+    if !parent.canonicalize()?.starts_with(root) {
+        return Err(unsafe_path(archive, name));
+    }
+    Ok(())
+}
+
+fn extract_zip(archive: &Path, target_dir: &Path, progress: &Progress) -> StorageResult<()> {
+    let file = BufReader::new(File::open(archive)?);
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| invalid_data(archive, e))?;
+    let root = target_dir.canonicalize()?;
+    let mut links = vec![];
+
+    for position in 0..zip.len() {
+        let mut entry = zip
+            .by_index(position)
+            .map_err(|e| invalid_data(archive, e))?;
+        let name = entry.name().to_string();
+        let member = normalize(&name).ok_or_else(|| unsafe_path(archive, &name))?;
+        if member.is_empty() {
+            continue;
+        }
+        let path = target_dir.join(&member);
+        create_parent(archive, &root, &name, &path)?;
+        if entry.is_dir() {
+            fs::create_dir_all(&path)?;
+            continue;
+        }
+
+        let mode = entry.unix_mode();
+        if mode.is_some_and(|mode| mode & 0o170000 == 0o120000) {
+            let mut target = String::new();
+            entry.read_to_string(&mut target)?;
+            links.push(PendingLink { name, path, target });
+            continue;
+        }
+
+        let mut file = File::options().write(true).create_new(true).open(&path)?;
+        io::copy(&mut ProgressReader::new(&mut entry, progress), &mut file)?;
+        if let Some(modified) = entry.last_modified().and_then(zip_time) {
+            let _ = file.set_modified(modified);
+        }
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o777))?;
+        }
+    }
+    create_links(archive, &root, &links)
+}
+
+fn extract_tar(
+    archive: &Path,
+    format: ArchiveFormat,
+    target_dir: &Path,
+    progress: &Progress,
+) -> StorageResult<()> {
+    let root = target_dir.canonicalize()?;
+    let mut links = vec![];
+    let mut tar = open_tar(archive, format)?;
+    tar.set_overwrite(false);
+    for entry in tar.entries().map_err(|e| invalid_data(archive, e))? {
+        let mut entry = entry.map_err(|e| invalid_data(archive, e))?;
+        let name = String::from_utf8_lossy(&entry.path_bytes()).to_string();
+        let size = entry.header().size().unwrap_or(0);
+        let link = entry
+            .link_name()
+            .map_err(|e| invalid_data(archive, e))?
+            .map(|target| target.to_string_lossy().to_string());
+        match (entry.header().entry_type(), link) {
+            (tar::EntryType::Symlink, Some(target)) => {
+                let member = normalize(&name).ok_or_else(|| unsafe_path(archive, &name))?;
+                let path = target_dir.join(&member);
+                create_parent(archive, &root, &name, &path)?;
+                links.push(PendingLink { name, path, target });
+                continue;
+            }
+            // 硬链接的源是压缩包中的另一个成员
+            (tar::EntryType::Link, Some(source)) if normalize(&source).is_none() => {
+                return Err(unsafe_path(archive, &name));
+            }
+            _ => {}
+        }
+        // `unpack_in` 会拒绝写到目标目录之外（包括经过符号链接）的成员
+        if !entry.unpack_in(target_dir)? {
+            return Err(unsafe_path(archive, &name));
+        }
+        progress.advance(size);
+    }
+    create_links(archive, &root, &links)
+}
+
+/// 最后创建符号链接，之前解压其他成员时不会经过压缩包中的链接写到别处
+///
+/// 全部创建后再逐个检查链接在磁盘上的实际指向：后创建的链接可能改变先创建的
+/// 链接的解析结果（如 `b -> .` 和 `a -> b/..`）。有链接指向目标目录之外时报错，
+/// 由调用方移除已解压的内容。
+fn create_links(archive: &Path, root: &Path, links: &[PendingLink]) -> StorageResult<()> {
+    for link in links {
+        create_parent(archive, root, &link.name, &link.path)?;
+        match create_symlink(&link.target, &link.path) {
+            // 其他成员已经解压到链接的路径下（压缩包想经过链接写入文件）
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                return Err(unsafe_path(archive, &link.name));
+            }
+            result => result?,
+        }
+    }
+    for link in links {
+        if fs::symlink_metadata(&link.path).is_ok_and(|metadata| metadata.is_symlink())
+            && !resolves_inside(root, &link.path)?
+        {
+            return Err(unsafe_path(archive, &link.name));
+        }
+    }
+    Ok(())
+}
+
+/// 按磁盘上的实际内容逐段解析符号链接，检查每一步是否都在 `root` 之内
+///
+/// 已经存在的部分用 `canonicalize` 解析（会经过其他符号链接），不存在的部分按字面
+/// 处理，这样的链接是悬空的。
+fn resolves_inside(root: &Path, link: &Path) -> io::Result<bool> {
+    let target = fs::read_link(link)?;
+    let Some(parent) = link.parent() else {
+        return Ok(false);
+    };
+    let mut current = parent.canonicalize()?;
+    for component in target.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                current.pop();
+            }
+            Component::Normal(part) => {
+                current.push(part);
+                if let Ok(resolved) = current.canonicalize() {
+                    current = resolved;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Ok(false),
+        }
+        if !current.starts_with(root) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn create_symlink(link: &str, path: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(link, path)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(link: &str, path: &Path) -> io::Result<()> {
+    tracing::warn!("跳过符号链接 {} -> {}", path.display(), link);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+//! 压缩包格式
+//!
+//! 根据文件名识别压缩包，并打开 tar 包（按需解压）的读取流。
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use explorer_storage::ArchiveFormat;
+
+/// 路径是否为支持的压缩包（只看文件名）
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    ArchiveFormat::from_path(path).is_some()
+}
+
+/// 打开 tar 包，返回解压后的 tar 数据流
+pub(crate) fn open_tar(
+    path: &Path,
+    format: ArchiveFormat,
+) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = match format {
+        ArchiveFormat::Tar => Box::new(file),
+        ArchiveFormat::TarGz => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        ArchiveFormat::TarBz2 => Box::new(bzip2::read::MultiBzDecoder::new(file)),
+        ArchiveFormat::TarXz => Box::new(xz2::read::XzDecoder::new_multi_decoder(file)),
+        ArchiveFormat::TarZst => Box::new(zstd::Decoder::with_buffer(file)?),
+        ArchiveFormat::Zip => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "不是 tar 包"));
+        }
+    };
+    Ok(tar::Archive::new(reader))
+}
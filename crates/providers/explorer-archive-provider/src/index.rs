@@ -0,0 +1,325 @@
+//! 压缩包目录
+//!
+//! 读取一遍压缩包，记录所有成员的路径、大小和修改时间。压缩包中没有单独记录的
+//! 上级目录会被补上；路径不安全（绝对路径或含 `..`）的成员不会出现在目录中，
+//! 而是单独记录下来，解压时据此拒绝整个压缩包。
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{Local, NaiveDate, TimeZone};
+use explorer_storage::{ArchiveFormat, StorageError, StorageResult};
+
+use crate::format::open_tar;
+
+/// 压缩包中的一个成员
+#[derive(Debug, Clone)]
+pub(crate) struct Member {
+    /// 在压缩包中的路径（以 `/` 分隔，没有开头和结尾的 `/`）
+    pub path: String,
+    pub kind: MemberKind,
+    pub size: u64,
+    pub modified: SystemTime,
+    /// Unix 权限
+    pub mode: Option<u32>,
+    /// 符号链接的目标
+    pub link_target: Option<String>,
+    /// 在压缩包中的序号，补上的目录为 `None`
+    pub position: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MemberKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// 一个压缩包的目录
+pub(crate) struct ArchiveIndex {
+    pub format: ArchiveFormat,
+    members: Vec<Member>,
+    by_path: HashMap<String, usize>,
+    /// 目录路径到其直接成员的映射，根目录为空字符串
+    children: HashMap<String, Vec<usize>>,
+    /// 路径不安全的成员
+    pub unsafe_paths: Vec<String>,
+}
+
+impl ArchiveIndex {
+    /// 读取压缩包的目录
+    pub fn read(path: &Path, format: ArchiveFormat) -> StorageResult<Self> {
+        let modified = path
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut unsafe_paths = vec![];
+        let members = match format {
+            ArchiveFormat::Zip => read_zip(path, &mut unsafe_paths)?,
+            _ => read_tar(path, format, &mut unsafe_paths)?,
+        };
+        if !unsafe_paths.is_empty() {
+            tracing::warn!(
+                "压缩包 {} 中有 {} 个不安全的路径",
+                path.display(),
+                unsafe_paths.len()
+            );
+        }
+
+        let mut index = Self {
+            format,
+            members: vec![],
+            by_path: HashMap::new(),
+            children: HashMap::from([(String::new(), vec![])]),
+            unsafe_paths,
+        };
+        for member in members {
+            index.insert_parents(&member.path, modified);
+            index.insert(member);
+        }
+        Ok(index)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Member> {
+        self.by_path.get(path).map(|&ix| &self.members[ix])
+    }
+
+    /// 目录的直接成员，`path` 为空时是压缩包的顶层
+    pub fn children(&self, path: &str) -> Option<impl Iterator<Item = &Member>> {
+        self.children
+            .get(path)
+            .map(|children| children.iter().map(|&ix| &self.members[ix]))
+    }
+
+    /// 所有文件的总大小（解压后）
+    pub fn total_size(&self) -> u64 {
+        self.members
+            .iter()
+            .filter(|member| member.kind == MemberKind::File)
+            .map(|member| member.size)
+            .sum()
+    }
+
+    /// 添加成员，同名成员以后出现的为准
+    fn insert(&mut self, member: Member) {
+        if let Some(&ix) = self.by_path.get(&member.path) {
+            // 补上的目录被真正的目录条目替换时保留其子成员
+            if member.kind == MemberKind::Directory {
+                self.children.entry(member.path.clone()).or_default();
+            }
+            self.members[ix] = member;
+            return;
+        }
+        let ix = self.members.len();
+        let parent = parent_of(&member.path).to_string();
+        self.children.entry(parent).or_default().push(ix);
+        if member.kind == MemberKind::Directory {
+            self.children.entry(member.path.clone()).or_default();
+        }
+        self.by_path.insert(member.path.clone(), ix);
+        self.members.push(member);
+    }
+
+    /// 补上压缩包中没有单独记录的上级目录
+    fn insert_parents(&mut self, path: &str, modified: SystemTime) {
+        let parent = parent_of(path);
+        if parent.is_empty() || self.by_path.contains_key(parent) {
+            return;
+        }
+        self.insert_parents(parent, modified);
+        self.insert(Member {
+            path: parent.to_string(),
+            kind: MemberKind::Directory,
+            size: 0,
+            modified,
+            mode: None,
+            link_target: None,
+            position: None,
+        });
+    }
+}
+
+/// 成员的上级目录路径，顶层成员为空字符串
+fn parent_of(path: &str) -> &str {
+    path.rsplit_once('/')
+        .map(|(parent, _)| parent)
+        .unwrap_or("")
+}
+
+/// 规范化成员路径（去掉 `.` 和多余的 `/`），绝对路径或含 `..` 时返回 `None`
+pub(crate) fn normalize(name: &str) -> Option<String> {
+    let is_absolute = name.starts_with(['/', '\\'])
+        || name.as_bytes().get(1) == Some(&b':') && name.as_bytes()[0].is_ascii_alphabetic();
+    if is_absolute {
+        return None;
+    }
+    let mut segments = vec![];
+    for segment in name.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+pub(crate) fn invalid_data(archive: &Path, err: impl std::fmt::Display) -> StorageError {
+    StorageError::Other(format!("无法读取压缩包 {}: {}", archive.display(), err))
+}
+
+fn read_zip(path: &Path, unsafe_paths: &mut Vec<String>) -> StorageResult<Vec<Member>> {
+    let file = BufReader::new(File::open(path)?);
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| invalid_data(path, e))?;
+    let mut members = Vec::with_capacity(archive.len());
+    for position in 0..archive.len() {
+        let entry = archive
+            .by_index_raw(position)
+            .map_err(|e| invalid_data(path, e))?;
+        let Some(member_path) = normalize(entry.name()) else {
+            unsafe_paths.push(entry.name().to_string());
+            continue;
+        };
+        if member_path.is_empty() {
+            continue;
+        }
+        let mode = entry.unix_mode();
+        let kind = if entry.is_dir() {
+            MemberKind::Directory
+        } else if mode.is_some_and(|mode| mode & 0o170000 == 0o120000) {
+            MemberKind::Symlink
+        } else {
+            MemberKind::File
+        };
+        let mut member = Member {
+            path: member_path,
+            kind,
+            size: entry.size(),
+            modified: entry
+                .last_modified()
+                .and_then(zip_time)
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+            mode: mode.map(|mode| mode & 0o7777),
+            link_target: None,
+            position: Some(position),
+        };
+        drop(entry);
+        // zip 中符号链接的目标保存为成员的内容
+        if kind == MemberKind::Symlink {
+            let mut target = String::new();
+            archive
+                .by_index(position)
+                .map_err(|e| invalid_data(path, e))?
+                .read_to_string(&mut target)
+                .map_err(|e| invalid_data(path, e))?;
+            member.link_target = Some(target);
+        }
+        members.push(member);
+    }
+    Ok(members)
+}
+
+/// zip 中的时间没有时区，按本地时间解释
+pub(crate) fn zip_time(time: zip::DateTime) -> Option<SystemTime> {
+    let date = NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?;
+    let time = date.and_hms_opt(
+        time.hour().into(),
+        time.minute().into(),
+        time.second().into(),
+    )?;
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+fn read_tar(
+    path: &Path,
+    format: ArchiveFormat,
+    unsafe_paths: &mut Vec<String>,
+) -> StorageResult<Vec<Member>> {
+    let mut archive = open_tar(path, format)?;
+    let mut members = vec![];
+    for (position, entry) in archive
+        .entries()
+        .map_err(|e| invalid_data(path, e))?
+        .enumerate()
+    {
+        let entry = entry.map_err(|e| invalid_data(path, e))?;
+        let name = entry.path_bytes();
+        let name = String::from_utf8_lossy(&name);
+        let Some(member_path) = normalize(&name) else {
+            unsafe_paths.push(name.to_string());
+            continue;
+        };
+        if member_path.is_empty() {
+            continue;
+        }
+        let header = entry.header();
+        let kind = match header.entry_type() {
+            tar::EntryType::Directory => MemberKind::Directory,
+            tar::EntryType::Symlink | tar::EntryType::Link => MemberKind::Symlink,
+            tar::EntryType::Regular | tar::EntryType::Continuous | tar::EntryType::GNUSparse => {
+                MemberKind::File
+            }
+            // 字符设备、FIFO 等特殊条目不显示
+            _ => continue,
+        };
+        members.push(Member {
+            path: member_path,
+            kind,
+            size: header.size().unwrap_or(0),
+            modified: header
+                .mtime()
+                .map(|mtime| SystemTime::UNIX_EPOCH + Duration::from_secs(mtime))
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+            mode: header.mode().ok().map(|mode| mode & 0o7777),
+            link_target: entry
+                .link_name()
+                .ok()
+                .flatten()
+                .map(|target| target.display().to_string()),
+            position: Some(position),
+        });
+    }
+    Ok(members)
+}
+
+/// 把成员的内容写入 `writer`，返回写入的字节数
+pub(crate) fn read_member(
+    archive: &Path,
+    format: ArchiveFormat,
+    member: &Member,
+    writer: &mut (dyn Write + Send),
+) -> StorageResult<u64> {
+    let Some(position) = member.position.filter(|_| member.kind == MemberKind::File) else {
+        return Err(StorageError::Other(format!("不是文件: {}", member.path)));
+    };
+    match format {
+        ArchiveFormat::Zip => {
+            let file = BufReader::new(File::open(archive)?);
+            let mut zip = zip::ZipArchive::new(file).map_err(|e| invalid_data(archive, e))?;
+            let mut entry = zip
+                .by_index(position)
+                .map_err(|e| invalid_data(archive, e))?;
+            Ok(io::copy(&mut entry, writer)?)
+        }
+        _ => {
+            // tar 包只能从头顺序读取
+            let mut tar = open_tar(archive, format)?;
+            let entries = tar.entries().map_err(|e| invalid_data(archive, e))?;
+            for (ix, entry) in entries.enumerate() {
+                let mut entry = entry.map_err(|e| invalid_data(archive, e))?;
+                if ix == position {
+                    return Ok(io::copy(&mut entry, writer)?);
+                }
+            }
+            Err(StorageError::PathNotFound(member.path.clone()))
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! 压缩包存储提供者
+//!
+//! 包装另一个存储提供者（本地文件系统），让 zip 和 tar（gz、bz2、xz、zst）压缩包
+//! 可以像文件夹一样浏览。压缩包内的路径为压缩包路径加成员路径，例如
+//! `/home/user/a.tar.gz/src/main.rs`；其他路径原样交给被包装的提供者。
+//!
+//! 压缩包内是只读的，但可以创建新的压缩包（zip、tar.gz、tar.zst）和把压缩包解压到
+//! 本机目录。读取过的压缩包目录会被缓存，压缩包的修改时间或大小变化后重新读取。
+
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use mime_guess::from_path;
+
+use explorer_storage::*;
+
+pub use format::is_archive;
+use index::{ArchiveIndex, Member, MemberKind, read_member};
+
+mod create;
+mod extract;
+mod format;
+mod index;
+
+/// 最多缓存的压缩包目录数
+const MAX_CACHED: usize = 16;
+
+/// 读取文件时在后台线程和调用方之间缓冲的数据块数
+const READ_CHUNKS: usize = 16;
+
+/// 缓存的压缩包目录
+#[derive(Default)]
+struct IndexCache {
+    entries: HashMap<PathBuf, CachedIndex>,
+    /// 每次使用缓存时递增，用来找出最久未使用的目录
+    clock: u64,
+}
+
+/// 压缩包目录及读取时压缩包的修改时间和大小
+struct CachedIndex {
+    modified: SystemTime,
+    size: u64,
+    index: Arc<ArchiveIndex>,
+    used: u64,
+}
+
+/// 压缩包存储提供者
+pub struct ArchiveProvider {
+    inner: Arc<dyn StorageProvider>,
+    cache: Arc<Mutex<IndexCache>>,
+}
+
+impl ArchiveProvider {
+    /// 包装存储提供者，只有路径为本机路径的提供者中的压缩包可以浏览
+    pub fn new(inner: Arc<dyn StorageProvider>) -> Self {
+        Self {
+            inner,
+            cache: Default::default(),
+        }
+    }
+
+    /// 拆分压缩包内的路径，返回压缩包路径和成员路径（压缩包本身为空字符串）
+    ///
+    /// 需要检查路径中的哪一级是压缩包文件，在后台线程中执行。
+    async fn locate(&self, path: &str) -> Option<(PathBuf, String)> {
+        if !self.inner.capabilities().local_paths {
+            return None;
+        }
+        let path = PathBuf::from(path);
+        smol::unblock(move || split_archive_path(&path)).await
+    }
+
+    /// 压缩包内的路径（不包括压缩包本身）
+    async fn inside_archive(&self, path: &str) -> bool {
+        self.locate(path)
+            .await
+            .is_some_and(|(_, member)| !member.is_empty())
+    }
+}
+
+fn split_archive_path(path: &Path) -> Option<(PathBuf, String)> {
+    let archive = path
+        .ancestors()
+        .find(|ancestor| is_archive(ancestor) && ancestor.is_file())?;
+    let member = path
+        .strip_prefix(archive)
+        .ok()?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((archive.to_path_buf(), member))
+}
+
+/// 读取压缩包目录（优先使用缓存），会读取文件，需要在后台线程中调用
+fn load_index(cache: &Mutex<IndexCache>, archive: &Path) -> StorageResult<Arc<ArchiveIndex>> {
+    let format = ArchiveFormat::from_path(archive)
+        .ok_or_else(|| StorageError::Other(format!("不是压缩包: {}", archive.display())))?;
+    let metadata = archive.metadata()?;
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let size = metadata.len();
+
+    {
+        let mut cache = cache.lock().unwrap();
+        cache.clock += 1;
+        let clock = cache.clock;
+        if let Some(cached) = cache.entries.get_mut(archive)
+            && (cached.modified, cached.size) == (modified, size)
+        {
+            cached.used = clock;
+            return Ok(cached.index.clone());
+        }
+    }
+
+    tracing::info!("读取压缩包: {}", archive.display());
+    let index = Arc::new(ArchiveIndex::read(archive, format)?);
+    let mut cache = cache.lock().unwrap();
+    if cache.entries.len() >= MAX_CACHED && !cache.entries.contains_key(archive) {
+        // 移除最久未使用的目录
+        let oldest = cache
+            .entries
+            .iter()
+            .min_by_key(|(_, cached)| cached.used)
+            .map(|(path, _)| path.clone());
+        if let Some(oldest) = oldest {
+            cache.entries.remove(&oldest);
+        }
+    }
+    cache.clock += 1;
+    let cached = CachedIndex {
+        modified,
+        size,
+        index: index.clone(),
+        used: cache.clock,
+    };
+    cache.entries.insert(archive.to_path_buf(), cached);
+    Ok(index)
+}
+
+/// 压缩包成员对应的条目
+fn member_item(archive: &Path, member: &Member) -> FileItem {
+    let path = archive.join(&member.path);
+    let name = member
+        .path
+        .rsplit('/')
+        .next()
+        .unwrap_or(&member.path)
+        .to_string();
+    let item_type = match member.kind {
+        MemberKind::File => ItemType::File,
+        MemberKind::Directory => ItemType::Directory,
+        MemberKind::Symlink => ItemType::Symlink,
+    };
+    let mime_type = if item_type == ItemType::File {
+        from_path(&path).first().map(|mime| mime.to_string())
+    } else {
+        None
+    };
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: path.display().to_string(),
+        item_type,
+        size: member.size,
+        modified: member.modified,
+        metadata: EntryMetadata {
+            permissions: member.mode,
+            mime_type,
+            // 压缩包中的链接不跟随，目标的类型未知
+            link_target: member.link_target.clone(),
+            custom_fields: HashMap::from([("archive".to_string(), archive.display().to_string())]),
+            ..Default::default()
+        },
+    }
+}
+
+/// 读取时更新进度
+pub(crate) struct ProgressReader<'a, R> {
+    inner: &'a mut R,
+    progress: &'a Progress,
+}
+
+impl<'a, R: Read> ProgressReader<'a, R> {
+    pub fn new(inner: &'a mut R, progress: &'a Progress) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.progress.advance(read as u64);
+        Ok(read)
+    }
+}
+
+/// 把写入的数据分块发送到通道，接收方断开时写入失败
+struct ChunkSender(smol::channel::Sender<Vec<u8>>);
+
+impl Write for ChunkSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .send_blocking(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 压缩包内不支持的修改操作
+fn read_only(operation: &str, path: &str) -> StorageError {
+    StorageError::Unsupported(format!("{}（压缩包是只读的）: {}", operation, path))
+}
+
+#[async_trait]
+impl StorageProvider for ArchiveProvider {
+    async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
+        self.inner.get_roots().await
+    }
+
+    async fn get_metadata(&self, path: &str) -> StorageResult<FileItem> {
+        let Some((archive, member)) = self
+            .locate(path)
+            .await
+            .filter(|(_, member)| !member.is_empty())
+        else {
+            return self.inner.get_metadata(path).await;
+        };
+        let cache = self.cache.clone();
+        let path = path.to_string();
+        smol::unblock(move || {
+            let index = load_index(&cache, &archive)?;
+            index
+                .get(&member)
+                .map(|member| member_item(&archive, member))
+                .ok_or(StorageError::PathNotFound(path))
+        })
+        .await
+    }
+
+    async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>> {
+        let Some((archive, member)) = self.locate(path).await else {
+            return self.inner.list_entries(path).await;
+        };
+        let cache = self.cache.clone();
+        let path = path.to_string();
+        smol::unblock(move || {
+            let index = load_index(&cache, &archive)?;
+            let children = index.children(&member).ok_or_else(|| {
+                if index.get(&member).is_some() {
+                    StorageError::Other(format!("路径不是目录: {}", path))
+                } else {
+                    StorageError::PathNotFound(path)
+                }
+            })?;
+            let mut entries: Vec<FileItem> = children
+                .map(|member| member_item(&archive, member))
+                .collect();
+            // 压缩包中的成员按写入的顺序排列
+            sort_entries(&mut entries);
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn exists(&self, path: &str) -> StorageResult<bool> {
+        let Some((archive, member)) = self
+            .locate(path)
+            .await
+            .filter(|(_, member)| !member.is_empty())
+        else {
+            return self.inner.exists(path).await;
+        };
+        let cache = self.cache.clone();
+        smol::unblock(move || Ok(load_index(&cache, &archive)?.get(&member).is_some())).await
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        let Some((archive, member)) = self
+            .locate(path)
+            .await
+            .filter(|(_, member)| !member.is_empty())
+        else {
+            return self.inner.read_file(path, writer).await;
+        };
+        // `writer` 是借用的，不能交给 `smol::unblock`：在后台线程中解压，
+        // 通过有界通道把数据块交给这里写入
+        let cache = self.cache.clone();
+        let path = path.to_string();
+        let (sender, receiver) = smol::channel::bounded(READ_CHUNKS);
+        let task = smol::unblock(move || {
+            let index = load_index(&cache, &archive)?;
+            let member = index.get(&member).ok_or(StorageError::PathNotFound(path))?;
+            read_member(&archive, index.format, member, &mut ChunkSender(sender))
+        });
+        while let Ok(chunk) = receiver.recv().await {
+            writer.write_all(&chunk)?;
+        }
+        task.await
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        if self.inside_archive(path).await {
+            return Err(read_only("写入文件", path));
+        }
+        self.inner.write_file(path, reader).await
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        if self.inside_archive(path).await {
+            return Err(read_only("创建目录", path));
+        }
+        self.inner.create_dir(path).await
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        if self.inside_archive(path).await {
+            return Err(read_only("创建文件", path));
+        }
+        self.inner.create_file(path).await
+    }
+
+    async fn create_symlink(&self, target: &str, path: &str) -> StorageResult<()> {
+        if self.inside_archive(path).await {
+            return Err(read_only("创建符号链接", path));
+        }
+        self.inner.create_symlink(target, path).await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        if self.inside_archive(from).await || self.inside_archive(to).await {
+            return Err(read_only("重命名", from));
+        }
+        self.inner.rename(from, to).await
+    }
+
+    async fn move_entry(&self, from: &str, to: &str) -> StorageResult<()> {
+        if self.inside_archive(from).await || self.inside_archive(to).await {
+            return Err(read_only("移动", from));
+        }
+        self.inner.move_entry(from, to).await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        if self.inside_archive(from).await || self.inside_archive(to).await {
+            return Err(read_only("复制", from));
+        }
+        self.inner.copy(from, to).await
+    }
+
+    async fn trash(&self, path: &str) -> StorageResult<String> {
+        if self.inside_archive(path).await {
+            return Err(read_only("移入回收站", path));
+        }
+        self.inner.trash(path).await
+    }
+
+    async fn restore(&self, trashed: &str, original: &str) -> StorageResult<()> {
+        self.inner.restore(trashed, original).await
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        if self.inside_archive(path).await {
+            return Err(read_only("删除", path));
+        }
+        self.inner.delete(path).await
+    }
+
+    async fn compress(
+        &self,
+        sources: &[String],
+        target: &str,
+        options: CompressOptions,
+        progress: Arc<Progress>,
+    ) -> StorageResult<()> {
+        if !self.capabilities().can_archive {
+            return Err(StorageError::Unsupported(format!("压缩: {}", target)));
+        }
+        for source in sources {
+            if self.inside_archive(source).await {
+                return Err(StorageError::Unsupported(format!(
+                    "压缩压缩包中的条目: {}",
+                    source
+                )));
+            }
+        }
+        if self.inside_archive(target).await {
+            return Err(read_only("压缩", target));
+        }
+        let sources: Vec<PathBuf> = sources.iter().map(PathBuf::from).collect();
+        let target = PathBuf::from(target);
+        smol::unblock(move || create::compress(&sources, &target, options, &progress)).await
+    }
+
+    async fn extract(
+        &self,
+        archive: &str,
+        target_dir: &str,
+        progress: Arc<Progress>,
+    ) -> StorageResult<Vec<String>> {
+        if !self.capabilities().can_archive || self.inside_archive(archive).await {
+            return Err(StorageError::Unsupported(format!("解压: {}", archive)));
+        }
+        if self.inside_archive(target_dir).await {
+            return Err(read_only("解压", target_dir));
+        }
+        let cache = self.cache.clone();
+        let (archive, target_dir) = (PathBuf::from(archive), PathBuf::from(target_dir));
+        smol::unblock(move || {
+            let index = load_index(&cache, &archive)?;
+            let created = extract::extract(&archive, &index, &target_dir, &progress)?;
+            Ok(created
+                .into_iter()
+                .map(|path| path.display().to_string())
+                .collect())
+        })
+        .await
+    }
+
+    async fn get_space(&self, path: &str) -> StorageResult<SpaceInfo> {
+        match self.locate(path).await {
+            Some((archive, _)) => self.inner.get_space(&archive.display().to_string()).await,
+            None => self.inner.get_space(path).await,
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        let capabilities = self.inner.capabilities();
+        ProviderCapabilities {
+            can_archive: capabilities.local_paths,
+            ..capabilities
+        }
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        self.inner.provider_type()
+    }
+}
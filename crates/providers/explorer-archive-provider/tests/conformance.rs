@@ -0,0 +1,98 @@
+//! 压缩包存储提供者的一致性测试
+
+use std::{
+    fs::{self, File},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use explorer_archive_provider::ArchiveProvider;
+use explorer_local_provider::LocalFileSystemProvider;
+use explorer_storage::{
+    ArchiveFormat, CompressOptions, CompressionLevel, Progress, StorageProvider,
+    conformance::{self, Fixture, TREE, TempDir},
+};
+
+/// 临时目录中由测试目录树压缩成的压缩包
+struct ArchiveFixture {
+    _dir: TempDir,
+    archive: String,
+    provider: ArchiveProvider,
+}
+
+impl ArchiveFixture {
+    fn new(format: ArchiveFormat) -> Self {
+        let dir = TempDir::new("archive-test");
+        let tree = dir.join("tree");
+        fs::create_dir_all(&tree).unwrap();
+        conformance::create_tree(&tree, false).unwrap();
+
+        let provider = ArchiveProvider::new(Arc::new(LocalFileSystemProvider::new()));
+        let top_level: Vec<&str> = TREE
+            .iter()
+            .map(|(relative, _)| *relative)
+            .filter(|relative| !relative.contains('/'))
+            .collect();
+        let archive = dir.join(format!("tree{}", format.extension()));
+        if format == ArchiveFormat::Tar {
+            // 提供者不创建未压缩的 tar 包
+            let mut builder = tar::Builder::new(File::create(&archive).unwrap());
+            for name in top_level {
+                let path = tree.join(name);
+                if path.is_dir() {
+                    builder.append_dir_all(name, &path).unwrap();
+                } else {
+                    builder.append_path_with_name(&path, name).unwrap();
+                }
+            }
+            builder.finish().unwrap();
+        } else {
+            let sources: Vec<String> = top_level
+                .iter()
+                .map(|name| tree.join(name).display().to_string())
+                .collect();
+            let options = CompressOptions {
+                format,
+                level: CompressionLevel::Normal,
+            };
+            smol::block_on(provider.compress(
+                &sources,
+                &archive.display().to_string(),
+                options,
+                Arc::new(Progress::default()),
+            ))
+            .unwrap();
+        }
+        Self {
+            _dir: dir,
+            archive: archive.display().to_string(),
+            provider,
+        }
+    }
+}
+
+impl Fixture for ArchiveFixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        self.archive.clone()
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn join(&self, dir: &str, name: &str) -> String {
+        PathBuf::from(dir).join(name).display().to_string()
+    }
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    for format in [ArchiveFormat::Zip, ArchiveFormat::Tar, ArchiveFormat::TarGz] {
+        let fixture = ArchiveFixture::new(format);
+        smol::block_on(conformance::run(&fixture));
+    }
+}
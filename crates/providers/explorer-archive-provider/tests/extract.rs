@@ -0,0 +1,184 @@
+//! 解压的测试：正常的 zip、tar 和 tar.gz 能解压，不安全的压缩包被拒绝且不写到目标目录之外
+
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use explorer_archive_provider::ArchiveProvider;
+use explorer_local_provider::LocalFileSystemProvider;
+use explorer_storage::{
+    ArchiveFormat, Progress, StorageError, StorageProvider, StorageResult, conformance::TempDir,
+};
+
+/// 压缩包中的成员，写入时原样保存路径（不做检查）
+enum Member<'a> {
+    File(&'a str, &'a str),
+    Dir(&'a str),
+    Link(&'a str, &'a str),
+}
+
+const FORMATS: [ArchiveFormat; 3] = [ArchiveFormat::Zip, ArchiveFormat::Tar, ArchiveFormat::TarGz];
+
+fn write_zip(path: &Path, members: &[Member]) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    for member in members {
+        match *member {
+            Member::File(name, content) => {
+                zip.start_file(name, options).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+            Member::Dir(name) => zip.add_directory(name, options).unwrap(),
+            Member::Link(name, target) => zip.add_symlink(name, target, options).unwrap(),
+        }
+    }
+    zip.finish().unwrap();
+}
+
+fn write_tar(writer: impl Write, members: &[Member]) {
+    let mut tar = tar::Builder::new(writer);
+    for member in members {
+        let mut header = tar::Header::new_gnu();
+        let (name, data) = match *member {
+            Member::File(name, content) => {
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_mode(0o644);
+                (name, content.as_bytes())
+            }
+            Member::Dir(name) => {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_mode(0o755);
+                (name, &[][..])
+            }
+            Member::Link(name, target) => {
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_mode(0o777);
+                header.set_link_name_literal(target).unwrap();
+                (name, &[][..])
+            }
+        };
+        // `set_path` 会拒绝不安全的路径，直接写入名称字段
+        let field = &mut header.as_old_mut().name;
+        field.fill(0);
+        field[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        tar.append(&header, data).unwrap();
+    }
+    tar.into_inner().unwrap().flush().unwrap();
+}
+
+/// 在 `dir` 中写入压缩包，返回压缩包路径
+fn write_archive(dir: &Path, format: ArchiveFormat, members: &[Member]) -> PathBuf {
+    let path = dir.join(format!("archive{}", format.extension()));
+    match format {
+        ArchiveFormat::Zip => write_zip(&path, members),
+        ArchiveFormat::Tar => write_tar(File::create(&path).unwrap(), members),
+        ArchiveFormat::TarGz => write_tar(
+            flate2::write::GzEncoder::new(
+                File::create(&path).unwrap(),
+                flate2::Compression::default(),
+            ),
+            members,
+        ),
+        format => unreachable!("{:?}", format),
+    }
+    path
+}
+
+fn extract(archive: &Path, target_dir: &Path) -> StorageResult<Vec<String>> {
+    let provider = ArchiveProvider::new(Arc::new(LocalFileSystemProvider::new()));
+    smol::block_on(provider.extract(
+        &archive.display().to_string(),
+        &target_dir.display().to_string(),
+        Arc::new(Progress::default()),
+    ))
+}
+
+/// 每种格式都拒绝解压，目标目录被移除，目录中只剩压缩包本身
+fn assert_rejected(members: &[Member]) {
+    for format in FORMATS {
+        let dir = TempDir::new("extract-test");
+        let archive = write_archive(&dir, format, members);
+        let target = dir.join("out");
+
+        let result = extract(&archive, &target);
+        assert!(
+            matches!(result, Err(StorageError::UnsafeArchivePath(_))),
+            "{:?}: {:?}",
+            format,
+            result
+        );
+        let left: Vec<PathBuf> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(left, [archive], "{:?}", format);
+    }
+}
+
+#[test]
+fn extracts_archives() {
+    for format in FORMATS {
+        let dir = TempDir::new("extract-test");
+        let archive = write_archive(
+            &dir,
+            format,
+            &[
+                Member::Dir("docs/"),
+                Member::File("docs/readme.txt", "read me"),
+                Member::Link("docs/link", "readme.txt"),
+                Member::Link("up", "docs/../docs"),
+                Member::File("empty.txt", ""),
+            ],
+        );
+        let target = dir.join("out");
+
+        let created = extract(&archive, &target).unwrap();
+        assert_eq!(created, [target.display().to_string()], "{:?}", format);
+        let read = |path: &str| fs::read_to_string(target.join(path)).unwrap();
+        assert_eq!(read("docs/readme.txt"), "read me");
+        assert_eq!(read("empty.txt"), "");
+        #[cfg(unix)]
+        {
+            assert_eq!(read("docs/link"), "read me");
+            assert_eq!(read("up/readme.txt"), "read me");
+        }
+    }
+}
+
+#[test]
+fn rejects_parent_paths() {
+    assert_rejected(&[Member::File("ok.txt", "ok"), Member::File("../x", "evil")]);
+}
+
+#[test]
+fn rejects_absolute_paths() {
+    let path = std::env::temp_dir().join(format!("explorer-extract-abs-{}", std::process::id()));
+    let name = path.display().to_string();
+    assert_rejected(&[Member::File(&name, "evil")]);
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn rejects_links_out_of_target() {
+    assert_rejected(&[Member::Link("evil", "..")]);
+    assert_rejected(&[Member::Link("evil", "/tmp")]);
+    // 每个链接的目标单独看都在目标目录之内，但 `b` 指向目标目录本身
+    assert_rejected(&[Member::Link("b", "."), Member::Link("a", "b/..")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn rejects_writing_through_links() {
+    assert_rejected(&[Member::Link("evil", ".."), Member::File("evil/x", "evil")]);
+    assert_rejected(&[
+        Member::Dir("docs/"),
+        Member::Link("docs/evil", "../.."),
+        Member::File("docs/evil/x", "evil"),
+    ]);
+}
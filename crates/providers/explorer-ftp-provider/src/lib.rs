@@ -0,0 +1,537 @@
+//! FTP/FTPS 存储提供者
+//!
+//! 路径形如 `ftp://user@host:21/pub/file`（FTPS 为 `ftps://`），每个已保存的连接是一个
+//! 网络驱动器根节点。数据连接只使用被动模式，目录列表优先使用 MLSD，服务器不支持时
+//! 解析 LIST 的输出。
+//!
+//! 下载中断时用 REST 从已收到的位置继续；上传可以用 [`FtpProvider::file_size`] 查询
+//! 服务器上已有的字节数，再用 [`FtpProvider::write_file_from`] 续传。
+
+use std::{
+    io::{self, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use mime_guess::from_path;
+use serde::{Deserialize, Serialize};
+
+use explorer_storage::*;
+
+use listing::{Entry, Kind};
+use session::{Session, join};
+
+mod listing;
+mod session;
+
+/// 路径前缀
+pub const SCHEME: &str = "ftp://";
+
+/// FTPS 的路径前缀
+pub const SECURE_SCHEME: &str = "ftps://";
+
+/// 每个服务器最多保留的空闲会话数
+const MAX_IDLE_SESSIONS: usize = 4;
+
+/// 一次下载中最多续传的次数
+const MAX_RESUMES: usize = 3;
+
+/// 连接的加密方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FtpSecurity {
+    /// 不加密，密码以明文传输
+    #[default]
+    None,
+    /// 连接后用 `AUTH TLS` 升级为 TLS
+    Explicit,
+    /// 连接时即使用 TLS（通常为 990 端口）
+    Implicit,
+}
+
+/// 已保存的 FTP 连接
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FtpConnection {
+    /// 显示名称
+    pub name: String,
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    #[serde(default = "default_user")]
+    pub user: String,
+    /// 密码（以明文保存），匿名登录时可以为空
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub security: FtpSecurity,
+    /// 额外信任的 CA 证书（PEM），用于使用自签名证书的服务器
+    #[serde(default)]
+    pub certificate: Option<PathBuf>,
+    /// 根节点打开的目录
+    #[serde(default = "default_root")]
+    pub root: String,
+}
+
+fn default_port() -> u16 {
+    21
+}
+
+fn default_user() -> String {
+    "anonymous".to_string()
+}
+
+fn default_root() -> String {
+    "/".to_string()
+}
+
+impl FtpConnection {
+    /// 连接的 `user@host:port` 部分
+    pub fn authority(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+
+    /// 服务器上的路径对应的完整路径
+    pub fn url(&self, remote: &str) -> String {
+        let scheme = match self.security {
+            FtpSecurity::None => SCHEME,
+            _ => SECURE_SCHEME,
+        };
+        format!("{}{}{}", scheme, self.authority(), remote)
+    }
+}
+
+/// 路径是否为 FTP 或 FTPS 路径
+pub fn is_ftp_path(path: &str) -> bool {
+    path.starts_with(SCHEME) || path.starts_with(SECURE_SCHEME)
+}
+
+/// 一个服务器的会话池
+struct Pool {
+    connection: FtpConnection,
+    idle: Mutex<Vec<Session>>,
+}
+
+impl Pool {
+    /// 用一个会话执行 `f`
+    ///
+    /// 复用的空闲会话可能已被服务器断开（空闲超时），此时重新连接后再执行一次
+    fn run<T>(&self, mut f: impl FnMut(&mut Session) -> StorageResult<T>) -> StorageResult<T> {
+        let idle = self.idle.lock().unwrap().pop();
+        let reused = idle.is_some();
+        let mut session = match idle {
+            Some(session) => session,
+            None => Session::connect(&self.connection)?,
+        };
+
+        let mut result = f(&mut session);
+        if reused && matches!(result, Err(StorageError::IoError(_))) && !session.is_usable() {
+            tracing::warn!("FTP 连接已断开，重新连接: {}", self.connection.authority());
+            session = Session::connect(&self.connection)?;
+            result = f(&mut session);
+        }
+        self.put(session);
+        result
+    }
+
+    /// 归还会话
+    fn put(&self, session: Session) {
+        if session.is_usable() {
+            let mut idle = self.idle.lock().unwrap();
+            if idle.len() < MAX_IDLE_SESSIONS {
+                idle.push(session);
+            }
+        }
+    }
+}
+
+/// FTP 存储提供者
+pub struct FtpProvider {
+    connections: Vec<FtpConnection>,
+    pools: Vec<Arc<Pool>>,
+}
+
+impl FtpProvider {
+    /// 使用已保存的连接创建提供者
+    pub fn new(connections: Vec<FtpConnection>) -> Self {
+        let pools = connections
+            .iter()
+            .map(|connection| {
+                Arc::new(Pool {
+                    connection: connection.clone(),
+                    idle: Mutex::new(vec![]),
+                })
+            })
+            .collect();
+        Self { connections, pools }
+    }
+
+    pub fn connections(&self) -> &[FtpConnection] {
+        &self.connections
+    }
+
+    /// 拆分路径，返回服务器的会话池和服务器上的路径
+    fn locate(&self, path: &str) -> StorageResult<(Arc<Pool>, String)> {
+        if !is_ftp_path(path) {
+            return Err(StorageError::Other(format!("不是 FTP 路径: {}", path)));
+        }
+        for pool in &self.pools {
+            let Some(remote) = path.strip_prefix(&pool.connection.url("")) else {
+                continue;
+            };
+            if !remote.is_empty() && !remote.starts_with('/') {
+                continue;
+            }
+            // 去掉末尾的 `/`，使同一目录只有一种写法
+            let remote = match remote.trim_end_matches('/') {
+                "" => "/",
+                trimmed => trimmed,
+            };
+            return Ok((pool.clone(), remote.to_string()));
+        }
+        let authority = path.split("://").nth(1).unwrap_or(path);
+        let authority = authority.split('/').next().unwrap_or(authority);
+        Err(StorageError::Other(format!(
+            "没有保存的连接: {}",
+            authority
+        )))
+    }
+
+    /// 拆分同一服务器上的两个路径
+    fn locate_pair(&self, from: &str, to: &str) -> StorageResult<(Arc<Pool>, String, String)> {
+        let (pool, from_remote) = self.locate(from)?;
+        let (to_pool, to_remote) = self.locate(to)?;
+        if !Arc::ptr_eq(&pool, &to_pool) {
+            return Err(StorageError::Unsupported(format!(
+                "在不同服务器之间操作: {} -> {}",
+                from, to
+            )));
+        }
+        Ok((pool, from_remote, to_remote))
+    }
+
+    /// 服务器上文件的大小，用于确定续传的起始位置
+    pub async fn file_size(&self, path: &str) -> StorageResult<u64> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || pool.run(|session| session.size(&remote))).await
+    }
+
+    /// 从 `offset` 开始下载文件，返回写入 `writer` 的字节数
+    ///
+    /// 传输中断时用 REST 从已收到的位置继续，最多重试 [`MAX_RESUMES`] 次
+    pub async fn read_file_from(
+        &self,
+        path: &str,
+        offset: u64,
+        writer: &mut (dyn Write + Send),
+    ) -> StorageResult<u64> {
+        // `writer` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (pool, remote) = self.locate(path)?;
+        let mut writer = Counting {
+            inner: writer,
+            count: 0,
+        };
+        let mut resumes = 0;
+        loop {
+            let result = pool.run(|session| {
+                session.retr(&remote, offset + writer.count, |data| {
+                    Ok(io::copy(data, &mut writer)?)
+                })
+            });
+            match result {
+                Ok(_) => return Ok(writer.count),
+                Err(StorageError::IoError(e)) if resumes < MAX_RESUMES => {
+                    resumes += 1;
+                    tracing::warn!(
+                        "下载中断，从 {} 字节处继续: {}: {}",
+                        offset + writer.count,
+                        path,
+                        e
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 从 `offset` 开始上传，`reader` 应从本地文件的同一位置开始读取
+    ///
+    /// `offset` 通常取自 [`Self::file_size`]，为 0 时创建或覆盖文件
+    pub async fn write_file_from(
+        &self,
+        path: &str,
+        offset: u64,
+        reader: &mut (dyn Read + Send),
+    ) -> StorageResult<u64> {
+        // `reader` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (pool, remote) = self.locate(path)?;
+        let mut reader = Counting {
+            inner: reader,
+            count: 0,
+        };
+        pool.run(|session| {
+            if reader.count > 0 {
+                return Err(StorageError::Other(format!("上传中断: {}", path)));
+            }
+            session.stor(&remote, offset, &mut reader)
+        })
+    }
+}
+
+/// 服务器上的条目对应的文件条目
+fn file_item(connection: &FtpConnection, remote: &str, entry: &Entry) -> FileItem {
+    let name = match remote.rsplit('/').next() {
+        Some("") | None => remote.to_string(),
+        Some(name) => name.to_string(),
+    };
+    let item_type = match entry.kind {
+        Kind::Directory => ItemType::Directory,
+        Kind::Symlink => ItemType::Symlink,
+        Kind::File => ItemType::File,
+    };
+    let mime_type = if item_type == ItemType::File {
+        from_path(&name).first().map(|mime| mime.to_string())
+    } else {
+        None
+    };
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: connection.url(remote),
+        item_type,
+        size: entry.size.unwrap_or(0),
+        modified: entry.modified.unwrap_or(std::time::SystemTime::UNIX_EPOCH),
+        metadata: EntryMetadata {
+            permissions: entry.permissions,
+            mime_type,
+            ..Default::default()
+        },
+    }
+}
+
+/// 目标已存在时返回错误
+fn ensure_absent(session: &mut Session, remote: &str) -> StorageResult<()> {
+    match session.stat(remote) {
+        Ok(_) => Err(StorageError::AlreadyExists(remote.to_string())),
+        Err(StorageError::PathNotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// 递归复制，文件的内容从一个会话的数据连接直接转发到另一个会话
+fn copy_tree(
+    source: &mut Session,
+    target: &mut Session,
+    from: &str,
+    to: &str,
+) -> StorageResult<()> {
+    if source.stat(from)?.kind == Kind::Directory {
+        target.mkdir(to)?;
+        for entry in source.list_dir(from)? {
+            copy_tree(
+                source,
+                target,
+                &join(from, &entry.name),
+                &join(to, &entry.name),
+            )?;
+        }
+        Ok(())
+    } else {
+        source
+            .retr(from, 0, |data| target.stor(to, 0, data))
+            .map(|_| ())
+    }
+}
+
+/// 递归删除，符号链接只删除链接本身
+fn delete_tree(session: &mut Session, remote: &str) -> StorageResult<()> {
+    if session.stat(remote)?.kind == Kind::Directory {
+        for entry in session.list_dir(remote)? {
+            delete_tree(session, &join(remote, &entry.name))?;
+        }
+        session.rmdir(remote)
+    } else {
+        session.delete(remote)
+    }
+}
+
+/// 记录读写过的字节数，用于续传和避免重复上传
+struct Counting<T> {
+    inner: T,
+    count: u64,
+}
+
+impl<T: Write> Write for Counting<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: Read> Read for Counting<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+        Ok(read)
+    }
+}
+
+#[async_trait]
+impl StorageProvider for FtpProvider {
+    async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
+        Ok(self
+            .connections
+            .iter()
+            .map(|connection| {
+                let path = connection.url(&connection.root);
+                RootItem {
+                    id: path.clone(),
+                    name: connection.name.clone(),
+                    path,
+                    provider_type: ProviderType::NetworkDrive,
+                    icon: None,
+                    mount: None,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_metadata(&self, path: &str) -> StorageResult<FileItem> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || {
+            let entry = pool.run(|session| session.stat(&remote))?;
+            Ok(file_item(&pool.connection, &remote, &entry))
+        })
+        .await
+    }
+
+    async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>> {
+        let (pool, remote) = self.locate(path)?;
+        let path = path.to_string();
+        smol::unblock(move || {
+            let entries = pool.run(|session| {
+                // 指向目录的符号链接也可以打开，列出失败时再报告不是目录
+                match session.stat(&remote)?.kind {
+                    Kind::Directory => session.list_dir(&remote),
+                    Kind::Symlink => session
+                        .list_dir(&remote)
+                        .map_err(|_| StorageError::Other(format!("路径不是目录: {}", path))),
+                    Kind::File => Err(StorageError::Other(format!("路径不是目录: {}", path))),
+                }
+            })?;
+
+            let mut entries: Vec<FileItem> = entries
+                .iter()
+                .map(|entry| file_item(&pool.connection, &join(&remote, &entry.name), entry))
+                .collect();
+            sort_entries(&mut entries);
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn exists(&self, path: &str) -> StorageResult<bool> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || {
+            pool.run(|session| match session.stat(&remote) {
+                Ok(_) => Ok(true),
+                Err(StorageError::PathNotFound(_)) => Ok(false),
+                Err(e) => Err(e),
+            })
+        })
+        .await
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        self.read_file_from(path, 0, writer).await
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        self.write_file_from(path, 0, reader).await
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || {
+            pool.run(|session| {
+                ensure_absent(session, &remote)?;
+                session.mkdir(&remote)
+            })
+        })
+        .await
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || {
+            pool.run(|session| {
+                ensure_absent(session, &remote)?;
+                session.stor(&remote, 0, &mut io::empty()).map(|_| ())
+            })
+        })
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (pool, from, to) = self.locate_pair(from, to)?;
+        smol::unblock(move || {
+            pool.run(|session| {
+                ensure_absent(session, &to)?;
+                session.rename(&from, &to)
+            })
+        })
+        .await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (pool, from, to) = self.locate_pair(from, to)?;
+        if to.starts_with(&format!("{}/", from)) {
+            return Err(StorageError::Other(format!(
+                "不能将目录复制到其自身内部: {}",
+                to
+            )));
+        }
+        smol::unblock(move || {
+            pool.run(|source| {
+                ensure_absent(source, &to)?;
+                // FTP 不能在服务器端复制，用第二个会话同时上传
+                let mut target = Session::connect(&pool.connection)?;
+                let result = copy_tree(source, &mut target, &from, &to);
+                pool.put(target);
+                result
+            })
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        if remote == "/" {
+            return Err(StorageError::PermissionDenied(path.to_string()));
+        }
+        smol::unblock(move || pool.run(|session| delete_tree(session, &remote))).await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            can_create: true,
+            can_rename: true,
+            can_copy: true,
+            can_move: true,
+            can_trash: false,
+            can_delete: true,
+            can_symlink: false,
+            can_archive: false,
+            local_paths: false,
+        }
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::NetworkDrive
+    }
+}
@@ -0,0 +1,281 @@
+//! 目录列表的解析
+//!
+//! 优先使用格式固定的 MLSD（RFC 3659）；服务器不支持时退回 LIST，其输出没有标准格式，
+//! 这里识别最常见的 Unix `ls -l` 风格和 Windows（IIS）的 DOS 风格。LIST 中的时间是
+//! 服务器的本地时间，无法得知时区，按 UTC 处理。
+
+use std::time::SystemTime;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+/// 条目的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// 列表中的一个条目
+#[derive(Debug, Clone)]
+pub(crate) struct Entry {
+    pub name: String,
+    pub kind: Kind,
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub permissions: Option<u32>,
+}
+
+/// 解析 MLSD 或 MLST 的一行，如 `type=file;size=12;modify=20240101120000; a.txt`
+///
+/// 当前目录和上级目录（`type=cdir`、`type=pdir`）返回 `None`
+pub(crate) fn parse_mlsd_line(line: &str) -> Option<Entry> {
+    let (facts, name) = line.split_once(' ')?;
+    if name.is_empty() {
+        return None;
+    }
+    let mut entry = Entry {
+        name: name.to_string(),
+        kind: Kind::File,
+        size: None,
+        modified: None,
+        permissions: None,
+    };
+    for fact in facts.split(';').filter(|fact| !fact.is_empty()) {
+        let Some((key, value)) = fact.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "type" => {
+                let value = value.to_ascii_lowercase();
+                entry.kind = match value.as_str() {
+                    "cdir" | "pdir" => return None,
+                    "dir" => Kind::Directory,
+                    "file" => Kind::File,
+                    // 如 `OS.unix=symlink` 或 `OS.unix=slink:/target`
+                    _ if value.contains("link") => Kind::Symlink,
+                    _ => Kind::File,
+                };
+            }
+            "size" => entry.size = value.parse().ok(),
+            "modify" => entry.modified = parse_mlsd_time(value),
+            "unix.mode" => entry.permissions = u32::from_str_radix(value, 8).ok(),
+            _ => {}
+        }
+    }
+    Some(entry)
+}
+
+/// 解析 MLST 回复中的条目，其名称是完整路径，目录的类型可能是 `cdir`
+pub(crate) fn parse_mlst_line(line: &str) -> Option<Entry> {
+    let (facts, path) = line.split_once(' ')?;
+    let facts = facts
+        .split(';')
+        .map(|fact| match fact.to_ascii_lowercase().as_str() {
+            "type=cdir" | "type=pdir" => "type=dir",
+            _ => fact,
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+    let mut entry = parse_mlsd_line(&format!("{} {}", facts, path))?;
+    let name = path.trim_end_matches('/');
+    entry.name = name.rsplit('/').next().unwrap_or(name).to_string();
+    Some(entry)
+}
+
+/// 解析 `YYYYMMDDHHMMSS[.sss]`（UTC）
+fn parse_mlsd_time(value: &str) -> Option<SystemTime> {
+    let value = value.split('.').next()?;
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%d%H%M%S").ok()?;
+    Some(time.and_utc().into())
+}
+
+/// 解析 LIST 的一行，自动识别 Unix 和 DOS 格式
+///
+/// 无法识别的行（如开头的 `total 12`）以及 `.` 和 `..` 返回 `None`
+pub(crate) fn parse_list_line(line: &str) -> Option<Entry> {
+    let entry = if line.starts_with(|c: char| c.is_ascii_digit()) {
+        parse_dos_line(line)
+    } else {
+        parse_unix_line(line)
+    }?;
+    (entry.name != "." && entry.name != "..").then_some(entry)
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// 按空白拆分，同时记录每一项在行中的起始位置
+fn fields(line: &str) -> Vec<(usize, &str)> {
+    let mut fields = vec![];
+    let mut start = None;
+    for (index, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(begin)) => {
+                fields.push((begin, &line[begin..index]));
+                start = None;
+            }
+            (false, None) => start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        fields.push((begin, &line[begin..]));
+    }
+    fields
+}
+
+/// `drwxr-xr-x 2 user group 4096 Jan 15 12:34 name`
+///
+/// 各服务器的列数不一（有的没有组，有的没有链接数），以日期中的月份定位：其前一列为
+/// 大小，其后两列为日期和时间（或年份），再往后是名称
+fn parse_unix_line(line: &str) -> Option<Entry> {
+    let fields = fields(line);
+    let mode = fields.first()?.1;
+    let kind = match mode.chars().next()? {
+        'd' => Kind::Directory,
+        'l' => Kind::Symlink,
+        '-' => Kind::File,
+        // 设备、管道等特殊文件
+        'b' | 'c' | 'p' | 's' => Kind::File,
+        _ => return None,
+    };
+    let month_index = (2..fields.len().saturating_sub(3)).find(|&index| {
+        month(fields[index].1).is_some() && fields[index - 1].1.parse::<u64>().is_ok()
+    })?;
+    let (name_start, _) = fields[month_index + 3];
+
+    let mut name = &line[name_start..];
+    if kind == Kind::Symlink {
+        name = name.split(" -> ").next().unwrap_or(name);
+    }
+    Some(Entry {
+        name: name.to_string(),
+        kind,
+        size: fields[month_index - 1].1.parse().ok(),
+        modified: parse_unix_time(
+            fields[month_index].1,
+            fields[month_index + 1].1,
+            fields[month_index + 2].1,
+        ),
+        permissions: parse_mode(mode),
+    })
+}
+
+fn month(text: &str) -> Option<u32> {
+    let text = text.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|month| *month == text)
+        .map(|index| index as u32 + 1)
+}
+
+/// `Jan 15 12:34`（半年内，省略年份）或 `Jan 15 2023`
+fn parse_unix_time(month_text: &str, day: &str, time_or_year: &str) -> Option<SystemTime> {
+    let time = parse_unix_time_at(month_text, day, time_or_year, Utc::now().naive_utc())?;
+    Some(time.and_utc().into())
+}
+
+/// 以 `now` 为当前时间解析 `ls -l` 中的时间
+///
+/// 没有年份时按今年计算，晚于现在半年以上或今年没有这一天（如 2 月 29 日）时为去年。
+fn parse_unix_time_at(
+    month_text: &str,
+    day: &str,
+    time_or_year: &str,
+    now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    let month = month(month_text)?;
+    let day: u32 = day.parse().ok()?;
+    let Some((hour, minute)) = time_or_year.split_once(':') else {
+        let date = NaiveDate::from_ymd_opt(time_or_year.parse().ok()?, month, day)?;
+        return Some(date.and_time(NaiveTime::MIN));
+    };
+    let time = NaiveTime::from_hms_opt(hour.parse().ok()?, minute.parse().ok()?, 0)?;
+    let this_year = NaiveDate::from_ymd_opt(now.year(), month, day)
+        .map(|date| date.and_time(time))
+        .filter(|this_year| *this_year <= now + chrono::Duration::days(183));
+    this_year.or_else(|| {
+        NaiveDate::from_ymd_opt(now.year() - 1, month, day).map(|date| date.and_time(time))
+    })
+}
+
+/// 把 `rwxr-xr-x` 形式的权限转换为数值（包括 setuid、setgid 和粘滞位）
+pub(crate) fn parse_mode(mode: &str) -> Option<u32> {
+    let bits: Vec<char> = mode.chars().skip(1).take(9).collect();
+    if bits.len() != 9 {
+        return None;
+    }
+    let mut permissions = 0;
+    for (index, &c) in bits.iter().enumerate() {
+        let expected = ['r', 'w', 'x'][index % 3];
+        let set = match c {
+            '-' => false,
+            // setuid、setgid 和粘滞位出现在执行位上，小写表示同时可执行
+            's' | 't' if index % 3 == 2 => true,
+            'S' | 'T' if index % 3 == 2 => false,
+            c if c == expected => true,
+            _ => return None,
+        };
+        if set {
+            permissions |= 1 << (8 - index);
+        }
+        if matches!(c, 's' | 'S' | 't' | 'T') {
+            permissions |= [0o4000, 0o2000, 0o1000][index / 3];
+        }
+    }
+    Some(permissions)
+}
+
+/// `01-15-24  03:04PM  <DIR>  Folder` 或 `01-15-2024  15:04  12345 file.txt`
+fn parse_dos_line(line: &str) -> Option<Entry> {
+    let fields = fields(line);
+    if fields.len() < 4 {
+        return None;
+    }
+    let date = fields[0].1;
+    let date = NaiveDate::parse_from_str(date, "%m-%d-%y")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%m-%d-%Y"))
+        .ok()?;
+    let time_text = fields[1].1.to_ascii_uppercase();
+    let time = NaiveTime::parse_from_str(&time_text, "%I:%M%p")
+        .or_else(|_| NaiveTime::parse_from_str(&time_text, "%H:%M"))
+        .ok()?;
+    let (kind, size) = match fields[2].1 {
+        "<DIR>" => (Kind::Directory, None),
+        size => (Kind::File, Some(size.replace(',', "").parse().ok()?)),
+    };
+    Some(Entry {
+        name: line[fields[3].0..].to_string(),
+        kind,
+        size,
+        modified: Some(date.and_time(time).and_utc().into()),
+        permissions: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn infers_missing_year() {
+        let now = at("2025-03-10 12:00");
+        let parse = |month, day, time| parse_unix_time_at(month, day, time, now);
+        assert_eq!(parse("Jan", "15", "08:30"), Some(at("2025-01-15 08:30")));
+        // 不超过半年的未来时间（服务器时钟较快）仍是今年
+        assert_eq!(parse("Jun", "1", "00:00"), Some(at("2025-06-01 00:00")));
+        assert_eq!(parse("Dec", "24", "18:00"), Some(at("2024-12-24 18:00")));
+        // 今年没有 2 月 29 日
+        assert_eq!(parse("Feb", "29", "09:15"), Some(at("2024-02-29 09:15")));
+        assert_eq!(parse("Feb", "30", "09:15"), None);
+        assert_eq!(parse("Jan", "15", "2023"), Some(at("2023-01-15 00:00")));
+        assert_eq!(parse("Foo", "15", "2023"), None);
+        assert_eq!(parse("Jan", "15", "25:00"), None);
+    }
+}
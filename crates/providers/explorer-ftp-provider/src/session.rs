@@ -0,0 +1,579 @@
+//! FTP 会话（RFC 959）
+//!
+//! 一个会话对应一个控制连接，每次传输另开一个被动模式的数据连接（优先 EPSV，不支持时
+//! 退回 PASV）。FTPS 时控制连接和数据连接都经过 TLS 加密（`PROT P`），数据连接复用控制
+//! 连接的 TLS 配置，以便服务器要求会话复用时能够通过。
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, Shutdown, SocketAddr, TcpStream, ToSocketAddrs},
+    sync::Arc,
+    time::Duration,
+};
+
+use rustls::{
+    ClientConfig, ClientConnection, RootCertStore, StreamOwned,
+    pki_types::{CertificateDer, ServerName, pem::PemObject},
+};
+
+use explorer_storage::{StorageError, StorageResult};
+
+use crate::{
+    FtpConnection, FtpSecurity,
+    listing::{Entry, Kind, parse_list_line, parse_mlsd_line, parse_mlst_line},
+};
+
+/// 连接和读写的超时时间
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 控制连接或数据连接，可能经过 TLS 加密
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Stream {
+    /// 在 TCP 连接上完成 TLS 握手
+    fn secure(tcp: TcpStream, tls: &Tls) -> io::Result<Self> {
+        let mut connection = ClientConnection::new(tls.config.clone(), tls.server_name.clone())
+            .map_err(io::Error::other)?;
+        let mut tcp = tcp;
+        while connection.is_handshaking() {
+            connection.complete_io(&mut tcp)?;
+        }
+        Ok(Self::Tls(Box::new(StreamOwned::new(connection, tcp))))
+    }
+
+    /// 结束数据连接：TLS 连接先发送 close_notify，服务器据此判断上传是否完整
+    fn close(self) -> io::Result<()> {
+        let mut tcp = match self {
+            Self::Plain(tcp) => tcp,
+            Self::Tls(mut stream) => {
+                stream.conn.send_close_notify();
+                stream.conn.complete_io(&mut stream.sock)?;
+                stream.sock
+            }
+        };
+        // 读完对方剩余的数据（如 TLS 1.3 的会话票据）再关闭，否则会发送 RST，服务器
+        // 可能收不到最后的数据。服务器可能已经关闭了连接，传输是否成功以其回复为准
+        let _ = tcp.shutdown(Shutdown::Write);
+        let _ = io::copy(&mut tcp, &mut io::sink());
+        Ok(())
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(stream) => stream.read(buf),
+            Self::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(stream) => stream.write(buf),
+            Self::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.flush(),
+            Self::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// TLS 配置和服务器名称
+struct Tls {
+    config: Arc<ClientConfig>,
+    server_name: ServerName<'static>,
+}
+
+impl Tls {
+    fn new(connection: &FtpConnection) -> StorageResult<Self> {
+        let mut roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        if let Some(path) = &connection.certificate {
+            let pem = fs::read(path)?;
+            for certificate in CertificateDer::pem_slice_iter(&pem) {
+                let certificate = certificate.map_err(|e| {
+                    StorageError::Other(format!("无法读取证书: {}: {}", path.display(), e))
+                })?;
+                roots.add(certificate).map_err(|e| {
+                    StorageError::Other(format!("无法使用证书: {}: {}", path.display(), e))
+                })?;
+            }
+        }
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|e| StorageError::Other(e.to_string()))?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let server_name = ServerName::try_from(connection.host.clone())
+            .map_err(|_| StorageError::Other(format!("无效的主机名: {}", connection.host)))?;
+        Ok(Self {
+            config: Arc::new(config),
+            server_name,
+        })
+    }
+}
+
+/// 服务器的回复
+pub(crate) struct Reply {
+    pub code: u16,
+    pub lines: Vec<String>,
+}
+
+impl Reply {
+    /// 回复的文本（去掉状态码）
+    fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.get(4..).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// 把失败的回复转换为存储错误
+fn reply_error(reply: &Reply, path: &str) -> StorageError {
+    let text = reply.text();
+    match reply.code {
+        // 数据连接失败或传输中断，可以重试
+        421 | 425 | 426 => StorageError::IoError(io::Error::other(format!(
+            "FTP 传输中断（{} {}）: {}",
+            reply.code, text, path
+        ))),
+        530 => StorageError::PermissionDenied(path.to_string()),
+        550 if text.to_ascii_lowercase().contains("permission") => {
+            StorageError::PermissionDenied(path.to_string())
+        }
+        550 => StorageError::PathNotFound(path.to_string()),
+        452 | 552 => StorageError::Other(format!("服务器空间不足: {}", path)),
+        _ => StorageError::Other(format!("FTP 命令失败（{} {}）: {}", reply.code, text, path)),
+    }
+}
+
+/// 读取一条回复（可能有多行，如 `211-Features:` ... `211 End`）
+fn read_reply(control: &mut BufReader<Stream>) -> io::Result<Reply> {
+    let mut lines: Vec<String> = vec![];
+    loop {
+        let mut line = vec![];
+        if control.read_until(b'\n', &mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "服务器关闭了连接",
+            ));
+        }
+        let line = String::from_utf8_lossy(&line)
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        let last = code.is_some() && line.as_bytes().get(3) != Some(&b'-');
+        // 多行回复以 `ddd-` 开始，以 `ddd ` 结束，中间的行格式不限
+        let done = match (lines.first(), code) {
+            (None, Some(_)) => last,
+            (Some(first), Some(code)) => last && first.starts_with(&code.to_string()),
+            (None, None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("无效的 FTP 回复: {}", line),
+                ));
+            }
+            (Some(_), None) => false,
+        };
+        lines.push(line);
+        if done {
+            let code = lines[0][..3].parse().unwrap_or_default();
+            return Ok(Reply { code, lines });
+        }
+    }
+}
+
+fn send(control: &mut BufReader<Stream>, command: &str) -> io::Result<Reply> {
+    // 一次写入整行，避免命令被拆成两个 TCP 段
+    let stream = control.get_mut();
+    stream.write_all(format!("{}\r\n", command).as_bytes())?;
+    stream.flush()?;
+    read_reply(control)
+}
+
+/// 服务器支持的扩展（FEAT）
+#[derive(Debug, Default)]
+pub(crate) struct Features {
+    pub mlst: bool,
+    pub utf8: bool,
+}
+
+pub(crate) struct Session {
+    control: BufReader<Stream>,
+    /// 数据连接的 TLS 配置（FTPS）
+    tls: Option<Tls>,
+    /// 控制连接的对端地址，被动模式的数据连接都连接到这里
+    peer: IpAddr,
+    features: Features,
+    /// 服务器是否支持 EPSV，不支持时使用 PASV
+    epsv: bool,
+    /// 控制连接已断开或回复不同步，不能再使用
+    broken: bool,
+}
+
+impl Session {
+    /// 连接并登录
+    pub fn connect(connection: &FtpConnection) -> StorageResult<Self> {
+        let authority = connection.authority();
+        let address = (connection.host.as_str(), connection.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| StorageError::Other(format!("无法解析主机名: {}", connection.host)))?;
+        let tcp = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        tcp.set_read_timeout(Some(TIMEOUT))?;
+        tcp.set_write_timeout(Some(TIMEOUT))?;
+
+        let tls = match connection.security {
+            FtpSecurity::None => None,
+            _ => Some(Tls::new(connection)?),
+        };
+        let handshake_error =
+            |e: io::Error| StorageError::Other(format!("TLS 握手失败: {}: {}", authority, e));
+        let stream = match (&tls, connection.security) {
+            (Some(tls), FtpSecurity::Implicit) => {
+                Stream::secure(tcp, tls).map_err(handshake_error)?
+            }
+            _ => Stream::Plain(tcp),
+        };
+        let mut control = BufReader::new(stream);
+        let unexpected = |reply: &Reply| reply_error(reply, &authority);
+
+        let mut greeting = read_reply(&mut control)?;
+        // 120 表示服务器稍后就绪
+        while greeting.code == 120 {
+            greeting = read_reply(&mut control)?;
+        }
+        if greeting.code != 220 {
+            return Err(unexpected(&greeting));
+        }
+
+        if let (Some(tls), FtpSecurity::Explicit) = (&tls, connection.security) {
+            let reply = send(&mut control, "AUTH TLS")?;
+            if reply.code != 234 {
+                return Err(StorageError::Other(format!(
+                    "服务器不支持 TLS（{} {}）: {}",
+                    reply.code,
+                    reply.text(),
+                    authority
+                )));
+            }
+            // 此时服务器在等待握手，缓冲区中没有未读的数据
+            let Stream::Plain(tcp) = control.into_inner() else {
+                unreachable!("AUTH TLS 之前的控制连接未加密");
+            };
+            control = BufReader::new(Stream::secure(tcp, tls).map_err(handshake_error)?);
+        }
+
+        let password = match &connection.password {
+            Some(password) => password.as_str(),
+            // 匿名登录习惯上用电子邮件地址作为密码
+            None if connection.user == "anonymous" => "anonymous@",
+            None => "",
+        };
+        let mut reply = send(&mut control, &format!("USER {}", connection.user))?;
+        if reply.code == 331 {
+            reply = send(&mut control, &format!("PASS {}", password))?;
+        }
+        match reply.code {
+            230 | 202 => {}
+            530 => return Err(StorageError::PermissionDenied(authority)),
+            _ => return Err(unexpected(&reply)),
+        }
+
+        if tls.is_some() {
+            for command in ["PBSZ 0", "PROT P"] {
+                let reply = send(&mut control, command)?;
+                if reply.code != 200 {
+                    return Err(unexpected(&reply));
+                }
+            }
+        }
+        let reply = send(&mut control, "TYPE I")?;
+        if reply.code != 200 {
+            return Err(unexpected(&reply));
+        }
+
+        let mut features = Features::default();
+        let reply = send(&mut control, "FEAT")?;
+        if reply.code == 211 {
+            for line in &reply.lines[1..] {
+                let feature = line.trim().to_ascii_uppercase();
+                features.mlst |= feature.starts_with("MLST");
+                features.utf8 |= feature == "UTF8";
+            }
+        }
+        if features.utf8 {
+            // 部分服务器默认不使用 UTF-8，失败时不影响其余操作
+            send(&mut control, "OPTS UTF8 ON")?;
+        }
+
+        Ok(Self {
+            control,
+            tls,
+            peer: address.ip(),
+            features,
+            epsv: true,
+            broken: false,
+        })
+    }
+
+    /// 会话是否还能继续使用
+    pub fn is_usable(&self) -> bool {
+        !self.broken
+    }
+
+    /// 发送命令并读取回复，控制连接出错时会话不能再使用
+    fn command(&mut self, command: &str) -> StorageResult<Reply> {
+        // 换行符会把路径变成另一条命令
+        if command.contains(['\r', '\n']) {
+            return Err(StorageError::Other(format!(
+                "路径中不能包含换行符: {}",
+                command.trim_end()
+            )));
+        }
+        match send(&mut self.control, command) {
+            Ok(reply) => {
+                if reply.code == 421 {
+                    self.broken = true;
+                }
+                Ok(reply)
+            }
+            Err(e) => {
+                self.broken = true;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// 发送命令，回复的状态码不是 `code` 时返回错误
+    fn expect(&mut self, command: &str, code: u16, path: &str) -> StorageResult<Reply> {
+        let reply = self.command(command)?;
+        if reply.code != code {
+            return Err(reply_error(&reply, path));
+        }
+        Ok(reply)
+    }
+
+    /// 打开被动模式的数据连接
+    fn open_data(&mut self, path: &str) -> StorageResult<TcpStream> {
+        let mut port = None;
+        if self.epsv {
+            let reply = self.command("EPSV")?;
+            match reply.code {
+                // `229 Entering Extended Passive Mode (|||6446|)`
+                229 => {
+                    port = reply
+                        .text()
+                        .split('|')
+                        .nth(3)
+                        .and_then(|port| port.parse().ok());
+                }
+                _ => self.epsv = false,
+            }
+        }
+        let port = match port {
+            Some(port) => port,
+            None => {
+                // `227 Entering Passive Mode (h1,h2,h3,h4,p1,p2)`，服务器给出的地址常常是内网
+                // 地址，只取端口，连接控制连接的对端
+                let reply = self.expect("PASV", 227, path)?;
+                let text = reply.text();
+                let numbers: Vec<u16> = text
+                    .split(|c: char| !c.is_ascii_digit())
+                    .filter_map(|number| number.parse().ok())
+                    .collect();
+                match numbers[..] {
+                    [.., p1, p2] if numbers.len() >= 6 => p1 * 256 + p2,
+                    _ => {
+                        return Err(StorageError::Other(format!("无效的 PASV 回复: {}", text)));
+                    }
+                }
+            }
+        };
+        let data = TcpStream::connect_timeout(&SocketAddr::new(self.peer, port), TIMEOUT)?;
+        data.set_read_timeout(Some(TIMEOUT))?;
+        data.set_write_timeout(Some(TIMEOUT))?;
+        Ok(data)
+    }
+
+    /// 执行使用数据连接的命令，`f` 在数据连接上读写
+    ///
+    /// `offset` 不为 0 时在命令之前发送 REST，REST 必须紧接在传输命令之前
+    fn transfer<T>(
+        &mut self,
+        command: &str,
+        path: &str,
+        offset: u64,
+        f: impl FnOnce(&mut dyn ReadWrite) -> StorageResult<T>,
+    ) -> StorageResult<T> {
+        let data = self.open_data(path)?;
+        if offset > 0 {
+            self.expect(&format!("REST {}", offset), 350, path)?;
+        }
+        let reply = self.command(command)?;
+        if !matches!(reply.code, 125 | 150) {
+            return Err(reply_error(&reply, path));
+        }
+
+        let mut stream = match &self.tls {
+            Some(tls) => match Stream::secure(data, tls) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    // 服务器还会回复传输失败，但已无法确定何时到达
+                    self.broken = true;
+                    return Err(e.into());
+                }
+            },
+            None => Stream::Plain(data),
+        };
+        let result = f(&mut stream).and_then(|value| Ok((value, stream.close()?)));
+
+        // 不论传输是否成功，服务器都会在控制连接上回复传输的结果
+        let reply = match read_reply(&mut self.control) {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.broken = true;
+                return Err(e.into());
+            }
+        };
+        let (value, ()) = result?;
+        if reply.code / 100 != 2 {
+            return Err(reply_error(&reply, path));
+        }
+        Ok(value)
+    }
+
+    /// 获取条目的信息，不支持 MLST 时在上级目录的列表中查找
+    pub fn stat(&mut self, path: &str) -> StorageResult<Entry> {
+        if path == "/" {
+            return Ok(Entry {
+                name: "/".to_string(),
+                kind: Kind::Directory,
+                size: None,
+                modified: None,
+                permissions: None,
+            });
+        }
+        if self.features.mlst {
+            let reply = self.expect(&format!("MLST {}", path), 250, path)?;
+            // 条目的信息在以空格开头的一行中
+            return reply
+                .lines
+                .iter()
+                .find_map(|line| line.strip_prefix(' ').and_then(parse_mlst_line))
+                .ok_or_else(|| StorageError::Other(format!("无效的 MLST 回复: {}", path)));
+        }
+
+        let (parent, name) = split(path);
+        self.list_dir(parent)?
+            .into_iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| StorageError::PathNotFound(path.to_string()))
+    }
+
+    /// 列出目录中的条目（不包括 `.` 和 `..`）
+    pub fn list_dir(&mut self, path: &str) -> StorageResult<Vec<Entry>> {
+        let mlsd = self.features.mlst;
+        let command = if mlsd {
+            format!("MLSD {}", path)
+        } else {
+            format!("LIST {}", path)
+        };
+        let data = self.transfer(&command, path, 0, |stream| {
+            let mut data = vec![];
+            stream.read_to_end(&mut data)?;
+            Ok(data)
+        })?;
+        Ok(String::from_utf8_lossy(&data)
+            .lines()
+            .filter_map(|line| {
+                if mlsd {
+                    parse_mlsd_line(line)
+                } else {
+                    parse_list_line(line)
+                }
+            })
+            .collect())
+    }
+
+    /// 从 `offset` 开始下载，把数据连接交给 `f` 读取
+    pub fn retr<T>(
+        &mut self,
+        path: &str,
+        offset: u64,
+        f: impl FnOnce(&mut dyn ReadWrite) -> StorageResult<T>,
+    ) -> StorageResult<T> {
+        self.transfer(&format!("RETR {}", path), path, offset, f)
+    }
+
+    /// 从 `offset` 开始上传 `reader` 中的内容，`offset` 为 0 时创建或覆盖文件
+    pub fn stor(&mut self, path: &str, offset: u64, reader: &mut dyn Read) -> StorageResult<u64> {
+        self.transfer(&format!("STOR {}", path), path, offset, |stream| {
+            Ok(io::copy(reader, stream)?)
+        })
+    }
+
+    pub fn size(&mut self, path: &str) -> StorageResult<u64> {
+        let reply = self.expect(&format!("SIZE {}", path), 213, path)?;
+        reply
+            .text()
+            .trim()
+            .parse()
+            .map_err(|_| StorageError::Other(format!("无效的 SIZE 回复: {}", path)))
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> StorageResult<()> {
+        self.expect(&format!("MKD {}", path), 257, path).map(|_| ())
+    }
+
+    pub fn rmdir(&mut self, path: &str) -> StorageResult<()> {
+        self.expect(&format!("RMD {}", path), 250, path).map(|_| ())
+    }
+
+    pub fn delete(&mut self, path: &str) -> StorageResult<()> {
+        self.expect(&format!("DELE {}", path), 250, path)
+            .map(|_| ())
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) -> StorageResult<()> {
+        self.expect(&format!("RNFR {}", from), 350, from)?;
+        self.expect(&format!("RNTO {}", to), 250, to).map(|_| ())
+    }
+}
+
+/// 数据连接
+pub(crate) trait ReadWrite: Read + Write {}
+
+impl<T: Read + Write> ReadWrite for T {}
+
+/// 拆分为上级目录和名称
+pub(crate) fn split(path: &str) -> (&str, &str) {
+    match path.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((parent, name)) => (parent, name),
+        None => ("/", path),
+    }
+}
+
+/// 拼接目录和名称
+pub(crate) fn join(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
@@ -0,0 +1,990 @@
+//! 使用进程内的 FTP 服务器替身的集成测试
+//!
+//! 替身把服务器上的路径映射到一个临时目录，实现了提供者用到的命令。可以关闭 MLSD
+//! 和 EPSV 来测试 LIST 解析和 PASV，可以切换 Unix 和 DOS 两种 LIST 格式，也可以在
+//! 下一次 RETR 传输到一半时断开数据连接。FTPS 使用 `tests/certs` 中的测试证书。
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    net::{TcpListener, TcpStream},
+    os::unix::fs::{PermissionsExt, symlink},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use rustls::{
+    ServerConfig, ServerConnection, StreamOwned,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+};
+
+use explorer_ftp_provider::{FtpConnection, FtpProvider, FtpSecurity};
+use explorer_storage::{
+    ItemType, ProviderType, StorageError, StorageProvider,
+    conformance::{self, TempDir},
+};
+
+const USER: &str = "alice";
+const PASSWORD: &str = "secret";
+
+/// LIST 的输出格式
+#[derive(Clone, Copy)]
+enum Style {
+    Unix,
+    Dos,
+}
+
+/// 替身的行为
+#[derive(Clone, Copy)]
+struct Options {
+    mlsd: bool,
+    epsv: bool,
+    style: Style,
+    security: FtpSecurity,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            mlsd: true,
+            epsv: true,
+            style: Style::Unix,
+            security: FtpSecurity::None,
+        }
+    }
+}
+
+/// 替身的共享状态
+struct State {
+    dir: PathBuf,
+    options: Options,
+    tls: Arc<ServerConfig>,
+    /// 收到的命令（不含密码）
+    log: Mutex<Vec<String>>,
+    /// 下一次 RETR 只发送一半数据后断开
+    cut_next_retr: AtomicBool,
+}
+
+/// 服务器替身及其根目录
+struct Fixture {
+    _dir: TempDir,
+    state: Arc<State>,
+    provider: FtpProvider,
+    connection: FtpConnection,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self::with_options(Options::default())
+    }
+
+    fn with_options(options: Options) -> Self {
+        Self::build(options, PASSWORD, Some(certs().join("ca.pem")))
+    }
+
+    fn build(options: Options, password: &str, certificate: Option<PathBuf>) -> Self {
+        let dir = TempDir::new("ftp-test");
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let state = Arc::new(State {
+            dir: dir.to_path_buf(),
+            options,
+            tls: server_config(),
+            log: Mutex::new(vec![]),
+            cut_next_retr: AtomicBool::new(false),
+        });
+        {
+            let state = state.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let state = state.clone();
+                    thread::spawn(move || {
+                        let _ = serve(stream?, &state);
+                        io::Result::Ok(())
+                    });
+                }
+            });
+        }
+
+        let connection = FtpConnection {
+            name: "旧系统".to_string(),
+            host: "localhost".to_string(),
+            port,
+            user: USER.to_string(),
+            password: Some(password.to_string()),
+            security: options.security,
+            certificate,
+            root: "/".to_string(),
+        };
+        Self {
+            _dir: dir,
+            state,
+            provider: FtpProvider::new(vec![connection.clone()]),
+            connection,
+        }
+    }
+
+    fn url(&self, name: &str) -> String {
+        self.connection.url(&format!("/{}", name))
+    }
+
+    fn local(&self, name: &str) -> PathBuf {
+        self.state.dir.join(name)
+    }
+
+    /// 取出已收到的命令
+    fn take_log(&self) -> Vec<String> {
+        std::mem::take(&mut self.state.log.lock().unwrap())
+    }
+
+    /// 创建测试用的目录结构
+    fn populate(&self) {
+        fs::create_dir(self.local("docs")).unwrap();
+        fs::write(self.local("docs/a.txt"), "aaa").unwrap();
+        fs::write(self.local("b.txt"), "hello").unwrap();
+        fs::write(self.local("My File.log"), "0123456789").unwrap();
+        symlink("docs", self.local("link")).unwrap();
+        fs::set_permissions(self.local("b.txt"), fs::Permissions::from_mode(0o640)).unwrap();
+        File::options()
+            .write(true)
+            .open(self.local("b.txt"))
+            .unwrap()
+            .set_modified(old_time())
+            .unwrap();
+    }
+}
+
+impl conformance::Fixture for Fixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        self.url("tree")
+    }
+
+    fn symlinks(&self) -> bool {
+        true
+    }
+}
+
+fn certs() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/certs")
+}
+
+fn server_config() -> Arc<ServerConfig> {
+    let chain = CertificateDer::pem_file_iter(certs().join("server.pem"))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(certs().join("server.key")).unwrap();
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(chain, key)
+        .unwrap();
+    Arc::new(config)
+}
+
+/// `b.txt` 的修改时间：2020-01-02 03:04:05 UTC
+fn old_time() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_577_934_245)
+}
+
+/// 控制连接或数据连接
+enum Stream {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ServerConnection, TcpStream>>),
+}
+
+impl Stream {
+    fn secure(tcp: TcpStream, config: &Arc<ServerConfig>) -> io::Result<Self> {
+        let connection = ServerConnection::new(config.clone()).map_err(io::Error::other)?;
+        Ok(Self::Tls(Box::new(StreamOwned::new(connection, tcp))))
+    }
+
+    /// 正常结束：TLS 连接发送 close_notify
+    fn close(self) -> io::Result<()> {
+        if let Self::Tls(mut stream) = self {
+            stream.conn.send_close_notify();
+            stream.conn.complete_io(&mut stream.sock)?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(stream) => stream.read(buf),
+            Self::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(stream) => stream.write(buf),
+            Self::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(stream) => stream.flush(),
+            Self::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// 一个控制连接的状态
+struct Control<'a> {
+    state: &'a State,
+    stream: BufReader<Stream>,
+    logged_in: bool,
+    protected: bool,
+    passive: Option<TcpListener>,
+    rest: u64,
+    rename_from: Option<PathBuf>,
+}
+
+impl Control<'_> {
+    fn reply(&mut self, text: &str) -> io::Result<()> {
+        let stream = self.stream.get_mut();
+        stream.write_all(format!("{}\r\n", text.replace('\n', "\r\n")).as_bytes())?;
+        stream.flush()
+    }
+
+    /// 接受被动模式的数据连接
+    fn accept_data(&mut self) -> io::Result<Stream> {
+        let listener = self
+            .passive
+            .take()
+            .ok_or_else(|| io::Error::other("没有被动模式的监听"))?;
+        let (tcp, _) = listener.accept()?;
+        if self.protected {
+            Stream::secure(tcp, &self.state.tls)
+        } else {
+            Ok(Stream::Plain(tcp))
+        }
+    }
+
+    /// 服务器上的路径对应的本地路径
+    fn local(&self, path: &str) -> Option<PathBuf> {
+        let mut local = self.state.dir.clone();
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            if segment == ".." {
+                return None;
+            }
+            local.push(segment);
+        }
+        Some(local)
+    }
+}
+
+fn serve(tcp: TcpStream, state: &State) -> io::Result<()> {
+    let stream = match state.options.security {
+        FtpSecurity::Implicit => Stream::secure(tcp, &state.tls)?,
+        _ => Stream::Plain(tcp),
+    };
+    let mut control = Control {
+        state,
+        stream: BufReader::new(stream),
+        logged_in: false,
+        protected: false,
+        passive: None,
+        rest: 0,
+        rename_from: None,
+    };
+    control.reply("220 测试 FTP 服务器")?;
+
+    loop {
+        let mut line = String::new();
+        if control.stream.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (verb, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let verb = verb.to_ascii_uppercase();
+        let logged = if verb == "PASS" { "PASS ***" } else { line };
+        state.log.lock().unwrap().push(logged.to_string());
+
+        match verb.as_str() {
+            "USER" | "PASS" | "AUTH" | "FEAT" | "QUIT" => {}
+            _ if !control.logged_in => {
+                control.reply("530 Please login with USER and PASS")?;
+                continue;
+            }
+            _ => {}
+        }
+        // 只有 RETR 和 STOR 使用 REST 设置的位置
+        let rest = std::mem::take(&mut control.rest);
+
+        match verb.as_str() {
+            "AUTH" if state.options.security == FtpSecurity::Explicit => {
+                control.reply("234 Proceed with negotiation")?;
+                let Stream::Plain(tcp) = control.stream.into_inner() else {
+                    return Ok(());
+                };
+                control.stream = BufReader::new(Stream::secure(tcp, &state.tls)?);
+            }
+            "AUTH" => control.reply("502 TLS not available")?,
+            "USER" => control.reply("331 Password required")?,
+            "PASS" if argument == PASSWORD => {
+                control.logged_in = true;
+                control.reply("230 Logged in")?;
+            }
+            "PASS" => control.reply("530 Login incorrect")?,
+            "PBSZ" => control.reply("200 PBSZ=0")?,
+            "PROT" => {
+                control.protected = argument.eq_ignore_ascii_case("P");
+                control.reply("200 Protection level set")?;
+            }
+            "TYPE" | "OPTS" => control.reply("200 OK")?,
+            "FEAT" => {
+                let mlst = if state.options.mlsd {
+                    "\n MLST type*;size*;modify*;unix.mode*;"
+                } else {
+                    ""
+                };
+                control.reply(&format!(
+                    "211-Features:{}\n REST STREAM\n SIZE\n UTF8\n211 End",
+                    mlst
+                ))?;
+            }
+            "EPSV" if state.options.epsv => {
+                let listener = TcpListener::bind("127.0.0.1:0")?;
+                let port = listener.local_addr()?.port();
+                control.passive = Some(listener);
+                control.reply(&format!(
+                    "229 Entering Extended Passive Mode (|||{}|)",
+                    port
+                ))?;
+            }
+            "PASV" => {
+                let listener = TcpListener::bind("127.0.0.1:0")?;
+                let port = listener.local_addr()?.port();
+                control.passive = Some(listener);
+                // 模拟 NAT 之后的服务器给出的内网地址
+                control.reply(&format!(
+                    "227 Entering Passive Mode (10,0,0,1,{},{})",
+                    port / 256,
+                    port % 256
+                ))?;
+            }
+            "REST" => match argument.parse() {
+                Ok(offset) => {
+                    control.rest = offset;
+                    control.reply(&format!("350 Restarting at {}", offset))?;
+                }
+                Err(_) => control.reply("501 Invalid offset")?,
+            },
+            "MLSD" | "LIST" | "MLST" | "SIZE" | "RETR" | "STOR" | "MKD" | "RMD" | "DELE"
+            | "RNFR" | "RNTO" => {
+                let Some(local) = control.local(argument) else {
+                    control.reply("550 Invalid path")?;
+                    continue;
+                };
+                if argument.contains("locked") && verb != "RNTO" {
+                    control.reply("550 Permission denied")?;
+                    continue;
+                }
+                file_command(&mut control, &verb, argument, &local, rest)?;
+            }
+            "QUIT" => {
+                control.reply("221 Goodbye")?;
+                return Ok(());
+            }
+            _ => control.reply("502 Command not implemented")?,
+        }
+    }
+}
+
+/// 处理与路径有关的命令
+fn file_command(
+    control: &mut Control,
+    verb: &str,
+    path: &str,
+    local: &Path,
+    rest: u64,
+) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(local).ok();
+    match verb {
+        "MLSD" | "LIST" => {
+            if !local.is_dir() {
+                return control.reply("550 Not a directory");
+            }
+            let mut names: Vec<_> = fs::read_dir(local)?
+                .map(|entry| entry.map(|entry| entry.file_name()))
+                .collect::<io::Result<_>>()?;
+            names.sort();
+            let mut listing = String::new();
+            if verb == "MLSD" {
+                listing.push_str("type=cdir;modify=20200101000000; .\r\n");
+            } else if matches!(control.state.options.style, Style::Unix) {
+                listing.push_str(&format!("total {}\r\n", names.len()));
+            }
+            for name in names {
+                let name = name.to_string_lossy();
+                let metadata = fs::symlink_metadata(local.join(&*name))?;
+                let line = match (verb, control.state.options.style) {
+                    ("MLSD", _) => mlsd_line(&metadata, &name),
+                    (_, Style::Unix) => unix_line(&metadata, &name, &local.join(&*name)),
+                    (_, Style::Dos) => dos_line(&metadata, &name),
+                };
+                listing.push_str(&line);
+                listing.push_str("\r\n");
+            }
+            control.reply("150 Here comes the directory listing")?;
+            let mut data = control.accept_data()?;
+            data.write_all(listing.as_bytes())?;
+            data.close()?;
+            control.reply("226 Directory send OK")
+        }
+        "MLST" => match metadata {
+            Some(metadata) => {
+                let line = mlsd_line(&metadata, path);
+                control.reply(&format!("250-Listing {}\n {}\n250 End", path, line))
+            }
+            None => control.reply("550 No such file or directory"),
+        },
+        "SIZE" => match metadata {
+            Some(metadata) if metadata.is_file() => {
+                control.reply(&format!("213 {}", metadata.len()))
+            }
+            _ => control.reply("550 Could not get file size"),
+        },
+        "RETR" => {
+            let Ok(content) = fs::read(local) else {
+                return control.reply("550 Failed to open file");
+            };
+            control.reply("150 Opening BINARY mode data connection")?;
+            let mut data = control.accept_data()?;
+            let content = &content[(rest as usize).min(content.len())..];
+            if control.state.cut_next_retr.swap(false, Ordering::SeqCst) {
+                // 发送一半后直接断开，TLS 连接也不发送 close_notify
+                data.write_all(&content[..content.len() / 2])?;
+                data.flush()?;
+                drop(data);
+                return control.reply("426 Connection closed; transfer aborted");
+            }
+            data.write_all(content)?;
+            data.close()?;
+            control.reply("226 Transfer complete")
+        }
+        "STOR" => {
+            if !local.parent().is_some_and(Path::is_dir) {
+                return control.reply("553 Could not create file");
+            }
+            control.reply("150 Ok to send data")?;
+            let mut data = control.accept_data()?;
+            let mut content = vec![];
+            data.read_to_end(&mut content)?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(rest == 0)
+                .open(local)?;
+            file.set_len(rest)?;
+            file.seek(SeekFrom::Start(rest))?;
+            file.write_all(&content)?;
+            control.reply("226 Transfer complete")
+        }
+        "MKD" if metadata.is_some() => control.reply("550 File exists"),
+        "MKD" => match fs::create_dir(local) {
+            Ok(()) => control.reply(&format!("257 \"{}\" created", path)),
+            Err(_) => control.reply("550 Create directory operation failed"),
+        },
+        "RMD" => match fs::remove_dir(local) {
+            Ok(()) => control.reply("250 Remove directory operation successful"),
+            Err(_) => control.reply("550 Remove directory operation failed"),
+        },
+        "DELE" if metadata.as_ref().is_some_and(|metadata| metadata.is_dir()) => {
+            control.reply("550 Delete operation failed")
+        }
+        "DELE" => match fs::remove_file(local) {
+            Ok(()) => control.reply("250 Delete operation successful"),
+            Err(_) => control.reply("550 Delete operation failed"),
+        },
+        "RNFR" if metadata.is_some() => {
+            control.rename_from = Some(local.to_path_buf());
+            control.reply("350 Ready for RNTO")
+        }
+        "RNFR" => control.reply("550 RNFR command failed"),
+        _ => match control.rename_from.take() {
+            Some(from) if fs::rename(&from, local).is_ok() => {
+                control.reply("250 Rename successful")
+            }
+            _ => control.reply("550 Rename failed"),
+        },
+    }
+}
+
+fn mlsd_line(metadata: &fs::Metadata, name: &str) -> String {
+    let kind = if metadata.is_symlink() {
+        "OS.unix=symlink"
+    } else if metadata.is_dir() {
+        "dir"
+    } else {
+        "file"
+    };
+    let modified: DateTime<Utc> = metadata.modified().unwrap().into();
+    format!(
+        "type={};size={};modify={};unix.mode=0{:o}; {}",
+        kind,
+        metadata.len(),
+        modified.format("%Y%m%d%H%M%S"),
+        metadata.permissions().mode() & 0o7777,
+        name
+    )
+}
+
+fn unix_line(metadata: &fs::Metadata, name: &str, local: &Path) -> String {
+    let mode = metadata.permissions().mode();
+    let kind = if metadata.is_symlink() {
+        'l'
+    } else if metadata.is_dir() {
+        'd'
+    } else {
+        '-'
+    };
+    let mut permissions = String::new();
+    for shift in [6, 3, 0] {
+        let bits = mode >> shift;
+        permissions.push(if bits & 4 != 0 { 'r' } else { '-' });
+        permissions.push(if bits & 2 != 0 { 'w' } else { '-' });
+        permissions.push(if bits & 1 != 0 { 'x' } else { '-' });
+    }
+    let modified: DateTime<Utc> = metadata.modified().unwrap().into();
+    // 半年内的时间不显示年份
+    let time = if Utc::now() - modified < chrono::Duration::days(180) {
+        modified.format("%b %e %H:%M").to_string()
+    } else {
+        modified.format("%b %e  %Y").to_string()
+    };
+    let target = match fs::read_link(local) {
+        Ok(target) => format!(" -> {}", target.display()),
+        Err(_) => String::new(),
+    };
+    format!(
+        "{}{} 1 ftp ftp {:>8} {} {}{}",
+        kind,
+        permissions,
+        metadata.len(),
+        time,
+        name,
+        target
+    )
+}
+
+fn dos_line(metadata: &fs::Metadata, name: &str) -> String {
+    let modified: DateTime<Utc> = metadata.modified().unwrap().into();
+    let size = if metadata.is_dir() {
+        "<DIR>".to_string()
+    } else {
+        metadata.len().to_string()
+    };
+    format!(
+        "{}       {:>14} {}",
+        modified.format("%m-%d-%y  %I:%M%p"),
+        size,
+        name
+    )
+}
+
+fn read(provider: &FtpProvider, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    smol::block_on(provider.read_file(path, &mut content)).unwrap();
+    content
+}
+
+/// 检查列出的根目录
+fn check_listing(fixture: &Fixture, permissions: bool, precise_time: bool) {
+    let entries = smol::block_on(fixture.provider.list_entries(&fixture.url(""))).unwrap();
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["docs", "My File.log", "b.txt", "link"]);
+
+    let docs = &entries[0];
+    assert_eq!(docs.item_type, ItemType::Directory);
+    assert_eq!(docs.path, fixture.url("docs"));
+
+    let file = &entries[2];
+    assert_eq!(file.item_type, ItemType::File);
+    assert_eq!(file.size, 5);
+    assert_eq!(file.metadata.mime_type.as_deref(), Some("text/plain"));
+    let expected = if precise_time {
+        old_time()
+    } else {
+        // LIST 只精确到分钟，超过半年的只有日期
+        let seconds = match fixture.state.options.style {
+            Style::Unix => 1_577_923_200,
+            Style::Dos => 1_577_934_240,
+        };
+        SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+    };
+    assert_eq!(file.modified, expected);
+    if permissions {
+        assert_eq!(file.metadata.permissions, Some(0o640));
+    }
+
+    assert_eq!(entries[1].size, 10);
+}
+
+#[test]
+fn roots_are_network_drives() {
+    let fixture = Fixture::new();
+    let roots = smol::block_on(fixture.provider.get_roots()).unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].name, "旧系统");
+    assert_eq!(roots[0].provider_type, ProviderType::NetworkDrive);
+    assert_eq!(roots[0].path, fixture.connection.url("/"));
+    assert!(roots[0].path.starts_with("ftp://alice@localhost:"));
+}
+
+#[test]
+fn lists_directories_with_mlsd() {
+    let fixture = Fixture::new();
+    fixture.populate();
+    check_listing(&fixture, true, true);
+
+    let entries = smol::block_on(fixture.provider.list_entries(&fixture.url(""))).unwrap();
+    assert_eq!(entries[3].item_type, ItemType::Symlink);
+    // 指向目录的符号链接可以打开
+    let linked = smol::block_on(fixture.provider.list_entries(&fixture.url("link"))).unwrap();
+    assert_eq!(linked[0].path, fixture.url("link/a.txt"));
+
+    let metadata = smol::block_on(fixture.provider.get_metadata(&fixture.url("b.txt"))).unwrap();
+    assert_eq!(metadata.size, 5);
+    assert_eq!(metadata.modified, old_time());
+    let docs = smol::block_on(fixture.provider.get_metadata(&fixture.url("docs/"))).unwrap();
+    assert_eq!(docs.item_type, ItemType::Directory);
+    assert_eq!(docs.name, "docs");
+
+    let log = fixture.take_log();
+    assert!(log.contains(&"MLSD /".to_string()));
+    assert!(log.contains(&"MLST /b.txt".to_string()));
+    assert!(log.contains(&"EPSV".to_string()));
+    assert!(!log.iter().any(|line| line.starts_with("LIST")));
+}
+
+#[test]
+fn falls_back_to_unix_list() {
+    let fixture = Fixture::with_options(Options {
+        mlsd: false,
+        epsv: false,
+        ..Default::default()
+    });
+    fixture.populate();
+    check_listing(&fixture, true, false);
+
+    let entries = smol::block_on(fixture.provider.list_entries(&fixture.url(""))).unwrap();
+    assert_eq!(entries[3].item_type, ItemType::Symlink);
+    assert_eq!(entries[3].name, "link");
+
+    // 没有 MLST 时在上级目录的列表中查找
+    let metadata =
+        smol::block_on(fixture.provider.get_metadata(&fixture.url("docs/a.txt"))).unwrap();
+    assert_eq!(metadata.size, 3);
+    let modified = metadata.modified.duration_since(SystemTime::now());
+    assert!(modified.map_or(true, |ahead| ahead < Duration::from_secs(60)));
+
+    // PASV 回复中的内网地址被忽略，数据连接仍然连到控制连接的对端
+    let log = fixture.take_log();
+    assert!(log.contains(&"PASV".to_string()));
+    assert!(log.contains(&"LIST /".to_string()));
+    assert!(!log.iter().any(|line| line.starts_with("MLS")));
+}
+
+#[test]
+fn falls_back_to_dos_list() {
+    let fixture = Fixture::with_options(Options {
+        mlsd: false,
+        style: Style::Dos,
+        ..Default::default()
+    });
+    fixture.populate();
+    check_listing(&fixture, false, false);
+}
+
+#[test]
+fn reads_and_writes_files() {
+    let fixture = Fixture::new();
+    let content: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+
+    let written = smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("large.bin"), &mut content.as_slice()),
+    )
+    .unwrap();
+    assert_eq!(written, content.len() as u64);
+    assert_eq!(fs::read(fixture.local("large.bin")).unwrap(), content);
+    assert_eq!(read(&fixture.provider, &fixture.url("large.bin")), content);
+
+    // 覆盖已有的文件
+    smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("large.bin"), &mut &b"short"[..]),
+    )
+    .unwrap();
+    assert_eq!(fs::read(fixture.local("large.bin")).unwrap(), b"short");
+
+    let missing_parent = smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("missing/a.txt"), &mut &b"x"[..]),
+    );
+    assert!(missing_parent.is_err());
+}
+
+#[test]
+fn creates_copies_moves_and_deletes() {
+    let fixture = Fixture::new();
+    fixture.populate();
+    smol::block_on(async {
+        let provider = &fixture.provider;
+        provider.create_dir(&fixture.url("new")).await.unwrap();
+        assert!(fixture.local("new").is_dir());
+        provider
+            .create_file(&fixture.url("new/empty.txt"))
+            .await
+            .unwrap();
+        assert_eq!(fs::read(fixture.local("new/empty.txt")).unwrap(), b"");
+        assert!(matches!(
+            provider.create_file(&fixture.url("b.txt")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            provider.create_dir(&fixture.url("docs")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        provider
+            .copy(&fixture.url("docs"), &fixture.url("new/docs"))
+            .await
+            .unwrap();
+        assert_eq!(fs::read(fixture.local("new/docs/a.txt")).unwrap(), b"aaa");
+        assert!(matches!(
+            provider
+                .copy(&fixture.url("docs"), &fixture.url("docs/inner"))
+                .await,
+            Err(StorageError::Other(_))
+        ));
+
+        provider
+            .rename(&fixture.url("b.txt"), &fixture.url("new/c.txt"))
+            .await
+            .unwrap();
+        assert!(!fixture.local("b.txt").exists());
+        assert_eq!(fs::read(fixture.local("new/c.txt")).unwrap(), b"hello");
+        assert!(matches!(
+            provider
+                .rename(&fixture.url("My File.log"), &fixture.url("new/c.txt"))
+                .await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        // 删除目录时不跟随其中的符号链接
+        symlink("../docs", fixture.local("new/docs-link")).unwrap();
+        provider.delete(&fixture.url("new")).await.unwrap();
+        assert!(!fixture.local("new").exists());
+        assert!(fixture.local("docs/a.txt").exists());
+        provider.delete(&fixture.url("link")).await.unwrap();
+        assert!(fixture.local("docs").is_dir());
+
+        assert!(provider.exists(&fixture.url("docs")).await.unwrap());
+        assert!(!provider.exists(&fixture.url("new")).await.unwrap());
+    });
+}
+
+#[test]
+fn resumes_interrupted_downloads() {
+    let fixture = Fixture::new();
+    let content: Vec<u8> = (0..200_000u32).map(|i| (i % 253) as u8).collect();
+    fs::write(fixture.local("large.bin"), &content).unwrap();
+
+    fixture.state.cut_next_retr.store(true, Ordering::SeqCst);
+    assert_eq!(read(&fixture.provider, &fixture.url("large.bin")), content);
+
+    let log = fixture.take_log();
+    let retrs = log.iter().filter(|line| *line == "RETR /large.bin").count();
+    assert_eq!(retrs, 2);
+    assert!(log.contains(&"REST 100000".to_string()));
+
+    // 从指定位置开始下载
+    let mut tail = vec![];
+    smol::block_on(
+        fixture
+            .provider
+            .read_file_from(&fixture.url("large.bin"), 199_990, &mut tail),
+    )
+    .unwrap();
+    assert_eq!(tail, content[199_990..]);
+}
+
+#[test]
+fn resumes_uploads() {
+    let fixture = Fixture::new();
+    let content: Vec<u8> = (0..100_000u32).map(|i| (i % 249) as u8).collect();
+    let url = fixture.url("upload.bin");
+
+    // 第一次上传只完成了一部分
+    smol::block_on(
+        fixture
+            .provider
+            .write_file_from(&url, 0, &mut &content[..40_000]),
+    )
+    .unwrap();
+    let uploaded = smol::block_on(fixture.provider.file_size(&url)).unwrap();
+    assert_eq!(uploaded, 40_000);
+
+    smol::block_on(fixture.provider.write_file_from(
+        &url,
+        uploaded,
+        &mut &content[uploaded as usize..],
+    ))
+    .unwrap();
+    assert_eq!(fs::read(fixture.local("upload.bin")).unwrap(), content);
+    assert!(fixture.take_log().contains(&"REST 40000".to_string()));
+}
+
+#[test]
+fn reports_errors() {
+    let fixture = Fixture::new();
+    fixture.populate();
+    fs::create_dir(fixture.local("locked")).unwrap();
+    smol::block_on(async {
+        let provider = &fixture.provider;
+        let missing = fixture.url("missing.txt");
+        assert!(matches!(
+            provider.get_metadata(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.list_entries(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        let mut sink = vec![];
+        assert!(matches!(
+            provider.read_file(&missing, &mut sink).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.list_entries(&fixture.url("b.txt")).await,
+            Err(StorageError::Other(_))
+        ));
+        assert!(matches!(
+            provider.delete(&fixture.url("locked")).await,
+            Err(StorageError::PermissionDenied(_))
+        ));
+        // 换行符不能用来注入命令
+        assert!(matches!(
+            provider.create_dir(&fixture.url("a\r\nDELE /b.txt")).await,
+            Err(StorageError::Other(_))
+        ));
+        assert!(fixture.local("b.txt").exists());
+        assert!(matches!(
+            provider.get_metadata("ftp://bob@localhost:21/").await,
+            Err(StorageError::Other(_))
+        ));
+    });
+
+    let wrong = Fixture::build(Options::default(), "wrong", None);
+    assert!(matches!(
+        smol::block_on(wrong.provider.list_entries(&wrong.url(""))),
+        Err(StorageError::PermissionDenied(_))
+    ));
+}
+
+#[test]
+fn connects_with_explicit_tls() {
+    let fixture = Fixture::with_options(Options {
+        security: FtpSecurity::Explicit,
+        ..Default::default()
+    });
+    fixture.populate();
+    assert!(fixture.url("").starts_with("ftps://"));
+    check_listing(&fixture, true, true);
+
+    let content: Vec<u8> = (0..150_000u32).map(|i| (i % 241) as u8).collect();
+    smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("secret.bin"), &mut content.as_slice()),
+    )
+    .unwrap();
+    assert_eq!(fs::read(fixture.local("secret.bin")).unwrap(), content);
+
+    // 加密的数据连接中断时同样续传
+    fixture.state.cut_next_retr.store(true, Ordering::SeqCst);
+    assert_eq!(read(&fixture.provider, &fixture.url("secret.bin")), content);
+
+    let log = fixture.take_log();
+    assert_eq!(log[0], "AUTH TLS");
+    assert!(log.contains(&"PROT P".to_string()));
+    assert!(log.contains(&"REST 75000".to_string()));
+}
+
+#[test]
+fn connects_with_implicit_tls() {
+    let fixture = Fixture::with_options(Options {
+        security: FtpSecurity::Implicit,
+        mlsd: false,
+        ..Default::default()
+    });
+    fixture.populate();
+    check_listing(&fixture, true, false);
+    assert_eq!(read(&fixture.provider, &fixture.url("docs/a.txt")), b"aaa");
+    assert!(!fixture.take_log().contains(&"AUTH TLS".to_string()));
+}
+
+#[test]
+fn rejects_untrusted_certificates() {
+    let fixture = Fixture::build(
+        Options {
+            security: FtpSecurity::Explicit,
+            ..Default::default()
+        },
+        PASSWORD,
+        None,
+    );
+    let result = smol::block_on(fixture.provider.list_entries(&fixture.url("")));
+    assert!(matches!(result, Err(StorageError::Other(message)) if message.contains("TLS")));
+    // 握手失败前不发送密码
+    assert!(
+        !fixture
+            .take_log()
+            .iter()
+            .any(|line| line.starts_with("PASS"))
+    );
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    let fixture = Fixture::new();
+    fs::create_dir(fixture.local("tree")).unwrap();
+    conformance::create_tree(&fixture.local("tree"), true).unwrap();
+    smol::block_on(conformance::run(&fixture));
+}
+
+#[test]
+fn conforms_to_provider_contract_with_list() {
+    let fixture = Fixture::with_options(Options {
+        mlsd: false,
+        ..Default::default()
+    });
+    fs::create_dir(fixture.local("tree")).unwrap();
+    conformance::create_tree(&fixture.local("tree"), true).unwrap();
+    smol::block_on(conformance::run(&fixture));
+}
@@ -1,16 +1,46 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::Path,
+};
 
 use async_trait::async_trait;
 use mime_guess::from_path;
 
 use explorer_storage::*;
 
+use ops::{
+    copy_path, create_symlink, ensure_absent, is_same_entry, map_io_error, move_path, remove_path,
+};
+use trash::{restore_path, trash_path};
+
+#[cfg(target_os = "linux")]
+pub use mounts::MountWatcher;
+
+#[cfg(target_os = "linux")]
+mod mounts;
+mod ops;
+#[cfg(unix)]
+mod owners;
+mod trash;
+
 /// 本地文件系统存储提供者
-pub struct LocalFileSystemProvider;
+pub struct LocalFileSystemProvider {
+    /// 根节点中是否包含虚拟文件系统和系统挂载（仅 Linux）
+    show_pseudo_mounts: bool,
+}
 
 impl LocalFileSystemProvider {
     pub fn new() -> Self {
-        Self
+        Self {
+            show_pseudo_mounts: false,
+        }
+    }
+
+    /// 设置根节点中是否包含 proc、sysfs、cgroup 等虚拟文件系统和系统挂载
+    pub fn show_pseudo_mounts(mut self, show: bool) -> Self {
+        self.show_pseudo_mounts = show;
+        self
     }
 
     /// 检查文件名是否为隐藏文件
@@ -45,6 +75,134 @@ impl LocalFileSystemProvider {
     fn guess_mime_type(path: &Path) -> Option<String> {
         from_path(path).first().map(|mime| mime.to_string())
     }
+
+    /// 由条目本身的元数据（不跟随符号链接）生成文件条目
+    fn file_item(path: &Path, metadata: &fs::Metadata) -> FileItem {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+
+        let item_type = if metadata.is_symlink() {
+            ItemType::Symlink
+        } else if metadata.is_dir() {
+            ItemType::Directory
+        } else {
+            ItemType::File
+        };
+
+        // 符号链接的目标：读取链接内容，并跟随链接获取最终目标的类型
+        let (link_target, link_target_type, broken_link) = if item_type == ItemType::Symlink {
+            let link_target = fs::read_link(path)
+                .ok()
+                .map(|target| target.display().to_string());
+            match fs::metadata(path) {
+                Ok(target) if target.is_dir() => (link_target, Some(ItemType::Directory), false),
+                Ok(_) => (link_target, Some(ItemType::File), false),
+                Err(e) => {
+                    // 目标不存在或链接成环时为悬空链接，没有权限访问目标时不算
+                    #[cfg(unix)]
+                    let broken = e.kind() == io::ErrorKind::NotFound
+                        || e.raw_os_error() == Some(libc::ELOOP);
+                    #[cfg(not(unix))]
+                    let broken = e.kind() == io::ErrorKind::NotFound;
+                    (link_target, None, broken)
+                }
+            }
+        } else {
+            (None, None, false)
+        };
+
+        // 获取修改时间
+        let modified = metadata
+            .modified()
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+
+        // 获取创建时间（某些平台可能不支持）
+        let created = metadata.created().ok();
+
+        // 获取访问时间（某些平台可能不支持）
+        let accessed = metadata.accessed().ok();
+
+        // 获取权限
+        #[cfg(unix)]
+        let permissions = {
+            use std::os::unix::fs::PermissionsExt;
+            Some(metadata.permissions().mode())
+        };
+
+        #[cfg(not(unix))]
+        let permissions = None;
+
+        // 获取设备号
+        #[cfg(unix)]
+        let device = {
+            use std::os::unix::fs::MetadataExt;
+            Some(metadata.dev())
+        };
+
+        #[cfg(not(unix))]
+        let device = None;
+
+        // 获取所有者、inode、硬链接数和占用的块数
+        #[cfg(unix)]
+        let (uid, gid, inode, hard_links, blocks) = {
+            use std::os::unix::fs::MetadataExt;
+            (
+                Some(metadata.uid()),
+                Some(metadata.gid()),
+                Some(metadata.ino()),
+                Some(metadata.nlink()),
+                Some(metadata.blocks()),
+            )
+        };
+
+        #[cfg(not(unix))]
+        let (uid, gid, inode, hard_links, blocks) = (None, None, None, None, None);
+
+        #[cfg(unix)]
+        let (user, group) = (
+            uid.and_then(owners::user_name),
+            gid.and_then(owners::group_name),
+        );
+
+        #[cfg(not(unix))]
+        let (user, group) = (None, None);
+
+        // 推断 MIME 类型（仅对文件）
+        let mime_type = if item_type == ItemType::File {
+            Self::guess_mime_type(path)
+        } else {
+            None
+        };
+
+        FileItem {
+            name: file_name,
+            path: path.display().to_string(),
+            item_type,
+            is_hidden: Self::is_hidden(path),
+            size: metadata.len(),
+            modified,
+            metadata: EntryMetadata {
+                permissions,
+                mime_type,
+                created,
+                accessed,
+                device,
+                uid,
+                user,
+                gid,
+                group,
+                inode,
+                hard_links,
+                blocks,
+                link_target,
+                link_target_type,
+                broken_link,
+                ..Default::default()
+            },
+        }
+    }
 }
 
 impl Default for LocalFileSystemProvider {
@@ -56,7 +214,9 @@ impl Default for LocalFileSystemProvider {
 #[async_trait]
 impl StorageProvider for LocalFileSystemProvider {
     async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
-        smol::unblock(|| {
+        #[cfg(target_os = "linux")]
+        let show_pseudo_mounts = self.show_pseudo_mounts;
+        smol::unblock(move || {
             let mut roots = Vec::new();
 
             #[cfg(target_os = "macos")]
@@ -75,6 +235,7 @@ impl StorageProvider for LocalFileSystemProvider {
                                         path,
                                         provider_type: ProviderType::LocalFileSystem,
                                         icon: None,
+                                        mount: None,
                                     });
                                 }
                             }
@@ -91,43 +252,15 @@ impl StorageProvider for LocalFileSystemProvider {
                         path: "/".to_string(),
                         provider_type: ProviderType::LocalFileSystem,
                         icon: None,
+                        mount: None,
                     },
                 );
             }
 
             #[cfg(target_os = "linux")]
             {
-                // Linux: 根目录和常见挂载点
-                roots.push(RootItem {
-                    id: "/".to_string(),
-                    name: "Root".to_string(),
-                    path: "/".to_string(),
-                    provider_type: ProviderType::LocalFileSystem,
-                    icon: None,
-                });
-
-                // 检查 /mnt 和 /media
-                for mount_point in &["/mnt", "/media"] {
-                    if let Ok(read_dir) = fs::read_dir(mount_point) {
-                        for entry in read_dir {
-                            if let Ok(entry) = entry {
-                                if let Ok(metadata) = entry.metadata() {
-                                    if metadata.is_dir() {
-                                        let name = entry.file_name().to_string_lossy().to_string();
-                                        let path = entry.path().display().to_string();
-                                        roots.push(RootItem {
-                                            id: path.clone(),
-                                            name,
-                                            path,
-                                            provider_type: ProviderType::LocalFileSystem,
-                                            icon: None,
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+                // Linux: 挂载表中的文件系统
+                roots.extend(mounts::mounted_roots(show_pseudo_mounts));
             }
 
             #[cfg(target_os = "windows")]
@@ -143,6 +276,7 @@ impl StorageProvider for LocalFileSystemProvider {
                             path,
                             provider_type: ProviderType::LocalFileSystem,
                             icon: None,
+                            mount: None,
                         });
                     }
                 }
@@ -158,68 +292,8 @@ impl StorageProvider for LocalFileSystemProvider {
 
         smol::unblock(move || {
             let path = Path::new(&path_str);
-
-            if !path.exists() {
-                return Err(StorageError::PathNotFound(path.display().to_string()));
-            }
-
-            let metadata = fs::metadata(path)?;
-            let file_name = path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| path.display().to_string());
-
-            let item_type = if metadata.is_dir() {
-                ItemType::Directory
-            } else if metadata.is_symlink() {
-                ItemType::Symlink
-            } else {
-                ItemType::File
-            };
-
-            // 获取修改时间
-            let modified = metadata
-                .modified()
-                .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-
-            // 获取创建时间（某些平台可能不支持）
-            let created = metadata.created().ok();
-
-            // 获取访问时间（某些平台可能不支持）
-            let accessed = metadata.accessed().ok();
-
-            // 获取权限
-            #[cfg(unix)]
-            let permissions = {
-                use std::os::unix::fs::PermissionsExt;
-                Some(metadata.permissions().mode())
-            };
-
-            #[cfg(not(unix))]
-            let permissions = None;
-
-            // 推断 MIME 类型（仅对文件）
-            let mime_type = if item_type == ItemType::File {
-                Self::guess_mime_type(path)
-            } else {
-                None
-            };
-
-            Ok(FileItem {
-                name: file_name.clone(),
-                path: path.display().to_string(),
-                item_type,
-                is_hidden: Self::is_hidden(path),
-                size: metadata.len(),
-                modified,
-                metadata: EntryMetadata {
-                    permissions,
-                    mime_type,
-                    created,
-                    accessed,
-                    ..Default::default()
-                },
-            })
+            let metadata = fs::symlink_metadata(path).map_err(|e| map_io_error(e, path))?;
+            Ok(Self::file_item(path, &metadata))
         })
         .await
     }
@@ -230,11 +304,9 @@ impl StorageProvider for LocalFileSystemProvider {
         smol::unblock(move || {
             let path = Path::new(&path_str);
 
-            if !path.exists() {
-                return Err(StorageError::PathNotFound(path.display().to_string()));
-            }
-
-            if !path.is_dir() {
+            // 跟随符号链接：指向目录的链接可以像目录一样打开
+            let metadata = fs::metadata(path).map_err(|e| map_io_error(e, path))?;
+            if !metadata.is_dir() {
                 return Err(StorageError::Other(format!(
                     "路径不是目录: {}",
                     path.display()
@@ -245,73 +317,12 @@ impl StorageProvider for LocalFileSystemProvider {
             let read_dir = fs::read_dir(path)?;
 
             for entry in read_dir {
-                let entry = entry?;
-                let entry_path = entry.path();
-                let metadata = entry.metadata()?;
-                let file_name = entry.file_name();
-                let name = file_name.to_string_lossy().to_string();
-                let path_str = entry_path.display().to_string();
-
-                let item_type = if metadata.is_dir() {
-                    ItemType::Directory
-                } else if metadata.is_symlink() {
-                    ItemType::Symlink
-                } else {
-                    ItemType::File
-                };
-
-                // 获取修改时间
-                let modified = metadata
-                    .modified()
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-
-                // 获取创建时间（某些平台可能不支持）
-                let created = metadata.created().ok();
-
-                // 获取访问时间（某些平台可能不支持）
-                let accessed = metadata.accessed().ok();
-
-                // 获取权限
-                #[cfg(unix)]
-                let permissions = {
-                    use std::os::unix::fs::PermissionsExt;
-                    Some(metadata.permissions().mode())
-                };
-
-                #[cfg(not(unix))]
-                let permissions = None;
-
-                // 推断 MIME 类型（仅对文件）
-                let mime_type = if item_type == ItemType::File {
-                    Self::guess_mime_type(&entry_path)
-                } else {
-                    None
-                };
-
-                entries.push(FileItem {
-                    name: name.clone(),
-                    path: path_str,
-                    item_type,
-                    is_hidden: Self::is_hidden(&entry_path),
-                    size: metadata.len(),
-                    modified,
-                    metadata: EntryMetadata {
-                        permissions,
-                        mime_type,
-                        created,
-                        accessed,
-                        ..Default::default()
-                    },
-                });
+                let entry_path = entry?.path();
+                let metadata = fs::symlink_metadata(&entry_path)?;
+                entries.push(Self::file_item(&entry_path, &metadata));
             }
 
-            // 按名称排序：目录在前，文件在后
-            entries.sort_by(|a, b| match (a.item_type, b.item_type) {
-                (ItemType::Directory, ItemType::Directory) => a.name.cmp(&b.name),
-                (ItemType::Directory, _) => std::cmp::Ordering::Less,
-                (_, ItemType::Directory) => std::cmp::Ordering::Greater,
-                _ => a.name.cmp(&b.name),
-            });
+            sort_entries(&mut entries);
 
             Ok(entries)
         })
@@ -319,7 +330,155 @@ impl StorageProvider for LocalFileSystemProvider {
     }
 
     async fn exists(&self, path: &str) -> StorageResult<bool> {
-        Ok(Path::new(path).exists())
+        // 悬空的符号链接也算存在
+        Ok(Path::new(path).symlink_metadata().is_ok())
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        // `writer` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let path = Path::new(path);
+        let mut file = fs::File::open(path).map_err(|e| map_io_error(e, path))?;
+        Ok(io::copy(&mut file, writer)?)
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        // `reader` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let path = Path::new(path);
+        let mut file = fs::File::create(path).map_err(|e| map_io_error(e, path))?;
+        let written = io::copy(reader, &mut file)?;
+        file.flush()?;
+        Ok(written)
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        let path_str = path.to_string();
+
+        smol::unblock(move || {
+            let path = Path::new(&path_str);
+            ensure_absent(path)?;
+            fs::create_dir(path).map_err(|e| map_io_error(e, path))
+        })
+        .await
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        let path_str = path.to_string();
+
+        smol::unblock(move || {
+            let path = Path::new(&path_str);
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+                .map(|_| ())
+                .map_err(|e| map_io_error(e, path))
+        })
+        .await
+    }
+
+    async fn create_symlink(&self, target: &str, path: &str) -> StorageResult<()> {
+        let (target_str, path_str) = (target.to_string(), path.to_string());
+
+        smol::unblock(move || {
+            let (target, path) = (Path::new(&target_str), Path::new(&path_str));
+            ensure_absent(path)?;
+            create_symlink(target, path)
+        })
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (from_str, to_str) = (from.to_string(), to.to_string());
+
+        smol::unblock(move || {
+            let (from, to) = (Path::new(&from_str), Path::new(&to_str));
+            // 不区分大小写的文件系统上，仅修改大小写的重命名会命中自身
+            if !is_same_entry(from, to) {
+                ensure_absent(to)?;
+            }
+            fs::rename(from, to).map_err(|e| map_io_error(e, from))
+        })
+        .await
+    }
+
+    async fn move_entry(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (from_str, to_str) = (from.to_string(), to.to_string());
+
+        smol::unblock(move || {
+            let (from, to) = (Path::new(&from_str), Path::new(&to_str));
+            ensure_absent(to)?;
+            move_path(from, to)
+        })
+        .await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (from_str, to_str) = (from.to_string(), to.to_string());
+
+        smol::unblock(move || {
+            let (from, to) = (Path::new(&from_str), Path::new(&to_str));
+            ensure_absent(to)?;
+            if to.starts_with(from) {
+                return Err(StorageError::Other(format!(
+                    "不能将目录复制到其自身内部: {}",
+                    to.display()
+                )));
+            }
+            copy_path(from, to)
+        })
+        .await
+    }
+
+    async fn trash(&self, path: &str) -> StorageResult<String> {
+        let path_str = path.to_string();
+
+        smol::unblock(move || {
+            trash_path(Path::new(&path_str)).map(|trashed| trashed.display().to_string())
+        })
+        .await
+    }
+
+    async fn restore(&self, trashed: &str, original: &str) -> StorageResult<()> {
+        let (trashed_str, original_str) = (trashed.to_string(), original.to_string());
+
+        smol::unblock(move || restore_path(Path::new(&trashed_str), Path::new(&original_str))).await
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        let path_str = path.to_string();
+
+        smol::unblock(move || remove_path(Path::new(&path_str))).await
+    }
+
+    async fn get_space(&self, path: &str) -> StorageResult<SpaceInfo> {
+        let path_str = path.to_string();
+
+        #[cfg(unix)]
+        {
+            smol::unblock(move || ops::disk_space(Path::new(&path_str))).await
+        }
+
+        #[cfg(not(unix))]
+        {
+            Err(StorageError::Unsupported(format!(
+                "获取可用空间: {}",
+                path_str
+            )))
+        }
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            can_create: true,
+            can_rename: true,
+            can_copy: true,
+            can_move: true,
+            can_trash: true,
+            can_delete: true,
+            can_symlink: true,
+            can_archive: false,
+            local_paths: true,
+        }
     }
 
     fn provider_type(&self) -> ProviderType {
@@ -0,0 +1,371 @@
+//! Linux 挂载点
+//!
+//! 解析 `/proc/self/mountinfo` 得到当前挂载的文件系统，卷标来自
+//! `/dev/disk/by-label`。默认过滤掉 proc、sysfs、cgroup 等虚拟文件系统以及
+//! `/proc`、`/sys`、`/dev`、`/run` 下的系统挂载（`/run/media` 除外）。
+//!
+//! 挂载表变化时内核会在打开的 `mountinfo` 上产生 `POLLPRI` 事件，
+//! [`MountWatcher`] 据此等待挂载和卸载，不需要定时轮询。
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io,
+    os::fd::AsRawFd,
+    path::{Path, PathBuf},
+};
+
+use explorer_storage::{MountInfo, ProviderType, RootItem};
+
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const LABELS_DIR: &str = "/dev/disk/by-label";
+
+/// 虚拟文件系统类型
+const PSEUDO_FS_TYPES: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "fuse.gvfsd-fuse",
+    "fuse.portal",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "selinuxfs",
+    "squashfs",
+    "sysfs",
+    "tracefs",
+];
+
+/// 系统挂载所在的目录
+const SYSTEM_PREFIXES: &[&str] = &["/proc", "/sys", "/dev", "/run", "/snap", "/var/lib/docker"];
+
+/// `mountinfo` 中的一个挂载
+#[derive(Debug, Clone)]
+struct Mount {
+    mount_point: String,
+    device: String,
+    fs_type: String,
+    read_only: bool,
+}
+
+/// 当前挂载的文件系统（`/` 在最前，其余按挂载点排序）
+///
+/// `include_pseudo` 为 `false` 时过滤虚拟文件系统和系统挂载
+pub fn mounted_roots(include_pseudo: bool) -> Vec<RootItem> {
+    let content = fs::read_to_string(MOUNTINFO_PATH).unwrap_or_default();
+
+    // 同一挂载点被多次挂载时只有最后一次可见
+    let mut mounts: Vec<Mount> = vec![];
+    for mount in content.lines().filter_map(parse_line) {
+        mounts.retain(|existing| existing.mount_point != mount.mount_point);
+        mounts.push(mount);
+    }
+    if !include_pseudo {
+        mounts.retain(is_user_mount);
+    }
+    // 无法读取挂载表时（如受限的容器中）至少保留根目录
+    if mounts.is_empty() {
+        mounts.push(Mount {
+            mount_point: "/".to_string(),
+            device: String::new(),
+            fs_type: String::new(),
+            read_only: false,
+        });
+    }
+    mounts.sort_by(
+        |a, b| match (a.mount_point.as_str(), b.mount_point.as_str()) {
+            ("/", "/") => std::cmp::Ordering::Equal,
+            ("/", _) => std::cmp::Ordering::Less,
+            (_, "/") => std::cmp::Ordering::Greater,
+            (a, b) => a.cmp(b),
+        },
+    );
+
+    let labels = read_labels();
+    mounts
+        .iter()
+        .map(|mount| {
+            let label = canonical(&mount.device).and_then(|device| labels.get(&device).cloned());
+            root_item(mount, label)
+        })
+        .collect()
+}
+
+/// 等待挂载表变化
+pub struct MountWatcher {
+    file: File,
+}
+
+impl MountWatcher {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            file: File::open(MOUNTINFO_PATH)?,
+        })
+    }
+
+    /// 阻塞直到挂载或卸载文件系统（打开之后的第一次变化，此后每次调用等待下一次变化）
+    pub fn wait(&self) -> io::Result<()> {
+        let mut poll_fd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLPRI,
+            revents: 0,
+        };
+        loop {
+            // SAFETY: poll_fd 在调用期间有效，数量与传入的一致
+            let ret = unsafe { libc::poll(&mut poll_fd, 1, -1) };
+            if ret < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+            if poll_fd.revents & (libc::POLLPRI | libc::POLLERR) != 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// 解析一行 `mountinfo`：
+///
+/// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+///
+/// 依次为挂载 ID、父 ID、设备号、挂载源中的根、挂载点、挂载选项、若干可选字段、
+/// 分隔符 `-`、文件系统类型、挂载源和超级块选项
+fn parse_line(line: &str) -> Option<Mount> {
+    let (before, after) = line.split_once(" - ")?;
+    let fields: Vec<&str> = before.split(' ').collect();
+    let mount_point = unescape(fields.get(4)?);
+    let options = fields.get(5)?;
+
+    let mut after = after.split(' ');
+    let fs_type = unescape(after.next()?);
+    let device = unescape(after.next()?);
+    let super_options = after.next().unwrap_or_default();
+
+    let read_only = options.split(',').any(|option| option == "ro")
+        || super_options.split(',').any(|option| option == "ro");
+    Some(Mount {
+        mount_point,
+        device,
+        fs_type,
+        read_only,
+    })
+}
+
+/// 还原 `mountinfo` 中的八进制转义（空格为 `\040`）
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && let Some(code) = bytes
+                .get(i + 1..i + 4)
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        {
+            result.push(code);
+            i += 4;
+        } else {
+            result.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// 是否为用户关心的挂载
+fn is_user_mount(mount: &Mount) -> bool {
+    if mount.mount_point == "/" {
+        return true;
+    }
+    if PSEUDO_FS_TYPES.contains(&mount.fs_type.as_str()) {
+        return false;
+    }
+    if mount.mount_point.starts_with("/run/media/") {
+        return true;
+    }
+    !SYSTEM_PREFIXES.iter().any(|prefix| {
+        mount.mount_point == *prefix || mount.mount_point.starts_with(&format!("{}/", prefix))
+    })
+}
+
+/// 设备到卷标的映射（`/dev/disk/by-label` 中的链接名，`\x20` 等为转义字符）
+fn read_labels() -> HashMap<PathBuf, String> {
+    let Ok(read_dir) = fs::read_dir(LABELS_DIR) else {
+        return HashMap::new();
+    };
+    read_dir
+        .flatten()
+        .filter_map(|entry| {
+            let device = fs::canonicalize(entry.path()).ok()?;
+            let label = unescape_label(&entry.file_name().to_string_lossy());
+            Some((device, label))
+        })
+        .collect()
+}
+
+/// 还原卷标中的 `\xHH` 转义
+fn unescape_label(name: &str) -> String {
+    let mut result = Vec::with_capacity(name.len());
+    let bytes = name.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && bytes.get(i + 1) == Some(&b'x')
+            && let Some(code) = bytes
+                .get(i + 2..i + 4)
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        {
+            result.push(code);
+            i += 4;
+        } else {
+            result.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&result).to_string()
+}
+
+/// 设备文件的真实路径（挂载源不是设备文件时返回 `None`）
+fn canonical(device: &str) -> Option<PathBuf> {
+    if !device.starts_with("/dev/") {
+        return None;
+    }
+    fs::canonicalize(device).ok()
+}
+
+fn root_item(mount: &Mount, label: Option<String>) -> RootItem {
+    let name = if mount.mount_point == "/" {
+        "Root".to_string()
+    } else {
+        label.clone().unwrap_or_else(|| {
+            Path::new(&mount.mount_point)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| mount.mount_point.clone())
+        })
+    };
+    RootItem {
+        id: mount.mount_point.clone(),
+        name,
+        path: mount.mount_point.clone(),
+        provider_type: ProviderType::LocalFileSystem,
+        icon: None,
+        mount: (!mount.fs_type.is_empty()).then(|| MountInfo {
+            device: mount.device.clone(),
+            fs_type: mount.fs_type.clone(),
+            label,
+            read_only: mount.read_only,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mount(mount_point: &str, fs_type: &str) -> Mount {
+        Mount {
+            mount_point: mount_point.to_string(),
+            device: "/dev/sda1".to_string(),
+            fs_type: fs_type.to_string(),
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn parses_lines() {
+        let mount = parse_line(
+            "36 35 98:0 /mnt1 /mnt/my\\040disk rw,noatime master:1 - ext3 /dev/root rw,errors=continue",
+        )
+        .unwrap();
+        assert_eq!(mount.mount_point, "/mnt/my disk");
+        assert_eq!(mount.device, "/dev/root");
+        assert_eq!(mount.fs_type, "ext3");
+        assert!(!mount.read_only);
+
+        // 没有可选字段，只读来自超级块选项
+        let mount = parse_line("25 1 0:22 / /boot ro,relatime - vfat /dev/sda1 ro").unwrap();
+        assert_eq!(
+            (mount.mount_point.as_str(), mount.read_only),
+            ("/boot", true)
+        );
+        let mount = parse_line("25 1 0:22 / /data rw - xfs /dev/sdb1 ro").unwrap();
+        assert!(mount.read_only);
+
+        assert!(parse_line("").is_none());
+        assert!(parse_line("36 35 98:0 /mnt1 /mnt2 rw").is_none());
+        assert!(parse_line("36 35 98:0 / - ext4 /dev/sda1 rw").is_none());
+    }
+
+    #[test]
+    fn parses_bind_mounts() {
+        // 绑定挂载的根为源目录中的子目录，挂载点和设备与普通挂载相同
+        let mount = parse_line(
+            "120 29 8:1 /home/user/shared /srv/shared rw,relatime shared:1 - ext4 /dev/sda1 rw",
+        )
+        .unwrap();
+        assert_eq!(mount.mount_point, "/srv/shared");
+        assert_eq!(mount.device, "/dev/sda1");
+        assert!(is_user_mount(&mount));
+    }
+
+    #[test]
+    fn unescapes_octal() {
+        assert_eq!(unescape("a\\040b\\011c\\134d"), "a b\tc\\d");
+        // 不完整或无效的转义原样保留
+        assert_eq!(unescape("a\\04"), "a\\04");
+        assert_eq!(unescape("a\\089"), "a\\089");
+        assert_eq!(unescape("\\346\\226\\207"), "文");
+    }
+
+    #[test]
+    fn unescapes_labels() {
+        assert_eq!(unescape_label("My\\x20Disk"), "My Disk");
+        assert_eq!(unescape_label("a\\x2"), "a\\x2");
+    }
+
+    #[test]
+    fn filters_system_mounts() {
+        assert!(is_user_mount(&mount("/", "ext4")));
+        assert!(is_user_mount(&mount("/home", "ext4")));
+        assert!(is_user_mount(&mount("/mnt/usb", "vfat")));
+        assert!(is_user_mount(&mount("/run/media/user/disk", "exfat")));
+        assert!(is_user_mount(&mount("/runner", "ext4")));
+
+        assert!(is_user_mount(&mount("/data", "tmpfs")));
+        for fs_type in ["proc", "sysfs", "cgroup2", "squashfs"] {
+            assert!(!is_user_mount(&mount("/data", fs_type)), "{}", fs_type);
+        }
+        for mount_point in [
+            "/proc",
+            "/sys/kernel",
+            "/dev/shm",
+            "/run/user/1000",
+            "/snap/core",
+        ] {
+            assert!(
+                !is_user_mount(&mount(mount_point, "tmpfs")),
+                "{}",
+                mount_point
+            );
+        }
+    }
+}
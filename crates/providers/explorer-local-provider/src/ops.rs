@@ -0,0 +1,140 @@
+//! 本地文件系统的写操作辅助函数
+
+use std::{fs, io, path::Path};
+
+use explorer_storage::{StorageError, StorageResult};
+
+/// 将 IO 错误转换为带路径信息的存储错误
+pub fn map_io_error(err: io::Error, path: &Path) -> StorageError {
+    let path = path.display().to_string();
+    match err.kind() {
+        io::ErrorKind::NotFound => StorageError::PathNotFound(path),
+        io::ErrorKind::PermissionDenied => StorageError::PermissionDenied(path),
+        io::ErrorKind::AlreadyExists => StorageError::AlreadyExists(path),
+        _ => StorageError::IoError(err),
+    }
+}
+
+/// 获取路径所在卷的容量（statvfs）
+#[cfg(unix)]
+// statvfs 各字段的类型随平台不同（如 macOS 上为 u32）
+#[allow(clippy::unnecessary_cast)]
+pub fn disk_space(path: &Path) -> StorageResult<explorer_storage::SpaceInfo> {
+    use std::{ffi::CString, mem::MaybeUninit, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| StorageError::Other(format!("无效的路径: {}", path.display())))?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: c_path 是以 NUL 结尾的字符串，stat 由 statvfs 在成功时完整写入
+    let result = unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) };
+    if result != 0 {
+        return Err(map_io_error(io::Error::last_os_error(), path));
+    }
+    // SAFETY: statvfs 返回 0 表示 stat 已初始化
+    let stat = unsafe { stat.assume_init() };
+
+    let block_size = stat.f_frsize as u64;
+    Ok(explorer_storage::SpaceInfo {
+        total: stat.f_blocks as u64 * block_size,
+        free: stat.f_bfree as u64 * block_size,
+        available: stat.f_bavail as u64 * block_size,
+    })
+}
+
+/// 确保目标路径不存在（包括悬空的符号链接）
+pub fn ensure_absent(path: &Path) -> StorageResult<()> {
+    if path.symlink_metadata().is_ok() {
+        return Err(StorageError::AlreadyExists(path.display().to_string()));
+    }
+    Ok(())
+}
+
+/// 判断两个路径是否指向同一个条目（不跟随符号链接）
+pub fn is_same_entry(a: &Path, b: &Path) -> bool {
+    let (Ok(meta_a), Ok(meta_b)) = (a.symlink_metadata(), b.symlink_metadata()) else {
+        return false;
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        meta_a.dev() == meta_b.dev() && meta_a.ino() == meta_b.ino()
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (meta_a, meta_b);
+        matches!((a.canonicalize(), b.canonicalize()), (Ok(x), Ok(y)) if x == y)
+    }
+}
+
+/// 递归复制文件或目录，符号链接按链接本身复制
+pub fn copy_path(from: &Path, to: &Path) -> StorageResult<()> {
+    let metadata = from.symlink_metadata().map_err(|e| map_io_error(e, from))?;
+
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(from).map_err(|e| map_io_error(e, from))?;
+        return create_symlink(&target, to);
+    }
+
+    if metadata.is_dir() {
+        fs::create_dir(to).map_err(|e| map_io_error(e, to))?;
+        for entry in fs::read_dir(from).map_err(|e| map_io_error(e, from))? {
+            let entry = entry?;
+            copy_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions()).map_err(|e| map_io_error(e, to))?;
+    } else {
+        fs::copy(from, to).map_err(|e| map_io_error(e, from))?;
+    }
+
+    Ok(())
+}
+
+/// 递归删除文件或目录（不跟随符号链接）
+pub fn remove_path(path: &Path) -> StorageResult<()> {
+    let metadata = path.symlink_metadata().map_err(|e| map_io_error(e, path))?;
+    if metadata.is_dir() {
+        fs::remove_dir_all(path).map_err(|e| map_io_error(e, path))
+    } else {
+        fs::remove_file(path).map_err(|e| map_io_error(e, path))
+    }
+}
+
+/// 移动文件或目录，跨设备时回退为复制后删除
+pub fn move_path(from: &Path, to: &Path) -> StorageResult<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_path(from, to)?;
+            remove_path(from)
+        }
+        Err(e) => Err(map_io_error(e, from)),
+    }
+}
+
+/// 创建符号链接
+pub fn create_symlink(target: &Path, link: &Path) -> StorageResult<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link).map_err(|e| map_io_error(e, link))
+    }
+
+    #[cfg(windows)]
+    {
+        let result = if target.is_dir() {
+            std::os::windows::fs::symlink_dir(target, link)
+        } else {
+            std::os::windows::fs::symlink_file(target, link)
+        };
+        result.map_err(|e| map_io_error(e, link))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        Err(StorageError::Unsupported(format!(
+            "创建符号链接: {}",
+            link.display()
+        )))
+    }
+}
@@ -0,0 +1,97 @@
+//! 解析 Unix 用户和组名
+//!
+//! 通过 `getpwuid_r`/`getgrgid_r` 查询（会经过 NSS，可能访问 LDAP 等网络服务），
+//! 结果按 ID 缓存，列出大目录时每个用户和组只查询一次。
+
+use std::{
+    collections::HashMap,
+    ffi::CStr,
+    mem::MaybeUninit,
+    sync::{Mutex, OnceLock},
+};
+
+/// 缓冲区的上限，超过时放弃查询
+const MAX_BUFFER: usize = 1 << 20;
+
+/// 用户名，不存在对应用户时返回 `None`
+pub fn user_name(uid: u32) -> Option<String> {
+    static CACHE: OnceLock<Mutex<HashMap<u32, Option<String>>>> = OnceLock::new();
+    cached(&CACHE, uid, lookup_user)
+}
+
+/// 组名，不存在对应组时返回 `None`
+pub fn group_name(gid: u32) -> Option<String> {
+    static CACHE: OnceLock<Mutex<HashMap<u32, Option<String>>>> = OnceLock::new();
+    cached(&CACHE, gid, lookup_group)
+}
+
+fn cached(
+    cache: &OnceLock<Mutex<HashMap<u32, Option<String>>>>,
+    id: u32,
+    lookup: fn(u32) -> Option<String>,
+) -> Option<String> {
+    let cache = cache.get_or_init(Default::default);
+    if let Some(name) = cache.lock().unwrap().get(&id) {
+        return name.clone();
+    }
+    // 查询期间不持有锁，并发查询同一 ID 时结果相同
+    let name = lookup(id);
+    cache.lock().unwrap().insert(id, name.clone());
+    name
+}
+
+fn lookup_user(uid: u32) -> Option<String> {
+    let mut buffer = vec![0u8; 1024];
+    loop {
+        let mut passwd = MaybeUninit::<libc::passwd>::uninit();
+        let mut result = std::ptr::null_mut();
+        // SAFETY: passwd 和 buffer 在调用期间有效，buffer 的长度与传入的一致
+        let code = unsafe {
+            libc::getpwuid_r(
+                uid,
+                passwd.as_mut_ptr(),
+                buffer.as_mut_ptr().cast(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+        if code == libc::ERANGE && buffer.len() < MAX_BUFFER {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || result.is_null() {
+            return None;
+        }
+        // SAFETY: 查询成功时 pw_name 指向 buffer 中以 NUL 结尾的字符串
+        let name = unsafe { CStr::from_ptr((*result).pw_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
+
+fn lookup_group(gid: u32) -> Option<String> {
+    let mut buffer = vec![0u8; 1024];
+    loop {
+        let mut group = MaybeUninit::<libc::group>::uninit();
+        let mut result = std::ptr::null_mut();
+        // SAFETY: group 和 buffer 在调用期间有效，buffer 的长度与传入的一致
+        let code = unsafe {
+            libc::getgrgid_r(
+                gid,
+                group.as_mut_ptr(),
+                buffer.as_mut_ptr().cast(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+        if code == libc::ERANGE && buffer.len() < MAX_BUFFER {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        if code != 0 || result.is_null() {
+            return None;
+        }
+        // SAFETY: 查询成功时 gr_name 指向 buffer 中以 NUL 结尾的字符串
+        let name = unsafe { CStr::from_ptr((*result).gr_name) };
+        return Some(name.to_string_lossy().into_owned());
+    }
+}
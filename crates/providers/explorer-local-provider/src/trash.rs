@@ -0,0 +1,251 @@
+//! 回收站支持
+//!
+//! - Linux：遵循 FreeDesktop Trash 规范，与主目录在同一文件系统上的条目写入
+//!   `$XDG_DATA_HOME/Trash/{files,info}`，其他文件系统上的条目写入该文件系统顶层的
+//!   `.Trash/$uid` 或 `.Trash-$uid`，避免跨文件系统复制
+//! - macOS：移动到 `~/.Trash`
+//! - 其他平台：暂不支持
+
+use std::path::{Path, PathBuf};
+
+use explorer_storage::{StorageError, StorageResult};
+
+use crate::ops::{map_io_error, move_path};
+
+/// 将条目移入回收站，返回其在回收站中的路径
+pub fn trash_path(path: &Path) -> StorageResult<PathBuf> {
+    if !path.exists() && path.symlink_metadata().is_err() {
+        return Err(StorageError::PathNotFound(path.display().to_string()));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        xdg::trash(path)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let trash_dir = dirs::home_dir()
+            .map(|home| home.join(".Trash"))
+            .ok_or_else(|| StorageError::Other("无法定位回收站目录".to_string()))?;
+        let target = unique_name(&trash_dir, path, None);
+        move_path(path, &target)?;
+        Ok(target)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Err(StorageError::Unsupported(format!(
+            "移入回收站: {}",
+            path.display()
+        )))
+    }
+}
+
+/// 将回收站中的条目还原到原位置
+pub fn restore_path(trashed: &Path, original: &Path) -> StorageResult<()> {
+    if original.symlink_metadata().is_ok() {
+        return Err(StorageError::AlreadyExists(original.display().to_string()));
+    }
+    if let Some(parent) = original.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| map_io_error(e, parent))?;
+    }
+    move_path(trashed, original)?;
+
+    #[cfg(target_os = "linux")]
+    xdg::remove_info(trashed);
+
+    Ok(())
+}
+
+/// 在目录中为条目生成不冲突的文件名（`name`、`name.2`、`name.3` ...）
+///
+/// 指定 `info_dir` 时还要求其中没有同名的 `.trashinfo` 文件。
+fn unique_name(dir: &Path, path: &Path, info_dir: Option<&Path>) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unnamed".to_string());
+
+    let taken = |candidate: &str| {
+        dir.join(candidate).symlink_metadata().is_ok()
+            || info_dir.is_some_and(|info_dir| {
+                info_dir
+                    .join(format!("{}.trashinfo", candidate))
+                    .symlink_metadata()
+                    .is_ok()
+            })
+    };
+    let mut candidate = name.clone();
+    let mut index = 2;
+    while taken(&candidate) {
+        candidate = format!("{}.{}", name, index);
+        index += 1;
+    }
+    dir.join(candidate)
+}
+
+#[cfg(target_os = "linux")]
+mod xdg {
+    use std::{
+        fs,
+        io::{self, Write},
+        os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        path::{Path, PathBuf},
+    };
+
+    use explorer_storage::{StorageError, StorageResult};
+
+    use super::unique_name;
+    use crate::ops::{map_io_error, move_path};
+
+    /// 主目录的回收站：`$XDG_DATA_HOME/Trash`
+    fn home_trash() -> StorageResult<PathBuf> {
+        dirs::data_dir()
+            .map(|dir| dir.join("Trash"))
+            .ok_or_else(|| StorageError::Other("无法定位回收站目录".to_string()))
+    }
+
+    /// 路径或其最近的已存在的上级所在的设备
+    fn device_of(path: &Path) -> Option<u64> {
+        path.ancestors()
+            .find_map(|ancestor| fs::metadata(ancestor).ok())
+            .map(|metadata| metadata.dev())
+    }
+
+    /// 文件系统的顶层目录：`dir` 的上级中与其在同一设备上的最上一级
+    fn top_dir(dir: &Path, device: u64) -> PathBuf {
+        dir.ancestors()
+            .take_while(|ancestor| fs::metadata(ancestor).is_ok_and(|m| m.dev() == device))
+            .last()
+            .unwrap_or(dir)
+            .to_path_buf()
+    }
+
+    /// 顶层目录中的回收站：管理员创建的 `$topdir/.Trash`（必须设置了粘滞位且不是
+    /// 符号链接）中的 `$uid`，否则为 `$topdir/.Trash-$uid`
+    fn top_dir_trash(top_dir: &Path) -> Option<PathBuf> {
+        // SAFETY: getuid 总是成功，没有副作用
+        let uid = unsafe { libc::getuid() };
+        let shared = top_dir.join(".Trash");
+        if fs::symlink_metadata(&shared)
+            .is_ok_and(|m| m.is_dir() && m.permissions().mode() & 0o1000 != 0)
+        {
+            let root = shared.join(uid.to_string());
+            if create_private_dir(&root) {
+                return Some(root);
+            }
+        }
+        let root = top_dir.join(format!(".Trash-{}", uid));
+        create_private_dir(&root).then_some(root)
+    }
+
+    /// 创建只有当前用户可以访问的目录，已存在时确认它是目录而不是符号链接
+    fn create_private_dir(path: &Path) -> bool {
+        match fs::DirBuilder::new().mode(0o700).create(path) {
+            Ok(()) => true,
+            Err(_) => fs::symlink_metadata(path).is_ok_and(|m| m.is_dir()),
+        }
+    }
+
+    /// 条目所在文件系统的回收站，放不进时退回主目录的回收站（跨文件系统移动）
+    fn trash_root(absolute: &Path) -> StorageResult<PathBuf> {
+        let home = home_trash()?;
+        let parent = absolute.parent().unwrap_or(absolute);
+        let Some(device) = fs::symlink_metadata(absolute).ok().map(|m| m.dev()) else {
+            return Ok(home);
+        };
+        if device_of(&home) == Some(device) {
+            return Ok(home);
+        }
+        Ok(top_dir_trash(&top_dir(parent, device)).unwrap_or(home))
+    }
+
+    /// 条目的绝对路径：只规范化上级目录，符号链接本身不被跟随
+    fn absolute_path(path: &Path) -> StorageResult<PathBuf> {
+        let absolute = std::path::absolute(path).map_err(|e| map_io_error(e, path))?;
+        match (absolute.parent(), absolute.file_name()) {
+            (Some(parent), Some(name)) => Ok(parent
+                .canonicalize()
+                .map_err(|e| map_io_error(e, parent))?
+                .join(name)),
+            _ => Ok(absolute),
+        }
+    }
+
+    pub fn trash(path: &Path) -> StorageResult<PathBuf> {
+        let absolute = absolute_path(path)?;
+        let root = trash_root(&absolute)?;
+        let files_dir = root.join("files");
+        let info_dir = root.join("info");
+        fs::create_dir_all(&files_dir).map_err(|e| map_io_error(e, &files_dir))?;
+        fs::create_dir_all(&info_dir).map_err(|e| map_io_error(e, &info_dir))?;
+
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(&absolute.to_string_lossy()),
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+        );
+
+        // 先写 .trashinfo，再移动文件，保证回收站中的条目始终有对应的信息文件。
+        // 规范要求以独占方式创建信息文件来占用名称，其他程序同时占用时换下一个名称
+        let (target, info_path) = loop {
+            let target = unique_name(&files_dir, path, Some(&info_dir));
+            let trash_name = target
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let info_path = info_dir.join(format!("{}.trashinfo", trash_name));
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&info_path)
+            {
+                Ok(mut file) => {
+                    if let Err(e) = file.write_all(info.as_bytes()) {
+                        let _ = fs::remove_file(&info_path);
+                        return Err(map_io_error(e, &info_path));
+                    }
+                    break (target, info_path);
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(map_io_error(e, &info_path)),
+            }
+        };
+
+        if let Err(e) = move_path(path, &target) {
+            let _ = fs::remove_file(&info_path);
+            return Err(e);
+        }
+        Ok(target)
+    }
+
+    /// 还原后删除对应的 .trashinfo 文件
+    pub fn remove_info(trashed: &Path) {
+        let Some(name) = trashed.file_name() else {
+            return;
+        };
+        let Some(info_dir) = trashed
+            .parent()
+            .and_then(|files| files.parent())
+            .map(|root| root.join("info"))
+        else {
+            return;
+        };
+        let _ = fs::remove_file(info_dir.join(format!("{}.trashinfo", name.to_string_lossy())));
+    }
+
+    /// 按规范对路径进行 URL 编码（保留 `/` 和非保留字符）
+    fn encode_path(path: &str) -> String {
+        let mut encoded = String::with_capacity(path.len());
+        for byte in path.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                    encoded.push(byte as char)
+                }
+                _ => encoded.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        encoded
+    }
+}
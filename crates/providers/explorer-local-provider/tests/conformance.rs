@@ -0,0 +1,73 @@
+//! 本地文件系统存储提供者的一致性测试
+
+use std::{fs, path::PathBuf};
+
+use explorer_local_provider::LocalFileSystemProvider;
+use explorer_storage::{
+    StorageProvider,
+    conformance::{self, Fixture, TempDir},
+};
+
+/// 临时目录中的测试目录树
+struct LocalFixture {
+    dir: TempDir,
+    provider: LocalFileSystemProvider,
+}
+
+impl LocalFixture {
+    fn new() -> Self {
+        let dir = TempDir::new("local-test");
+        conformance::create_tree(&dir, cfg!(unix)).unwrap();
+        Self {
+            dir,
+            provider: LocalFileSystemProvider::new(),
+        }
+    }
+}
+
+impl Fixture for LocalFixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn symlinks(&self) -> bool {
+        cfg!(unix)
+    }
+
+    fn join(&self, dir: &str, name: &str) -> String {
+        PathBuf::from(dir).join(name).display().to_string()
+    }
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    let fixture = LocalFixture::new();
+    smol::block_on(conformance::run(&fixture));
+}
+
+#[cfg(unix)]
+#[test]
+fn reports_unix_metadata() {
+    use std::os::unix::fs::MetadataExt;
+
+    let fixture = LocalFixture::new();
+    let file = fixture.dir.join("docs").join("readme.txt");
+    fs::hard_link(&file, fixture.dir.join("docs").join("hard")).unwrap();
+    let expected = fs::metadata(&file).unwrap();
+
+    let item = smol::block_on(fixture.provider.get_metadata(&file.display().to_string())).unwrap();
+    let metadata = item.metadata;
+    assert_eq!(metadata.inode, Some(expected.ino()));
+    assert_eq!(metadata.hard_links, Some(2));
+    assert_eq!(metadata.device, Some(expected.dev()));
+    assert_eq!(metadata.blocks, Some(expected.blocks()));
+    assert_eq!(metadata.uid, Some(expected.uid()));
+    assert_eq!(metadata.gid, Some(expected.gid()));
+    if expected.uid() == 0 {
+        assert_eq!(metadata.user.as_deref(), Some("root"));
+    }
+}
@@ -0,0 +1,58 @@
+//! 回收站的测试
+//!
+//! 回收站的位置来自 `XDG_DATA_HOME`，修改环境变量会影响同一进程中的其他测试，
+//! 所以放在单独的测试程序中。
+
+#![cfg(target_os = "linux")]
+
+use std::fs;
+
+use explorer_local_provider::LocalFileSystemProvider;
+use explorer_storage::{StorageProvider, conformance::TempDir};
+
+#[test]
+fn trashes_and_restores_symlinks() {
+    let dir = TempDir::new("trash-test");
+    // SAFETY: 这个测试程序中只有一个测试，设置时没有其他线程读取环境变量
+    unsafe { std::env::set_var("XDG_DATA_HOME", dir.join("data")) };
+    fs::create_dir(dir.join("files")).unwrap();
+    fs::write(dir.join("files/target.txt"), "content").unwrap();
+    std::os::unix::fs::symlink("target.txt", dir.join("files/link")).unwrap();
+
+    let provider = LocalFileSystemProvider::new();
+    let link = dir.join("files/link").display().to_string();
+    let trashed = smol::block_on(provider.trash(&link)).unwrap();
+    assert!(fs::symlink_metadata(&trashed).unwrap().is_symlink());
+    assert!(dir.join("files/target.txt").is_file());
+
+    // 记录的是链接本身的路径，而不是链接目标的路径
+    let info = fs::read_to_string(dir.join("data/Trash/info/link.trashinfo")).unwrap();
+    let expected = dir.canonicalize().unwrap().join("files/link");
+    assert!(
+        info.lines()
+            .any(|line| line == format!("Path={}", expected.display())),
+        "{}",
+        info
+    );
+
+    smol::block_on(provider.restore(&trashed, &link)).unwrap();
+    assert_eq!(
+        fs::read_link(dir.join("files/link")).unwrap(),
+        std::path::Path::new("target.txt")
+    );
+    assert!(!dir.join("data/Trash/info/link.trashinfo").exists());
+
+    // 其他程序留下的信息文件不会被覆盖，名称已被占用时换下一个名称
+    let orphan = dir.join("data/Trash/info/target.txt.trashinfo");
+    fs::write(&orphan, "orphan").unwrap();
+    let target = dir.join("files/target.txt").display().to_string();
+    let trashed = smol::block_on(provider.trash(&target)).unwrap();
+    assert_eq!(
+        trashed,
+        dir.join("data/Trash/files/target.txt.2")
+            .display()
+            .to_string()
+    );
+    assert_eq!(fs::read_to_string(&orphan).unwrap(), "orphan");
+    assert!(dir.join("data/Trash/info/target.txt.2.trashinfo").is_file());
+}
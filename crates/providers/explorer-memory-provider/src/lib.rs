@@ -0,0 +1,536 @@
+//! 内存存储提供者
+//!
+//! 整个目录树保存在内存中，进程退出即丢失，用于演示和界面测试。路径形如
+//! `memory:///docs/a.txt`，只有一个根节点。
+//!
+//! [`MemoryProvider::insert_file`] 和 [`MemoryProvider::insert_dir`] 可以同步地准备
+//! 内容，缺少的上级目录会自动创建。支持符号链接，路径中途的链接总是被跟随。
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+    ops::Bound,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use mime_guess::from_path;
+
+use explorer_storage::*;
+
+/// 路径前缀
+pub const SCHEME: &str = "memory://";
+
+/// 路径是否为内存中的路径
+pub fn is_memory_path(path: &str) -> bool {
+    path.starts_with(SCHEME)
+}
+
+/// 树中的一个条目
+#[derive(Debug, Clone)]
+struct Node {
+    kind: NodeKind,
+    created: SystemTime,
+    modified: SystemTime,
+}
+
+#[derive(Debug, Clone)]
+enum NodeKind {
+    Directory,
+    /// 文件内容，读取时只复制引用
+    File(Arc<Vec<u8>>),
+    /// 符号链接中保存的目标（`/` 开头的树中路径，或相对于链接所在目录的路径）
+    Symlink(String),
+}
+
+impl Node {
+    fn new(kind: NodeKind) -> Self {
+        let now = SystemTime::now();
+        Self {
+            kind,
+            created: now,
+            modified: now,
+        }
+    }
+
+    fn is_dir(&self) -> bool {
+        matches!(self.kind, NodeKind::Directory)
+    }
+}
+
+/// 符号链接最多跟随的层数，超过时认为链接成环
+const MAX_LINKS: usize = 40;
+
+/// 以路径（`/` 开头，没有结尾的 `/`，根为 `/`）为键的目录树
+///
+/// 按路径排序，目录的所有后代在 `目录/` 开头的一段连续范围中
+type Tree = BTreeMap<String, Node>;
+
+/// `path` 的所有后代
+fn descendants<'a>(tree: &'a Tree, path: &str) -> impl Iterator<Item = (&'a String, &'a Node)> {
+    let prefix = match path {
+        "/" => "/".to_string(),
+        _ => format!("{}/", path),
+    };
+    tree.range::<String, _>((Bound::Excluded(&prefix), Bound::Unbounded))
+        .take_while(move |(key, _)| key.starts_with(&prefix))
+}
+
+/// 上级目录，根没有上级目录
+fn parent(path: &str) -> Option<&str> {
+    match path.rsplit_once('/') {
+        Some(("", "")) | None => None,
+        Some(("", _)) => Some("/"),
+        Some((parent, _)) => Some(parent),
+    }
+}
+
+/// 目录中条目的路径
+fn join(dir: &str, name: &str) -> String {
+    match dir {
+        "/" => format!("/{}", name),
+        _ => format!("{}/{}", dir, name),
+    }
+}
+
+/// 跟随路径中的符号链接，返回条目在树中的实际路径
+///
+/// `follow_last` 为假时不跟随最后一级，得到链接本身。中途不存在的部分原样拼接，
+/// 由调用方在树中查找时报告 `PathNotFound`。
+fn resolve(tree: &Tree, remote: &str, follow_last: bool) -> StorageResult<String> {
+    // 待处理的名称，末尾的最先处理
+    let mut pending: Vec<&str> = remote.split('/').rev().collect();
+    let mut current = "/".to_string();
+    let mut links = 0;
+    while let Some(name) = pending.pop() {
+        match name {
+            "" | "." => continue,
+            ".." => {
+                current = parent(&current).unwrap_or("/").to_string();
+                continue;
+            }
+            _ => {}
+        }
+        let path = join(&current, name);
+        match tree.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Symlink(target)) if follow_last || !pending.is_empty() => {
+                links += 1;
+                if links > MAX_LINKS {
+                    return Err(StorageError::Other(format!(
+                        "符号链接层数过多: {}",
+                        MemoryProvider::url(remote)
+                    )));
+                }
+                if target.starts_with('/') {
+                    current = "/".to_string();
+                }
+                pending.extend(target.split('/').rev());
+            }
+            _ => current = path,
+        }
+    }
+    Ok(current)
+}
+
+/// 内存存储提供者
+pub struct MemoryProvider {
+    name: String,
+    tree: Mutex<Tree>,
+}
+
+impl MemoryProvider {
+    /// 创建只有根目录的提供者
+    pub fn new() -> Self {
+        Self {
+            name: "内存".to_string(),
+            tree: Mutex::new(BTreeMap::from([(
+                "/".to_string(),
+                Node::new(NodeKind::Directory),
+            )])),
+        }
+    }
+
+    /// 设置根节点的显示名称
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// 树中的路径对应的完整路径
+    pub fn url(remote: &str) -> String {
+        format!("{}{}", SCHEME, remote)
+    }
+
+    /// 创建目录（包括缺少的上级目录），已存在时不做任何事
+    pub fn insert_dir(&self, path: &str) -> StorageResult<()> {
+        let remote = locate(path)?;
+        let mut tree = self.tree.lock().unwrap();
+        insert_parents(&mut tree, &remote)?;
+        match tree.get(&remote) {
+            Some(node) if node.is_dir() => Ok(()),
+            Some(_) => Err(StorageError::AlreadyExists(path.to_string())),
+            None => {
+                tree.insert(remote, Node::new(NodeKind::Directory));
+                Ok(())
+            }
+        }
+    }
+
+    /// 写入文件（包括缺少的上级目录），已存在时覆盖
+    pub fn insert_file(&self, path: &str, content: impl Into<Vec<u8>>) -> StorageResult<()> {
+        let remote = locate(path)?;
+        let mut tree = self.tree.lock().unwrap();
+        insert_parents(&mut tree, &remote)?;
+        store(&mut tree, &remote, path, content.into())
+    }
+
+    /// 获取条目，不存在时返回 `PathNotFound`
+    fn node(&self, path: &str, follow_last: bool) -> StorageResult<(String, Node)> {
+        let remote = locate(path)?;
+        let tree = self.tree.lock().unwrap();
+        let node = tree
+            .get(&resolve(&tree, &remote, follow_last)?)
+            .cloned()
+            .ok_or_else(|| StorageError::PathNotFound(path.to_string()))?;
+        Ok((remote, node))
+    }
+}
+
+impl Default for MemoryProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 完整路径对应的树中的路径
+fn locate(path: &str) -> StorageResult<String> {
+    let remote = path
+        .strip_prefix(SCHEME)
+        .filter(|remote| remote.starts_with('/'))
+        .ok_or_else(|| StorageError::Other(format!("不是内存中的路径: {}", path)))?;
+    let mut normalized = String::new();
+    for name in remote.split('/').filter(|name| !name.is_empty()) {
+        if name == "." || name == ".." {
+            return Err(StorageError::Other(format!(
+                "路径中不能有 . 或 ..: {}",
+                path
+            )));
+        }
+        normalized.push('/');
+        normalized.push_str(name);
+    }
+    if normalized.is_empty() {
+        normalized.push('/');
+    }
+    Ok(normalized)
+}
+
+/// 创建缺少的上级目录
+fn insert_parents(tree: &mut Tree, remote: &str) -> StorageResult<()> {
+    let Some(parent) = parent(remote) else {
+        return Ok(());
+    };
+    match tree.get(parent) {
+        Some(node) if node.is_dir() => Ok(()),
+        Some(_) => Err(StorageError::Other(format!(
+            "路径不是目录: {}",
+            MemoryProvider::url(parent)
+        ))),
+        None => {
+            insert_parents(tree, parent)?;
+            tree.insert(parent.to_string(), Node::new(NodeKind::Directory));
+            Ok(())
+        }
+    }
+}
+
+/// 检查上级目录存在
+fn ensure_parent(tree: &Tree, remote: &str) -> StorageResult<()> {
+    let Some(parent) = parent(remote) else {
+        return Err(StorageError::AlreadyExists(MemoryProvider::url(remote)));
+    };
+    match tree.get(parent) {
+        Some(node) if node.is_dir() => Ok(()),
+        Some(_) => Err(StorageError::Other(format!(
+            "路径不是目录: {}",
+            MemoryProvider::url(parent)
+        ))),
+        None => Err(StorageError::PathNotFound(MemoryProvider::url(parent))),
+    }
+}
+
+/// 写入文件内容，保留已有文件的创建时间
+fn store(tree: &mut Tree, remote: &str, path: &str, content: Vec<u8>) -> StorageResult<()> {
+    let mut node = Node::new(NodeKind::File(Arc::new(content)));
+    match tree.get(remote) {
+        Some(existing) if existing.is_dir() => {
+            return Err(StorageError::Other(format!("路径是目录: {}", path)));
+        }
+        Some(existing) => node.created = existing.created,
+        None => {}
+    }
+    tree.insert(remote.to_string(), node);
+    Ok(())
+}
+
+/// 完整路径对应的树中的实际路径（不跟随最后一级符号链接）
+fn locate_entry(tree: &Tree, path: &str) -> StorageResult<String> {
+    resolve(tree, &locate(path)?, false)
+}
+
+/// 拆分来源和目标，检查来源存在、目标不存在且不在来源之内
+fn prepare_transfer(
+    tree: &Tree,
+    from: &str,
+    to: &str,
+    operation: &str,
+) -> StorageResult<(String, String)> {
+    let (from_remote, to_remote) = (locate_entry(tree, from)?, locate_entry(tree, to)?);
+    if !tree.contains_key(&from_remote) {
+        return Err(StorageError::PathNotFound(from.to_string()));
+    }
+    if from_remote == "/" {
+        return Err(StorageError::PermissionDenied(from.to_string()));
+    }
+    if tree.contains_key(&to_remote) {
+        return Err(StorageError::AlreadyExists(to.to_string()));
+    }
+    if to_remote.starts_with(&format!("{}/", from_remote)) {
+        return Err(StorageError::Other(format!(
+            "不能将目录{}到其自身内部: {}",
+            operation, to
+        )));
+    }
+    ensure_parent(tree, &to_remote)?;
+    Ok((from_remote, to_remote))
+}
+
+/// 条目及其所有后代，键换成目标下的路径
+fn subtree(tree: &Tree, from: &str, to: &str) -> Vec<(String, Node)> {
+    let root = tree.get(from).cloned().map(|node| (to.to_string(), node));
+    let children = descendants(tree, from)
+        .map(|(key, node)| (format!("{}{}", to, &key[from.len()..]), node.clone()));
+    root.into_iter().chain(children).collect()
+}
+
+fn file_item(tree: &Tree, remote: &str, node: &Node) -> FileItem {
+    let name = match remote.rsplit('/').next() {
+        Some("") | None => remote.to_string(),
+        Some(name) => name.to_string(),
+    };
+    let (item_type, size) = match &node.kind {
+        NodeKind::Directory => (ItemType::Directory, 0),
+        NodeKind::File(content) => (ItemType::File, content.len() as u64),
+        NodeKind::Symlink(target) => (ItemType::Symlink, target.len() as u64),
+    };
+    let (link_target, link_target_type) = match &node.kind {
+        NodeKind::Symlink(target) => {
+            let resolved = resolve(tree, remote, true).ok();
+            let target_type = resolved
+                .and_then(|resolved| tree.get(&resolved))
+                .map(|target| match target.kind {
+                    NodeKind::Directory => ItemType::Directory,
+                    _ => ItemType::File,
+                });
+            (Some(target.clone()), target_type)
+        }
+        _ => (None, None),
+    };
+    let mime_type = if item_type == ItemType::File {
+        from_path(&name).first().map(|mime| mime.to_string())
+    } else {
+        None
+    };
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: MemoryProvider::url(remote),
+        item_type,
+        size,
+        modified: node.modified,
+        metadata: EntryMetadata {
+            mime_type,
+            created: Some(node.created),
+            broken_link: link_target.is_some() && link_target_type.is_none(),
+            link_target,
+            link_target_type,
+            ..Default::default()
+        },
+    }
+}
+
+#[async_trait]
+impl StorageProvider for MemoryProvider {
+    async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
+        let path = Self::url("/");
+        Ok(vec![RootItem {
+            id: path.clone(),
+            name: self.name.clone(),
+            path,
+            provider_type: ProviderType::LocalFileSystem,
+            icon: None,
+            mount: None,
+        }])
+    }
+
+    async fn get_metadata(&self, path: &str) -> StorageResult<FileItem> {
+        let (remote, node) = self.node(path, false)?;
+        Ok(file_item(&self.tree.lock().unwrap(), &remote, &node))
+    }
+
+    async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>> {
+        let remote = locate(path)?;
+        let tree = self.tree.lock().unwrap();
+        // 跟随符号链接：指向目录的链接可以像目录一样打开，条目的路径仍在链接之下
+        let resolved = resolve(&tree, &remote, true)?;
+        match tree.get(&resolved) {
+            Some(node) if node.is_dir() => {}
+            Some(_) => return Err(StorageError::Other(format!("路径不是目录: {}", path))),
+            None => return Err(StorageError::PathNotFound(path.to_string())),
+        }
+
+        let depth = match resolved.as_str() {
+            "/" => 1,
+            _ => resolved.matches('/').count() + 1,
+        };
+        let mut entries: Vec<FileItem> = descendants(&tree, &resolved)
+            .filter(|(key, _)| key.matches('/').count() == depth)
+            .map(|(key, node)| {
+                let name = &key[key.rfind('/').unwrap_or(0) + 1..];
+                file_item(&tree, &join(&remote, name), node)
+            })
+            .collect();
+        sort_entries(&mut entries);
+        Ok(entries)
+    }
+
+    async fn exists(&self, path: &str) -> StorageResult<bool> {
+        // 悬空的符号链接也算存在
+        let tree = self.tree.lock().unwrap();
+        Ok(tree.contains_key(&locate_entry(&tree, path)?))
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        let (_, node) = self.node(path, true)?;
+        let NodeKind::File(content) = node.kind else {
+            return Err(StorageError::Other(format!("路径是目录: {}", path)));
+        };
+        writer.write_all(&content)?;
+        Ok(content.len() as u64)
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        let remote = locate(path)?;
+        {
+            let tree = self.tree.lock().unwrap();
+            ensure_parent(&tree, &resolve(&tree, &remote, true)?)?;
+        }
+        // 读取时不持有锁，读完后再检查一次上级目录
+        let mut content = vec![];
+        io::copy(reader, &mut content)?;
+        let written = content.len() as u64;
+        let mut tree = self.tree.lock().unwrap();
+        // 写入指向文件的符号链接时写入其目标
+        let remote = resolve(&tree, &remote, true)?;
+        ensure_parent(&tree, &remote)?;
+        store(&mut tree, &remote, path, content)?;
+        Ok(written)
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        let mut tree = self.tree.lock().unwrap();
+        let remote = locate_entry(&tree, path)?;
+        if tree.contains_key(&remote) {
+            return Err(StorageError::AlreadyExists(path.to_string()));
+        }
+        ensure_parent(&tree, &remote)?;
+        tree.insert(remote, Node::new(NodeKind::Directory));
+        Ok(())
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        let mut tree = self.tree.lock().unwrap();
+        let remote = locate_entry(&tree, path)?;
+        if tree.contains_key(&remote) {
+            return Err(StorageError::AlreadyExists(path.to_string()));
+        }
+        ensure_parent(&tree, &remote)?;
+        tree.insert(remote, Node::new(NodeKind::File(Arc::default())));
+        Ok(())
+    }
+
+    async fn create_symlink(&self, target: &str, path: &str) -> StorageResult<()> {
+        let mut tree = self.tree.lock().unwrap();
+        let remote = locate_entry(&tree, path)?;
+        if tree.contains_key(&remote) {
+            return Err(StorageError::AlreadyExists(path.to_string()));
+        }
+        ensure_parent(&tree, &remote)?;
+        // 完整路径保存为树中的路径，其他原样保存
+        let target = match target.strip_prefix(SCHEME) {
+            Some(_) => locate(target)?,
+            None => target.to_string(),
+        };
+        tree.insert(remote, Node::new(NodeKind::Symlink(target)));
+        Ok(())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        let mut tree = self.tree.lock().unwrap();
+        let (from, to) = prepare_transfer(&tree, from, to, "移动")?;
+        let moved = subtree(&tree, &from, &to);
+        tree.retain(|key, _| key != &from && !key.starts_with(&format!("{}/", from)));
+        tree.extend(moved);
+        Ok(())
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        let mut tree = self.tree.lock().unwrap();
+        let (from, to) = prepare_transfer(&tree, from, to, "复制")?;
+        let now = SystemTime::now();
+        let copied = subtree(&tree, &from, &to).into_iter().map(|(key, node)| {
+            // 副本是新条目，只保留修改时间
+            let node = Node {
+                created: now,
+                ..node
+            };
+            (key, node)
+        });
+        tree.extend(copied);
+        Ok(())
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        let mut tree = self.tree.lock().unwrap();
+        let remote = locate_entry(&tree, path)?;
+        if remote == "/" {
+            return Err(StorageError::PermissionDenied(path.to_string()));
+        }
+        if tree.remove(&remote).is_none() {
+            return Err(StorageError::PathNotFound(path.to_string()));
+        }
+        let prefix = format!("{}/", remote);
+        tree.retain(|key, _| !key.starts_with(&prefix));
+        Ok(())
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            can_create: true,
+            can_rename: true,
+            can_copy: true,
+            can_move: true,
+            can_trash: false,
+            can_delete: true,
+            can_symlink: true,
+            can_archive: false,
+            local_paths: false,
+        }
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        // 条目都在本进程中，与本地文件系统同类；路径不是本机路径由 `local_paths` 表示
+        ProviderType::LocalFileSystem
+    }
+}
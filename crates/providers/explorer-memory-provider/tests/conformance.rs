@@ -0,0 +1,107 @@
+//! 内存存储提供者的一致性测试
+
+use explorer_memory_provider::MemoryProvider;
+use explorer_storage::{
+    ItemType, StorageProvider,
+    conformance::{self, Fixture},
+};
+
+struct MemoryFixture {
+    provider: MemoryProvider,
+}
+
+impl Fixture for MemoryFixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        MemoryProvider::url("/tree")
+    }
+
+    fn symlinks(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    let fixture = MemoryFixture {
+        provider: MemoryProvider::new(),
+    };
+    smol::block_on(async {
+        fixture.provider.create_dir(&fixture.root()).await.unwrap();
+        conformance::write_tree(&fixture).await.unwrap();
+        conformance::run(&fixture).await;
+    });
+}
+
+#[test]
+fn inserts_missing_parents() {
+    let provider = MemoryProvider::new();
+    provider
+        .insert_file(&MemoryProvider::url("/a/b/c.txt"), "content")
+        .unwrap();
+    provider.insert_dir(&MemoryProvider::url("/a/d")).unwrap();
+    smol::block_on(async {
+        let entries = provider
+            .list_entries(&MemoryProvider::url("/a"))
+            .await
+            .unwrap();
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["b", "d"]);
+        let file = provider
+            .get_metadata(&MemoryProvider::url("/a/b/c.txt"))
+            .await
+            .unwrap();
+        assert_eq!(file.size, 7);
+    });
+    // 文件不能作为上级目录
+    assert!(
+        provider
+            .insert_file(&MemoryProvider::url("/a/b/c.txt/d"), "x")
+            .is_err()
+    );
+}
+
+#[test]
+fn follows_symlinks_in_paths() {
+    let provider = MemoryProvider::new();
+    provider
+        .insert_file(&MemoryProvider::url("/a/b/c.txt"), "content")
+        .unwrap();
+    provider.insert_dir(&MemoryProvider::url("/d")).unwrap();
+    smol::block_on(async {
+        let up = MemoryProvider::url("/d/up");
+        provider.create_symlink("../a", &up).await.unwrap();
+        let mut content = vec![];
+        provider
+            .read_file(&MemoryProvider::url("/d/up/b/c.txt"), &mut content)
+            .await
+            .unwrap();
+        assert_eq!(content, b"content");
+
+        let item = provider.get_metadata(&up).await.unwrap();
+        assert_eq!(item.item_type, ItemType::Symlink);
+        assert_eq!(item.metadata.link_target.as_deref(), Some("../a"));
+        assert_eq!(item.metadata.link_target_type, Some(ItemType::Directory));
+        assert!(item.is_dir_like());
+
+        // 完整路径保存为树中的路径
+        let absolute = MemoryProvider::url("/d/file");
+        provider
+            .create_symlink(&MemoryProvider::url("/a/b/c.txt"), &absolute)
+            .await
+            .unwrap();
+        let item = provider.get_metadata(&absolute).await.unwrap();
+        assert_eq!(item.metadata.link_target.as_deref(), Some("/a/b/c.txt"));
+        assert_eq!(item.metadata.link_target_type, Some(ItemType::File));
+
+        // 互相指向的链接是悬空的，打开时报错而不是无限循环
+        let (x, y) = (MemoryProvider::url("/x"), MemoryProvider::url("/y"));
+        provider.create_symlink("y", &x).await.unwrap();
+        provider.create_symlink("x", &y).await.unwrap();
+        assert!(provider.get_metadata(&x).await.unwrap().is_broken_link());
+        assert!(provider.list_entries(&x).await.is_err());
+    });
+}
@@ -0,0 +1,476 @@
+//! S3 HTTP 请求
+//!
+//! 每个请求都用 SigV4 签名，请求体的 SHA-256 也参与签名；大文件分段上传，每段读入内存后
+//! 再计算摘要和发送。
+
+use std::{
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use chrono::Utc;
+use mime_guess::from_path;
+
+use explorer_storage::{StorageError, StorageResult};
+
+use crate::{
+    S3Connection,
+    response::{self, ListPage, Object},
+    signature::{Request, Signer, canonical_query, sha256_hex, uri_encode},
+};
+
+/// 分段上传时每段的大小（S3 要求除最后一段外不小于 5 MiB）
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// 分段上传最多的段数
+const MAX_PARTS: usize = 10_000;
+
+/// 单次 CopyObject 能复制的最大对象，更大的对象需要分段复制
+const MAX_COPY_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// 分段复制时每段的大小
+const COPY_PART_SIZE: u64 = 512 * 1024 * 1024;
+
+/// 拆分服务端点为协议和主机（去掉默认端口和末尾的路径）
+pub(crate) fn split_endpoint(endpoint: &str) -> (&str, &str) {
+    let (scheme, rest) = endpoint.split_once("://").unwrap_or(("https", endpoint));
+    let host = rest.split('/').next().unwrap_or(rest);
+    let default_port = if scheme == "http" { ":80" } else { ":443" };
+    (scheme, host.strip_suffix(default_port).unwrap_or(host))
+}
+
+pub(crate) struct Client {
+    agent: ureq::Agent,
+    signer: Signer,
+    scheme: String,
+    host: String,
+    path_style: bool,
+    /// 路径前缀 `s3://access_key@host`
+    root: String,
+}
+
+impl Client {
+    pub fn new(connection: &S3Connection) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(60))
+            // 重定向到其他区域时需要重新签名，不自动跟随
+            .redirects(0)
+            .build();
+        let (scheme, host) = split_endpoint(&connection.endpoint);
+        Self {
+            agent,
+            signer: Signer {
+                access_key: connection.access_key.clone(),
+                secret_key: connection.secret_key.clone(),
+                region: connection.region.clone(),
+            },
+            scheme: scheme.to_string(),
+            host: host.to_string(),
+            path_style: connection.path_style,
+            root: connection.root(),
+        }
+    }
+
+    /// 存储桶中的对象键对应的完整路径，键为空时即存储桶本身
+    pub fn path(&self, bucket: &str, key: &str) -> String {
+        if key.is_empty() {
+            format!("{}/{}", self.root, bucket)
+        } else {
+            format!("{}/{}/{}", self.root, bucket, key)
+        }
+    }
+
+    /// 请求的主机和已编码的路径
+    fn address(&self, bucket: &str, key: &str) -> (String, String) {
+        let key = uri_encode(key, true);
+        if bucket.is_empty() {
+            (self.host.clone(), "/".to_string())
+        } else if self.path_style {
+            (self.host.clone(), format!("/{}/{}", bucket, key))
+        } else {
+            (format!("{}.{}", bucket, self.host), format!("/{}", key))
+        }
+    }
+
+    fn request<'a>(&'a self, method: &'a str, bucket: &'a str, key: &'a str) -> Call<'a> {
+        Call {
+            client: self,
+            method,
+            bucket,
+            key,
+            query: vec![],
+            headers: vec![],
+        }
+    }
+
+    pub fn list_buckets(&self) -> StorageResult<Vec<String>> {
+        let response = self.request("GET", "", "").send(&[])?;
+        response::parse_buckets(&response.into_string()?)
+    }
+
+    pub fn head_bucket(&self, bucket: &str) -> StorageResult<()> {
+        self.request("HEAD", bucket, "").send(&[])?;
+        Ok(())
+    }
+
+    pub fn head_object(&self, bucket: &str, key: &str) -> StorageResult<Object> {
+        let response = self.request("HEAD", bucket, key).send(&[])?;
+        Ok(Object {
+            key: key.to_string(),
+            size: response
+                .header("Content-Length")
+                .and_then(|size| size.parse().ok())
+                .unwrap_or(0),
+            modified: response
+                .header("Last-Modified")
+                .and_then(|time| chrono::DateTime::parse_from_rfc2822(time).ok())
+                .map(Into::into),
+            etag: response.header("ETag").map(str::to_string),
+            storage_class: response.header("x-amz-storage-class").map(str::to_string),
+            content_type: response.header("Content-Type").map(str::to_string),
+        })
+    }
+
+    /// 列出前缀下的一页对象，`delimiter` 为真时按 `/` 归并子目录
+    pub fn list_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: bool,
+        continuation_token: Option<&str>,
+        max_keys: Option<u32>,
+    ) -> StorageResult<ListPage> {
+        let mut call = self
+            .request("GET", bucket, "")
+            .query("list-type", "2")
+            .query("prefix", prefix);
+        if delimiter {
+            call = call.query("delimiter", "/");
+        }
+        if let Some(token) = continuation_token {
+            call = call.query("continuation-token", token);
+        }
+        if let Some(max_keys) = max_keys {
+            call = call.query("max-keys", &max_keys.to_string());
+        }
+        response::parse_list(&call.send(&[])?.into_string()?)
+    }
+
+    /// 列出前缀下的所有对象和子目录（按续传令牌翻页）
+    pub fn list(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: bool,
+    ) -> StorageResult<(Vec<Object>, Vec<String>)> {
+        let (mut objects, mut prefixes) = (vec![], vec![]);
+        let mut token = None;
+        loop {
+            let page = self.list_page(bucket, prefix, delimiter, token.as_deref(), None)?;
+            objects.extend(page.objects);
+            prefixes.extend(page.prefixes);
+            match page.continuation_token {
+                Some(next) => token = Some(next),
+                None => return Ok((objects, prefixes)),
+            }
+        }
+    }
+
+    pub fn get_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        writer: &mut dyn Write,
+    ) -> StorageResult<u64> {
+        let response = self.request("GET", bucket, key).send(&[])?;
+        Ok(io::copy(&mut response.into_reader(), writer)?)
+    }
+
+    /// 上传对象，`if_none_match` 为真时对象已存在则返回 `AlreadyExists`
+    pub fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: &[u8],
+        if_none_match: bool,
+    ) -> StorageResult<()> {
+        let mut call = self
+            .request("PUT", bucket, key)
+            .header("content-type", &content_type(key));
+        if if_none_match {
+            call = call.header("if-none-match", "*");
+        }
+        call.send(body)?;
+        Ok(())
+    }
+
+    /// 上传 `reader` 中的内容，超过一段时使用分段上传
+    pub fn upload(&self, bucket: &str, key: &str, reader: &mut dyn Read) -> StorageResult<u64> {
+        let mut part = read_part(reader)?;
+        if part.len() < PART_SIZE {
+            self.put_object(bucket, key, &part, false)?;
+            return Ok(part.len() as u64);
+        }
+
+        self.multipart(bucket, key, |upload_id| {
+            let (mut etags, mut total) = (vec![], 0);
+            loop {
+                if etags.len() == MAX_PARTS {
+                    return Err(StorageError::Other(format!(
+                        "文件太大，超过分段上传的上限: {}",
+                        self.path(bucket, key)
+                    )));
+                }
+                let response = self
+                    .request("PUT", bucket, key)
+                    .query("partNumber", &(etags.len() + 1).to_string())
+                    .query("uploadId", upload_id)
+                    .send(&part)?;
+                etags.push(part_etag(&response)?);
+                total += part.len() as u64;
+                if part.len() < PART_SIZE {
+                    break;
+                }
+                part = read_part(reader)?;
+                if part.is_empty() {
+                    break;
+                }
+            }
+            Ok((etags, total))
+        })
+    }
+
+    /// 在服务器上复制对象，超过 5 GiB 的对象分段复制
+    pub fn copy_object(
+        &self,
+        from_bucket: &str,
+        from: &Object,
+        bucket: &str,
+        key: &str,
+    ) -> StorageResult<()> {
+        let source = format!("/{}/{}", from_bucket, uri_encode(&from.key, true));
+        if from.size <= MAX_COPY_SIZE {
+            let response = self
+                .request("PUT", bucket, key)
+                .header("x-amz-copy-source", &source)
+                .send(&[])?;
+            check_body(&response.into_string()?, &self.path(bucket, key))?;
+            return Ok(());
+        }
+
+        self.multipart(bucket, key, |upload_id| {
+            let mut etags = vec![];
+            let mut start = 0;
+            while start < from.size {
+                let end = (start + COPY_PART_SIZE).min(from.size) - 1;
+                let response = self
+                    .request("PUT", bucket, key)
+                    .query("partNumber", &(etags.len() + 1).to_string())
+                    .query("uploadId", upload_id)
+                    .header("x-amz-copy-source", &source)
+                    .header(
+                        "x-amz-copy-source-range",
+                        &format!("bytes={}-{}", start, end),
+                    )
+                    .send(&[])?;
+                etags.push(response::parse_copy_part_etag(&response.into_string()?)?);
+                start = end + 1;
+            }
+            Ok((etags, from.size))
+        })
+        .map(|_| ())
+    }
+
+    pub fn delete_object(&self, bucket: &str, key: &str) -> StorageResult<()> {
+        self.request("DELETE", bucket, key).send(&[])?;
+        Ok(())
+    }
+
+    /// 生成有效期为 `expires` 秒的预签名 GET URL
+    pub fn presign(&self, bucket: &str, key: &str, expires: u64) -> String {
+        let (host, path) = self.address(bucket, key);
+        let query = self
+            .signer
+            .presign("GET", &path, &host, expires, Utc::now());
+        format!(
+            "{}://{}{}?{}",
+            self.scheme,
+            host,
+            path,
+            canonical_query(&query)
+        )
+    }
+
+    /// 分段上传：`upload_parts` 上传各段并返回其 ETag 和总字节数，成功后合并，失败时放弃
+    /// 已上传的段
+    fn multipart(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_parts: impl FnOnce(&str) -> StorageResult<(Vec<String>, u64)>,
+    ) -> StorageResult<u64> {
+        let response = self
+            .request("POST", bucket, key)
+            .query("uploads", "")
+            .header("content-type", &content_type(key))
+            .send(&[])?;
+        let upload_id = response::parse_upload_id(&response.into_string()?)?;
+
+        let result = upload_parts(&upload_id).and_then(|(etags, total)| {
+            let response = self
+                .request("POST", bucket, key)
+                .query("uploadId", &upload_id)
+                .send(response::complete_body(&etags).as_bytes())?;
+            check_body(&response.into_string()?, &self.path(bucket, key))?;
+            Ok(total)
+        });
+        if result.is_err() {
+            let abort = self
+                .request("DELETE", bucket, key)
+                .query("uploadId", &upload_id)
+                .send(&[]);
+            if let Err(e) = abort {
+                tracing::warn!("放弃分段上传失败: {}: {}", self.path(bucket, key), e);
+            }
+        }
+        result
+    }
+}
+
+/// 一个待发送的请求
+struct Call<'a> {
+    client: &'a Client,
+    method: &'a str,
+    bucket: &'a str,
+    key: &'a str,
+    query: Vec<(String, String)>,
+    /// 请求头，名称为小写，都参与签名
+    headers: Vec<(String, String)>,
+}
+
+impl Call<'_> {
+    fn query(mut self, name: &str, value: &str) -> Self {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn send(self, body: &[u8]) -> StorageResult<ureq::Response> {
+        let client = self.client;
+        let (host, path) = client.address(self.bucket, self.key);
+        let time = Utc::now();
+        let payload_hash = sha256_hex(body);
+
+        let mut headers = self.headers;
+        headers.push(("host".to_string(), host.clone()));
+        headers.push((
+            "x-amz-date".to_string(),
+            time.format("%Y%m%dT%H%M%SZ").to_string(),
+        ));
+        headers.push(("x-amz-content-sha256".to_string(), payload_hash.clone()));
+        let authorization = client.signer.authorization(
+            &Request {
+                method: self.method,
+                path: &path,
+                query: &self.query,
+                headers: &headers,
+            },
+            &payload_hash,
+            time,
+        );
+
+        let mut url = format!("{}://{}{}", client.scheme, host, path);
+        if !self.query.is_empty() {
+            url.push('?');
+            url.push_str(&canonical_query(&self.query));
+        }
+        let mut request = client.agent.request(self.method, &url);
+        for (name, value) in &headers {
+            request = request.set(name, value);
+        }
+        request = request.set("Authorization", &authorization);
+        let result = match self.method {
+            "PUT" | "POST" => request.send_bytes(body),
+            _ => request.call(),
+        };
+        check(result, &client.path(self.bucket, self.key))
+    }
+}
+
+/// 读取一段内容，读到末尾时可能不足一段
+fn read_part(reader: &mut dyn Read) -> io::Result<Vec<u8>> {
+    let mut part = Vec::with_capacity(PART_SIZE);
+    reader.take(PART_SIZE as u64).read_to_end(&mut part)?;
+    Ok(part)
+}
+
+fn part_etag(response: &ureq::Response) -> StorageResult<String> {
+    response
+        .header("ETag")
+        .map(str::to_string)
+        .ok_or_else(|| StorageError::Other("S3 响应中缺少 ETag".to_string()))
+}
+
+fn content_type(key: &str) -> String {
+    from_path(key).first().map_or_else(
+        || "application/octet-stream".to_string(),
+        |mime| mime.to_string(),
+    )
+}
+
+/// 检查状态为 200 的响应体中是否为错误
+fn check_body(body: &str, path: &str) -> StorageResult<()> {
+    match response::parse_error(body) {
+        Some((code, message)) => Err(StorageError::Other(format!(
+            "S3 请求失败（{}: {}）: {}",
+            code, message, path
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// 把请求结果中的 HTTP 错误转换为存储错误
+///
+/// 不自动跟随重定向（通常是存储桶位于其他区域），收到重定向时报告错误
+fn check(result: Result<ureq::Response, ureq::Error>, path: &str) -> StorageResult<ureq::Response> {
+    let response = result.map_err(|e| map_error(e, path))?;
+    if (300..400).contains(&response.status()) {
+        return Err(StorageError::Other(format!(
+            "服务器将 {} 重定向到 {}，请检查服务端点和区域",
+            path,
+            response.header("Location").unwrap_or_default()
+        )));
+    }
+    Ok(response)
+}
+
+/// 把 HTTP 错误转换为存储错误
+fn map_error(error: ureq::Error, path: &str) -> StorageError {
+    let path = path.to_string();
+    match error {
+        ureq::Error::Status(status, response) => {
+            let (code, message) = response
+                .into_string()
+                .ok()
+                .and_then(|body| response::parse_error(&body))
+                .unwrap_or_default();
+            match (status, code.as_str()) {
+                (404, _) => StorageError::PathNotFound(path),
+                (403, "AccessDenied") | (403, "") => StorageError::PermissionDenied(path),
+                (412, _) => StorageError::AlreadyExists(path),
+                _ => StorageError::Other(format!(
+                    "S3 请求失败（{} {}: {}）: {}",
+                    status, code, message, path
+                )),
+            }
+        }
+        ureq::Error::Transport(transport) => {
+            StorageError::IoError(io::Error::other(transport.to_string()))
+        }
+    }
+}
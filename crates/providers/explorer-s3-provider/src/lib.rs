@@ -0,0 +1,463 @@
+//! S3 兼容对象存储提供者
+//!
+//! 支持 AWS S3 以及 MinIO 等兼容服务。路径形如 `s3://access_key@host/bucket/dir/file`，
+//! 每个存储桶是一个云存储根节点；对象键按 `/` 分隔，公共前缀显示为目录，以 `/` 结尾的
+//! 空对象则作为空目录的占位。
+//!
+//! S3 没有重命名操作，移动通过在服务器上复制后再删除完成，目录的移动和删除要逐个处理
+//! 其中的对象，不是原子操作。
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use mime_guess::from_path;
+use serde::{Deserialize, Serialize};
+
+use explorer_storage::*;
+
+use client::{Client, split_endpoint};
+use response::Object;
+
+mod client;
+mod response;
+mod signature;
+
+/// 路径前缀
+pub const SCHEME: &str = "s3://";
+
+/// 预签名 URL 的最长有效期（SigV4 的上限为 7 天）
+pub const MAX_PRESIGN_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 已保存的 S3 连接
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S3Connection {
+    /// 显示名称
+    pub name: String,
+    /// 服务端点，例如 `https://s3.us-east-1.amazonaws.com` 或 `http://127.0.0.1:9000`
+    pub endpoint: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key: String,
+    /// 私有访问密钥（以明文保存）
+    pub secret_key: String,
+    /// 使用路径形式的地址（`host/bucket/key`），MinIO 等服务需要；否则使用虚拟主机形式
+    /// （`bucket.host/key`）
+    #[serde(default = "default_path_style")]
+    pub path_style: bool,
+    /// 显示的存储桶，为空时列出账户中的所有存储桶
+    ///
+    /// 密钥没有 ListBuckets 权限时需要在这里指定
+    #[serde(default)]
+    pub buckets: Vec<String>,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_path_style() -> bool {
+    true
+}
+
+impl S3Connection {
+    /// 连接的 `access_key@host` 部分
+    pub fn authority(&self) -> String {
+        format!("{}@{}", self.access_key, split_endpoint(&self.endpoint).1)
+    }
+
+    /// 连接中所有路径的前缀
+    pub fn root(&self) -> String {
+        format!("{}{}", SCHEME, self.authority())
+    }
+}
+
+/// 路径是否为 S3 路径
+pub fn is_s3_path(path: &str) -> bool {
+    path.starts_with(SCHEME)
+}
+
+/// 存储桶和对象键
+type Location = (String, String);
+
+/// S3 存储提供者
+pub struct S3Provider {
+    connections: Vec<(S3Connection, Arc<Client>)>,
+}
+
+impl S3Provider {
+    /// 使用已保存的连接创建提供者
+    pub fn new(connections: Vec<S3Connection>) -> Self {
+        Self {
+            connections: connections
+                .into_iter()
+                .map(|connection| {
+                    let client = Arc::new(Client::new(&connection));
+                    (connection, client)
+                })
+                .collect(),
+        }
+    }
+
+    /// 拆分路径，返回连接的客户端、存储桶和对象键（不以 `/` 结尾）
+    fn locate(&self, path: &str) -> StorageResult<(Arc<Client>, String, String)> {
+        let rest = path
+            .strip_prefix(SCHEME)
+            .ok_or_else(|| StorageError::Other(format!("不是 S3 路径: {}", path)))?;
+        let (authority, rest) = rest.split_once('/').unwrap_or((rest, ""));
+        let client = self
+            .connections
+            .iter()
+            .find(|(connection, _)| connection.authority() == authority)
+            .map(|(_, client)| client.clone())
+            .ok_or_else(|| StorageError::Other(format!("没有保存的连接: {}", authority)))?;
+
+        let rest = rest.trim_end_matches('/');
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(StorageError::Other(format!("路径中缺少存储桶: {}", path)));
+        }
+        Ok((client, bucket.to_string(), key.to_string()))
+    }
+
+    /// 拆分同一连接中的两个路径
+    fn locate_pair(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> StorageResult<(Arc<Client>, Location, Location)> {
+        let (client, from_bucket, from_key) = self.locate(from)?;
+        let (to_client, to_bucket, to_key) = self.locate(to)?;
+        if !Arc::ptr_eq(&client, &to_client) {
+            return Err(StorageError::Unsupported(format!(
+                "在不同服务器之间操作: {} -> {}",
+                from, to
+            )));
+        }
+        if from_key.is_empty() || to_key.is_empty() {
+            return Err(StorageError::Unsupported(format!(
+                "复制或移动存储桶: {} -> {}",
+                from, to
+            )));
+        }
+        Ok((client, (from_bucket, from_key), (to_bucket, to_key)))
+    }
+
+    /// 生成对象的预签名下载 URL，持有 URL 的人无需凭据即可在有效期内下载
+    pub fn presigned_url(&self, path: &str, expires: Duration) -> StorageResult<String> {
+        let (client, bucket, key) = self.locate(path)?;
+        if key.is_empty() {
+            return Err(StorageError::Other(format!("路径不是文件: {}", path)));
+        }
+        if expires.is_zero() || expires > MAX_PRESIGN_EXPIRY {
+            return Err(StorageError::Other(
+                "预签名 URL 的有效期必须在 1 秒到 7 天之间".to_string(),
+            ));
+        }
+        Ok(client.presign(&bucket, &key, expires.as_secs()))
+    }
+}
+
+/// 对象对应的文件条目
+fn file_item(client: &Client, bucket: &str, object: &Object) -> FileItem {
+    let name = object
+        .key
+        .rsplit('/')
+        .next()
+        .unwrap_or(&object.key)
+        .to_string();
+    let mime_type = object
+        .content_type
+        .clone()
+        .or_else(|| from_path(&name).first().map(|mime| mime.to_string()));
+
+    let mut custom_fields = HashMap::new();
+    if let Some(etag) = &object.etag {
+        custom_fields.insert("etag".to_string(), etag.clone());
+    }
+    if let Some(storage_class) = &object.storage_class {
+        custom_fields.insert("storage_class".to_string(), storage_class.clone());
+    }
+
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: client.path(bucket, &object.key),
+        item_type: ItemType::File,
+        size: object.size,
+        modified: object.modified.unwrap_or(SystemTime::UNIX_EPOCH),
+        metadata: EntryMetadata {
+            mime_type,
+            custom_fields,
+            ..Default::default()
+        },
+    }
+}
+
+/// 前缀（或存储桶）对应的目录条目，`key` 不以 `/` 结尾
+fn dir_item(client: &Client, bucket: &str, key: &str) -> FileItem {
+    let name = match key.rsplit('/').next() {
+        Some("") | None => bucket.to_string(),
+        Some(name) => name.to_string(),
+    };
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: client.path(bucket, key),
+        item_type: ItemType::Directory,
+        size: 0,
+        modified: SystemTime::UNIX_EPOCH,
+        metadata: EntryMetadata::default(),
+    }
+}
+
+/// 获取对象或目录的信息；没有同名对象但有以 `key/` 开头的对象时即为目录
+fn stat(client: &Client, bucket: &str, key: &str) -> StorageResult<FileItem> {
+    if key.is_empty() {
+        client.head_bucket(bucket)?;
+        return Ok(dir_item(client, bucket, key));
+    }
+    match client.head_object(bucket, key) {
+        Ok(object) => return Ok(file_item(client, bucket, &object)),
+        Err(StorageError::PathNotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    let page = client.list_page(bucket, &format!("{}/", key), false, None, Some(1))?;
+    if page.objects.is_empty() && page.prefixes.is_empty() {
+        return Err(StorageError::PathNotFound(client.path(bucket, key)));
+    }
+    Ok(dir_item(client, bucket, key))
+}
+
+/// 要处理的对象：文件本身，或目录中的所有对象（包括占位对象）
+fn objects_under(client: &Client, bucket: &str, key: &str) -> StorageResult<Vec<Object>> {
+    match client.head_object(bucket, key) {
+        Ok(object) => return Ok(vec![object]),
+        Err(StorageError::PathNotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+    let (objects, _) = client.list(bucket, &format!("{}/", key), false)?;
+    if objects.is_empty() {
+        return Err(StorageError::PathNotFound(client.path(bucket, key)));
+    }
+    Ok(objects)
+}
+
+/// 在服务器上复制文件或目录
+fn copy_tree(
+    client: &Client,
+    (from_bucket, from_key): (&str, &str),
+    (to_bucket, to_key): (&str, &str),
+) -> StorageResult<Vec<Object>> {
+    if from_bucket == to_bucket && to_key.starts_with(&format!("{}/", from_key)) {
+        return Err(StorageError::Other(format!(
+            "不能将目录复制到其自身内部: {}",
+            client.path(to_bucket, to_key)
+        )));
+    }
+    match stat(client, to_bucket, to_key) {
+        Ok(_) => return Err(StorageError::AlreadyExists(client.path(to_bucket, to_key))),
+        Err(StorageError::PathNotFound(_)) => {}
+        Err(e) => return Err(e),
+    }
+
+    let objects = objects_under(client, from_bucket, from_key)?;
+    for object in &objects {
+        let key = format!("{}{}", to_key, &object.key[from_key.len()..]);
+        client.copy_object(from_bucket, object, to_bucket, &key)?;
+    }
+    Ok(objects)
+}
+
+#[async_trait]
+impl StorageProvider for S3Provider {
+    async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
+        let connections = self.connections.clone();
+        smol::unblock(move || {
+            let mut roots = vec![];
+            for (connection, client) in connections {
+                let buckets = if connection.buckets.is_empty() {
+                    match client.list_buckets() {
+                        Ok(buckets) => buckets,
+                        Err(e) => {
+                            tracing::warn!("无法列出存储桶: {}: {}", connection.name, e);
+                            continue;
+                        }
+                    }
+                } else {
+                    connection.buckets.clone()
+                };
+                roots.extend(buckets.into_iter().map(|bucket| {
+                    let path = client.path(&bucket, "");
+                    RootItem {
+                        id: path.clone(),
+                        name: bucket,
+                        path,
+                        provider_type: ProviderType::CloudStorage {
+                            provider_name: "s3".to_string(),
+                        },
+                        icon: None,
+                        mount: None,
+                    }
+                }));
+            }
+            Ok(roots)
+        })
+        .await
+    }
+
+    async fn get_metadata(&self, path: &str) -> StorageResult<FileItem> {
+        let (client, bucket, key) = self.locate(path)?;
+        smol::unblock(move || stat(&client, &bucket, &key)).await
+    }
+
+    async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>> {
+        let (client, bucket, key) = self.locate(path)?;
+        let path = path.to_string();
+        smol::unblock(move || {
+            let prefix = if key.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", key)
+            };
+            let (objects, prefixes) = client.list(&bucket, &prefix, true)?;
+            if !key.is_empty() && objects.is_empty() && prefixes.is_empty() {
+                return match client.head_object(&bucket, &key) {
+                    Ok(_) => Err(StorageError::Other(format!("路径不是目录: {}", path))),
+                    Err(e) => Err(e),
+                };
+            }
+
+            let mut entries: Vec<FileItem> = prefixes
+                .iter()
+                .map(|prefix| dir_item(&client, &bucket, prefix.trim_end_matches('/')))
+                .chain(
+                    objects
+                        .iter()
+                        // 跳过目录本身的占位对象
+                        .filter(|object| object.key != prefix)
+                        .map(|object| file_item(&client, &bucket, object)),
+                )
+                // `a//b` 这样的键会产生空名称，无法在界面中表示
+                .filter(|entry| !entry.name.is_empty())
+                .collect();
+            sort_entries(&mut entries);
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn exists(&self, path: &str) -> StorageResult<bool> {
+        let (client, bucket, key) = self.locate(path)?;
+        smol::unblock(move || match stat(&client, &bucket, &key) {
+            Ok(_) => Ok(true),
+            Err(StorageError::PathNotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        })
+        .await
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        // `writer` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (client, bucket, key) = self.locate(path)?;
+        if key.is_empty() {
+            return Err(StorageError::Other(format!("路径不是文件: {}", path)));
+        }
+        client.get_object(&bucket, &key, writer)
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        // `reader` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (client, bucket, key) = self.locate(path)?;
+        if key.is_empty() {
+            return Err(StorageError::Other(format!("路径不是文件: {}", path)));
+        }
+        client.upload(&bucket, &key, reader)
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        let (client, bucket, key) = self.locate(path)?;
+        let path = path.to_string();
+        smol::unblock(move || {
+            match stat(&client, &bucket, &key) {
+                Ok(_) => return Err(StorageError::AlreadyExists(path)),
+                Err(StorageError::PathNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+            client.put_object(&bucket, &format!("{}/", key), &[], false)
+        })
+        .await
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        let (client, bucket, key) = self.locate(path)?;
+        let path = path.to_string();
+        smol::unblock(move || {
+            match stat(&client, &bucket, &key) {
+                Ok(_) => return Err(StorageError::AlreadyExists(path)),
+                Err(StorageError::PathNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+            client.put_object(&bucket, &key, &[], true)
+        })
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (client, (from_bucket, from_key), (to_bucket, to_key)) = self.locate_pair(from, to)?;
+        smol::unblock(move || {
+            let objects = copy_tree(&client, (&from_bucket, &from_key), (&to_bucket, &to_key))?;
+            for object in objects {
+                client.delete_object(&from_bucket, &object.key)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (client, (from_bucket, from_key), (to_bucket, to_key)) = self.locate_pair(from, to)?;
+        smol::unblock(move || {
+            copy_tree(&client, (&from_bucket, &from_key), (&to_bucket, &to_key)).map(|_| ())
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        let (client, bucket, key) = self.locate(path)?;
+        if key.is_empty() {
+            return Err(StorageError::Unsupported(format!("删除存储桶: {}", path)));
+        }
+        smol::unblock(move || {
+            for object in objects_under(&client, &bucket, &key)? {
+                client.delete_object(&bucket, &object.key)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            can_create: true,
+            can_rename: true,
+            can_copy: true,
+            can_move: true,
+            can_trash: false,
+            can_delete: true,
+            can_symlink: false,
+            can_archive: false,
+            local_paths: false,
+        }
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::CloudStorage {
+            provider_name: "s3".to_string(),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! S3 的 XML 响应
+//!
+//! AWS 和 MinIO 的响应带有 `http://s3.amazonaws.com/doc/2006-03-01/` 命名空间，有的兼容
+//! 服务则没有，因此只按元素的本地名称匹配。
+
+use std::time::SystemTime;
+
+use chrono::DateTime;
+use explorer_storage::{StorageError, StorageResult};
+
+fn parse_document(xml: &str) -> StorageResult<roxmltree::Document<'_>> {
+    roxmltree::Document::parse(xml)
+        .map_err(|e| StorageError::Other(format!("无法解析 S3 响应: {}", e)))
+}
+
+/// 指定名称的子元素
+fn children<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// 指定名称的子元素的文本
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &'static str) -> Option<&'a str> {
+    children(node, name).next().and_then(|child| child.text())
+}
+
+fn parse_time(text: Option<&str>) -> Option<SystemTime> {
+    text.and_then(|text| DateTime::parse_from_rfc3339(text.trim()).ok())
+        .map(Into::into)
+}
+
+/// 解析 ListBuckets 的响应，返回存储桶的名称
+pub fn parse_buckets(xml: &str) -> StorageResult<Vec<String>> {
+    let document = parse_document(xml)?;
+    Ok(children(document.root_element(), "Buckets")
+        .flat_map(|buckets| children(buckets, "Bucket"))
+        .filter_map(|bucket| child_text(bucket, "Name"))
+        .map(str::to_string)
+        .collect())
+}
+
+/// 对象
+#[derive(Debug, Clone)]
+pub struct Object {
+    pub key: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub etag: Option<String>,
+    pub storage_class: Option<String>,
+    /// 内容类型（只有 HEAD 请求返回）
+    pub content_type: Option<String>,
+}
+
+/// ListObjectsV2 的一页结果
+#[derive(Debug)]
+pub struct ListPage {
+    pub objects: Vec<Object>,
+    /// 按分隔符归并的公共前缀（即子目录），以 `/` 结尾
+    pub prefixes: Vec<String>,
+    /// 还有下一页时的续传令牌
+    pub continuation_token: Option<String>,
+}
+
+/// 解析 ListObjectsV2 的响应
+pub fn parse_list(xml: &str) -> StorageResult<ListPage> {
+    let document = parse_document(xml)?;
+    let root = document.root_element();
+    let objects = children(root, "Contents")
+        .filter_map(|contents| {
+            Some(Object {
+                key: child_text(contents, "Key")?.to_string(),
+                size: child_text(contents, "Size")
+                    .and_then(|size| size.trim().parse().ok())
+                    .unwrap_or(0),
+                modified: parse_time(child_text(contents, "LastModified")),
+                etag: child_text(contents, "ETag").map(str::to_string),
+                storage_class: child_text(contents, "StorageClass").map(str::to_string),
+                content_type: None,
+            })
+        })
+        .collect();
+    let prefixes = children(root, "CommonPrefixes")
+        .filter_map(|prefixes| child_text(prefixes, "Prefix"))
+        .map(str::to_string)
+        .collect();
+    let truncated = child_text(root, "IsTruncated").is_some_and(|text| text.trim() == "true");
+    let continuation_token = child_text(root, "NextContinuationToken")
+        .filter(|_| truncated)
+        .map(str::to_string);
+    Ok(ListPage {
+        objects,
+        prefixes,
+        continuation_token,
+    })
+}
+
+/// 解析 CreateMultipartUpload 的响应，返回上传 ID
+pub fn parse_upload_id(xml: &str) -> StorageResult<String> {
+    let document = parse_document(xml)?;
+    child_text(document.root_element(), "UploadId")
+        .map(str::to_string)
+        .ok_or_else(|| StorageError::Other("S3 响应中缺少 UploadId".to_string()))
+}
+
+/// 解析 UploadPartCopy 的响应，返回分段的 ETag
+pub fn parse_copy_part_etag(xml: &str) -> StorageResult<String> {
+    let document = parse_document(xml)?;
+    child_text(document.root_element(), "ETag")
+        .map(str::to_string)
+        .ok_or_else(|| StorageError::Other("S3 响应中缺少 ETag".to_string()))
+}
+
+/// 错误响应的错误码和说明
+///
+/// CopyObject 和 CompleteMultipartUpload 即使失败也可能返回 200，需要检查响应体
+pub fn parse_error(xml: &str) -> Option<(String, String)> {
+    let document = roxmltree::Document::parse(xml).ok()?;
+    let root = document.root_element();
+    if root.tag_name().name() != "Error" {
+        return None;
+    }
+    Some((
+        child_text(root, "Code").unwrap_or_default().to_string(),
+        child_text(root, "Message").unwrap_or_default().to_string(),
+    ))
+}
+
+/// CompleteMultipartUpload 的请求体
+pub fn complete_body(etags: &[String]) -> String {
+    let mut body = String::from("<CompleteMultipartUpload>");
+    for (index, etag) in etags.iter().enumerate() {
+        body.push_str(&format!(
+            "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+            index + 1,
+            escape(etag)
+        ));
+    }
+    body.push_str("</CompleteMultipartUpload>");
+    body
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,945 @@
+//! 使用进程内的 S3 服务器替身的集成测试
+//!
+//! 替身模仿 MinIO 的路径形式地址，数据保存在内存中，并独立地校验每个请求的 SigV4 签名。
+//! 列表每页最多返回两项，以覆盖续传令牌的翻页。
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::{self, Read},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use sha2::{Digest, Sha256};
+use tiny_http::{Header, Request, Response, Server};
+
+use explorer_s3_provider::{S3Connection, S3Provider};
+use explorer_storage::{ItemType, ProviderType, StorageError, StorageProvider, conformance};
+
+const ACCESS_KEY: &str = "minioadmin";
+const SECRET_KEY: &str = "minio-secret";
+const REGION: &str = "us-east-1";
+/// 列表每页最多的条目数
+const PAGE_SIZE: usize = 2;
+/// 分段上传时每段的最小大小
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Clone)]
+struct Stored {
+    data: Vec<u8>,
+    content_type: String,
+    modified: DateTime<Utc>,
+    etag: String,
+}
+
+struct Upload {
+    bucket: String,
+    key: String,
+    content_type: String,
+    parts: BTreeMap<u32, Stored>,
+}
+
+#[derive(Default)]
+struct Store {
+    buckets: BTreeMap<String, BTreeMap<String, Stored>>,
+    uploads: HashMap<String, Upload>,
+    next_upload: u32,
+    /// 收到的请求（方法和查询参数名）
+    log: Vec<String>,
+}
+
+fn stored(data: Vec<u8>, content_type: &str) -> Stored {
+    Stored {
+        etag: format!("\"{}\"", &hex::encode(Sha256::digest(&data))[..32]),
+        data,
+        content_type: content_type.to_string(),
+        modified: Utc::now(),
+    }
+}
+
+/// 服务器替身及连接到它的提供者
+struct Fixture {
+    server: Arc<Server>,
+    store: Arc<Mutex<Store>>,
+    connection: S3Connection,
+    provider: S3Provider,
+}
+
+impl Fixture {
+    fn new(buckets: &[&str]) -> Self {
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let port = server.server_addr().to_ip().unwrap().port();
+        let store = Arc::new(Mutex::new(Store::default()));
+        for bucket in buckets {
+            store
+                .lock()
+                .unwrap()
+                .buckets
+                .insert(bucket.to_string(), BTreeMap::new());
+        }
+        {
+            let (server, store) = (server.clone(), store.clone());
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(request, &store);
+                }
+            });
+        }
+
+        let connection = S3Connection {
+            name: "MinIO".to_string(),
+            endpoint: format!("http://127.0.0.1:{}", port),
+            region: REGION.to_string(),
+            access_key: ACCESS_KEY.to_string(),
+            secret_key: SECRET_KEY.to_string(),
+            path_style: true,
+            buckets: vec![],
+        };
+        Self {
+            server,
+            store,
+            provider: S3Provider::new(vec![connection.clone()]),
+            connection,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.connection.root(), path)
+    }
+
+    fn put(&self, bucket: &str, key: &str, data: &[u8]) {
+        self.store
+            .lock()
+            .unwrap()
+            .buckets
+            .get_mut(bucket)
+            .unwrap()
+            .insert(key.to_string(), stored(data.to_vec(), "text/plain"));
+    }
+
+    fn get(&self, bucket: &str, key: &str) -> Option<Vec<u8>> {
+        let store = self.store.lock().unwrap();
+        store.buckets[bucket]
+            .get(key)
+            .map(|stored| stored.data.clone())
+    }
+
+    fn keys(&self, bucket: &str) -> Vec<String> {
+        self.store.lock().unwrap().buckets[bucket]
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    fn log(&self) -> Vec<String> {
+        std::mem::take(&mut self.store.lock().unwrap().log)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+impl conformance::Fixture for Fixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        self.url("photos/tree")
+    }
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.to_string().eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str())
+}
+
+fn xml(status: u16, body: String) -> Response<io::Cursor<Vec<u8>>> {
+    Response::from_string(format!(r#"<?xml version="1.0" encoding="UTF-8"?>{}"#, body))
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/xml").unwrap())
+}
+
+fn error(status: u16, code: &str) -> Response<io::Cursor<Vec<u8>>> {
+    xml(
+        status,
+        format!(
+            "<Error><Code>{}</Code><Message>{} 错误</Message></Error>",
+            code, code
+        ),
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn decode(text: &str) -> String {
+    percent_decode_str(text).decode_utf8().unwrap().into_owned()
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 按 SigV4 计算签名
+fn signature(
+    canonical_request: &str,
+    time: &str,
+    date: &str,
+    region: &str,
+    secret_key: &str,
+) -> String {
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        time,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+    let key = hmac(format!("AWS4{}", secret_key).as_bytes(), date);
+    let key = hmac(&key, region);
+    let key = hmac(&key, "s3");
+    let key = hmac(&key, "aws4_request");
+    hex::encode(hmac(&key, &string_to_sign))
+}
+
+/// 校验请求的签名（请求头中的签名或预签名 URL 中的签名）
+fn verify(request: &Request, path: &str, query: &[(String, String)], body: &[u8]) -> bool {
+    let canonical_query = |skip: &str| {
+        let mut pairs: Vec<_> = query
+            .iter()
+            .filter(|(key, _)| key != skip)
+            .map(|(key, value)| {
+                (
+                    utf8_percent_encode(key, UNRESERVED).to_string(),
+                    utf8_percent_encode(value, UNRESERVED).to_string(),
+                )
+            })
+            .collect();
+        pairs.sort();
+        pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("&")
+    };
+    let canonical_headers = |signed: &str| {
+        signed
+            .split(';')
+            .map(|name| format!("{}:{}\n", name, header(request, name).unwrap_or("").trim()))
+            .collect::<String>()
+    };
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if let Some(expected) = param("X-Amz-Signature") {
+        // 预签名 URL
+        let credential = param("X-Amz-Credential").unwrap_or_default();
+        let parts: Vec<_> = credential.split('/').collect();
+        let time = param("X-Amz-Date").unwrap_or_default();
+        let signed = param("X-Amz-SignedHeaders").unwrap_or_default();
+        let expires: i64 = param("X-Amz-Expires").unwrap_or("0").parse().unwrap();
+        let issued = NaiveDateTime::parse_from_str(time, "%Y%m%dT%H%M%SZ")
+            .unwrap()
+            .and_utc();
+        if parts.len() != 5
+            || parts[0] != ACCESS_KEY
+            || Utc::now() > issued + chrono::Duration::seconds(expires)
+        {
+            return false;
+        }
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\nUNSIGNED-PAYLOAD",
+            request.method(),
+            path,
+            canonical_query("X-Amz-Signature"),
+            canonical_headers(signed),
+            signed
+        );
+        return signature(&canonical_request, time, parts[1], parts[2], SECRET_KEY) == expected;
+    }
+
+    let Some(authorization) = header(request, "Authorization") else {
+        return false;
+    };
+    let fields: HashMap<_, _> = authorization
+        .trim_start_matches("AWS4-HMAC-SHA256 ")
+        .split(", ")
+        .filter_map(|field| field.split_once('='))
+        .collect();
+    let parts: Vec<_> = fields["Credential"].split('/').collect();
+    let payload_hash = header(request, "x-amz-content-sha256").unwrap_or_default();
+    if parts[0] != ACCESS_KEY || payload_hash != hex::encode(Sha256::digest(body)) {
+        return false;
+    }
+    let signed = fields["SignedHeaders"];
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method(),
+        path,
+        canonical_query(""),
+        canonical_headers(signed),
+        signed,
+        payload_hash
+    );
+    let time = header(request, "x-amz-date").unwrap_or_default();
+    signature(&canonical_request, time, parts[1], parts[2], SECRET_KEY) == fields["Signature"]
+}
+
+fn handle(mut request: Request, store: &Mutex<Store>) {
+    let url = request.url().to_string();
+    let (path, raw_query) = url.split_once('?').unwrap_or((&url, ""));
+    let query: Vec<(String, String)> = raw_query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+    let mut body = vec![];
+    request.as_reader().read_to_end(&mut body).unwrap();
+
+    let response = if verify(&request, path, &query, &body) {
+        let mut store = store.lock().unwrap();
+        let names: Vec<_> = query.iter().map(|(key, _)| key.as_str()).collect();
+        store
+            .log
+            .push(format!("{} {}", request.method(), names.join("&")));
+        route(&request, &mut store, path, &query, body)
+    } else {
+        error(403, "SignatureDoesNotMatch").boxed()
+    };
+    let _ = request.respond(response);
+}
+
+fn route(
+    request: &Request,
+    store: &mut Store,
+    path: &str,
+    query: &[(String, String)],
+    body: Vec<u8>,
+) -> tiny_http::ResponseBox {
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let method = request.method().as_str();
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        let buckets: String = store
+            .buckets
+            .keys()
+            .map(|name| format!("<Bucket><Name>{}</Name></Bucket>", name))
+            .collect();
+        return xml(
+            200,
+            format!(
+                "<ListAllMyBucketsResult><Buckets>{}</Buckets></ListAllMyBucketsResult>",
+                buckets
+            ),
+        )
+        .boxed();
+    }
+    let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+    let key = decode(key);
+    if !store.buckets.contains_key(bucket) {
+        return error(404, "NoSuchBucket").boxed();
+    }
+
+    if key.is_empty() {
+        if method == "HEAD" {
+            return Response::empty(200).boxed();
+        }
+        return list(&store.buckets[bucket], query).boxed();
+    }
+
+    let upload_id = param("uploadId").map(str::to_string);
+    let copy_source = header(request, "x-amz-copy-source").map(|source| {
+        let (bucket, key) = source.trim_start_matches('/').split_once('/').unwrap();
+        (bucket.to_string(), decode(key))
+    });
+    let source = copy_source.as_ref().map(|(bucket, key)| {
+        store
+            .buckets
+            .get(bucket)
+            .and_then(|objects| objects.get(key))
+            .cloned()
+    });
+    let content_type = header(request, "Content-Type")
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    match (method, upload_id) {
+        ("POST", None) if param("uploads").is_some() => {
+            store.next_upload += 1;
+            let id = format!("upload-{}", store.next_upload);
+            store.uploads.insert(
+                id.clone(),
+                Upload {
+                    bucket: bucket.to_string(),
+                    key: key.clone(),
+                    content_type,
+                    parts: BTreeMap::new(),
+                },
+            );
+            xml(
+                200,
+                format!(
+                    "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key>\
+                     <UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                    bucket,
+                    escape(&key),
+                    id
+                ),
+            )
+            .boxed()
+        }
+        ("PUT", Some(id)) => {
+            let number: u32 = param("partNumber").unwrap().parse().unwrap();
+            let Some(upload) = store.uploads.get_mut(&id) else {
+                return error(404, "NoSuchUpload").boxed();
+            };
+            match source {
+                Some(None) => error(404, "NoSuchKey").boxed(),
+                Some(Some(source)) => {
+                    let range = header(request, "x-amz-copy-source-range").unwrap();
+                    let (start, end) = range.trim_start_matches("bytes=").split_once('-').unwrap();
+                    let data = source.data[start.parse().unwrap()..=end.parse().unwrap()].to_vec();
+                    let part = stored(data, "");
+                    let etag = part.etag.clone();
+                    upload.parts.insert(number, part);
+                    xml(
+                        200,
+                        format!("<CopyPartResult><ETag>{}</ETag></CopyPartResult>", etag),
+                    )
+                    .boxed()
+                }
+                None => {
+                    let part = stored(body, "");
+                    let etag = part.etag.clone();
+                    upload.parts.insert(number, part);
+                    Response::empty(200)
+                        .with_header(Header::from_bytes("ETag", etag).unwrap())
+                        .boxed()
+                }
+            }
+        }
+        ("POST", Some(id)) => {
+            let Some(upload) = store.uploads.remove(&id) else {
+                return error(404, "NoSuchUpload").boxed();
+            };
+            let text = String::from_utf8(body).unwrap();
+            let document = roxmltree::Document::parse(&text).unwrap();
+            let requested: Vec<(u32, String)> = document
+                .descendants()
+                .filter(|node| node.has_tag_name("Part"))
+                .map(|part| {
+                    let child = |name| {
+                        part.children()
+                            .find(|node| node.has_tag_name(name))
+                            .and_then(|node| node.text())
+                            .unwrap()
+                            .to_string()
+                    };
+                    (child("PartNumber").parse().unwrap(), child("ETag"))
+                })
+                .collect();
+            let mut data = vec![];
+            for (index, (number, etag)) in requested.iter().enumerate() {
+                let Some(part) = upload.parts.get(number).filter(|part| &part.etag == etag) else {
+                    return error(400, "InvalidPart").boxed();
+                };
+                if index + 1 < requested.len() && part.data.len() < MIN_PART_SIZE {
+                    return error(400, "EntityTooSmall").boxed();
+                }
+                data.extend_from_slice(&part.data);
+            }
+            let object = stored(data, &upload.content_type);
+            let etag = object.etag.clone();
+            store
+                .buckets
+                .get_mut(&upload.bucket)
+                .unwrap()
+                .insert(upload.key.clone(), object);
+            xml(
+                200,
+                format!(
+                    "<CompleteMultipartUploadResult><Key>{}</Key><ETag>{}</ETag>\
+                     </CompleteMultipartUploadResult>",
+                    escape(&upload.key),
+                    etag
+                ),
+            )
+            .boxed()
+        }
+        ("DELETE", Some(id)) => {
+            store.uploads.remove(&id);
+            Response::empty(204).boxed()
+        }
+        ("HEAD" | "GET", None) => match store.buckets[bucket].get(&key) {
+            None if method == "HEAD" => Response::empty(404).boxed(),
+            None => error(404, "NoSuchKey").boxed(),
+            Some(object) => Response::from_data(object.data.clone())
+                .with_header(Header::from_bytes("ETag", object.etag.clone()).unwrap())
+                .with_header(
+                    Header::from_bytes("Content-Type", object.content_type.clone()).unwrap(),
+                )
+                .with_header(
+                    Header::from_bytes(
+                        "Last-Modified",
+                        object
+                            .modified
+                            .format("%a, %d %b %Y %H:%M:%S GMT")
+                            .to_string(),
+                    )
+                    .unwrap(),
+                )
+                .boxed(),
+        },
+        ("PUT", None) => {
+            let objects = store.buckets.get_mut(bucket).unwrap();
+            match source {
+                Some(None) => error(404, "NoSuchKey").boxed(),
+                Some(Some(source)) => {
+                    let etag = source.etag.clone();
+                    objects.insert(key, stored(source.data, &source.content_type));
+                    xml(
+                        200,
+                        format!("<CopyObjectResult><ETag>{}</ETag></CopyObjectResult>", etag),
+                    )
+                    .boxed()
+                }
+                None if header(request, "If-None-Match") == Some("*")
+                    && objects.contains_key(&key) =>
+                {
+                    error(412, "PreconditionFailed").boxed()
+                }
+                None => {
+                    objects.insert(key, stored(body, &content_type));
+                    Response::empty(200).boxed()
+                }
+            }
+        }
+        ("DELETE", None) => {
+            store.buckets.get_mut(bucket).unwrap().remove(&key);
+            Response::empty(204).boxed()
+        }
+        _ => error(405, "MethodNotAllowed").boxed(),
+    }
+}
+
+/// ListObjectsV2
+fn list(
+    objects: &BTreeMap<String, Stored>,
+    query: &[(String, String)],
+) -> Response<io::Cursor<Vec<u8>>> {
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let prefix = param("prefix").unwrap_or_default();
+    let delimiter = param("delimiter");
+    let max_keys = param("max-keys").map_or(PAGE_SIZE, |max| max.parse().unwrap());
+
+    // 对象和公共前缀按名称排列在一起，续传令牌为上一页的最后一项
+    let mut entries = BTreeSet::new();
+    for key in objects.keys().filter(|key| key.starts_with(prefix)) {
+        let rest = &key[prefix.len()..];
+        match delimiter.and_then(|delimiter| rest.find(delimiter)) {
+            Some(index) => entries.insert((format!("{}{}", prefix, &rest[..=index]), true)),
+            None => entries.insert((key.clone(), false)),
+        };
+    }
+    let token = param("continuation-token");
+    let remaining: Vec<_> = entries
+        .into_iter()
+        .filter(|(name, _)| token.is_none_or(|token| name.as_str() > token))
+        .collect();
+    let page = &remaining[..remaining.len().min(max_keys.min(PAGE_SIZE))];
+
+    let mut body =
+        String::from(r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#);
+    for (name, is_prefix) in page {
+        if *is_prefix {
+            body.push_str(&format!(
+                "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                escape(name)
+            ));
+        } else {
+            let object = &objects[name];
+            body.push_str(&format!(
+                "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>{}</ETag>\
+                 <Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                escape(name),
+                object.modified.to_rfc3339(),
+                escape(&object.etag),
+                object.data.len()
+            ));
+        }
+    }
+    let truncated = page.len() < remaining.len();
+    body.push_str(&format!("<IsTruncated>{}</IsTruncated>", truncated));
+    if truncated {
+        body.push_str(&format!(
+            "<NextContinuationToken>{}</NextContinuationToken>",
+            escape(&page.last().unwrap().0)
+        ));
+    }
+    body.push_str("</ListBucketResult>");
+    xml(200, body)
+}
+
+/// 读取到指定字节数后出错
+struct FailingReader {
+    remaining: usize,
+}
+
+impl Read for FailingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            return Err(io::Error::new(io::ErrorKind::ConnectionReset, "读取失败"));
+        }
+        let len = buf.len().min(self.remaining);
+        buf[..len].fill(7);
+        self.remaining -= len;
+        Ok(len)
+    }
+}
+
+fn read(provider: &S3Provider, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    smol::block_on(provider.read_file(path, &mut content)).unwrap();
+    content
+}
+
+fn names(entries: &[explorer_storage::FileItem]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[test]
+fn buckets_are_cloud_storage_roots() {
+    let fixture = Fixture::new(&["photos", "backup"]);
+    let roots = smol::block_on(fixture.provider.get_roots()).unwrap();
+    let names: Vec<_> = roots.iter().map(|root| root.name.as_str()).collect();
+    assert_eq!(names, ["backup", "photos"]);
+    assert_eq!(roots[0].path, fixture.url("backup"));
+    assert_eq!(
+        roots[0].provider_type,
+        ProviderType::CloudStorage {
+            provider_name: "s3".to_string()
+        }
+    );
+
+    // 指定了存储桶时不再列出
+    let provider = S3Provider::new(vec![S3Connection {
+        buckets: vec!["photos".to_string()],
+        ..fixture.connection.clone()
+    }]);
+    fixture.log();
+    let roots = smol::block_on(provider.get_roots()).unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].name, "photos");
+    assert!(fixture.log().is_empty());
+}
+
+#[test]
+fn lists_prefixes_as_directories() {
+    let fixture = Fixture::new(&["data"]);
+    for key in [
+        "docs/a.txt",
+        "docs/b & c.txt",
+        "docs/sub/deep.txt",
+        "docs/空 目录/",
+        "docs/z.txt",
+        "photos/1.jpg",
+        "readme.md",
+    ] {
+        fixture.put("data", key, key.as_bytes());
+    }
+    let provider = &fixture.provider;
+
+    let root = smol::block_on(provider.list_entries(&fixture.url("data"))).unwrap();
+    assert_eq!(names(&root), ["docs", "photos", "readme.md"]);
+    assert_eq!(root[0].item_type, ItemType::Directory);
+    assert_eq!(root[0].path, fixture.url("data/docs"));
+
+    fixture.log();
+    let docs = smol::block_on(provider.list_entries(&fixture.url("data/docs/"))).unwrap();
+    assert_eq!(
+        names(&docs),
+        ["sub", "空 目录", "a.txt", "b & c.txt", "z.txt"]
+    );
+    // 五项分三页返回
+    let log = fixture.log();
+    assert_eq!(
+        log.iter()
+            .filter(|entry| entry.contains("continuation-token"))
+            .count(),
+        2
+    );
+
+    let file = &docs[3];
+    assert_eq!(file.path, fixture.url("data/docs/b & c.txt"));
+    assert_eq!(file.size, "docs/b & c.txt".len() as u64);
+    assert_eq!(file.metadata.mime_type.as_deref(), Some("text/plain"));
+    assert_eq!(
+        file.metadata
+            .custom_fields
+            .get("storage_class")
+            .map(String::as_str),
+        Some("STANDARD")
+    );
+    assert!(file.metadata.custom_fields.contains_key("etag"));
+
+    let empty = smol::block_on(provider.list_entries(&fixture.url("data/docs/空 目录")));
+    assert!(empty.unwrap().is_empty());
+    assert!(matches!(
+        smol::block_on(provider.list_entries(&fixture.url("data/readme.md"))),
+        Err(StorageError::Other(_))
+    ));
+    assert!(matches!(
+        smol::block_on(provider.list_entries(&fixture.url("data/missing"))),
+        Err(StorageError::PathNotFound(_))
+    ));
+    assert!(matches!(
+        smol::block_on(provider.list_entries(&fixture.url("nobucket"))),
+        Err(StorageError::PathNotFound(_))
+    ));
+
+    let dir = smol::block_on(provider.get_metadata(&fixture.url("data/docs/sub"))).unwrap();
+    assert_eq!(dir.item_type, ItemType::Directory);
+    let file = smol::block_on(provider.get_metadata(&fixture.url("data/readme.md"))).unwrap();
+    assert_eq!(file.item_type, ItemType::File);
+    assert_eq!(file.metadata.mime_type.as_deref(), Some("text/plain"));
+    assert!(!smol::block_on(provider.exists(&fixture.url("data/doc"))).unwrap());
+}
+
+#[test]
+fn uploads_large_files_in_parts() {
+    let fixture = Fixture::new(&["data"]);
+    let provider = &fixture.provider;
+
+    let small = b"hello".to_vec();
+    let written =
+        smol::block_on(provider.write_file(&fixture.url("data/small.txt"), &mut small.as_slice()));
+    assert_eq!(written.unwrap(), 5);
+    assert!(
+        fixture
+            .log()
+            .iter()
+            .all(|entry| !entry.contains("uploadId"))
+    );
+
+    let content: Vec<u8> = (0..2 * PART_SIZE as u32 + 12_345)
+        .map(|i| (i % 251) as u8)
+        .collect();
+    let written = smol::block_on(
+        provider.write_file(&fixture.url("data/large.bin"), &mut content.as_slice()),
+    );
+    assert_eq!(written.unwrap(), content.len() as u64);
+    assert_eq!(fixture.get("data", "large.bin").unwrap(), content);
+    let parts = fixture
+        .log()
+        .iter()
+        .filter(|entry| entry.starts_with("PUT") && entry.contains("partNumber"))
+        .count();
+    assert_eq!(parts, 3);
+    assert_eq!(read(provider, &fixture.url("data/large.bin")), content);
+
+    // 正好是整数段时不上传空的最后一段
+    let exact = vec![1u8; PART_SIZE];
+    smol::block_on(provider.write_file(&fixture.url("data/exact.bin"), &mut exact.as_slice()))
+        .unwrap();
+    assert_eq!(fixture.get("data", "exact.bin").unwrap(), exact);
+}
+
+#[test]
+fn aborts_failed_uploads() {
+    let fixture = Fixture::new(&["data"]);
+    let mut reader = FailingReader {
+        remaining: PART_SIZE + 100,
+    };
+    let result = smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("data/broken.bin"), &mut reader),
+    );
+    assert!(matches!(result, Err(StorageError::IoError(_))));
+    assert!(fixture.get("data", "broken.bin").is_none());
+    assert!(fixture.store.lock().unwrap().uploads.is_empty());
+}
+
+#[test]
+fn creates_copies_moves_and_deletes() {
+    let fixture = Fixture::new(&["data", "archive"]);
+    let provider = &fixture.provider;
+    smol::block_on(async {
+        provider.create_dir(&fixture.url("data/dir")).await.unwrap();
+        assert_eq!(fixture.keys("data"), ["dir/"]);
+        assert!(matches!(
+            provider.create_dir(&fixture.url("data/dir")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+        provider
+            .create_file(&fixture.url("data/dir/new.txt"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            provider.create_file(&fixture.url("data/dir/new.txt")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+        fixture.put("data", "dir/nested/file.txt", b"nested");
+
+        provider
+            .copy(&fixture.url("data/dir"), &fixture.url("data/copy"))
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.keys("data"),
+            [
+                "copy/",
+                "copy/nested/file.txt",
+                "copy/new.txt",
+                "dir/",
+                "dir/nested/file.txt",
+                "dir/new.txt"
+            ]
+        );
+        assert!(matches!(
+            provider
+                .copy(&fixture.url("data/dir"), &fixture.url("data/dir/inner"))
+                .await,
+            Err(StorageError::Other(_))
+        ));
+        assert!(matches!(
+            provider
+                .copy(&fixture.url("data/dir"), &fixture.url("data/copy"))
+                .await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        // 在存储桶之间移动
+        provider
+            .move_entry(&fixture.url("data/copy"), &fixture.url("archive/moved"))
+            .await
+            .unwrap();
+        assert!(!provider.exists(&fixture.url("data/copy")).await.unwrap());
+        assert_eq!(
+            fixture.get("archive", "moved/nested/file.txt").unwrap(),
+            b"nested"
+        );
+
+        provider
+            .rename(
+                &fixture.url("data/dir/new.txt"),
+                &fixture.url("data/dir/renamed.txt"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.keys("data"),
+            ["dir/", "dir/nested/file.txt", "dir/renamed.txt"]
+        );
+
+        provider.delete(&fixture.url("data/dir")).await.unwrap();
+        assert!(fixture.keys("data").is_empty());
+        assert!(matches!(
+            provider.delete(&fixture.url("data/dir")).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.delete(&fixture.url("data")).await,
+            Err(StorageError::Unsupported(_))
+        ));
+    });
+}
+
+#[test]
+fn presigned_urls_download_without_credentials() {
+    let fixture = Fixture::new(&["data"]);
+    fixture.put("data", "报告 2024.pdf", b"report");
+    let path = fixture.url("data/报告 2024.pdf");
+
+    let url = fixture
+        .provider
+        .presigned_url(&path, Duration::from_secs(60))
+        .unwrap();
+    let response = ureq::get(&url).call().unwrap();
+    assert_eq!(response.into_string().unwrap(), "report");
+
+    let tampered = url.replace("X-Amz-Expires=60", "X-Amz-Expires=600");
+    assert!(matches!(
+        ureq::get(&tampered).call(),
+        Err(ureq::Error::Status(403, _))
+    ));
+    assert!(
+        fixture
+            .provider
+            .presigned_url(&path, Duration::ZERO)
+            .is_err()
+    );
+    assert!(
+        fixture
+            .provider
+            .presigned_url(&path, Duration::from_secs(8 * 24 * 60 * 60))
+            .is_err()
+    );
+}
+
+#[test]
+fn rejects_wrong_credentials() {
+    let fixture = Fixture::new(&["data"]);
+    let provider = S3Provider::new(vec![S3Connection {
+        secret_key: "wrong".to_string(),
+        ..fixture.connection.clone()
+    }]);
+    assert!(smol::block_on(provider.get_roots()).unwrap().is_empty());
+    let error = smol::block_on(provider.list_entries(&fixture.url("data"))).unwrap_err();
+    assert!(error.to_string().contains("SignatureDoesNotMatch"));
+    assert!(matches!(
+        smol::block_on(provider.list_entries("s3://other@elsewhere/data")),
+        Err(StorageError::Other(_))
+    ));
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    let fixture = Fixture::new(&["photos"]);
+    smol::block_on(async {
+        let root = conformance::Fixture::root(&fixture);
+        fixture.provider.create_dir(&root).await.unwrap();
+        conformance::write_tree(&fixture).await.unwrap();
+        conformance::run(&fixture).await;
+    });
+}
@@ -0,0 +1,490 @@
+//! SFTP 存储提供者
+//!
+//! 通过系统的 OpenSSH 客户端（`ssh -s <主机> sftp`）连接服务器，身份验证和主机密钥
+//! 检查都交给 ssh 完成。路径形如 `sftp://user@host:22/home/user/file`，每个已保存的
+//! 连接是一个网络驱动器根节点。
+//!
+//! 每个服务器有一个连接池，空闲的会话留待复用；复用的会话已断开时自动重新连接。
+
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use mime_guess::from_path;
+use serde::{Deserialize, Serialize};
+
+use explorer_storage::*;
+
+use protocol::Attrs;
+use session::{Session, join};
+
+mod protocol;
+mod session;
+
+/// 路径前缀
+pub const SCHEME: &str = "sftp://";
+
+/// 每个服务器最多保留的空闲会话数
+const MAX_IDLE_SESSIONS: usize = 4;
+
+/// 身份验证方式
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SftpAuth {
+    /// 使用 ssh-agent 中的密钥（以及 ssh 配置中的默认密钥）
+    #[default]
+    Agent,
+    /// 使用指定的私钥文件
+    Key {
+        path: PathBuf,
+        /// 私钥的口令（私钥未加密时为空）
+        #[serde(default)]
+        passphrase: Option<String>,
+    },
+    /// 使用密码（以明文保存，建议优先使用密钥或 ssh-agent）
+    Password { password: String },
+}
+
+/// 已保存的 SFTP 连接
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SftpConnection {
+    /// 显示名称
+    pub name: String,
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub user: String,
+    #[serde(default)]
+    pub auth: SftpAuth,
+    /// 验证主机密钥使用的 known_hosts 文件，为空时使用 ssh 的默认设置
+    ///
+    /// 主机密钥不在其中或与记录的不一致时拒绝连接
+    #[serde(default)]
+    pub known_hosts: Option<PathBuf>,
+    /// 根节点打开的目录
+    #[serde(default = "default_root")]
+    pub root: String,
+    /// 代替 ssh 运行的命令，其标准输入输出即 SFTP 通道
+    ///
+    /// 例如直接运行 `sftp-server`，或通过自定义的跳板命令连接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+}
+
+fn default_port() -> u16 {
+    22
+}
+
+fn default_root() -> String {
+    "/".to_string()
+}
+
+impl SftpConnection {
+    /// 连接的 `user@host:port` 部分
+    pub fn authority(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+
+    /// 服务器上的路径对应的完整路径
+    pub fn url(&self, remote: &str) -> String {
+        format!("{}{}{}", SCHEME, self.authority(), remote)
+    }
+}
+
+/// 路径是否为 SFTP 路径
+pub fn is_sftp_path(path: &str) -> bool {
+    path.starts_with(SCHEME)
+}
+
+/// 一个服务器的会话池
+struct Pool {
+    connection: SftpConnection,
+    idle: Mutex<Vec<Session>>,
+}
+
+impl Pool {
+    /// 用一个会话执行 `f`
+    ///
+    /// 复用的空闲会话可能已被服务器断开，此时重新连接后再执行一次
+    fn run<T>(&self, mut f: impl FnMut(&mut Session) -> StorageResult<T>) -> StorageResult<T> {
+        let idle = self.idle.lock().unwrap().pop();
+        let reused = idle.is_some();
+        let mut session = match idle {
+            Some(session) => session,
+            None => Session::connect(&self.connection)?,
+        };
+
+        let mut result = f(&mut session);
+        if reused && matches!(result, Err(StorageError::IoError(_))) && session.is_closed() {
+            tracing::warn!("SFTP 连接已断开，重新连接: {}", self.connection.authority());
+            session = Session::connect(&self.connection)?;
+            result = f(&mut session);
+        }
+
+        if session.is_usable() {
+            let mut idle = self.idle.lock().unwrap();
+            if idle.len() < MAX_IDLE_SESSIONS {
+                idle.push(session);
+            }
+        }
+        result
+    }
+}
+
+/// SFTP 存储提供者
+pub struct SftpProvider {
+    connections: Vec<SftpConnection>,
+    pools: Mutex<HashMap<String, Arc<Pool>>>,
+}
+
+impl SftpProvider {
+    /// 使用已保存的连接创建提供者
+    pub fn new(connections: Vec<SftpConnection>) -> Self {
+        Self {
+            connections,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn connections(&self) -> &[SftpConnection] {
+        &self.connections
+    }
+
+    /// 拆分路径，返回服务器的会话池和服务器上的路径
+    fn locate(&self, path: &str) -> StorageResult<(Arc<Pool>, String)> {
+        let rest = path
+            .strip_prefix(SCHEME)
+            .ok_or_else(|| StorageError::Other(format!("不是 SFTP 路径: {}", path)))?;
+        let (authority, remote) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        // 去掉末尾的 `/`，使同一目录只有一种写法
+        let remote = match remote.trim_end_matches('/') {
+            "" => "/",
+            trimmed => trimmed,
+        };
+
+        let mut pools = self.pools.lock().unwrap();
+        let pool = match pools.get(authority) {
+            Some(pool) => pool.clone(),
+            None => {
+                let connection = self
+                    .connections
+                    .iter()
+                    .find(|connection| connection.authority() == authority)
+                    .ok_or_else(|| StorageError::Other(format!("没有保存的连接: {}", authority)))?;
+                let pool = Arc::new(Pool {
+                    connection: connection.clone(),
+                    idle: Mutex::new(vec![]),
+                });
+                pools.insert(authority.to_string(), pool.clone());
+                pool
+            }
+        };
+        Ok((pool, remote.to_string()))
+    }
+
+    /// 拆分同一服务器上的两个路径
+    fn locate_pair(&self, from: &str, to: &str) -> StorageResult<(Arc<Pool>, String, String)> {
+        let (pool, from_remote) = self.locate(from)?;
+        let (to_pool, to_remote) = self.locate(to)?;
+        if !Arc::ptr_eq(&pool, &to_pool) {
+            return Err(StorageError::Unsupported(format!(
+                "在不同服务器之间操作: {} -> {}",
+                from, to
+            )));
+        }
+        Ok((pool, from_remote, to_remote))
+    }
+}
+
+/// 符号链接的目标
+struct LinkInfo {
+    /// 链接中保存的路径
+    target: Option<String>,
+    /// 最终指向的条目类型
+    target_type: Option<ItemType>,
+    /// 目标不存在
+    broken: bool,
+}
+
+impl LinkInfo {
+    /// 读取链接内容，并跟随链接获取目标的类型；只有连接错误会返回错误
+    fn read(session: &mut Session, remote: &str) -> StorageResult<Self> {
+        let target = match session.readlink(remote) {
+            Ok(target) => Some(target),
+            Err(e @ StorageError::IoError(_)) => return Err(e),
+            Err(_) => None,
+        };
+        let (target_type, broken) = match session.stat(remote) {
+            Ok(attrs) if attrs.is_dir() => (Some(ItemType::Directory), false),
+            Ok(_) => (Some(ItemType::File), false),
+            Err(StorageError::PathNotFound(_)) => (None, true),
+            Err(e @ StorageError::IoError(_)) => return Err(e),
+            Err(_) => (None, false),
+        };
+        Ok(Self {
+            target,
+            target_type,
+            broken,
+        })
+    }
+
+    fn apply(self, item: &mut FileItem) {
+        item.metadata.link_target = self.target;
+        item.metadata.link_target_type = self.target_type;
+        item.metadata.broken_link = self.broken;
+    }
+}
+
+/// 服务器上的条目对应的文件条目
+fn file_item(connection: &SftpConnection, remote: &str, attrs: &Attrs) -> FileItem {
+    let name = match remote.rsplit('/').next() {
+        Some("") | None => remote.to_string(),
+        Some(name) => name.to_string(),
+    };
+    let item_type = if attrs.is_dir() {
+        ItemType::Directory
+    } else if attrs.is_symlink() {
+        ItemType::Symlink
+    } else {
+        ItemType::File
+    };
+    let mime_type = if item_type == ItemType::File {
+        from_path(&name).first().map(|mime| mime.to_string())
+    } else {
+        None
+    };
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: connection.url(remote),
+        item_type,
+        size: attrs.size.unwrap_or(0),
+        modified: attrs.modified(),
+        metadata: EntryMetadata {
+            permissions: attrs.permissions,
+            mime_type,
+            accessed: attrs.accessed(),
+            ..Default::default()
+        },
+    }
+}
+
+/// 记录读写过的字节数，已经读写过数据后不能在重新连接后再执行一次
+struct Counting<T> {
+    inner: T,
+    count: u64,
+}
+
+impl<T: Write> Write for Counting<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: Read> Read for Counting<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+        Ok(read)
+    }
+}
+
+#[async_trait]
+impl StorageProvider for SftpProvider {
+    async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
+        Ok(self
+            .connections
+            .iter()
+            .map(|connection| {
+                let path = connection.url(&connection.root);
+                RootItem {
+                    id: path.clone(),
+                    name: connection.name.clone(),
+                    path,
+                    provider_type: ProviderType::NetworkDrive,
+                    icon: None,
+                    mount: None,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_metadata(&self, path: &str) -> StorageResult<FileItem> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || {
+            // 与列表一致，符号链接本身的属性（悬空的链接也能获取）
+            pool.run(|session| {
+                let attrs = session.lstat(&remote)?;
+                let mut item = file_item(&pool.connection, &remote, &attrs);
+                if attrs.is_symlink() {
+                    LinkInfo::read(session, &remote)?.apply(&mut item);
+                }
+                Ok(item)
+            })
+        })
+        .await
+    }
+
+    async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>> {
+        let (pool, remote) = self.locate(path)?;
+        let path = path.to_string();
+        smol::unblock(move || {
+            let mut entries = pool.run(|session| {
+                if !session.stat(&remote)?.is_dir() {
+                    return Err(StorageError::Other(format!("路径不是目录: {}", path)));
+                }
+                let mut entries = vec![];
+                for (name, attrs) in session.read_dir(&remote)? {
+                    let child = join(&remote, &name);
+                    let mut item = file_item(&pool.connection, &child, &attrs);
+                    // 符号链接的目标需要额外的请求
+                    if attrs.is_symlink() {
+                        LinkInfo::read(session, &child)?.apply(&mut item);
+                    }
+                    entries.push(item);
+                }
+                Ok(entries)
+            })?;
+
+            sort_entries(&mut entries);
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn exists(&self, path: &str) -> StorageResult<bool> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || pool.run(|session| session.exists(&remote))).await
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        // `writer` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (pool, remote) = self.locate(path)?;
+        let mut writer = Counting {
+            inner: writer,
+            count: 0,
+        };
+        pool.run(|session| {
+            if writer.count > 0 {
+                return Err(StorageError::Other(format!("下载中断: {}", path)));
+            }
+            session.download(&remote, &mut writer)
+        })
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        // `reader` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (pool, remote) = self.locate(path)?;
+        let mut reader = Counting {
+            inner: reader,
+            count: 0,
+        };
+        pool.run(|session| {
+            if reader.count > 0 {
+                return Err(StorageError::Other(format!("上传中断: {}", path)));
+            }
+            session.upload(&remote, &mut reader)
+        })
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || {
+            pool.run(|session| {
+                session.ensure_absent(&remote)?;
+                session.mkdir(&remote)
+            })
+        })
+        .await
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || pool.run(|session| session.create_file(&remote))).await
+    }
+
+    async fn create_symlink(&self, target: &str, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        // 同一服务器上的完整路径保存为服务器上的路径，其他原样保存
+        let target = match target.starts_with(SCHEME) {
+            true => self.locate_pair(target, path)?.1,
+            false => target.to_string(),
+        };
+        smol::unblock(move || {
+            pool.run(|session| {
+                session.ensure_absent(&remote)?;
+                session.symlink(&target, &remote)
+            })
+        })
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (pool, from, to) = self.locate_pair(from, to)?;
+        smol::unblock(move || {
+            pool.run(|session| {
+                session.ensure_absent(&to)?;
+                session.rename(&from, &to)
+            })
+        })
+        .await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (pool, from, to) = self.locate_pair(from, to)?;
+        if to.starts_with(&format!("{}/", from)) {
+            return Err(StorageError::Other(format!(
+                "不能将目录复制到其自身内部: {}",
+                to
+            )));
+        }
+        smol::unblock(move || {
+            pool.run(|session| {
+                session.ensure_absent(&to)?;
+                session.copy_all(&from, &to)
+            })
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || pool.run(|session| session.remove_all(&remote))).await
+    }
+
+    async fn get_space(&self, path: &str) -> StorageResult<SpaceInfo> {
+        let (pool, remote) = self.locate(path)?;
+        smol::unblock(move || pool.run(|session| session.statvfs(&remote))).await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            can_create: true,
+            can_rename: true,
+            can_copy: true,
+            can_move: true,
+            can_trash: false,
+            can_delete: true,
+            can_symlink: true,
+            can_archive: false,
+            local_paths: false,
+        }
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::NetworkDrive
+    }
+}
@@ -0,0 +1,227 @@
+//! SFTP 协议（版本 3）
+//!
+//! 数据包为 `uint32 长度 + byte 类型 + 内容`，整数均为大端序，字符串为
+//! `uint32 长度 + 字节`。参见 draft-ietf-secsh-filexfer-02。
+
+use std::{
+    io::{self, Read, Write},
+    time::{Duration, SystemTime},
+};
+
+pub const VERSION: u32 = 3;
+
+// 请求
+pub const FXP_INIT: u8 = 1;
+pub const FXP_OPEN: u8 = 3;
+pub const FXP_CLOSE: u8 = 4;
+pub const FXP_READ: u8 = 5;
+pub const FXP_WRITE: u8 = 6;
+pub const FXP_LSTAT: u8 = 7;
+pub const FXP_OPENDIR: u8 = 11;
+pub const FXP_READDIR: u8 = 12;
+pub const FXP_REMOVE: u8 = 13;
+pub const FXP_MKDIR: u8 = 14;
+pub const FXP_RMDIR: u8 = 15;
+pub const FXP_STAT: u8 = 17;
+pub const FXP_RENAME: u8 = 18;
+pub const FXP_READLINK: u8 = 19;
+pub const FXP_SYMLINK: u8 = 20;
+pub const FXP_EXTENDED: u8 = 200;
+
+// 响应
+pub const FXP_VERSION: u8 = 2;
+pub const FXP_STATUS: u8 = 101;
+pub const FXP_HANDLE: u8 = 102;
+pub const FXP_DATA: u8 = 103;
+pub const FXP_NAME: u8 = 104;
+pub const FXP_ATTRS: u8 = 105;
+pub const FXP_EXTENDED_REPLY: u8 = 201;
+
+// 打开文件的标志
+pub const FXF_READ: u32 = 0x01;
+pub const FXF_WRITE: u32 = 0x02;
+pub const FXF_CREAT: u32 = 0x08;
+pub const FXF_TRUNC: u32 = 0x10;
+pub const FXF_EXCL: u32 = 0x20;
+
+// 状态码
+pub const FX_OK: u32 = 0;
+pub const FX_EOF: u32 = 1;
+pub const FX_NO_SUCH_FILE: u32 = 2;
+pub const FX_PERMISSION_DENIED: u32 = 3;
+
+// 属性标志
+const ATTR_SIZE: u32 = 0x01;
+const ATTR_UIDGID: u32 = 0x02;
+const ATTR_PERMISSIONS: u32 = 0x04;
+const ATTR_ACMODTIME: u32 = 0x08;
+const ATTR_EXTENDED: u32 = 0x8000_0000;
+
+/// 单个数据包的最大长度，超过时视为协议错误
+const MAX_PACKET: u32 = 256 * 1024;
+
+/// 文件属性
+#[derive(Debug, Clone, Default)]
+pub struct Attrs {
+    pub size: Option<u64>,
+    pub permissions: Option<u32>,
+    pub atime: Option<u32>,
+    pub mtime: Option<u32>,
+}
+
+impl Attrs {
+    const FILE_TYPE_MASK: u32 = 0o170000;
+    const DIRECTORY: u32 = 0o040000;
+    const SYMLINK: u32 = 0o120000;
+
+    pub fn is_dir(&self) -> bool {
+        self.permissions
+            .is_some_and(|mode| mode & Self::FILE_TYPE_MASK == Self::DIRECTORY)
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.permissions
+            .is_some_and(|mode| mode & Self::FILE_TYPE_MASK == Self::SYMLINK)
+    }
+
+    pub fn modified(&self) -> SystemTime {
+        time(self.mtime)
+    }
+
+    pub fn accessed(&self) -> Option<SystemTime> {
+        self.atime.map(|atime| time(Some(atime)))
+    }
+}
+
+fn time(seconds: Option<u32>) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds.unwrap_or(0).into())
+}
+
+/// 正在构造的数据包
+pub struct Packet {
+    buf: Vec<u8>,
+}
+
+impl Packet {
+    pub fn new(kind: u8) -> Self {
+        // 先占位长度，发送时填入
+        let mut buf = vec![0; 4];
+        buf.push(kind);
+        Self { buf }
+    }
+
+    pub fn u32(mut self, value: u32) -> Self {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    pub fn u64(mut self, value: u64) -> Self {
+        self.buf.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    pub fn bytes(self, value: &[u8]) -> Self {
+        let mut packet = self.u32(value.len() as u32);
+        packet.buf.extend_from_slice(value);
+        packet
+    }
+
+    pub fn string(self, value: &str) -> Self {
+        self.bytes(value.as_bytes())
+    }
+
+    /// 空属性（让服务器使用默认值）
+    pub fn empty_attrs(self) -> Self {
+        self.u32(0)
+    }
+
+    pub fn send(mut self, writer: &mut impl Write) -> io::Result<()> {
+        let len = (self.buf.len() - 4) as u32;
+        self.buf[..4].copy_from_slice(&len.to_be_bytes());
+        writer.write_all(&self.buf)?;
+        writer.flush()
+    }
+}
+
+/// 收到的数据包
+pub struct Reply {
+    pub kind: u8,
+    data: Vec<u8>,
+    pos: usize,
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "SFTP 数据包格式错误")
+}
+
+impl Reply {
+    pub fn receive(reader: &mut impl Read) -> io::Result<Self> {
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len);
+        if len == 0 || len > MAX_PACKET {
+            return Err(malformed());
+        }
+        let mut data = vec![0; len as usize];
+        reader.read_exact(&mut data)?;
+        Ok(Self {
+            kind: data[0],
+            data,
+            pos: 1,
+        })
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&[u8]> {
+        let end = self.pos.checked_add(len).ok_or_else(malformed)?;
+        let bytes = self.data.get(self.pos..end).ok_or_else(malformed)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    pub fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn bytes(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.u32()? as usize;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    pub fn string(&mut self) -> io::Result<String> {
+        Ok(String::from_utf8_lossy(&self.bytes()?).to_string())
+    }
+
+    pub fn has_remaining(&self) -> bool {
+        self.pos < self.data.len()
+    }
+
+    pub fn attrs(&mut self) -> io::Result<Attrs> {
+        let flags = self.u32()?;
+        let mut attrs = Attrs::default();
+        if flags & ATTR_SIZE != 0 {
+            attrs.size = Some(self.u64()?);
+        }
+        if flags & ATTR_UIDGID != 0 {
+            let _uid = self.u32()?;
+            let _gid = self.u32()?;
+        }
+        if flags & ATTR_PERMISSIONS != 0 {
+            attrs.permissions = Some(self.u32()?);
+        }
+        if flags & ATTR_ACMODTIME != 0 {
+            attrs.atime = Some(self.u32()?);
+            attrs.mtime = Some(self.u32()?);
+        }
+        if flags & ATTR_EXTENDED != 0 {
+            for _ in 0..self.u32()? {
+                self.bytes()?;
+                self.bytes()?;
+            }
+        }
+        Ok(attrs)
+    }
+}
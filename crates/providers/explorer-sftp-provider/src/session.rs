@@ -0,0 +1,729 @@
+//! SFTP 会话
+//!
+//! 每个会话是一个子进程（通常是 `ssh -s <主机> sftp`），其标准输入输出即 SFTP
+//! 通道。请求按顺序发送并等待响应，只有上传和下载文件时才同时发出多个请求。
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, BufReader, BufWriter, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use explorer_storage::{SpaceInfo, StorageError, StorageResult};
+
+use crate::{SftpAuth, SftpConnection, protocol::*};
+
+/// 每个读写请求的数据量
+const CHUNK_SIZE: u32 = 32 * 1024;
+/// 上传和下载时同时发出的请求数
+const MAX_PENDING_REQUESTS: usize = 16;
+/// 最多保留的 ssh 错误输出
+const MAX_STDERR: usize = 4096;
+
+pub(crate) struct Session {
+    child: Child,
+    writer: BufWriter<ChildStdin>,
+    reader: BufReader<ChildStdout>,
+    stderr: Arc<Mutex<String>>,
+    next_id: u32,
+    /// 服务器支持的扩展
+    extensions: HashMap<String, String>,
+    /// 请求与响应是否仍然一一对应（出错中断时可能有未读取的响应）
+    in_sync: bool,
+}
+
+/// 把状态码转换为存储错误
+fn status_error(code: u32, message: &str, path: &str) -> StorageError {
+    match code {
+        FX_NO_SUCH_FILE => StorageError::PathNotFound(path.to_string()),
+        FX_PERMISSION_DENIED => StorageError::PermissionDenied(path.to_string()),
+        _ if message.is_empty() => {
+            StorageError::Other(format!("SFTP 错误（状态码 {}）: {}", code, path))
+        }
+        _ => StorageError::Other(format!("{}: {}", message, path)),
+    }
+}
+
+fn unexpected(kind: u8) -> StorageError {
+    StorageError::IoError(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("意外的 SFTP 响应类型: {}", kind),
+    ))
+}
+
+impl Session {
+    /// 启动子进程并完成 SFTP 握手
+    pub fn connect(connection: &SftpConnection) -> StorageResult<Self> {
+        let mut command = match &connection.command {
+            Some(argv) => {
+                let (program, args) = argv
+                    .split_first()
+                    .ok_or_else(|| StorageError::Other("连接命令为空".to_string()))?;
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+            None => ssh_command(connection)?,
+        };
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // 持续读取错误输出，避免管道写满阻塞子进程
+        let stderr = Arc::new(Mutex::new(String::new()));
+        if let Some(mut pipe) = child.stderr.take() {
+            let stderr = stderr.clone();
+            thread::spawn(move || {
+                let mut buf = [0; 1024];
+                while let Ok(read @ 1..) = pipe.read(&mut buf) {
+                    let mut stderr = stderr.lock().unwrap();
+                    if stderr.len() < MAX_STDERR {
+                        stderr.push_str(&String::from_utf8_lossy(&buf[..read]));
+                    }
+                }
+            });
+        }
+
+        let mut session = Self {
+            writer: BufWriter::new(child.stdin.take().unwrap()),
+            reader: BufReader::new(child.stdout.take().unwrap()),
+            child,
+            stderr,
+            next_id: 0,
+            extensions: HashMap::new(),
+            in_sync: true,
+        };
+        if let Err(e) = session.handshake() {
+            return Err(session.connect_error(connection, e));
+        }
+        tracing::info!("已连接 SFTP 服务器: {}", connection.authority());
+        Ok(session)
+    }
+
+    fn handshake(&mut self) -> io::Result<()> {
+        Packet::new(FXP_INIT).u32(VERSION).send(&mut self.writer)?;
+        let mut reply = Reply::receive(&mut self.reader)?;
+        if reply.kind != FXP_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "服务器没有返回 SFTP 版本",
+            ));
+        }
+        let version = reply.u32()?;
+        if version < VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("不支持的 SFTP 版本: {}", version),
+            ));
+        }
+        while reply.has_remaining() {
+            let name = reply.string()?;
+            let data = reply.string()?;
+            self.extensions.insert(name, data);
+        }
+        Ok(())
+    }
+
+    /// 握手失败时根据子进程的错误输出说明原因
+    fn connect_error(mut self, connection: &SftpConnection, error: io::Error) -> StorageError {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let stderr = self.stderr.lock().unwrap().trim().to_string();
+        let authority = connection.authority();
+        tracing::error!("连接 SFTP 服务器失败 {}: {} {}", authority, error, stderr);
+        if stderr.contains("Host key verification failed")
+            || stderr.contains("REMOTE HOST IDENTIFICATION HAS CHANGED")
+        {
+            StorageError::Other(format!(
+                "主机密钥验证失败（不在 known_hosts 中或与记录的不一致）: {}",
+                authority
+            ))
+        } else if stderr.contains("Permission denied") {
+            StorageError::PermissionDenied(format!("{}（身份验证失败）", authority))
+        } else if stderr.is_empty() {
+            StorageError::Other(format!("无法连接 {}: {}", authority, error))
+        } else {
+            StorageError::Other(format!("无法连接 {}: {}", authority, stderr))
+        }
+    }
+
+    /// 子进程是否已经退出（连接已断开）
+    pub fn is_closed(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// 会话是否可以继续使用
+    pub fn is_usable(&mut self) -> bool {
+        self.in_sync && !self.is_closed()
+    }
+
+    fn send(&mut self, kind: u8, build: impl FnOnce(Packet) -> Packet) -> io::Result<u32> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        build(Packet::new(kind).u32(id)).send(&mut self.writer)?;
+        Ok(id)
+    }
+
+    /// 读取一个响应，返回请求编号和响应
+    fn receive(&mut self) -> io::Result<(u32, Reply)> {
+        let mut reply = Reply::receive(&mut self.reader)?;
+        let id = reply.u32()?;
+        Ok((id, reply))
+    }
+
+    /// 发送请求并等待其响应
+    fn request(&mut self, kind: u8, build: impl FnOnce(Packet) -> Packet) -> StorageResult<Reply> {
+        let id = self.send(kind, build)?;
+        let (reply_id, reply) = self.receive()?;
+        if reply_id != id {
+            self.in_sync = false;
+            return Err(StorageError::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "SFTP 响应编号不匹配",
+            )));
+        }
+        Ok(reply)
+    }
+
+    /// 读取状态响应中的状态码和说明
+    fn read_status(reply: &mut Reply) -> StorageResult<(u32, String)> {
+        let code = reply.u32()?;
+        // 版本 3 的状态响应中说明和语言是可选的
+        let message = if reply.has_remaining() {
+            reply.string()?
+        } else {
+            String::new()
+        };
+        Ok((code, message))
+    }
+
+    /// 期望响应为成功状态
+    fn expect_ok(mut reply: Reply, path: &str) -> StorageResult<()> {
+        match reply.kind {
+            FXP_STATUS => match Self::read_status(&mut reply)? {
+                (FX_OK, _) => Ok(()),
+                (code, message) => Err(status_error(code, &message, path)),
+            },
+            kind => Err(unexpected(kind)),
+        }
+    }
+
+    /// 期望响应为 `expected` 类型，状态响应转换为错误
+    fn expect(mut reply: Reply, expected: u8, path: &str) -> StorageResult<Reply> {
+        match reply.kind {
+            kind if kind == expected => Ok(reply),
+            FXP_STATUS => {
+                let (code, message) = Self::read_status(&mut reply)?;
+                Err(status_error(code, &message, path))
+            }
+            kind => Err(unexpected(kind)),
+        }
+    }
+
+    pub fn stat(&mut self, path: &str) -> StorageResult<Attrs> {
+        let reply = self.request(FXP_STAT, |packet| packet.string(path))?;
+        Ok(Self::expect(reply, FXP_ATTRS, path)?.attrs()?)
+    }
+
+    /// 获取属性，不跟随符号链接
+    pub fn lstat(&mut self, path: &str) -> StorageResult<Attrs> {
+        let reply = self.request(FXP_LSTAT, |packet| packet.string(path))?;
+        Ok(Self::expect(reply, FXP_ATTRS, path)?.attrs()?)
+    }
+
+    /// 路径是否存在（包括悬空的符号链接）
+    pub fn exists(&mut self, path: &str) -> StorageResult<bool> {
+        match self.lstat(path) {
+            Ok(_) => Ok(true),
+            Err(StorageError::PathNotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 确保目标路径不存在
+    pub fn ensure_absent(&mut self, path: &str) -> StorageResult<()> {
+        if self.exists(path)? {
+            return Err(StorageError::AlreadyExists(path.to_string()));
+        }
+        Ok(())
+    }
+
+    /// 列出目录中的条目名称和属性（不包括 `.` 和 `..`）
+    pub fn read_dir(&mut self, path: &str) -> StorageResult<Vec<(String, Attrs)>> {
+        let reply = self.request(FXP_OPENDIR, |packet| packet.string(path))?;
+        let handle = Self::expect(reply, FXP_HANDLE, path)?.bytes()?;
+
+        let mut entries = vec![];
+        let result = loop {
+            let mut reply = self.request(FXP_READDIR, |packet| packet.bytes(&handle))?;
+            if reply.kind == FXP_STATUS {
+                match Self::read_status(&mut reply)? {
+                    (FX_EOF, _) => break Ok(()),
+                    (code, message) => break Err(status_error(code, &message, path)),
+                }
+            }
+            let mut reply = Self::expect(reply, FXP_NAME, path)?;
+            for _ in 0..reply.u32()? {
+                let name = reply.string()?;
+                let _long_name = reply.bytes()?;
+                let attrs = reply.attrs()?;
+                if name != "." && name != ".." {
+                    entries.push((name, attrs));
+                }
+            }
+        };
+        self.close(&handle, path)?;
+        result.map(|_| entries)
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> StorageResult<()> {
+        let reply = self.request(FXP_MKDIR, |packet| packet.string(path).empty_attrs())?;
+        Self::expect_ok(reply, path)
+    }
+
+    pub fn rmdir(&mut self, path: &str) -> StorageResult<()> {
+        let reply = self.request(FXP_RMDIR, |packet| packet.string(path))?;
+        Self::expect_ok(reply, path)
+    }
+
+    pub fn remove(&mut self, path: &str) -> StorageResult<()> {
+        let reply = self.request(FXP_REMOVE, |packet| packet.string(path))?;
+        Self::expect_ok(reply, path)
+    }
+
+    /// 重命名（目标已存在时服务器会拒绝）
+    pub fn rename(&mut self, from: &str, to: &str) -> StorageResult<()> {
+        let reply = self.request(FXP_RENAME, |packet| packet.string(from).string(to))?;
+        Self::expect_ok(reply, from)
+    }
+
+    pub fn readlink(&mut self, path: &str) -> StorageResult<String> {
+        let reply = self.request(FXP_READLINK, |packet| packet.string(path))?;
+        let mut reply = Self::expect(reply, FXP_NAME, path)?;
+        if reply.u32()? == 0 {
+            return Err(StorageError::PathNotFound(path.to_string()));
+        }
+        Ok(reply.string()?)
+    }
+
+    /// 创建符号链接 `path`，指向 `target`
+    pub fn symlink(&mut self, target: &str, path: &str) -> StorageResult<()> {
+        // OpenSSH 的参数顺序与协议草案相反：先目标，后链接路径
+        let reply = self.request(FXP_SYMLINK, |packet| packet.string(target).string(path))?;
+        Self::expect_ok(reply, path)
+    }
+
+    pub fn open(&mut self, path: &str, flags: u32) -> StorageResult<Vec<u8>> {
+        let reply = self.request(FXP_OPEN, |packet| {
+            packet.string(path).u32(flags).empty_attrs()
+        })?;
+        Ok(Self::expect(reply, FXP_HANDLE, path)?.bytes()?)
+    }
+
+    pub fn close(&mut self, handle: &[u8], path: &str) -> StorageResult<()> {
+        let reply = self.request(FXP_CLOSE, |packet| packet.bytes(handle))?;
+        Self::expect_ok(reply, path)
+    }
+
+    /// 创建空文件，目标已存在时失败
+    pub fn create_file(&mut self, path: &str) -> StorageResult<()> {
+        self.ensure_absent(path)?;
+        let handle = self.open(path, FXF_WRITE | FXF_CREAT | FXF_EXCL)?;
+        self.close(&handle, path)
+    }
+
+    /// 下载文件内容写入 `writer`，返回写入的字节数
+    pub fn download(&mut self, path: &str, writer: &mut dyn Write) -> StorageResult<u64> {
+        let handle = self.open(path, FXF_READ)?;
+        // 中途出错时仍有未读取的响应，会话不能再用
+        self.in_sync = false;
+        let written = self.read_handle(&handle, path, writer)?;
+        self.in_sync = true;
+        self.close(&handle, path)?;
+        Ok(written)
+    }
+
+    /// 同时发出多个读取请求，按偏移顺序写入
+    fn read_handle(
+        &mut self,
+        handle: &[u8],
+        path: &str,
+        writer: &mut dyn Write,
+    ) -> StorageResult<u64> {
+        // 已发出的请求：编号、偏移、长度
+        let mut pending: VecDeque<(u32, u64, u32)> = VecDeque::new();
+        // 先于前面的请求到达的响应
+        let mut arrived: HashMap<u32, Reply> = HashMap::new();
+        let mut next_offset = 0;
+        let mut eof = false;
+        let mut written = 0;
+
+        loop {
+            while !eof && pending.len() < MAX_PENDING_REQUESTS {
+                let id = self.send_read(handle, next_offset, CHUNK_SIZE)?;
+                pending.push_back((id, next_offset, CHUNK_SIZE));
+                next_offset += CHUNK_SIZE as u64;
+            }
+            let Some(&(id, offset, len)) = pending.front() else {
+                return Ok(written);
+            };
+            let mut reply = match arrived.remove(&id) {
+                Some(reply) => reply,
+                None => {
+                    let (reply_id, reply) = self.receive()?;
+                    if reply_id != id {
+                        arrived.insert(reply_id, reply);
+                        continue;
+                    }
+                    reply
+                }
+            };
+            pending.pop_front();
+
+            match reply.kind {
+                FXP_DATA => {
+                    let data = reply.bytes()?;
+                    writer.write_all(&data)?;
+                    written += data.len() as u64;
+                    // 读到的比请求的少时补发剩余部分
+                    let read = data.len() as u32;
+                    if !data.is_empty() && read < len {
+                        let id = self.send_read(handle, offset + read as u64, len - read)?;
+                        pending.push_front((id, offset + read as u64, len - read));
+                    }
+                }
+                FXP_STATUS => match Self::read_status(&mut reply)? {
+                    // 后面的请求也都在文件末尾之后，只需读取其响应
+                    (FX_EOF, _) => eof = true,
+                    (code, message) => return Err(status_error(code, &message, path)),
+                },
+                kind => return Err(unexpected(kind)),
+            }
+        }
+    }
+
+    /// 从 `reader` 读取内容写入文件（覆盖已有文件），返回写入的字节数
+    pub fn upload(&mut self, path: &str, reader: &mut dyn Read) -> StorageResult<u64> {
+        let handle = self.open(path, FXF_WRITE | FXF_CREAT | FXF_TRUNC)?;
+        // 中途出错时仍有未读取的响应，会话不能再用
+        self.in_sync = false;
+        let written = self.write_handle(&handle, path, reader)?;
+        self.in_sync = true;
+        self.close(&handle, path)?;
+        Ok(written)
+    }
+
+    /// 同时发出多个写入请求，再依次读取其状态
+    fn write_handle(
+        &mut self,
+        handle: &[u8],
+        path: &str,
+        reader: &mut dyn Read,
+    ) -> StorageResult<u64> {
+        let mut buf = vec![0; CHUNK_SIZE as usize];
+        let mut offset = 0;
+        let mut pending = 0;
+        loop {
+            let read = read_full(reader, &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            self.send(FXP_WRITE, |packet| {
+                packet.bytes(handle).u64(offset).bytes(&buf[..read])
+            })?;
+            offset += read as u64;
+            pending += 1;
+            if pending == MAX_PENDING_REQUESTS {
+                self.receive_write_status(path)?;
+                pending -= 1;
+            }
+        }
+        for _ in 0..pending {
+            self.receive_write_status(path)?;
+        }
+        Ok(offset)
+    }
+
+    fn receive_write_status(&mut self, path: &str) -> StorageResult<()> {
+        let (_, reply) = self.receive()?;
+        Self::expect_ok(reply, path)
+    }
+
+    fn send_read(&mut self, handle: &[u8], offset: u64, len: u32) -> io::Result<u32> {
+        self.send(FXP_READ, |packet| packet.bytes(handle).u64(offset).u32(len))
+    }
+
+    /// 在服务器上复制文件内容，目标已存在时失败
+    pub fn copy_file(&mut self, from: &str, to: &str) -> StorageResult<()> {
+        let source = self.open(from, FXF_READ)?;
+        let target = match self.open(to, FXF_WRITE | FXF_CREAT | FXF_EXCL) {
+            Ok(target) => target,
+            Err(e) => {
+                self.close(&source, from)?;
+                return Err(e);
+            }
+        };
+        let result = if self.extensions.contains_key("copy-data") {
+            // 服务器端复制，数据不经过本机
+            self.request(FXP_EXTENDED, |packet| {
+                packet
+                    .string("copy-data")
+                    .bytes(&source)
+                    .u64(0)
+                    .u64(0)
+                    .bytes(&target)
+                    .u64(0)
+            })
+            .and_then(|reply| Self::expect_ok(reply, from))
+        } else {
+            self.copy_handle(&source, &target, from, to)
+        };
+        self.close(&source, from)?;
+        self.close(&target, to)?;
+        if result.is_err() {
+            let _ = self.remove(to);
+        }
+        result
+    }
+
+    /// 逐块读取再写回服务器
+    fn copy_handle(
+        &mut self,
+        source: &[u8],
+        target: &[u8],
+        from: &str,
+        to: &str,
+    ) -> StorageResult<()> {
+        let mut offset = 0;
+        loop {
+            let mut reply = self.request(FXP_READ, |packet| {
+                packet.bytes(source).u64(offset).u32(CHUNK_SIZE)
+            })?;
+            if reply.kind == FXP_STATUS {
+                return match Self::read_status(&mut reply)? {
+                    (FX_EOF, _) => Ok(()),
+                    (code, message) => Err(status_error(code, &message, from)),
+                };
+            }
+            let data = Self::expect(reply, FXP_DATA, from)?.bytes()?;
+            let reply = self.request(FXP_WRITE, |packet| {
+                packet.bytes(target).u64(offset).bytes(&data)
+            })?;
+            Self::expect_ok(reply, to)?;
+            offset += data.len() as u64;
+        }
+    }
+
+    /// 递归复制（符号链接复制链接本身）
+    pub fn copy_all(&mut self, from: &str, to: &str) -> StorageResult<()> {
+        let attrs = self.lstat(from)?;
+        if attrs.is_symlink() {
+            let target = self.readlink(from)?;
+            self.symlink(&target, to)
+        } else if attrs.is_dir() {
+            self.ensure_absent(to)?;
+            self.mkdir(to)?;
+            for (name, _) in self.read_dir(from)? {
+                self.copy_all(&join(from, &name), &join(to, &name))?;
+            }
+            Ok(())
+        } else {
+            self.copy_file(from, to)
+        }
+    }
+
+    /// 递归删除（不跟随符号链接）
+    pub fn remove_all(&mut self, path: &str) -> StorageResult<()> {
+        let attrs = self.lstat(path)?;
+        if attrs.is_dir() {
+            for (name, _) in self.read_dir(path)? {
+                self.remove_all(&join(path, &name))?;
+            }
+            self.rmdir(path)
+        } else {
+            self.remove(path)
+        }
+    }
+
+    /// 获取路径所在文件系统的容量（需要服务器支持 `statvfs@openssh.com` 扩展）
+    pub fn statvfs(&mut self, path: &str) -> StorageResult<SpaceInfo> {
+        const EXTENSION: &str = "statvfs@openssh.com";
+        if !self.extensions.contains_key(EXTENSION) {
+            return Err(StorageError::Unsupported(format!("获取可用空间: {}", path)));
+        }
+        let reply = self.request(FXP_EXTENDED, |packet| packet.string(EXTENSION).string(path))?;
+        let mut reply = Self::expect(reply, FXP_EXTENDED_REPLY, path)?;
+        let _block_size = reply.u64()?;
+        let fragment_size = reply.u64()?;
+        let blocks = reply.u64()?;
+        let free = reply.u64()?;
+        let available = reply.u64()?;
+        Ok(SpaceInfo {
+            total: blocks * fragment_size,
+            free: free * fragment_size,
+            available: available * fragment_size,
+        })
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // 关闭标准输入后服务器会自行退出，ssh 则需要结束进程
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// 读满 `buf`，到达末尾时可能读到的更少
+fn read_full(reader: &mut dyn Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// 拼接服务器上的路径
+pub(crate) fn join(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// 构造 `ssh -s -- <主机> sftp` 命令
+fn ssh_command(connection: &SftpConnection) -> StorageResult<Command> {
+    // 以 `-` 开头的值会被 ssh 当作选项（如 `-oProxyCommand=...` 可以执行任意命令）
+    if connection.host.is_empty() || connection.host.starts_with('-') {
+        return Err(StorageError::Other(format!(
+            "无效的主机名: {}",
+            connection.host
+        )));
+    }
+    if connection.user.starts_with('-') {
+        return Err(StorageError::Other(format!(
+            "无效的用户名: {}",
+            connection.user
+        )));
+    }
+
+    let mut command = Command::new("ssh");
+    command
+        .arg("-p")
+        .arg(connection.port.to_string())
+        .arg("-l")
+        .arg(&connection.user)
+        // 不转发 X11 和 ssh-agent，不分配终端
+        .args(["-x", "-a", "-T"])
+        .args(["-o", "StrictHostKeyChecking=yes"])
+        .args(["-o", "ConnectTimeout=10"])
+        .args(["-o", "ServerAliveInterval=15"])
+        .args(["-o", "LogLevel=ERROR"]);
+    if let Some(known_hosts) = &connection.known_hosts {
+        command
+            .arg("-o")
+            .arg(format!("UserKnownHostsFile=\"{}\"", known_hosts.display()));
+    }
+
+    match &connection.auth {
+        SftpAuth::Agent => {
+            command.args([
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "PreferredAuthentications=publickey",
+            ]);
+        }
+        SftpAuth::Key { path, passphrase } => {
+            command
+                .arg("-i")
+                .arg(path)
+                .args(["-o", "IdentitiesOnly=yes"])
+                .args(["-o", "PreferredAuthentications=publickey"]);
+            match passphrase {
+                Some(passphrase) => askpass(&mut command, passphrase)?,
+                None => {
+                    command.args(["-o", "BatchMode=yes"]);
+                }
+            }
+        }
+        SftpAuth::Password { password } => {
+            command
+                .args([
+                    "-o",
+                    "PreferredAuthentications=password,keyboard-interactive",
+                ])
+                .args(["-o", "PubkeyAuthentication=no"])
+                .args(["-o", "NumberOfPasswordPrompts=1"]);
+            askpass(&mut command, password)?;
+        }
+    }
+
+    command
+        .arg("-s")
+        .arg("--")
+        .arg(&connection.host)
+        .arg("sftp");
+    Ok(command)
+}
+
+/// 通过 `SSH_ASKPASS` 程序向 ssh 提供密码或私钥口令
+///
+/// 口令通过环境变量传给 askpass 脚本，不出现在命令行参数中。
+#[cfg(unix)]
+fn askpass(command: &mut Command, secret: &str) -> StorageResult<()> {
+    use std::{
+        fs,
+        os::unix::fs::{OpenOptionsExt, PermissionsExt},
+        path::PathBuf,
+        sync::OnceLock,
+    };
+
+    static SCRIPT: OnceLock<Result<PathBuf, String>> = OnceLock::new();
+    let script = SCRIPT
+        .get_or_init(|| {
+            let path =
+                std::env::temp_dir().join(format!("explorer-sftp-askpass-{}", std::process::id()));
+            let _ = fs::remove_file(&path);
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o700)
+                .open(&path)
+                .and_then(|mut file| {
+                    file.write_all(b"#!/bin/sh\nprintf '%s\\n' \"$EXPLORER_SFTP_SECRET\"\n")
+                })
+                .and_then(|_| fs::set_permissions(&path, fs::Permissions::from_mode(0o700)))
+                .map(|_| path)
+                .map_err(|e| e.to_string())
+        })
+        .as_ref()
+        .map_err(|e| StorageError::Other(format!("无法创建 askpass 脚本: {}", e)))?;
+
+    command
+        .env("SSH_ASKPASS", script)
+        .env("SSH_ASKPASS_REQUIRE", "force")
+        .env("EXPLORER_SFTP_SECRET", secret);
+    // 旧版 OpenSSH 只在设置了 DISPLAY 时使用 askpass
+    if std::env::var_os("DISPLAY").is_none() {
+        command.env("DISPLAY", ":0");
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn askpass(_command: &mut Command, _secret: &str) -> StorageResult<()> {
+    Err(StorageError::Unsupported(
+        "在此平台上使用密码登录，请改用密钥或 ssh-agent".to_string(),
+    ))
+}
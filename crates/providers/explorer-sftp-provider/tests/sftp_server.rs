@@ -0,0 +1,344 @@
+//! 连接本机 OpenSSH `sftp-server` 子进程的集成测试
+//!
+//! `sftp-server` 的位置可以用环境变量 `EXPLORER_SFTP_SERVER` 指定；找不到时测试失败，
+//! 而不是不做检查就通过。
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use explorer_sftp_provider::{SftpConnection, SftpProvider};
+use explorer_storage::{
+    ItemType, ProviderType, StorageError, StorageProvider,
+    conformance::{self, TempDir},
+};
+
+const SERVER_PATHS: &[&str] = &[
+    "/usr/lib/openssh/sftp-server",
+    "/usr/libexec/openssh/sftp-server",
+    "/usr/libexec/sftp-server",
+    "/usr/lib/ssh/sftp-server",
+    "/usr/lib/sftp-server",
+];
+
+fn find_server() -> PathBuf {
+    if let Some(path) = std::env::var_os("EXPLORER_SFTP_SERVER") {
+        return path.into();
+    }
+    SERVER_PATHS
+        .iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+        .expect("没有找到 sftp-server：请安装 OpenSSH 的 sftp-server，或用 EXPLORER_SFTP_SERVER 指定其位置")
+}
+
+/// 临时目录及以其为根的 SFTP 连接
+struct Fixture {
+    dir: TempDir,
+    provider: SftpProvider,
+    connection: SftpConnection,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self::with_command(vec![find_server().display().to_string()])
+    }
+
+    fn with_command(command: Vec<String>) -> Self {
+        let dir = TempDir::new("sftp-test");
+        let connection = SftpConnection {
+            name: "测试服务器".to_string(),
+            host: "localhost".to_string(),
+            port: 22,
+            user: "tester".to_string(),
+            auth: Default::default(),
+            known_hosts: None,
+            root: dir.display().to_string(),
+            command: Some(command),
+        };
+        Self {
+            provider: SftpProvider::new(vec![connection.clone()]),
+            dir,
+            connection,
+        }
+    }
+
+    /// 临时目录中的条目对应的 SFTP 路径
+    fn url(&self, name: &str) -> String {
+        self.connection
+            .url(&self.dir.join(name).display().to_string())
+    }
+
+    fn local(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl conformance::Fixture for Fixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        self.url("tree")
+    }
+
+    fn symlinks(&self) -> bool {
+        true
+    }
+}
+
+fn read(provider: &SftpProvider, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    smol::block_on(provider.read_file(path, &mut content)).unwrap();
+    content
+}
+
+#[test]
+fn roots_are_network_drives() {
+    let fixture = Fixture::new();
+    let roots = smol::block_on(fixture.provider.get_roots()).unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].name, "测试服务器");
+    assert_eq!(roots[0].provider_type, ProviderType::NetworkDrive);
+    assert_eq!(roots[0].path, fixture.url("").trim_end_matches('/'));
+}
+
+#[test]
+fn lists_directories_first() {
+    let fixture = Fixture::new();
+    fs::write(fixture.local("b.txt"), "b").unwrap();
+    fs::write(fixture.local(".hidden"), "").unwrap();
+    fs::create_dir(fixture.local("z")).unwrap();
+    fs::create_dir(fixture.local("a")).unwrap();
+
+    let root = fixture.connection.url(&fixture.connection.root);
+    let entries = smol::block_on(fixture.provider.list_entries(&root)).unwrap();
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["a", "z", ".hidden", "b.txt"]);
+    assert_eq!(entries[0].item_type, ItemType::Directory);
+    assert_eq!(entries[0].path, fixture.url("a"));
+    assert!(entries[2].is_hidden);
+    assert_eq!(entries[3].size, 1);
+    assert_eq!(entries[3].metadata.mime_type.as_deref(), Some("text/plain"));
+
+    let error = smol::block_on(fixture.provider.list_entries(&fixture.url("b.txt")));
+    assert!(matches!(error, Err(StorageError::Other(_))));
+}
+
+#[test]
+fn reads_large_files() {
+    let fixture = Fixture::new();
+    // 大于多个并发读取请求的总量，且不是块大小的整数倍
+    let content: Vec<u8> = (0..3_000_017u32).map(|i| (i * 31 % 251) as u8).collect();
+    fs::write(fixture.local("large.bin"), &content).unwrap();
+    fs::write(fixture.local("empty"), "").unwrap();
+
+    assert_eq!(read(&fixture.provider, &fixture.url("large.bin")), content);
+    assert!(read(&fixture.provider, &fixture.url("empty")).is_empty());
+
+    let metadata = smol::block_on(fixture.provider.get_metadata(&fixture.url("large.bin")));
+    assert_eq!(metadata.unwrap().size, content.len() as u64);
+}
+
+#[test]
+fn writes_files() {
+    let fixture = Fixture::new();
+    let content: Vec<u8> = (0..1_000_003u32).map(|i| (i * 7 % 253) as u8).collect();
+    let written = smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("upload.bin"), &mut content.as_slice()),
+    );
+    assert_eq!(written.unwrap(), content.len() as u64);
+    assert_eq!(fs::read(fixture.local("upload.bin")).unwrap(), content);
+
+    // 覆盖已有文件
+    smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("upload.bin"), &mut &b"short"[..]),
+    )
+    .unwrap();
+    assert_eq!(fs::read(fixture.local("upload.bin")).unwrap(), b"short");
+
+    let missing_parent = smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("missing/file"), &mut &b""[..]),
+    );
+    assert!(matches!(missing_parent, Err(StorageError::PathNotFound(_))));
+}
+
+#[test]
+fn creates_renames_and_deletes() {
+    let fixture = Fixture::new();
+    let provider = &fixture.provider;
+    smol::block_on(async {
+        provider.create_dir(&fixture.url("dir")).await.unwrap();
+        provider
+            .create_file(&fixture.url("dir/file"))
+            .await
+            .unwrap();
+        assert!(fixture.local("dir/file").is_file());
+        assert!(provider.exists(&fixture.url("dir/file")).await.unwrap());
+
+        assert!(matches!(
+            provider.create_dir(&fixture.url("dir")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            provider.create_file(&fixture.url("dir/file")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        provider
+            .rename(&fixture.url("dir/file"), &fixture.url("dir/renamed"))
+            .await
+            .unwrap();
+        assert!(!fixture.local("dir/file").exists());
+        fs::write(fixture.local("other"), "").unwrap();
+        assert!(matches!(
+            provider
+                .move_entry(&fixture.url("other"), &fixture.url("dir/renamed"))
+                .await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        provider.delete(&fixture.url("dir")).await.unwrap();
+        assert!(!fixture.local("dir").exists());
+        assert!(!provider.exists(&fixture.url("dir")).await.unwrap());
+    });
+}
+
+#[test]
+fn copies_directories_recursively() {
+    let fixture = Fixture::new();
+    fs::create_dir_all(fixture.local("src/nested")).unwrap();
+    fs::write(fixture.local("src/a.txt"), "hello").unwrap();
+    let big: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+    fs::write(fixture.local("src/nested/big.bin"), &big).unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("a.txt", fixture.local("src/link")).unwrap();
+
+    smol::block_on(async {
+        let provider = &fixture.provider;
+        provider
+            .copy(&fixture.url("src"), &fixture.url("dst"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            provider
+                .copy(&fixture.url("src"), &fixture.url("src/nested/inner"))
+                .await,
+            Err(StorageError::Other(_))
+        ));
+        assert!(matches!(
+            provider
+                .copy(&fixture.url("src/a.txt"), &fixture.url("dst/a.txt"))
+                .await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+    });
+    assert_eq!(fs::read(fixture.local("dst/a.txt")).unwrap(), b"hello");
+    assert_eq!(fs::read(fixture.local("dst/nested/big.bin")).unwrap(), big);
+    #[cfg(unix)]
+    assert_eq!(
+        fs::read_link(fixture.local("dst/link")).unwrap(),
+        Path::new("a.txt")
+    );
+}
+
+#[test]
+fn reports_missing_paths() {
+    let fixture = Fixture::new();
+    smol::block_on(async {
+        let provider = &fixture.provider;
+        let missing = fixture.url("missing");
+        assert!(!provider.exists(&missing).await.unwrap());
+        assert!(matches!(
+            provider.get_metadata(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.list_entries(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.delete(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        let mut content = vec![];
+        assert!(matches!(
+            provider.read_file(&missing, &mut content).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.list_entries("sftp://nobody@elsewhere:22/").await,
+            Err(StorageError::Other(_))
+        ));
+    });
+}
+
+#[test]
+fn reports_space() {
+    let fixture = Fixture::new();
+    let root = fixture.connection.url(&fixture.connection.root);
+    let space = smol::block_on(fixture.provider.get_space(&root)).unwrap();
+    assert!(space.total > 0);
+    assert!(space.available <= space.total);
+}
+
+#[test]
+fn reconnects_after_server_exits() {
+    let server = find_server();
+    assert!(
+        Path::new("/usr/bin/timeout").is_file(),
+        "没有找到 /usr/bin/timeout"
+    );
+    // 服务器一秒后退出，模拟空闲连接被断开
+    let fixture = Fixture::with_command(vec![
+        "/usr/bin/timeout".to_string(),
+        "1".to_string(),
+        server.display().to_string(),
+    ]);
+    fs::write(fixture.local("file"), "content").unwrap();
+
+    assert_eq!(read(&fixture.provider, &fixture.url("file")), b"content");
+    thread::sleep(Duration::from_millis(1500));
+    assert_eq!(read(&fixture.provider, &fixture.url("file")), b"content");
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    let fixture = Fixture::new();
+    fs::create_dir(fixture.local("tree")).unwrap();
+    conformance::create_tree(&fixture.local("tree"), true).unwrap();
+    smol::block_on(conformance::run(&fixture));
+}
+
+#[test]
+fn rejects_option_like_hosts() {
+    for (host, user) in [("-oProxyCommand=false", "tester"), ("localhost", "-oX=y")] {
+        let connection = SftpConnection {
+            name: "测试服务器".to_string(),
+            host: host.to_string(),
+            port: 22,
+            user: user.to_string(),
+            auth: Default::default(),
+            known_hosts: None,
+            root: "/".to_string(),
+            command: None,
+        };
+        let provider = SftpProvider::new(vec![connection.clone()]);
+        let result = smol::block_on(provider.list_entries(&connection.url("/")));
+        assert!(
+            matches!(&result, Err(StorageError::Other(message)) if message.starts_with("无效的")),
+            "{:?}",
+            result
+        );
+    }
+}
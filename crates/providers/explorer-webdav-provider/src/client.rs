@@ -0,0 +1,345 @@
+//! WebDAV HTTP 请求
+//!
+//! 路径中保存的是解码后的 URL（如 `https://host/dav/我的 文档`），发送请求时再按路径段
+//! 进行百分号编码；响应中的 href 则解码后换回这种形式。
+
+use std::{
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use base64::Engine;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+
+use explorer_storage::{StorageError, StorageResult};
+
+use crate::{
+    WebDavConnection,
+    multistatus::{self, Resource},
+};
+
+/// 路径段中需要编码的字符（保留 RFC 3986 中允许出现在路径段里的字符）
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b'+')
+    .remove(b',')
+    .remove(b';')
+    .remove(b'=')
+    .remove(b':')
+    .remove(b'@');
+
+/// 下载中断后最多续传的次数
+const MAX_RESUMES: usize = 3;
+
+/// 拆分 URL 为 `scheme://authority` 和路径部分
+pub(crate) fn split_origin(url: &str) -> (&str, &str) {
+    let start = url.find("://").map_or(0, |index| index + 3);
+    match url[start..].find('/') {
+        Some(index) => url.split_at(start + index),
+        None => (url, ""),
+    }
+}
+
+/// 把路径编码为请求的 URL
+fn encode(path: &str) -> String {
+    let (origin, rest) = split_origin(path);
+    let mut url = origin.to_string();
+    for segment in rest.split('/').skip(1) {
+        url.push('/');
+        url.extend(utf8_percent_encode(segment, PATH_SEGMENT));
+    }
+    url
+}
+
+/// 解码 URL，并去掉末尾的 `/`
+pub(crate) fn decode(url: &str) -> String {
+    let decoded = percent_decode_str(url).decode_utf8_lossy();
+    let (origin, rest) = split_origin(&decoded);
+    format!("{}{}", origin, rest.trim_end_matches('/'))
+}
+
+/// PROPFIND 的深度
+#[derive(Clone, Copy)]
+pub(crate) enum Depth {
+    /// 只有资源本身
+    Zero,
+    /// 资源本身及其直接子资源
+    One,
+}
+
+pub(crate) struct Client {
+    agent: ureq::Agent,
+    authorization: Option<String>,
+    /// 服务器的 `scheme://authority`
+    origin: String,
+}
+
+impl Client {
+    pub fn new(connection: &WebDavConnection) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(60))
+            // 非 GET 请求的重定向需要自行处理
+            .redirects(0)
+            .build();
+        let authorization = connection.user.as_ref().map(|user| {
+            let credentials = format!(
+                "{}:{}",
+                user,
+                connection.password.as_deref().unwrap_or_default()
+            );
+            format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            )
+        });
+        Self {
+            agent,
+            authorization,
+            origin: split_origin(&decode(&connection.url)).0.to_string(),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let request = self.agent.request(method, &encode(path));
+        match &self.authorization {
+            Some(authorization) => request.set("Authorization", authorization),
+            None => request,
+        }
+    }
+
+    /// href 对应的路径
+    fn href_path(&self, href: &str) -> String {
+        let (origin, rest) = split_origin(href);
+        if href.contains("://") {
+            decode(&format!("{}{}", origin, rest))
+        } else {
+            decode(&format!("{}{}", self.origin, href))
+        }
+    }
+
+    /// PROPFIND，返回资源及其路径
+    pub fn propfind(
+        &self,
+        path: &str,
+        depth: Depth,
+        body: &str,
+    ) -> StorageResult<Vec<(String, Resource)>> {
+        let send = |path: &str| {
+            self.request("PROPFIND", path)
+                .set(
+                    "Depth",
+                    match depth {
+                        Depth::Zero => "0",
+                        Depth::One => "1",
+                    },
+                )
+                .set("Content-Type", "application/xml; charset=utf-8")
+                .send_string(body)
+                .map_err(|e| map_error(e, path))
+        };
+        let mut response = send(path)?;
+        // 有的服务器要求集合的路径以 `/` 结尾，并把请求重定向过去
+        if (300..400).contains(&response.status())
+            && response
+                .header("Location")
+                .is_some_and(|location| location.ends_with('/'))
+        {
+            response = send(&format!("{}/", path))?;
+        }
+        let response = reject_redirect(response, path)?;
+
+        let xml = response.into_string()?;
+        Ok(multistatus::parse(&xml)?
+            .into_iter()
+            .map(|resource| (self.href_path(&resource.href), resource))
+            .collect())
+    }
+
+    /// 下载到 `writer`，连接中断时用 Range 请求从断点继续
+    pub fn download(&self, path: &str, writer: &mut dyn Write) -> StorageResult<u64> {
+        let mut written = 0;
+        let mut etag: Option<String> = None;
+        let mut resumes = 0;
+        loop {
+            let mut request = self.request("GET", path);
+            if written > 0 {
+                request = request.set("Range", &format!("bytes={}-", written));
+                // 文件在两次请求之间变化时服务器返回完整内容，不能拼接
+                if let Some(etag) = &etag {
+                    request = request.set("If-Range", etag);
+                }
+            }
+            let response = check(request.call(), path)?;
+            if written > 0 && response.status() != 206 {
+                return Err(StorageError::Other(format!(
+                    "下载中断，且无法从断点继续: {}",
+                    path
+                )));
+            }
+            if written == 0 {
+                etag = response.header("ETag").map(str::to_string);
+            }
+
+            let mut reader = response.into_reader();
+            let mut buf = [0; 64 * 1024];
+            let error = loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => return Ok(written),
+                    Ok(read) => {
+                        writer.write_all(&buf[..read])?;
+                        written += read as u64;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => break e,
+                }
+            };
+            if resumes == MAX_RESUMES {
+                return Err(error.into());
+            }
+            resumes += 1;
+            tracing::warn!("下载中断，从 {} 字节处继续: {}: {}", written, path, error);
+        }
+    }
+
+    /// 读取从 `offset` 开始的最多 `len` 个字节
+    pub fn read_range(&self, path: &str, offset: u64, len: u64) -> StorageResult<Vec<u8>> {
+        if len == 0 {
+            return Ok(vec![]);
+        }
+        let response = self
+            .request("GET", path)
+            .set("Range", &format!("bytes={}-{}", offset, offset + len - 1))
+            .call();
+        let response = match response {
+            // 起始位置在文件末尾之后
+            Err(ureq::Error::Status(416, _)) => return Ok(vec![]),
+            result => check(result, path)?,
+        };
+        let partial = response.status() == 206;
+        let mut reader = response.into_reader();
+        // 服务器不支持 Range 时返回完整内容，跳过前面的部分
+        if !partial {
+            io::copy(&mut (&mut reader).take(offset), &mut io::sink())?;
+        }
+        let mut data = vec![];
+        reader.take(len).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn put(&self, path: &str, reader: &mut dyn Read) -> StorageResult<u64> {
+        let mut counting = Counting {
+            inner: reader,
+            count: 0,
+        };
+        let response = self
+            .request("PUT", path)
+            .set("Content-Type", "application/octet-stream")
+            .send(&mut counting);
+        check(response, path)?;
+        Ok(counting.count)
+    }
+
+    /// 创建空文件，已存在时返回 `AlreadyExists`
+    pub fn create_file(&self, path: &str) -> StorageResult<()> {
+        let response = self
+            .request("PUT", path)
+            .set("If-None-Match", "*")
+            .send_bytes(&[]);
+        check(response, path)?;
+        Ok(())
+    }
+
+    pub fn mkcol(&self, path: &str) -> StorageResult<()> {
+        match self.request("MKCOL", &format!("{}/", path)).call() {
+            // 路径已被占用
+            Err(ureq::Error::Status(405, _)) => Err(StorageError::AlreadyExists(path.to_string())),
+            result => check(result, path).map(|_| ()),
+        }
+    }
+
+    /// MOVE 或 COPY，目标已存在时返回 `AlreadyExists`
+    pub fn transfer(&self, method: &str, from: &str, to: &str) -> StorageResult<()> {
+        let response = self
+            .request(method, from)
+            .set("Destination", &encode(to))
+            .set("Overwrite", "F")
+            .set("Depth", "infinity")
+            .call();
+        match response {
+            Err(ureq::Error::Status(412, _)) => Err(StorageError::AlreadyExists(to.to_string())),
+            result => check(result, from).map(|_| ()),
+        }
+    }
+
+    pub fn delete(&self, path: &str) -> StorageResult<()> {
+        let response = self.request("DELETE", path).call();
+        check(response, path)?;
+        Ok(())
+    }
+}
+
+/// 记录读取的字节数
+struct Counting<'a> {
+    inner: &'a mut dyn Read,
+    count: u64,
+}
+
+impl Read for Counting<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read as u64;
+        Ok(read)
+    }
+}
+
+/// 把请求结果中的 HTTP 错误转换为存储错误
+fn check(result: Result<ureq::Response, ureq::Error>, path: &str) -> StorageResult<ureq::Response> {
+    reject_redirect(result.map_err(|e| map_error(e, path))?, path)
+}
+
+/// 不自动跟随重定向，收到重定向时报告目标位置
+fn reject_redirect(response: ureq::Response, path: &str) -> StorageResult<ureq::Response> {
+    if (300..400).contains(&response.status()) {
+        return Err(StorageError::Other(format!(
+            "服务器将 {} 重定向到 {}，请检查连接地址",
+            path,
+            response.header("Location").unwrap_or_default()
+        )));
+    }
+    Ok(response)
+}
+
+/// 把 HTTP 错误转换为存储错误
+fn map_error(error: ureq::Error, path: &str) -> StorageError {
+    let path = path.to_string();
+    match error {
+        ureq::Error::Status(status, response) => match status {
+            404 => StorageError::PathNotFound(path),
+            // 父目录不存在
+            409 => StorageError::PathNotFound(path),
+            401 | 403 => StorageError::PermissionDenied(path),
+            412 => StorageError::AlreadyExists(path),
+            507 => StorageError::Other(format!("服务器空间不足: {}", path)),
+            _ => StorageError::Other(format!(
+                "WebDAV 请求失败（{} {}）: {}",
+                status,
+                response.status_text(),
+                path
+            )),
+        },
+        ureq::Error::Transport(transport) => {
+            StorageError::IoError(io::Error::other(transport.to_string()))
+        }
+    }
+}
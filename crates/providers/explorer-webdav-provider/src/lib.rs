@@ -0,0 +1,300 @@
+//! WebDAV 存储提供者
+//!
+//! 通过 WebDAV（RFC 4918）浏览 Nextcloud、ownCloud 等服务器上的文件。路径为解码后的
+//! 资源 URL，例如 `https://cloud.example.com/remote.php/dav/files/user/文档`，每个已保存的
+//! 连接是一个网络驱动器根节点。
+//!
+//! 条目的 ETag 和内容类型保存在 `custom_fields` 的 `etag` 和 `content_type` 中。
+
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use mime_guess::from_path;
+use serde::{Deserialize, Serialize};
+
+use explorer_storage::*;
+
+use client::{Client, Depth, decode, split_origin};
+use multistatus::{PROPFIND_ENTRIES, PROPFIND_QUOTA, Resource};
+
+mod client;
+mod multistatus;
+
+/// 已保存的 WebDAV 连接
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebDavConnection {
+    /// 显示名称
+    pub name: String,
+    /// 根目录的 URL，例如 `https://cloud.example.com/remote.php/dav/files/user/`
+    pub url: String,
+    /// 用户名，为空时不进行身份验证
+    #[serde(default)]
+    pub user: Option<String>,
+    /// 密码（以明文保存，建议使用服务器生成的应用密码）
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl WebDavConnection {
+    /// 根目录的路径
+    pub fn root(&self) -> String {
+        decode(&self.url)
+    }
+}
+
+/// 路径是否为 WebDAV 路径（http 或 https URL）
+pub fn is_webdav_path(path: &str) -> bool {
+    path.starts_with("https://") || path.starts_with("http://")
+}
+
+/// WebDAV 存储提供者
+pub struct WebDavProvider {
+    connections: Vec<(WebDavConnection, Arc<Client>)>,
+}
+
+impl WebDavProvider {
+    /// 使用已保存的连接创建提供者
+    pub fn new(connections: Vec<WebDavConnection>) -> Self {
+        Self {
+            connections: connections
+                .into_iter()
+                .map(|connection| {
+                    let client = Arc::new(Client::new(&connection));
+                    (connection, client)
+                })
+                .collect(),
+        }
+    }
+
+    /// 找到路径所属的连接，返回其客户端和去掉末尾 `/` 的路径
+    fn locate(&self, path: &str) -> StorageResult<(Arc<Client>, String)> {
+        let (origin, rest) = split_origin(path);
+        let path = format!("{}{}", origin, rest.trim_end_matches('/'));
+        self.connections
+            .iter()
+            .filter(|(connection, _)| {
+                let root = connection.root();
+                path.strip_prefix(&root)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|(connection, _)| connection.root().len())
+            .map(|(_, client)| (client.clone(), path.clone()))
+            .ok_or_else(|| StorageError::Other(format!("没有保存的连接: {}", path)))
+    }
+
+    /// 同一连接中的两个路径
+    fn locate_pair(&self, from: &str, to: &str) -> StorageResult<(Arc<Client>, String, String)> {
+        let (client, from_path) = self.locate(from)?;
+        let (to_client, to_path) = self.locate(to)?;
+        if !Arc::ptr_eq(&client, &to_client) {
+            return Err(StorageError::Unsupported(format!(
+                "在不同服务器之间操作: {} -> {}",
+                from, to
+            )));
+        }
+        Ok((client, from_path, to_path))
+    }
+
+    /// 读取从 `offset` 开始的最多 `len` 个字节（使用 Range 请求，不下载整个文件）
+    pub async fn read_range(&self, path: &str, offset: u64, len: u64) -> StorageResult<Vec<u8>> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || client.read_range(&path, offset, len)).await
+    }
+}
+
+/// 资源对应的文件条目
+fn file_item(path: &str, resource: &Resource) -> FileItem {
+    let name = path.rsplit('/').next().unwrap_or(path).to_string();
+    let item_type = if resource.is_collection {
+        ItemType::Directory
+    } else {
+        ItemType::File
+    };
+    let mime_type = match item_type {
+        ItemType::File => resource
+            .content_type
+            .clone()
+            .or_else(|| from_path(&name).first().map(|mime| mime.to_string())),
+        _ => None,
+    };
+
+    let mut custom_fields = HashMap::new();
+    if let Some(etag) = &resource.etag {
+        custom_fields.insert("etag".to_string(), etag.clone());
+    }
+    if let Some(content_type) = &resource.content_type {
+        custom_fields.insert("content_type".to_string(), content_type.clone());
+    }
+
+    FileItem {
+        is_hidden: name.starts_with('.'),
+        name,
+        path: path.to_string(),
+        item_type,
+        size: resource.size.unwrap_or(0),
+        modified: resource
+            .modified
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
+        metadata: EntryMetadata {
+            mime_type,
+            created: resource.created,
+            custom_fields,
+            ..Default::default()
+        },
+    }
+}
+
+/// PROPFIND 深度为 0 时取出资源本身
+fn single(path: &str, resources: Vec<(String, Resource)>) -> StorageResult<Resource> {
+    resources
+        .into_iter()
+        .next()
+        .map(|(_, resource)| resource)
+        .ok_or_else(|| StorageError::PathNotFound(path.to_string()))
+}
+
+#[async_trait]
+impl StorageProvider for WebDavProvider {
+    async fn get_roots(&self) -> StorageResult<Vec<RootItem>> {
+        Ok(self
+            .connections
+            .iter()
+            .map(|(connection, _)| {
+                let path = connection.root();
+                RootItem {
+                    id: path.clone(),
+                    name: connection.name.clone(),
+                    path,
+                    provider_type: ProviderType::NetworkDrive,
+                    icon: None,
+                    mount: None,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_metadata(&self, path: &str) -> StorageResult<FileItem> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || {
+            let resources = client.propfind(&path, Depth::Zero, PROPFIND_ENTRIES)?;
+            Ok(file_item(&path, &single(&path, resources)?))
+        })
+        .await
+    }
+
+    async fn list_entries(&self, path: &str) -> StorageResult<Vec<FileItem>> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || {
+            let resources = client.propfind(&path, Depth::One, PROPFIND_ENTRIES)?;
+            let mut entries = vec![];
+            for (resource_path, resource) in resources {
+                if resource_path == path {
+                    if !resource.is_collection {
+                        return Err(StorageError::Other(format!("路径不是目录: {}", path)));
+                    }
+                    continue;
+                }
+                entries.push(file_item(&resource_path, &resource));
+            }
+
+            sort_entries(&mut entries);
+            Ok(entries)
+        })
+        .await
+    }
+
+    async fn exists(&self, path: &str) -> StorageResult<bool> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(
+            move || match client.propfind(&path, Depth::Zero, PROPFIND_ENTRIES) {
+                Ok(_) => Ok(true),
+                Err(StorageError::PathNotFound(_)) => Ok(false),
+                Err(e) => Err(e),
+            },
+        )
+        .await
+    }
+
+    async fn read_file(&self, path: &str, writer: &mut (dyn Write + Send)) -> StorageResult<u64> {
+        // `writer` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (client, path) = self.locate(path)?;
+        client.download(&path, writer)
+    }
+
+    async fn write_file(&self, path: &str, reader: &mut (dyn Read + Send)) -> StorageResult<u64> {
+        // `reader` 是借用的，不能交给 `smol::unblock`，由调用方保证在后台执行
+        let (client, path) = self.locate(path)?;
+        client.put(&path, reader)
+    }
+
+    async fn create_dir(&self, path: &str) -> StorageResult<()> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || client.mkcol(&path)).await
+    }
+
+    async fn create_file(&self, path: &str) -> StorageResult<()> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || client.create_file(&path)).await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (client, from, to) = self.locate_pair(from, to)?;
+        smol::unblock(move || client.transfer("MOVE", &from, &to)).await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> StorageResult<()> {
+        let (client, from, to) = self.locate_pair(from, to)?;
+        if to.starts_with(&format!("{}/", from)) {
+            return Err(StorageError::Other(format!(
+                "不能将目录复制到其自身内部: {}",
+                to
+            )));
+        }
+        smol::unblock(move || client.transfer("COPY", &from, &to)).await
+    }
+
+    async fn delete(&self, path: &str) -> StorageResult<()> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || client.delete(&path)).await
+    }
+
+    async fn get_space(&self, path: &str) -> StorageResult<SpaceInfo> {
+        let (client, path) = self.locate(path)?;
+        smol::unblock(move || {
+            let resources = client.propfind(&path, Depth::Zero, PROPFIND_QUOTA)?;
+            let resource = single(&path, resources)?;
+            // 服务器用负数表示配额未知或不限
+            match (resource.quota_used, resource.quota_available) {
+                (Some(used @ 0..), Some(available @ 0..)) => Ok(SpaceInfo {
+                    total: (used + available) as u64,
+                    free: available as u64,
+                    available: available as u64,
+                }),
+                _ => Err(StorageError::Unsupported(format!("获取可用空间: {}", path))),
+            }
+        })
+        .await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            can_create: true,
+            can_rename: true,
+            can_copy: true,
+            can_move: true,
+            can_trash: false,
+            can_delete: true,
+            can_symlink: false,
+            can_archive: false,
+            local_paths: false,
+        }
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::NetworkDrive
+    }
+}
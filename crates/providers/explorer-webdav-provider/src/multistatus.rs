@@ -0,0 +1,108 @@
+//! PROPFIND 请求体和 207 Multi-Status 响应（RFC 4918）
+
+use std::time::SystemTime;
+
+use chrono::DateTime;
+use explorer_storage::{StorageError, StorageResult};
+
+const DAV: &str = "DAV:";
+
+/// 列出条目时请求的属性
+pub const PROPFIND_ENTRIES: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:resourcetype/>
+    <d:getcontentlength/>
+    <d:getlastmodified/>
+    <d:creationdate/>
+    <d:getetag/>
+    <d:getcontenttype/>
+  </d:prop>
+</d:propfind>"#;
+
+/// 获取配额时请求的属性（RFC 4331）
+pub const PROPFIND_QUOTA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:quota-available-bytes/>
+    <d:quota-used-bytes/>
+  </d:prop>
+</d:propfind>"#;
+
+/// 响应中的一个资源
+#[derive(Debug, Default)]
+pub struct Resource {
+    /// 资源的 href（未解码）
+    pub href: String,
+    pub is_collection: bool,
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    pub etag: Option<String>,
+    pub content_type: Option<String>,
+    /// 配额，服务器用负数表示未知或不限
+    pub quota_available: Option<i64>,
+    pub quota_used: Option<i64>,
+}
+
+/// 解析 Multi-Status 响应，只读取状态为 200 的属性
+pub fn parse(xml: &str) -> StorageResult<Vec<Resource>> {
+    let document = roxmltree::Document::parse(xml)
+        .map_err(|e| StorageError::Other(format!("无法解析 WebDAV 响应: {}", e)))?;
+
+    let mut resources = vec![];
+    for response in children(document.root_element(), "response") {
+        let Some(href) = children(response, "href")
+            .next()
+            .and_then(|node| node.text())
+        else {
+            continue;
+        };
+        let mut resource = Resource {
+            href: href.trim().to_string(),
+            ..Default::default()
+        };
+        let props = children(response, "propstat")
+            .filter(|propstat| {
+                children(*propstat, "status")
+                    .next()
+                    .and_then(|status| status.text())
+                    .is_some_and(|status| status.split_whitespace().nth(1) == Some("200"))
+            })
+            .flat_map(|propstat| children(propstat, "prop"))
+            .flat_map(|prop| prop.children().filter(|node| node.is_element()));
+        for prop in props {
+            let text = prop.text().map(str::trim).unwrap_or_default();
+            match prop.tag_name().name() {
+                "resourcetype" => {
+                    resource.is_collection = children(prop, "collection").next().is_some();
+                }
+                "getcontentlength" => resource.size = text.parse().ok(),
+                "getlastmodified" => {
+                    resource.modified = DateTime::parse_from_rfc2822(text).ok().map(Into::into);
+                }
+                "creationdate" => {
+                    resource.created = DateTime::parse_from_rfc3339(text).ok().map(Into::into);
+                }
+                "getetag" if !text.is_empty() => resource.etag = Some(text.to_string()),
+                "getcontenttype" if !text.is_empty() => {
+                    resource.content_type = Some(text.to_string());
+                }
+                "quota-available-bytes" => resource.quota_available = text.parse().ok(),
+                "quota-used-bytes" => resource.quota_used = text.parse().ok(),
+                _ => {}
+            }
+        }
+        resources.push(resource);
+    }
+    Ok(resources)
+}
+
+/// `DAV:` 命名空间中指定名称的子元素
+fn children<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.has_tag_name((DAV, name)))
+}
@@ -0,0 +1,570 @@
+//! 使用进程内的 WebDAV 服务器替身的集成测试
+//!
+//! 替身把 `/dav/` 下的请求映射到一个临时目录，实现了提供者用到的方法，并模拟 Apache
+//! 对不以 `/` 结尾的集合路径的重定向。
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::SystemTime,
+};
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use tiny_http::{Header, Request, Response, Server};
+
+use explorer_storage::{
+    ItemType, ProviderType, StorageError, StorageProvider,
+    conformance::{self, TempDir},
+};
+use explorer_webdav_provider::{WebDavConnection, WebDavProvider};
+
+const USER: &str = "alice";
+const PASSWORD: &str = "secret";
+
+/// 服务器替身及其根目录
+struct Fixture {
+    dir: TempDir,
+    server: Arc<Server>,
+    root: String,
+    provider: WebDavProvider,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Self::with_password(PASSWORD)
+    }
+
+    fn with_password(password: &str) -> Self {
+        let dir = TempDir::new("webdav-test");
+
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let port = server.server_addr().to_ip().unwrap().port();
+        {
+            let (server, dir) = (server.clone(), dir.to_path_buf());
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    handle(request, &dir);
+                }
+            });
+        }
+
+        let root = format!("http://127.0.0.1:{}/dav", port);
+        let provider = WebDavProvider::new(vec![WebDavConnection {
+            name: "网盘".to_string(),
+            url: format!("{}/", root),
+            user: Some(USER.to_string()),
+            password: Some(password.to_string()),
+        }]);
+        Self {
+            dir,
+            server,
+            root,
+            provider,
+        }
+    }
+
+    fn url(&self, name: &str) -> String {
+        format!("{}/{}", self.root, name)
+    }
+
+    fn local(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        self.server.unblock();
+    }
+}
+
+impl conformance::Fixture for Fixture {
+    fn provider(&self) -> &dyn StorageProvider {
+        &self.provider
+    }
+
+    fn root(&self) -> String {
+        self.url("tree")
+    }
+}
+
+fn header<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.to_string().eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str())
+}
+
+fn with_header<R: Read>(response: Response<R>, name: &str, value: &str) -> Response<R> {
+    response.with_header(Header::from_bytes(name, value).unwrap())
+}
+
+fn status(code: u16) -> Response<io::Empty> {
+    Response::new_empty(code.into())
+}
+
+/// 请求的 URL 对应的本地路径（必须在 `/dav` 下）
+fn local_path(dir: &Path, url: &str) -> Option<PathBuf> {
+    let path = url.split("://").nth(1).map_or(url, |rest| {
+        rest.find('/').map_or("", |index| &rest[index..])
+    });
+    let rest = path.strip_prefix("/dav")?;
+    let decoded = percent_decode_str(rest).decode_utf8().ok()?;
+    let mut local = dir.to_path_buf();
+    for segment in decoded.split('/').filter(|segment| !segment.is_empty()) {
+        if segment == ".." {
+            return None;
+        }
+        local.push(segment);
+    }
+    Some(local)
+}
+
+fn href(dir: &Path, path: &Path) -> String {
+    let mut href = "/dav".to_string();
+    for component in path.strip_prefix(dir).unwrap().components() {
+        href.push('/');
+        href.extend(utf8_percent_encode(
+            &component.as_os_str().to_string_lossy(),
+            NON_ALPHANUMERIC,
+        ));
+    }
+    if path.is_dir() {
+        href.push('/');
+    }
+    href
+}
+
+fn etag(metadata: &fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .unwrap()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap();
+    format!("\"{}-{}\"", metadata.len(), modified.as_nanos())
+}
+
+fn propstat(dir: &Path, path: &Path, quota: bool) -> String {
+    let metadata = fs::metadata(path).unwrap();
+    let mut props = String::new();
+    if quota {
+        props.push_str("<d:quota-used-bytes>1000</d:quota-used-bytes>");
+        props.push_str("<d:quota-available-bytes>5000</d:quota-available-bytes>");
+    } else if metadata.is_dir() {
+        props.push_str("<d:resourcetype><d:collection/></d:resourcetype>");
+    } else {
+        let modified = DateTime::<Utc>::from(metadata.modified().unwrap());
+        let content_type = if path.extension().is_some_and(|ext| ext == "txt") {
+            "text/plain"
+        } else {
+            "application/octet-stream"
+        };
+        props.push_str(&format!(
+            "<d:resourcetype/><d:getcontentlength>{}</d:getcontentlength>\
+             <d:getlastmodified>{}</d:getlastmodified>\
+             <d:getetag>{}</d:getetag><d:getcontenttype>{}</d:getcontenttype>",
+            metadata.len(),
+            modified.format("%a, %d %b %Y %H:%M:%S GMT"),
+            etag(&metadata),
+            content_type
+        ));
+    }
+    format!(
+        "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop>\
+         <d:status>HTTP/1.1 200 OK</d:status></d:propstat>\
+         <d:propstat><d:prop><d:displayname/></d:prop>\
+         <d:status>HTTP/1.1 404 Not Found</d:status></d:propstat></d:response>",
+        href(dir, path),
+        props
+    )
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+fn handle(mut request: Request, dir: &Path) {
+    let expected = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", USER, PASSWORD))
+    );
+    if header(&request, "Authorization") != Some(expected.as_str()) {
+        let _ = request.respond(status(401));
+        return;
+    }
+    let Some(path) = local_path(dir, request.url()) else {
+        let _ = request.respond(status(403));
+        return;
+    };
+    let mut body = vec![];
+    let _ = request.as_reader().read_to_end(&mut body);
+    let exists = path.symlink_metadata().is_ok();
+    let parent_exists = path.parent().is_some_and(Path::is_dir);
+
+    let response = match request.method().as_str() {
+        "PROPFIND" => {
+            if !exists {
+                status(404).boxed()
+            } else if path.is_dir() && !request.url().ends_with('/') {
+                with_header(status(301), "Location", &format!("{}/", request.url())).boxed()
+            } else {
+                let quota = String::from_utf8_lossy(&body).contains("quota-available-bytes");
+                let mut xml = String::from(
+                    r#"<?xml version="1.0" encoding="utf-8"?><d:multistatus xmlns:d="DAV:">"#,
+                );
+                xml.push_str(&propstat(dir, &path, quota));
+                if header(&request, "Depth") == Some("1") && path.is_dir() {
+                    for entry in fs::read_dir(&path).unwrap() {
+                        xml.push_str(&propstat(dir, &entry.unwrap().path(), false));
+                    }
+                }
+                xml.push_str("</d:multistatus>");
+                with_header(
+                    Response::from_string(xml).with_status_code(207),
+                    "Content-Type",
+                    "application/xml; charset=utf-8",
+                )
+                .boxed()
+            }
+        }
+        "GET" if path.is_file() => {
+            let content = fs::read(&path).unwrap();
+            let tag = etag(&fs::metadata(&path).unwrap());
+            let range = header(&request, "Range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .filter(|_| header(&request, "If-Range").is_none_or(|value| value == tag));
+            match range {
+                Some(range) => {
+                    let (start, end) = range.split_once('-').unwrap();
+                    let start: usize = start.parse().unwrap();
+                    let end = end
+                        .parse::<usize>()
+                        .map_or(content.len(), |end| (end + 1).min(content.len()));
+                    if start >= content.len() {
+                        status(416).boxed()
+                    } else {
+                        with_header(
+                            Response::from_data(content[start..end].to_vec()).with_status_code(206),
+                            "Content-Range",
+                            &format!("bytes {}-{}/{}", start, end - 1, content.len()),
+                        )
+                        .boxed()
+                    }
+                }
+                None => with_header(Response::from_data(content), "ETag", &tag).boxed(),
+            }
+        }
+        "GET" => status(404).boxed(),
+        "PUT" => {
+            if !parent_exists {
+                status(409).boxed()
+            } else if exists && header(&request, "If-None-Match") == Some("*") {
+                status(412).boxed()
+            } else {
+                fs::write(&path, &body).unwrap();
+                status(if exists { 204 } else { 201 }).boxed()
+            }
+        }
+        "MKCOL" => {
+            if exists {
+                status(405).boxed()
+            } else if !parent_exists {
+                status(409).boxed()
+            } else {
+                fs::create_dir(&path).unwrap();
+                status(201).boxed()
+            }
+        }
+        method @ ("MOVE" | "COPY") => {
+            let destination = header(&request, "Destination").and_then(|url| local_path(dir, url));
+            match destination {
+                None => status(400).boxed(),
+                Some(_) if !exists => status(404).boxed(),
+                Some(target) if target.symlink_metadata().is_ok() => status(412).boxed(),
+                Some(target) if !target.parent().is_some_and(Path::is_dir) => status(409).boxed(),
+                Some(target) => {
+                    if method == "MOVE" {
+                        fs::rename(&path, &target).unwrap();
+                    } else {
+                        copy_recursive(&path, &target).unwrap();
+                    }
+                    status(201).boxed()
+                }
+            }
+        }
+        "DELETE" => {
+            if !exists {
+                status(404).boxed()
+            } else {
+                if path.is_dir() {
+                    fs::remove_dir_all(&path).unwrap();
+                } else {
+                    fs::remove_file(&path).unwrap();
+                }
+                status(204).boxed()
+            }
+        }
+        _ => status(405).boxed(),
+    };
+    let _ = request.respond(response);
+}
+
+fn read(provider: &WebDavProvider, path: &str) -> Vec<u8> {
+    let mut content = vec![];
+    smol::block_on(provider.read_file(path, &mut content)).unwrap();
+    content
+}
+
+#[test]
+fn roots_are_network_drives() {
+    let fixture = Fixture::new();
+    let roots = smol::block_on(fixture.provider.get_roots()).unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].name, "网盘");
+    assert_eq!(roots[0].path, fixture.root);
+    assert_eq!(roots[0].provider_type, ProviderType::NetworkDrive);
+}
+
+#[test]
+fn lists_entries_with_etag_and_content_type() {
+    let fixture = Fixture::new();
+    fs::create_dir(fixture.local("我的 文档")).unwrap();
+    fs::write(fixture.local("笔记 #1.txt"), "hello").unwrap();
+    fs::write(fixture.local("data.bin"), [0u8; 10]).unwrap();
+
+    let entries = smol::block_on(fixture.provider.list_entries(&fixture.root)).unwrap();
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["我的 文档", "data.bin", "笔记 #1.txt"]);
+    assert_eq!(entries[0].item_type, ItemType::Directory);
+    assert_eq!(entries[0].path, fixture.url("我的 文档"));
+
+    let note = &entries[2];
+    assert_eq!(note.path, fixture.url("笔记 #1.txt"));
+    assert_eq!(note.size, 5);
+    assert_eq!(note.metadata.mime_type.as_deref(), Some("text/plain"));
+    assert_eq!(
+        note.metadata
+            .custom_fields
+            .get("content_type")
+            .map(String::as_str),
+        Some("text/plain")
+    );
+    let expected_etag = etag(&fs::metadata(fixture.local("笔记 #1.txt")).unwrap());
+    assert_eq!(
+        note.metadata.custom_fields.get("etag"),
+        Some(&expected_etag)
+    );
+    assert_ne!(note.modified, SystemTime::UNIX_EPOCH);
+
+    // 集合的路径不以 `/` 结尾时服务器会重定向
+    let nested = smol::block_on(fixture.provider.list_entries(&fixture.url("我的 文档")));
+    assert!(nested.unwrap().is_empty());
+    let not_dir = smol::block_on(fixture.provider.list_entries(&fixture.url("data.bin")));
+    assert!(matches!(not_dir, Err(StorageError::Other(_))));
+}
+
+#[test]
+fn reads_and_writes_files() {
+    let fixture = Fixture::new();
+    let content: Vec<u8> = (0..500_000u32).map(|i| (i % 251) as u8).collect();
+    let written = smol::block_on(
+        fixture
+            .provider
+            .write_file(&fixture.url("upload.bin"), &mut content.as_slice()),
+    );
+    assert_eq!(written.unwrap(), content.len() as u64);
+    assert_eq!(fs::read(fixture.local("upload.bin")).unwrap(), content);
+    assert_eq!(read(&fixture.provider, &fixture.url("upload.bin")), content);
+
+    let range = smol::block_on(
+        fixture
+            .provider
+            .read_range(&fixture.url("upload.bin"), 1000, 10),
+    );
+    assert_eq!(range.unwrap(), content[1000..1010]);
+    let past_end = smol::block_on(fixture.provider.read_range(
+        &fixture.url("upload.bin"),
+        600_000,
+        10,
+    ));
+    assert!(past_end.unwrap().is_empty());
+
+    let metadata = smol::block_on(fixture.provider.get_metadata(&fixture.url("upload.bin")));
+    assert_eq!(metadata.unwrap().size, content.len() as u64);
+}
+
+#[test]
+fn resumes_interrupted_downloads() {
+    // tiny_http 不会主动断开连接，这里直接用 TCP 模拟：第一次只发送一半内容就断开，
+    // 第二次按 Range 和 If-Range 返回剩余部分
+    let content: Vec<u8> = (0..200_000u32).map(|i| (i % 247) as u8).collect();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = {
+        let content = content.clone();
+        thread::spawn(move || {
+            let mut requests = vec![];
+            for half in [true, false] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                while reader.read_line(&mut head).unwrap() > 2 {}
+                let head = head.to_ascii_lowercase();
+                if half {
+                    let sent = content.len() / 2;
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\r\n",
+                        content.len()
+                    )
+                    .unwrap();
+                    stream.write_all(&content[..sent]).unwrap();
+                } else {
+                    let start = content.len() / 2;
+                    write!(
+                        stream,
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\
+                         Content-Range: bytes {}-{}/{}\r\n\r\n",
+                        content.len() - start,
+                        start,
+                        content.len() - 1,
+                        content.len()
+                    )
+                    .unwrap();
+                    stream.write_all(&content[start..]).unwrap();
+                }
+                requests.push(head);
+            }
+            requests
+        })
+    };
+
+    let root = format!("http://127.0.0.1:{}/dav", port);
+    let provider = WebDavProvider::new(vec![WebDavConnection {
+        name: "网盘".to_string(),
+        url: root.clone(),
+        user: None,
+        password: None,
+    }]);
+    assert_eq!(read(&provider, &format!("{}/large.bin", root)), content);
+
+    let requests = server.join().unwrap();
+    assert!(!requests[0].contains("range:"));
+    assert!(requests[1].contains("range: bytes=100000-\r\n"));
+    assert!(requests[1].contains("if-range: \"v1\"\r\n"));
+}
+
+#[test]
+fn creates_moves_copies_and_deletes() {
+    let fixture = Fixture::new();
+    let provider = &fixture.provider;
+    smol::block_on(async {
+        provider.create_dir(&fixture.url("dir")).await.unwrap();
+        provider
+            .create_file(&fixture.url("dir/a.txt"))
+            .await
+            .unwrap();
+        assert!(fixture.local("dir/a.txt").is_file());
+        assert!(matches!(
+            provider.create_dir(&fixture.url("dir")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            provider.create_file(&fixture.url("dir/a.txt")).await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        provider
+            .copy(&fixture.url("dir"), &fixture.url("copy"))
+            .await
+            .unwrap();
+        assert!(fixture.local("copy/a.txt").is_file());
+        assert!(matches!(
+            provider
+                .copy(&fixture.url("dir"), &fixture.url("dir/inner"))
+                .await,
+            Err(StorageError::Other(_))
+        ));
+        assert!(matches!(
+            provider
+                .move_entry(&fixture.url("copy"), &fixture.url("dir"))
+                .await,
+            Err(StorageError::AlreadyExists(_))
+        ));
+
+        provider
+            .rename(&fixture.url("dir/a.txt"), &fixture.url("dir/新 名称.txt"))
+            .await
+            .unwrap();
+        assert!(fixture.local("dir/新 名称.txt").is_file());
+
+        provider.delete(&fixture.url("dir")).await.unwrap();
+        assert!(!fixture.local("dir").exists());
+        assert!(!provider.exists(&fixture.url("dir")).await.unwrap());
+        assert!(provider.exists(&fixture.url("copy")).await.unwrap());
+    });
+}
+
+#[test]
+fn reports_errors() {
+    let fixture = Fixture::new();
+    let provider = &fixture.provider;
+    smol::block_on(async {
+        let missing = fixture.url("missing");
+        assert!(matches!(
+            provider.get_metadata(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.delete(&missing).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.create_dir(&fixture.url("missing/dir")).await,
+            Err(StorageError::PathNotFound(_))
+        ));
+        assert!(matches!(
+            provider.list_entries("https://elsewhere.example/dav").await,
+            Err(StorageError::Other(_))
+        ));
+    });
+
+    let unauthorized = Fixture::with_password("wrong");
+    assert!(matches!(
+        smol::block_on(unauthorized.provider.list_entries(&unauthorized.root)),
+        Err(StorageError::PermissionDenied(_))
+    ));
+}
+
+#[test]
+fn reports_quota() {
+    let fixture = Fixture::new();
+    let space = smol::block_on(fixture.provider.get_space(&fixture.root)).unwrap();
+    assert_eq!(space.total, 6000);
+    assert_eq!(space.available, 5000);
+    assert_eq!(space.used(), 1000);
+}
+
+#[test]
+fn conforms_to_provider_contract() {
+    let fixture = Fixture::new();
+    fs::create_dir(fixture.local("tree")).unwrap();
+    conformance::create_tree(&fixture.local("tree"), false).unwrap();
+    smol::block_on(conformance::run(&fixture));
+}